@@ -5,7 +5,7 @@
 
 #define check(call) {                                                             \
     TRACT_RESULT result = call;                                                 \
-    if(result == TRACT_RESULT_KO) {                                             \
+    if(result != TRACT_RESULT_OK) {                                             \
         fprintf(stderr, "Error calling tract: %s", tract_get_last_error());     \
         exit(1) ;                                                               \
     }                                                                           \
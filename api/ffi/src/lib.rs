@@ -4,14 +4,18 @@ use anyhow::{Context, Result};
 use std::cell::RefCell;
 use std::ffi::{c_char, c_void, CStr, CString};
 use tract_api::{
-    AsFact, DatumType, InferenceModelInterface, ModelInterface, NnefInterface, OnnxInterface,
-    RunnableInterface, StateInterface, ValueInterface,
+    AsFact, CancellationTokenInterface, Cancelled, DatumType, InferenceModelInterface,
+    ModelBuilderInterface, ModelInterface, NnefInterface, OnnxInterface, RunnableInterface,
+    StateInterface, TensorflowInterface, TfliteInterface, ValueInterface,
 };
-use tract_rs::{State, Value};
+use tract_rs::{CancellationToken, State, Value};
 
 /// Used as a return type of functions that can encounter errors.
 /// If the function encountered an error, you can retrieve it using the `tract_get_last_error`
 /// function
+///
+/// Any value other than `TRACT_RESULT_OK` is a failure: interrupted runs do not return
+/// `TRACT_RESULT_KO`, so callers must test for `!= TRACT_RESULT_OK`.
 #[repr(C)]
 #[allow(non_camel_case_types)]
 #[derive(Debug, PartialEq, Eq)]
@@ -20,6 +24,10 @@ pub enum TRACT_RESULT {
     TRACT_RESULT_OK = 0,
     /// The function returned an error
     TRACT_RESULT_KO = 1,
+    /// The run was interrupted by cancelling its cancellation token
+    TRACT_RESULT_CANCELLED = 2,
+    /// The run was interrupted as it ran past the time budget of its cancellation token
+    TRACT_RESULT_DEADLINE_EXCEEDED = 3,
 }
 
 thread_local! {
@@ -30,6 +38,7 @@ fn wrap<F: FnOnce() -> anyhow::Result<()>>(func: F) -> TRACT_RESULT {
     match func() {
         Ok(_) => TRACT_RESULT::TRACT_RESULT_OK,
         Err(e) => {
+            let cancelled = e.downcast_ref::<Cancelled>().copied();
             let msg = format!("{e:?}");
             if std::env::var("TRACT_ERROR_STDERR").is_ok() {
                 eprintln!("{msg}");
@@ -40,13 +49,17 @@ fn wrap<F: FnOnce() -> anyhow::Result<()>>(func: F) -> TRACT_RESULT {
                         .unwrap()
                 }))
             });
-            TRACT_RESULT::TRACT_RESULT_KO
+            match cancelled {
+                Some(Cancelled::Requested) => TRACT_RESULT::TRACT_RESULT_CANCELLED,
+                Some(Cancelled::DeadlineExceeded) => TRACT_RESULT::TRACT_RESULT_DEADLINE_EXCEEDED,
+                None => TRACT_RESULT::TRACT_RESULT_KO,
+            }
         }
     }
 }
 
 /// Retrieve the last error that happened in this thread. A function encountered an error if
-/// its return type is of type `TRACT_RESULT` and it returned `TRACT_RESULT_KO`.
+/// its return type is of type `TRACT_RESULT` and it did not return `TRACT_RESULT_OK`.
 ///
 /// # Return value
 ///  It returns a pointer to a null-terminated UTF-8 string that will contain the error description.
@@ -79,6 +92,16 @@ pub unsafe extern "C" fn tract_free_cstring(ptr: *mut std::ffi::c_char) {
     }
 }
 
+/// Frees a byte buffer allocated by libtract. `len` must be the length returned with the buffer.
+#[no_mangle]
+pub unsafe extern "C" fn tract_free_bytes(ptr: *mut u8, len: usize) {
+    unsafe {
+        if !ptr.is_null() {
+            let _ = Box::from_raw(std::ptr::slice_from_raw_parts_mut(ptr, len));
+        }
+    }
+}
+
 macro_rules! check_not_null {
     ($($ptr:expr),*) => {
         $(
@@ -186,6 +209,27 @@ pub unsafe extern "C" fn tract_nnef_model_for_path(
     })
 }
 
+/// Parse and load an NNEF model from a memory buffer as a tract TypedModel.
+///
+/// `data` points to the `len` bytes of a tar or tar.gz archive. The buffer only needs to be alive
+/// for the duration of the call.
+#[no_mangle]
+pub unsafe extern "C" fn tract_nnef_model_for_bytes(
+    nnef: *const TractNnef,
+    data: *const c_void,
+    len: usize,
+    model: *mut *mut TractModel,
+) -> TRACT_RESULT {
+    wrap(|| unsafe {
+        check_not_null!(nnef, data, model);
+        *model = std::ptr::null_mut();
+        let data = std::slice::from_raw_parts(data as *const u8, len);
+        let m = Box::new(TractModel((*nnef).0.model_for_bytes(data)?));
+        *model = Box::into_raw(m);
+        Ok(())
+    })
+}
+
 /// Dump a TypedModel as a NNEF tar file.
 ///
 /// `path` is a null-terminated utf-8 string pointer to the `.tar` file to be created.
@@ -282,6 +326,223 @@ pub unsafe extern "C" fn tract_onnx_model_for_path(
     })
 }
 
+/// Parse and load an ONNX model from a memory buffer as a tract InferenceModel.
+///
+/// `data` points to the `len` bytes of a `.onnx` model. The buffer only needs to be alive for the
+/// duration of the call. Models with external data must use
+/// `tract_onnx_model_for_bytes_with_external_data`.
+#[no_mangle]
+pub unsafe extern "C" fn tract_onnx_model_for_bytes(
+    onnx: *const TractOnnx,
+    data: *const c_void,
+    len: usize,
+    model: *mut *mut TractInferenceModel,
+) -> TRACT_RESULT {
+    tract_onnx_model_for_bytes_with_external_data(
+        onnx,
+        data,
+        len,
+        0,
+        std::ptr::null(),
+        std::ptr::null(),
+        std::ptr::null(),
+        model,
+    )
+}
+
+/// Parse and load an ONNX model and its external data from memory buffers as a tract
+/// InferenceModel.
+///
+/// `data` points to the `len` bytes of a `.onnx` model. `external_data_count` external data
+/// files are provided: `external_data_locations` are null-terminated utf-8 strings, matching the
+/// locations recorded in the model, and `external_data` and `external_data_lens` the matching
+/// buffers and their sizes in bytes. All buffers only need to be alive for the duration of the
+/// call.
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn tract_onnx_model_for_bytes_with_external_data(
+    onnx: *const TractOnnx,
+    data: *const c_void,
+    len: usize,
+    external_data_count: usize,
+    external_data_locations: *const *const c_char,
+    external_data: *const *const c_void,
+    external_data_lens: *const usize,
+    model: *mut *mut TractInferenceModel,
+) -> TRACT_RESULT {
+    wrap(|| unsafe {
+        check_not_null!(onnx, data, model);
+        *model = std::ptr::null_mut();
+        let data = std::slice::from_raw_parts(data as *const u8, len);
+        let mut external = vec![];
+        if external_data_count > 0 {
+            check_not_null!(external_data_locations, external_data, external_data_lens);
+            for ix in 0..external_data_count {
+                let location = CStr::from_ptr(*external_data_locations.add(ix)).to_str()?;
+                let bytes = std::slice::from_raw_parts(
+                    *external_data.add(ix) as *const u8,
+                    *external_data_lens.add(ix),
+                );
+                external.push((location, bytes));
+            }
+        }
+        let m = (*onnx).0.model_for_bytes_with_external_data(data, &external)?;
+        *model = Box::into_raw(Box::new(TractInferenceModel(m)));
+        Ok(())
+    })
+}
+
+// TFLITE
+pub struct TractTflite(tract_rs::Tflite);
+
+/// Creates an instance of a TFLite framework and parser that can be used to load and dump TFLite
+/// models.
+///
+/// The returned object should be destroyed with `tract_tflite_destroy` once the model
+/// has been loaded.
+#[no_mangle]
+pub unsafe extern "C" fn tract_tflite_create(tflite: *mut *mut TractTflite) -> TRACT_RESULT {
+    wrap(|| unsafe {
+        check_not_null!(tflite);
+        *tflite = Box::into_raw(Box::new(TractTflite(tract_rs::tflite()?)));
+        Ok(())
+    })
+}
+
+/// Destroy the TFLite parser. It is safe to detroy the TFLite parser once the model had been
+/// loaded.
+#[no_mangle]
+pub unsafe extern "C" fn tract_tflite_destroy(tflite: *mut *mut TractTflite) -> TRACT_RESULT {
+    release!(tflite)
+}
+
+/// Parse and load a TFLite model as a tract TypedModel.
+///
+/// `path` is a null-terminated utf-8 string pointer. It must point to a `.tflite` model file.
+#[no_mangle]
+pub unsafe extern "C" fn tract_tflite_model_for_path(
+    tflite: *const TractTflite,
+    path: *const c_char,
+    model: *mut *mut TractModel,
+) -> TRACT_RESULT {
+    wrap(|| unsafe {
+        check_not_null!(tflite, path, model);
+        *model = std::ptr::null_mut();
+        let path = CStr::from_ptr(path).to_str()?;
+        let m = Box::new(TractModel(
+            (*tflite).0.model_for_path(path).with_context(|| format!("opening file {path:?}"))?,
+        ));
+        *model = Box::into_raw(m);
+        Ok(())
+    })
+}
+
+/// Parse and load a TFLite model from a memory buffer as a tract TypedModel.
+///
+/// `data` points to the `len` bytes of a `.tflite` model. The buffer only needs to be alive for
+/// the duration of the call.
+#[no_mangle]
+pub unsafe extern "C" fn tract_tflite_model_for_bytes(
+    tflite: *const TractTflite,
+    data: *const c_void,
+    len: usize,
+    model: *mut *mut TractModel,
+) -> TRACT_RESULT {
+    wrap(|| unsafe {
+        check_not_null!(tflite, data, model);
+        *model = std::ptr::null_mut();
+        let data = std::slice::from_raw_parts(data as *const u8, len);
+        let m = Box::new(TractModel((*tflite).0.model_for_bytes(data)?));
+        *model = Box::into_raw(m);
+        Ok(())
+    })
+}
+
+/// Dump a TypedModel as a TFLite file.
+///
+/// `path` is a null-terminated utf-8 string pointer to the `.tflite` file to be created.
+#[no_mangle]
+pub unsafe extern "C" fn tract_tflite_write_model_to_path(
+    tflite: *const TractTflite,
+    path: *const c_char,
+    model: *const TractModel,
+) -> TRACT_RESULT {
+    wrap(|| unsafe {
+        check_not_null!(tflite, model, path);
+        let path = CStr::from_ptr(path).to_str()?;
+        (*tflite).0.write_model_to_path(path, &(*model).0)?;
+        Ok(())
+    })
+}
+
+// TENSORFLOW
+pub struct TractTensorflow(tract_rs::Tensorflow);
+
+/// Creates an instance of a TensorFlow framework and parser that can be used to load frozen
+/// graphs.
+///
+/// The returned object should be destroyed with `tract_tensorflow_destroy` once the model
+/// has been loaded.
+#[no_mangle]
+pub unsafe extern "C" fn tract_tensorflow_create(
+    tensorflow: *mut *mut TractTensorflow,
+) -> TRACT_RESULT {
+    wrap(|| unsafe {
+        check_not_null!(tensorflow);
+        *tensorflow = Box::into_raw(Box::new(TractTensorflow(tract_rs::tensorflow()?)));
+        Ok(())
+    })
+}
+
+/// Destroy the TensorFlow parser. It is safe to detroy the TensorFlow parser once the model had
+/// been loaded.
+#[no_mangle]
+pub unsafe extern "C" fn tract_tensorflow_destroy(
+    tensorflow: *mut *mut TractTensorflow,
+) -> TRACT_RESULT {
+    release!(tensorflow)
+}
+
+/// Parse and load a TensorFlow frozen graph as a tract InferenceModel.
+///
+/// `path` is a null-terminated utf-8 string pointer. It must point to a `.pb` frozen graph file.
+#[no_mangle]
+pub unsafe extern "C" fn tract_tensorflow_model_for_path(
+    tensorflow: *const TractTensorflow,
+    path: *const c_char,
+    model: *mut *mut TractInferenceModel,
+) -> TRACT_RESULT {
+    wrap(|| unsafe {
+        check_not_null!(tensorflow, path, model);
+        *model = std::ptr::null_mut();
+        let path = CStr::from_ptr(path).to_str()?;
+        let m = Box::new(TractInferenceModel((*tensorflow).0.model_for_path(path)?));
+        *model = Box::into_raw(m);
+        Ok(())
+    })
+}
+
+/// Parse and load a TensorFlow frozen graph from a memory buffer as a tract InferenceModel.
+///
+/// `data` points to the `len` bytes of a `.pb` frozen graph. The buffer only needs to be alive
+/// for the duration of the call.
+#[no_mangle]
+pub unsafe extern "C" fn tract_tensorflow_model_for_bytes(
+    tensorflow: *const TractTensorflow,
+    data: *const c_void,
+    len: usize,
+    model: *mut *mut TractInferenceModel,
+) -> TRACT_RESULT {
+    wrap(|| unsafe {
+        check_not_null!(tensorflow, data, model);
+        *model = std::ptr::null_mut();
+        let data = std::slice::from_raw_parts(data as *const u8, len);
+        let m = Box::new(TractInferenceModel((*tensorflow).0.model_for_bytes(data)?));
+        *model = Box::into_raw(m);
+        Ok(())
+    })
+}
+
 // INFERENCE MODEL
 pub struct TractInferenceModel(tract_rs::InferenceModel);
 
@@ -809,6 +1070,195 @@ pub unsafe extern "C" fn tract_model_destroy(model: *mut *mut TractModel) -> TRA
     release!(model)
 }
 
+// MODEL BUILDER
+pub struct TractModelBuilder(tract_rs::ModelBuilder);
+
+/// Start building a TypedModel from scratch.
+///
+/// Operators are resolved through the registries enabled on the `nnef` framework object.
+///
+/// The returned builder must be turned into a model with `tract_model_builder_into_model`, or
+/// destroyed with `tract_model_builder_destroy`.
+#[no_mangle]
+pub unsafe extern "C" fn tract_nnef_model_builder(
+    nnef: *const TractNnef,
+    builder: *mut *mut TractModelBuilder,
+) -> TRACT_RESULT {
+    wrap(|| unsafe {
+        check_not_null!(nnef, builder);
+        *builder = std::ptr::null_mut();
+        let b = (*nnef).0.model_builder()?;
+        *builder = Box::into_raw(Box::new(TractModelBuilder(b)));
+        Ok(())
+    })
+}
+
+/// Start editing an existing TypedModel.
+///
+/// This function transfers ownership of the `model` argument to the newly-created builder.
+#[no_mangle]
+pub unsafe extern "C" fn tract_nnef_model_builder_for_model(
+    nnef: *const TractNnef,
+    model: *mut *mut TractModel,
+    builder: *mut *mut TractModelBuilder,
+) -> TRACT_RESULT {
+    wrap(|| unsafe {
+        check_not_null!(nnef, model, *model, builder);
+        *builder = std::ptr::null_mut();
+        let m = Box::from_raw(*model).0;
+        *model = std::ptr::null_mut();
+        let b = (*nnef).0.model_builder_for_model(m)?;
+        *builder = Box::into_raw(Box::new(TractModelBuilder(b)));
+        Ok(())
+    })
+}
+
+/// Add an input to the model.
+///
+/// `name` and `fact` are null-terminated utf-8 strings, `fact` being a fact specification like
+/// `"1,3,224,224,f32"`.
+#[no_mangle]
+pub unsafe extern "C" fn tract_model_builder_add_input(
+    builder: *mut TractModelBuilder,
+    name: *const c_char,
+    fact: *const c_char,
+) -> TRACT_RESULT {
+    wrap(|| unsafe {
+        check_not_null!(builder, name, fact);
+        let name = CStr::from_ptr(name).to_str()?;
+        let fact = CStr::from_ptr(fact).to_str()?;
+        (*builder).0.add_input(name, fact)
+    })
+}
+
+/// Add a constant to the model.
+///
+/// `name` is a null-terminated utf-8 string. The `value` is copied, and remains owned by the caller.
+#[no_mangle]
+pub unsafe extern "C" fn tract_model_builder_add_const(
+    builder: *mut TractModelBuilder,
+    name: *const c_char,
+    value: *const TractValue,
+) -> TRACT_RESULT {
+    wrap(|| unsafe {
+        check_not_null!(builder, name, value);
+        let name = CStr::from_ptr(name).to_str()?;
+        (*builder).0.add_const(name, (*value).0.clone())
+    })
+}
+
+/// Replace the value of a constant of the model.
+///
+/// The new value must have the same datum type and shape as the current one. `name` is a
+/// null-terminated utf-8 string. The `value` is copied, and remains owned by the caller.
+#[no_mangle]
+pub unsafe extern "C" fn tract_model_builder_replace_const(
+    builder: *mut TractModelBuilder,
+    name: *const c_char,
+    value: *const TractValue,
+) -> TRACT_RESULT {
+    wrap(|| unsafe {
+        check_not_null!(builder, name, value);
+        let name = CStr::from_ptr(name).to_str()?;
+        (*builder).0.replace_const(name, (*value).0.clone())
+    })
+}
+
+/// Wire an operator, designated by its NNEF name, in the model.
+///
+/// * `name` is a null-terminated utf-8 string naming the operator outputs: `name` if it has a
+/// single output, `name:0`, `name:1`... otherwise.
+/// * `op` is the NNEF name of the operator, like `conv` or `tract_core_gelu`.
+/// * `inputs` is an array of `nb_inputs` wire names, passed to the operator as positional
+/// arguments.
+/// * `attribute_names` and `attribute_values` are arrays of `nb_attributes` strings, passed to
+/// the operator as named arguments, values being NNEF expressions like `[2, 2]` or `'constant'`.
+/// * if `nb_outputs` is not null, it receives the number of outputs of the operator.
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn tract_model_builder_wire(
+    builder: *mut TractModelBuilder,
+    name: *const c_char,
+    op: *const c_char,
+    nb_inputs: usize,
+    inputs: *const *const c_char,
+    nb_attributes: usize,
+    attribute_names: *const *const c_char,
+    attribute_values: *const *const c_char,
+    nb_outputs: *mut usize,
+) -> TRACT_RESULT {
+    wrap(|| unsafe {
+        check_not_null!(builder, name, op);
+        let name = CStr::from_ptr(name).to_str()?;
+        let op = CStr::from_ptr(op).to_str()?;
+        let mut input_names = vec![];
+        if nb_inputs > 0 {
+            check_not_null!(inputs);
+            for ix in 0..nb_inputs {
+                input_names.push(CStr::from_ptr(*inputs.add(ix)).to_str()?);
+            }
+        }
+        let mut attributes = vec![];
+        if nb_attributes > 0 {
+            check_not_null!(attribute_names, attribute_values);
+            for ix in 0..nb_attributes {
+                attributes.push((
+                    CStr::from_ptr(*attribute_names.add(ix)).to_str()?,
+                    CStr::from_ptr(*attribute_values.add(ix)).to_str()?,
+                ));
+            }
+        }
+        let outputs = (*builder).0.wire(name, op, &input_names, &attributes)?;
+        if !nb_outputs.is_null() {
+            *nb_outputs = outputs.len();
+        }
+        Ok(())
+    })
+}
+
+/// Set the model outputs.
+///
+/// `names` is an array containing `len` pointers to null terminated wire names.
+#[no_mangle]
+pub unsafe extern "C" fn tract_model_builder_set_output_names(
+    builder: *mut TractModelBuilder,
+    len: usize,
+    names: *const *const c_char,
+) -> TRACT_RESULT {
+    wrap(|| unsafe {
+        check_not_null!(builder, names);
+        let names = (0..len)
+            .map(|i| Ok(CStr::from_ptr(*names.add(i)).to_str()?.to_owned()))
+            .collect::<Result<Vec<_>>>()?;
+        (*builder).0.set_output_names(&names)
+    })
+}
+
+/// Convert a model builder into the TypedModel it has built.
+///
+/// This function transfers ownership of the `builder` argument to the newly-created `model`.
+#[no_mangle]
+pub unsafe extern "C" fn tract_model_builder_into_model(
+    builder: *mut *mut TractModelBuilder,
+    model: *mut *mut TractModel,
+) -> TRACT_RESULT {
+    wrap(|| unsafe {
+        check_not_null!(builder, *builder, model);
+        let b = Box::from_raw(*builder).0;
+        *builder = std::ptr::null_mut();
+        *model = Box::into_raw(Box::new(TractModel(b.into_model()?)));
+        Ok(())
+    })
+}
+
+/// Destroy a model builder.
+#[no_mangle]
+pub unsafe extern "C" fn tract_model_builder_destroy(
+    builder: *mut *mut TractModelBuilder,
+) -> TRACT_RESULT {
+    release!(builder)
+}
+
 // RUNNABLE MODEL
 pub struct TractRunnable(tract_rs::Runnable);
 
@@ -1006,11 +1456,119 @@ pub unsafe extern "C" fn tract_state_output_count(
     })
 }
 
+/// Serialize what a State carries from one run to the next (delay buffers, caches...).
+///
+/// `data` and `len` will be overwritten with a newly allocated buffer and its length. The buffer
+/// must be freed by the caller using `tract_free_bytes`.
+#[no_mangle]
+pub unsafe extern "C" fn tract_state_freeze(
+    state: *const TractState,
+    data: *mut *mut u8,
+    len: *mut usize,
+) -> TRACT_RESULT {
+    wrap(|| unsafe {
+        check_not_null!(state, data, len);
+        *data = std::ptr::null_mut();
+        let bytes = (*state).0.freeze()?.into_boxed_slice();
+        *len = bytes.len();
+        *data = Box::into_raw(bytes) as *mut u8;
+        Ok(())
+    })
+}
+
+/// Restore a State from the serialized form produced by `tract_state_freeze`.
+///
+/// `state` must be a State of the same model, possibly in another process. The buffer only needs
+/// to be alive for the duration of the call.
+#[no_mangle]
+pub unsafe extern "C" fn tract_state_thaw(
+    state: *mut TractState,
+    data: *const c_void,
+    len: usize,
+) -> TRACT_RESULT {
+    wrap(|| unsafe {
+        check_not_null!(state, data);
+        let data = std::slice::from_raw_parts(data as *const u8, len);
+        (*state).0.thaw(data)
+    })
+}
+
+/// Attach a cancellation token to a State, or detach it if `token` is null.
+///
+/// The token is checked between nodes of the following runs. An interrupted run returns
+/// `TRACT_RESULT_CANCELLED` or `TRACT_RESULT_DEADLINE_EXCEEDED`, and leaves the State to be
+/// destroyed. The token can be destroyed independently of the State.
+#[no_mangle]
+pub unsafe extern "C" fn tract_state_set_cancellation_token(
+    state: *mut TractState,
+    token: *const TractCancellationToken,
+) -> TRACT_RESULT {
+    wrap(|| unsafe {
+        check_not_null!(state);
+        (*state).0.set_cancellation_token(token.as_ref().map(|t| &t.0))
+    })
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn tract_state_destroy(state: *mut *mut TractState) -> TRACT_RESULT {
     release!(state)
 }
 
+// CANCELLATION TOKEN
+pub struct TractCancellationToken(CancellationToken);
+
+/// Create a cancellation token.
+///
+/// The token must be destroyed with `tract_cancellation_token_destroy`.
+#[no_mangle]
+pub unsafe extern "C" fn tract_cancellation_token_create(
+    token: *mut *mut TractCancellationToken,
+) -> TRACT_RESULT {
+    wrap(|| unsafe {
+        check_not_null!(token);
+        *token = Box::into_raw(Box::new(TractCancellationToken(CancellationToken::new()?)));
+        Ok(())
+    })
+}
+
+/// Create a cancellation token interrupting each run once `budget_ms` milliseconds have elapsed
+/// since the run started.
+///
+/// The token must be destroyed with `tract_cancellation_token_destroy`.
+#[no_mangle]
+pub unsafe extern "C" fn tract_cancellation_token_create_with_time_budget(
+    budget_ms: u64,
+    token: *mut *mut TractCancellationToken,
+) -> TRACT_RESULT {
+    wrap(|| unsafe {
+        check_not_null!(token);
+        let budget = std::time::Duration::from_millis(budget_ms);
+        let t = CancellationToken::with_time_budget(budget)?;
+        *token = Box::into_raw(Box::new(TractCancellationToken(t)));
+        Ok(())
+    })
+}
+
+/// Cancel the runs checking this token.
+///
+/// This function can be called from any thread, while a run is in progress.
+#[no_mangle]
+pub unsafe extern "C" fn tract_cancellation_token_cancel(
+    token: *const TractCancellationToken,
+) -> TRACT_RESULT {
+    wrap(|| unsafe {
+        check_not_null!(token);
+        (*token).0.cancel()
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn tract_cancellation_token_destroy(
+    token: *mut *mut TractCancellationToken,
+) -> TRACT_RESULT {
+    release!(token)
+}
+
 // FACT
 pub struct TractFact(tract_rs::Fact);
 
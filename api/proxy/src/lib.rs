@@ -11,11 +11,16 @@ use ndarray::*;
 macro_rules! check {
     ($expr:expr) => {
         unsafe {
-            if $expr == sys::TRACT_RESULT_TRACT_RESULT_KO {
+            let result = $expr;
+            if result == sys::TRACT_RESULT_TRACT_RESULT_OK {
+                Ok(())
+            } else if result == sys::TRACT_RESULT_TRACT_RESULT_CANCELLED {
+                Err(anyhow::Error::from(Cancelled::Requested))
+            } else if result == sys::TRACT_RESULT_TRACT_RESULT_DEADLINE_EXCEEDED {
+                Err(anyhow::Error::from(Cancelled::DeadlineExceeded))
+            } else {
                 let buf = CStr::from_ptr(sys::tract_get_last_error());
                 Err(anyhow::anyhow!(buf.to_string_lossy().to_string()))
-            } else {
-                Ok(())
             }
         }
     };
@@ -48,6 +53,18 @@ pub fn onnx() -> Result<Onnx> {
     Ok(Onnx(onnx))
 }
 
+pub fn tflite() -> Result<Tflite> {
+    let mut tflite = null_mut();
+    check!(sys::tract_tflite_create(&mut tflite))?;
+    Ok(Tflite(tflite))
+}
+
+pub fn tensorflow() -> Result<Tensorflow> {
+    let mut tensorflow = null_mut();
+    check!(sys::tract_tensorflow_create(&mut tensorflow))?;
+    Ok(Tensorflow(tensorflow))
+}
+
 pub fn version() -> &'static str {
     unsafe { CStr::from_ptr(sys::tract_version()).to_str().unwrap() }
 }
@@ -55,6 +72,7 @@ pub fn version() -> &'static str {
 wrapper!(Nnef, TractNnef, tract_nnef_destroy);
 impl NnefInterface for Nnef {
     type Model = Model;
+    type ModelBuilder = ModelBuilder;
     fn model_for_path(&self, path: impl AsRef<Path>) -> Result<Model> {
         let path = path.as_ref();
         let path = CString::new(
@@ -65,6 +83,17 @@ impl NnefInterface for Nnef {
         Ok(Model(model))
     }
 
+    fn model_for_bytes(&self, bytes: &[u8]) -> Result<Model> {
+        let mut model = null_mut();
+        check!(sys::tract_nnef_model_for_bytes(
+            self.0,
+            bytes.as_ptr() as _,
+            bytes.len(),
+            &mut model
+        ))?;
+        Ok(Model(model))
+    }
+
     fn enable_tract_core(&mut self) -> Result<()> {
         check!(sys::tract_nnef_enable_tract_core(self.0))
     }
@@ -111,6 +140,19 @@ impl NnefInterface for Nnef {
         check!(sys::tract_nnef_write_model_to_tar_gz(self.0, path.as_ptr(), model.0))?;
         Ok(())
     }
+
+    fn model_builder(&self) -> Result<ModelBuilder> {
+        let mut builder = null_mut();
+        check!(sys::tract_nnef_model_builder(self.0, &mut builder))?;
+        Ok(ModelBuilder(builder))
+    }
+
+    fn model_builder_for_model(&self, model: Model) -> Result<ModelBuilder> {
+        let mut model = model;
+        let mut builder = null_mut();
+        check!(sys::tract_nnef_model_builder_for_model(self.0, &mut model.0, &mut builder))?;
+        Ok(ModelBuilder(builder))
+    }
 }
 
 // ONNX
@@ -127,6 +169,93 @@ impl OnnxInterface for Onnx {
         check!(sys::tract_onnx_model_for_path(self.0, path.as_ptr(), &mut model))?;
         Ok(InferenceModel(model))
     }
+
+    fn model_for_bytes_with_external_data(
+        &self,
+        bytes: &[u8],
+        external_data: &[(&str, &[u8])],
+    ) -> Result<InferenceModel> {
+        let locations = external_data
+            .iter()
+            .map(|(location, _)| CString::new(*location))
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        let location_ptrs = locations.iter().map(|l| l.as_ptr()).collect::<Vec<_>>();
+        let data_ptrs = external_data.iter().map(|(_, d)| d.as_ptr() as _).collect::<Vec<_>>();
+        let data_lens = external_data.iter().map(|(_, d)| d.len()).collect::<Vec<_>>();
+        let mut model = null_mut();
+        check!(sys::tract_onnx_model_for_bytes_with_external_data(
+            self.0,
+            bytes.as_ptr() as _,
+            bytes.len(),
+            external_data.len(),
+            location_ptrs.as_ptr(),
+            data_ptrs.as_ptr(),
+            data_lens.as_ptr(),
+            &mut model
+        ))?;
+        Ok(InferenceModel(model))
+    }
+}
+
+// TFLITE
+wrapper!(Tflite, TractTflite, tract_tflite_destroy);
+impl TfliteInterface for Tflite {
+    type Model = Model;
+    fn model_for_path(&self, path: impl AsRef<Path>) -> Result<Model> {
+        let path = path.as_ref();
+        let path = CString::new(
+            path.to_str().with_context(|| format!("Failed to re-encode {path:?} to uff-8"))?,
+        )?;
+        let mut model = null_mut();
+        check!(sys::tract_tflite_model_for_path(self.0, path.as_ptr(), &mut model))?;
+        Ok(Model(model))
+    }
+
+    fn model_for_bytes(&self, bytes: &[u8]) -> Result<Model> {
+        let mut model = null_mut();
+        check!(sys::tract_tflite_model_for_bytes(
+            self.0,
+            bytes.as_ptr() as _,
+            bytes.len(),
+            &mut model
+        ))?;
+        Ok(Model(model))
+    }
+
+    fn write_model_to_path(&self, path: impl AsRef<Path>, model: &Model) -> Result<()> {
+        let path = path.as_ref();
+        let path = CString::new(
+            path.to_str().with_context(|| format!("Failed to re-encode {path:?} to uff-8"))?,
+        )?;
+        check!(sys::tract_tflite_write_model_to_path(self.0, path.as_ptr(), model.0))?;
+        Ok(())
+    }
+}
+
+// TENSORFLOW
+wrapper!(Tensorflow, TractTensorflow, tract_tensorflow_destroy);
+impl TensorflowInterface for Tensorflow {
+    type InferenceModel = InferenceModel;
+    fn model_for_path(&self, path: impl AsRef<Path>) -> Result<InferenceModel> {
+        let path = path.as_ref();
+        let path = CString::new(
+            path.to_str().with_context(|| format!("Failed to re-encode {path:?} to uff-8"))?,
+        )?;
+        let mut model = null_mut();
+        check!(sys::tract_tensorflow_model_for_path(self.0, path.as_ptr(), &mut model))?;
+        Ok(InferenceModel(model))
+    }
+
+    fn model_for_bytes(&self, bytes: &[u8]) -> Result<InferenceModel> {
+        let mut model = null_mut();
+        check!(sys::tract_tensorflow_model_for_bytes(
+            self.0,
+            bytes.as_ptr() as _,
+            bytes.len(),
+            &mut model
+        ))?;
+        Ok(InferenceModel(model))
+    }
 }
 
 // INFERENCE MODEL
@@ -408,6 +537,94 @@ impl ModelInterface for Model {
     }
 }
 
+// MODEL BUILDER
+wrapper!(ModelBuilder, TractModelBuilder, tract_model_builder_destroy);
+impl ModelBuilderInterface for ModelBuilder {
+    type Model = Model;
+    type Value = Value;
+
+    fn add_input(&mut self, name: impl AsRef<str>, fact: impl AsRef<str>) -> Result<()> {
+        let name = CString::new(name.as_ref())?;
+        let fact = CString::new(fact.as_ref())?;
+        check!(sys::tract_model_builder_add_input(self.0, name.as_ptr(), fact.as_ptr()))
+    }
+
+    fn add_const<V, E>(&mut self, name: impl AsRef<str>, value: V) -> Result<()>
+    where
+        V: TryInto<Value, Error = E>,
+        E: Into<anyhow::Error>,
+    {
+        let name = CString::new(name.as_ref())?;
+        let value: Value = value.try_into().map_err(Into::<anyhow::Error>::into)?;
+        check!(sys::tract_model_builder_add_const(self.0, name.as_ptr(), value.0))
+    }
+
+    fn replace_const<V, E>(&mut self, name: impl AsRef<str>, value: V) -> Result<()>
+    where
+        V: TryInto<Value, Error = E>,
+        E: Into<anyhow::Error>,
+    {
+        let name = CString::new(name.as_ref())?;
+        let value: Value = value.try_into().map_err(Into::<anyhow::Error>::into)?;
+        check!(sys::tract_model_builder_replace_const(self.0, name.as_ptr(), value.0))
+    }
+
+    fn wire(
+        &mut self,
+        name: impl AsRef<str>,
+        op: impl AsRef<str>,
+        inputs: &[&str],
+        attributes: &[(&str, &str)],
+    ) -> Result<Vec<String>> {
+        let name = name.as_ref();
+        let c_name = CString::new(name)?;
+        let op = CString::new(op.as_ref())?;
+        let inputs: Vec<CString> =
+            inputs.iter().map(|i| Ok(CString::new(*i)?)).collect::<Result<_>>()?;
+        let input_ptrs: Vec<_> = inputs.iter().map(|cs| cs.as_ptr()).collect();
+        let keys: Vec<CString> =
+            attributes.iter().map(|(k, _)| Ok(CString::new(*k)?)).collect::<Result<_>>()?;
+        let key_ptrs: Vec<_> = keys.iter().map(|cs| cs.as_ptr()).collect();
+        let values: Vec<CString> =
+            attributes.iter().map(|(_, v)| Ok(CString::new(*v)?)).collect::<Result<_>>()?;
+        let value_ptrs: Vec<_> = values.iter().map(|cs| cs.as_ptr()).collect();
+        let mut outputs = 0;
+        check!(sys::tract_model_builder_wire(
+            self.0,
+            c_name.as_ptr(),
+            op.as_ptr(),
+            input_ptrs.len(),
+            input_ptrs.as_ptr(),
+            key_ptrs.len(),
+            key_ptrs.as_ptr(),
+            value_ptrs.as_ptr(),
+            &mut outputs
+        ))?;
+        if outputs == 1 {
+            Ok(vec![name.to_string()])
+        } else {
+            Ok((0..outputs).map(|ix| format!("{name}:{ix}")).collect())
+        }
+    }
+
+    fn set_output_names(
+        &mut self,
+        outputs: impl IntoIterator<Item = impl AsRef<str>>,
+    ) -> Result<()> {
+        let c_strings: Vec<CString> =
+            outputs.into_iter().map(|a| Ok(CString::new(a.as_ref())?)).collect::<Result<_>>()?;
+        let ptrs: Vec<_> = c_strings.iter().map(|cs| cs.as_ptr()).collect();
+        check!(sys::tract_model_builder_set_output_names(self.0, c_strings.len(), ptrs.as_ptr()))
+    }
+
+    fn into_model(self) -> Result<Model> {
+        let mut builder = self;
+        let mut model = null_mut();
+        check!(sys::tract_model_builder_into_model(&mut builder.0, &mut model))?;
+        Ok(Model(model))
+    }
+}
+
 // RUNNABLE
 wrapper!(Runnable, TractRunnable, tract_runnable_release);
 
@@ -448,6 +665,8 @@ wrapper!(State, TractState, tract_state_destroy);
 
 impl StateInterface for State {
     type Value = Value;
+    type CancellationToken = CancellationToken;
+
     fn run<I, V, E>(&mut self, inputs: I) -> Result<Vec<Value>>
     where
         I: IntoIterator<Item = V>,
@@ -476,6 +695,51 @@ impl StateInterface for State {
         check!(sys::tract_state_output_count(self.0, &mut count))?;
         Ok(count)
     }
+
+    fn freeze(&self) -> Result<Vec<u8>> {
+        let mut data = null_mut();
+        let mut len = 0;
+        check!(sys::tract_state_freeze(self.0, &mut data, &mut len))?;
+        unsafe {
+            let bytes = std::slice::from_raw_parts(data, len).to_vec();
+            sys::tract_free_bytes(data, len);
+            Ok(bytes)
+        }
+    }
+
+    fn thaw(&mut self, bytes: &[u8]) -> Result<()> {
+        check!(sys::tract_state_thaw(self.0, bytes.as_ptr() as _, bytes.len()))
+    }
+
+    fn set_cancellation_token(&mut self, token: Option<&CancellationToken>) -> Result<()> {
+        check!(sys::tract_state_set_cancellation_token(self.0, token.map_or(null(), |t| t.0)))
+    }
+}
+
+// CANCELLATION TOKEN
+wrapper!(CancellationToken, TractCancellationToken, tract_cancellation_token_destroy);
+
+// the underlying token is an atomic flag, meant to be cancelled from any thread
+unsafe impl Send for CancellationToken {}
+unsafe impl Sync for CancellationToken {}
+
+impl CancellationTokenInterface for CancellationToken {
+    fn new() -> Result<Self> {
+        let mut token = null_mut();
+        check!(sys::tract_cancellation_token_create(&mut token))?;
+        Ok(CancellationToken(token))
+    }
+
+    fn with_time_budget(budget: std::time::Duration) -> Result<Self> {
+        let mut token = null_mut();
+        let budget_ms = budget.as_millis().try_into().unwrap_or(u64::MAX);
+        check!(sys::tract_cancellation_token_create_with_time_budget(budget_ms, &mut token))?;
+        Ok(CancellationToken(token))
+    }
+
+    fn cancel(&self) -> Result<()> {
+        check!(sys::tract_cancellation_token_cancel(self.0))
+    }
 }
 
 // VALUE
@@ -28,6 +28,9 @@
  * Used as a return type of functions that can encounter errors.
  * If the function encountered an error, you can retrieve it using the `tract_get_last_error`
  * function
+ *
+ * Any value other than `TRACT_RESULT_OK` is a failure: interrupted runs do not return
+ * `TRACT_RESULT_KO`, so callers must test for `!= TRACT_RESULT_OK`.
  */
 typedef enum TRACT_RESULT {
   /**
@@ -38,8 +41,18 @@ typedef enum TRACT_RESULT {
    * The function returned an error
    */
   TRACT_RESULT_KO = 1,
+  /**
+   * The run was interrupted by cancelling its cancellation token
+   */
+  TRACT_RESULT_CANCELLED = 2,
+  /**
+   * The run was interrupted as it ran past the time budget of its cancellation token
+   */
+  TRACT_RESULT_DEADLINE_EXCEEDED = 3,
 } TRACT_RESULT;
 
+typedef struct TractCancellationToken TractCancellationToken;
+
 typedef struct TractFact TractFact;
 
 typedef struct TractInferenceFact TractInferenceFact;
@@ -48,6 +61,8 @@ typedef struct TractInferenceModel TractInferenceModel;
 
 typedef struct TractModel TractModel;
 
+typedef struct TractModelBuilder TractModelBuilder;
+
 typedef struct TractNnef TractNnef;
 
 typedef struct TractOnnx TractOnnx;
@@ -56,11 +71,15 @@ typedef struct TractRunnable TractRunnable;
 
 typedef struct TractState TractState;
 
+typedef struct TractTensorflow TractTensorflow;
+
+typedef struct TractTflite TractTflite;
+
 typedef struct TractValue TractValue;
 
 /**
  * Retrieve the last error that happened in this thread. A function encountered an error if
- * its return type is of type `TRACT_RESULT` and it returned `TRACT_RESULT_KO`.
+ * its return type is of type `TRACT_RESULT` and it did not return `TRACT_RESULT_OK`.
  *
  * # Return value
  *  It returns a pointer to a null-terminated UTF-8 string that will contain the error description.
@@ -82,6 +101,11 @@ const char *tract_version(void);
  */
 void tract_free_cstring(char *ptr);
 
+/**
+ * Frees a byte buffer allocated by libtract. `len` must be the length returned with the buffer.
+ */
+void tract_free_bytes(uint8_t *ptr, uintptr_t len);
+
 /**
  * Creates an instance of an NNEF framework and parser that can be used to load and dump NNEF models.
  *
@@ -115,6 +139,17 @@ enum TRACT_RESULT tract_nnef_model_for_path(const struct TractNnef *nnef,
                                             const char *path,
                                             struct TractModel **model);
 
+/**
+ * Parse and load an NNEF model from a memory buffer as a tract TypedModel.
+ *
+ * `data` points to the `len` bytes of a tar or tar.gz archive. The buffer only needs to be alive
+ * for the duration of the call.
+ */
+enum TRACT_RESULT tract_nnef_model_for_bytes(const struct TractNnef *nnef,
+                                             const void *data,
+                                             uintptr_t len,
+                                             struct TractModel **model);
+
 /**
  * Dump a TypedModel as a NNEF tar file.
  *
@@ -168,6 +203,116 @@ enum TRACT_RESULT tract_onnx_model_for_path(const struct TractOnnx *onnx,
                                             const char *path,
                                             struct TractInferenceModel **model);
 
+/**
+ * Parse and load an ONNX model from a memory buffer as a tract InferenceModel.
+ *
+ * `data` points to the `len` bytes of a `.onnx` model. The buffer only needs to be alive for the
+ * duration of the call. Models with external data must use
+ * `tract_onnx_model_for_bytes_with_external_data`.
+ */
+enum TRACT_RESULT tract_onnx_model_for_bytes(const struct TractOnnx *onnx,
+                                             const void *data,
+                                             uintptr_t len,
+                                             struct TractInferenceModel **model);
+
+/**
+ * Parse and load an ONNX model and its external data from memory buffers as a tract
+ * InferenceModel.
+ *
+ * `data` points to the `len` bytes of a `.onnx` model. `external_data_count` external data
+ * files are provided: `external_data_locations` are null-terminated utf-8 strings, matching the
+ * locations recorded in the model, and `external_data` and `external_data_lens` the matching
+ * buffers and their sizes in bytes. All buffers only need to be alive for the duration of the
+ * call.
+ */
+enum TRACT_RESULT tract_onnx_model_for_bytes_with_external_data(const struct TractOnnx *onnx,
+                                                                const void *data,
+                                                                uintptr_t len,
+                                                                uintptr_t external_data_count,
+                                                                const char *const *external_data_locations,
+                                                                const void *const *external_data,
+                                                                const uintptr_t *external_data_lens,
+                                                                struct TractInferenceModel **model);
+
+/**
+ * Creates an instance of a TFLite framework and parser that can be used to load and dump TFLite
+ * models.
+ *
+ * The returned object should be destroyed with `tract_tflite_destroy` once the model
+ * has been loaded.
+ */
+enum TRACT_RESULT tract_tflite_create(struct TractTflite **tflite);
+
+/**
+ * Destroy the TFLite parser. It is safe to detroy the TFLite parser once the model had been
+ * loaded.
+ */
+enum TRACT_RESULT tract_tflite_destroy(struct TractTflite **tflite);
+
+/**
+ * Parse and load a TFLite model as a tract TypedModel.
+ *
+ * `path` is a null-terminated utf-8 string pointer. It must point to a `.tflite` model file.
+ */
+enum TRACT_RESULT tract_tflite_model_for_path(const struct TractTflite *tflite,
+                                              const char *path,
+                                              struct TractModel **model);
+
+/**
+ * Parse and load a TFLite model from a memory buffer as a tract TypedModel.
+ *
+ * `data` points to the `len` bytes of a `.tflite` model. The buffer only needs to be alive for
+ * the duration of the call.
+ */
+enum TRACT_RESULT tract_tflite_model_for_bytes(const struct TractTflite *tflite,
+                                               const void *data,
+                                               uintptr_t len,
+                                               struct TractModel **model);
+
+/**
+ * Dump a TypedModel as a TFLite file.
+ *
+ * `path` is a null-terminated utf-8 string pointer to the `.tflite` file to be created.
+ */
+enum TRACT_RESULT tract_tflite_write_model_to_path(const struct TractTflite *tflite,
+                                                   const char *path,
+                                                   const struct TractModel *model);
+
+/**
+ * Creates an instance of a TensorFlow framework and parser that can be used to load frozen
+ * graphs.
+ *
+ * The returned object should be destroyed with `tract_tensorflow_destroy` once the model
+ * has been loaded.
+ */
+enum TRACT_RESULT tract_tensorflow_create(struct TractTensorflow **tensorflow);
+
+/**
+ * Destroy the TensorFlow parser. It is safe to detroy the TensorFlow parser once the model had
+ * been loaded.
+ */
+enum TRACT_RESULT tract_tensorflow_destroy(struct TractTensorflow **tensorflow);
+
+/**
+ * Parse and load a TensorFlow frozen graph as a tract InferenceModel.
+ *
+ * `path` is a null-terminated utf-8 string pointer. It must point to a `.pb` frozen graph file.
+ */
+enum TRACT_RESULT tract_tensorflow_model_for_path(const struct TractTensorflow *tensorflow,
+                                                  const char *path,
+                                                  struct TractInferenceModel **model);
+
+/**
+ * Parse and load a TensorFlow frozen graph from a memory buffer as a tract InferenceModel.
+ *
+ * `data` points to the `len` bytes of a `.pb` frozen graph. The buffer only needs to be alive
+ * for the duration of the call.
+ */
+enum TRACT_RESULT tract_tensorflow_model_for_bytes(const struct TractTensorflow *tensorflow,
+                                                   const void *data,
+                                                   uintptr_t len,
+                                                   struct TractInferenceModel **model);
+
 /**
  * Query an InferenceModel input counts.
  */
@@ -407,6 +552,99 @@ enum TRACT_RESULT tract_model_property(const struct TractModel *model,
  */
 enum TRACT_RESULT tract_model_destroy(struct TractModel **model);
 
+/**
+ * Start building a TypedModel from scratch.
+ *
+ * Operators are resolved through the registries enabled on the `nnef` framework object.
+ *
+ * The returned builder must be turned into a model with `tract_model_builder_into_model`, or
+ * destroyed with `tract_model_builder_destroy`.
+ */
+enum TRACT_RESULT tract_nnef_model_builder(const struct TractNnef *nnef,
+                                           struct TractModelBuilder **builder);
+
+/**
+ * Start editing an existing TypedModel.
+ *
+ * This function transfers ownership of the `model` argument to the newly-created builder.
+ */
+enum TRACT_RESULT tract_nnef_model_builder_for_model(const struct TractNnef *nnef,
+                                                     struct TractModel **model,
+                                                     struct TractModelBuilder **builder);
+
+/**
+ * Add an input to the model.
+ *
+ * `name` and `fact` are null-terminated utf-8 strings, `fact` being a fact specification like
+ * `"1,3,224,224,f32"`.
+ */
+enum TRACT_RESULT tract_model_builder_add_input(struct TractModelBuilder *builder,
+                                                const char *name,
+                                                const char *fact);
+
+/**
+ * Add a constant to the model.
+ *
+ * `name` is a null-terminated utf-8 string. The `value` is copied, and remains owned by the caller.
+ */
+enum TRACT_RESULT tract_model_builder_add_const(struct TractModelBuilder *builder,
+                                                const char *name,
+                                                const struct TractValue *value);
+
+/**
+ * Replace the value of a constant of the model.
+ *
+ * The new value must have the same datum type and shape as the current one. `name` is a
+ * null-terminated utf-8 string. The `value` is copied, and remains owned by the caller.
+ */
+enum TRACT_RESULT tract_model_builder_replace_const(struct TractModelBuilder *builder,
+                                                    const char *name,
+                                                    const struct TractValue *value);
+
+/**
+ * Wire an operator, designated by its NNEF name, in the model.
+ *
+ * * `name` is a null-terminated utf-8 string naming the operator outputs: `name` if it has a
+ * single output, `name:0`, `name:1`... otherwise.
+ * * `op` is the NNEF name of the operator, like `conv` or `tract_core_gelu`.
+ * * `inputs` is an array of `nb_inputs` wire names, passed to the operator as positional
+ * arguments.
+ * * `attribute_names` and `attribute_values` are arrays of `nb_attributes` strings, passed to
+ * the operator as named arguments, values being NNEF expressions like `[2, 2]` or `'constant'`.
+ * * if `nb_outputs` is not null, it receives the number of outputs of the operator.
+ */
+enum TRACT_RESULT tract_model_builder_wire(struct TractModelBuilder *builder,
+                                           const char *name,
+                                           const char *op,
+                                           uintptr_t nb_inputs,
+                                           const char *const *inputs,
+                                           uintptr_t nb_attributes,
+                                           const char *const *attribute_names,
+                                           const char *const *attribute_values,
+                                           uintptr_t *nb_outputs);
+
+/**
+ * Set the model outputs.
+ *
+ * `names` is an array containing `len` pointers to null terminated wire names.
+ */
+enum TRACT_RESULT tract_model_builder_set_output_names(struct TractModelBuilder *builder,
+                                                       uintptr_t len,
+                                                       const char *const *names);
+
+/**
+ * Convert a model builder into the TypedModel it has built.
+ *
+ * This function transfers ownership of the `builder` argument to the newly-created `model`.
+ */
+enum TRACT_RESULT tract_model_builder_into_model(struct TractModelBuilder **builder,
+                                                 struct TractModel **model);
+
+/**
+ * Destroy a model builder.
+ */
+enum TRACT_RESULT tract_model_builder_destroy(struct TractModelBuilder **builder);
+
 /**
  * Spawn a session state from a runnable model.
  *
@@ -502,8 +740,61 @@ enum TRACT_RESULT tract_state_input_count(const struct TractState *state, uintpt
  */
 enum TRACT_RESULT tract_state_output_count(const struct TractState *state, uintptr_t *outputs);
 
+/**
+ * Serialize what a State carries from one run to the next (delay buffers, caches...).
+ *
+ * `data` and `len` will be overwritten with a newly allocated buffer and its length. The buffer
+ * must be freed by the caller using `tract_free_bytes`.
+ */
+enum TRACT_RESULT tract_state_freeze(const struct TractState *state,
+                                     uint8_t **data,
+                                     uintptr_t *len);
+
+/**
+ * Restore a State from the serialized form produced by `tract_state_freeze`.
+ *
+ * `state` must be a State of the same model, possibly in another process. The buffer only needs
+ * to be alive for the duration of the call.
+ */
+enum TRACT_RESULT tract_state_thaw(struct TractState *state, const void *data, uintptr_t len);
+
+/**
+ * Attach a cancellation token to a State, or detach it if `token` is null.
+ *
+ * The token is checked between nodes of the following runs. An interrupted run returns
+ * `TRACT_RESULT_CANCELLED` or `TRACT_RESULT_DEADLINE_EXCEEDED`, and leaves the State to be
+ * destroyed. The token can be destroyed independently of the State.
+ */
+enum TRACT_RESULT tract_state_set_cancellation_token(struct TractState *state,
+                                                     const struct TractCancellationToken *token);
+
 enum TRACT_RESULT tract_state_destroy(struct TractState **state);
 
+/**
+ * Create a cancellation token.
+ *
+ * The token must be destroyed with `tract_cancellation_token_destroy`.
+ */
+enum TRACT_RESULT tract_cancellation_token_create(struct TractCancellationToken **token);
+
+/**
+ * Create a cancellation token interrupting each run once `budget_ms` milliseconds have elapsed
+ * since the run started.
+ *
+ * The token must be destroyed with `tract_cancellation_token_destroy`.
+ */
+enum TRACT_RESULT tract_cancellation_token_create_with_time_budget(uint64_t budget_ms,
+                                                                   struct TractCancellationToken **token);
+
+/**
+ * Cancel the runs checking this token.
+ *
+ * This function can be called from any thread, while a run is in progress.
+ */
+enum TRACT_RESULT tract_cancellation_token_cancel(const struct TractCancellationToken *token);
+
+enum TRACT_RESULT tract_cancellation_token_destroy(struct TractCancellationToken **token);
+
 /**
  * Parse a fact specification string into an Fact.
  *
@@ -47,6 +47,13 @@ def test_state():
     confidences = result[0].to_numpy()
     assert numpy.argmax(confidences) == 652
 
+def test_onnx_for_bytes():
+    with open("./mobilenetv2-7.onnx", "rb") as f:
+        model = tract.onnx().model_for_bytes(f.read()).into_optimized().into_runnable()
+    result = model.run([grace_hopper_1x3x224x244()])
+    confidences = result[0].to_numpy()
+    assert numpy.argmax(confidences) == 652
+
 def test_nnef_register():
     tract.nnef().with_tract_core().with_onnx().with_pulse().with_tract_extra()
 
@@ -61,6 +68,13 @@ def test_nnef():
     confidences = result[0].to_numpy()
     assert numpy.argmax(confidences) == 652
 
+def test_nnef_for_bytes():
+    with open("mobilenet_v2_1.0.onnx.nnef.tgz", "rb") as f:
+        model = tract.nnef().model_for_bytes(f.read()).into_optimized().into_runnable()
+    result = model.run([grace_hopper_1x3x224x244()])
+    confidences = result[0].to_numpy()
+    assert numpy.argmax(confidences) == 652
+
 def test_inference_model():
     model = tract.onnx().model_for_path("./mobilenetv2-7.onnx")
     assert model.input_count() == 1
@@ -175,6 +189,23 @@ def test_typed_model_to_nnef_and_back():
         assert str(reloaded.input_fact(0)) == "B,3,224,224,F32"
         assert str(reloaded.output_fact(0)) == "B,1000,F32"
 
+def test_typed_model_to_tflite_and_back():
+    model = tract.onnx().model_for_path("./mobilenetv2-7.onnx")
+    model.set_input_fact(0, "1,3,224,224,f32")
+    model.analyse()
+    typed = model.into_typed()
+    typed.declutter()
+    with tempfile.TemporaryDirectory() as tmpdirname:
+        path = Path(tmpdirname) / "mobilenet.tflite"
+        tflite = tract.tflite()
+        tflite.write_model_to_path(typed, path)
+        reloaded = tflite.model_for_path(path)
+        assert str(reloaded.input_fact(0)) == "1,3,224,224,F32"
+        reloaded = tflite.model_for_bytes(path.read_bytes()).into_optimized().into_runnable()
+        result = reloaded.run([grace_hopper_1x3x224x244()])
+        confidences = result[0].to_numpy()
+        assert numpy.argmax(confidences) == 652
+
 def test_cost():
     model = tract.nnef().model_for_path("mobilenet_v2_1.0.onnx.nnef.tgz")
     assert str(model.input_fact(0)) == "1,3,224,224,F32"
@@ -203,3 +234,64 @@ def test_profile():
     if "secs_per_iter" in profile["nodes"][0]:
         assert profile["nodes"][0]["secs_per_iter"] >= 0
     assert next(filter(lambda node: "cost" in node and "FMA(F32)" in node["cost"], profile["nodes"]), None) != None
+
+def test_model_builder():
+    nnef = tract.nnef().with_tract_core()
+    builder = nnef.model_builder()
+    builder.add_input("x", "2,3,f32")
+    builder.add_const("w", numpy.array([[1], [2], [3]], dtype=numpy.float32))
+    builder.wire("mm", "matmul", ["x", "w"])
+    assert builder.wire("s", "unstack", ["x"], {"axis": "1"}) == ["s:0", "s:1", "s:2"]
+    builder.set_output_names(["mm", "s:1"])
+    model = builder.into_model()
+
+    builder = nnef.model_builder_for_model(model)
+    builder.replace_const("w", numpy.ones((3, 1), dtype=numpy.float32))
+    runnable = builder.into_model().into_optimized().into_runnable()
+    x = numpy.array([[1, 2, 3], [4, 5, 6]], dtype=numpy.float32)
+    result = runnable.run([x])
+    assert numpy.array_equal(result[0].to_numpy(), [[6], [15]])
+    assert numpy.array_equal(result[1].to_numpy(), [2, 5])
+
+def test_state_freeze_and_thaw():
+    builder = tract.nnef().with_pulse().model_builder()
+    builder.add_input("x", "4,f32")
+    builder.wire("y", "tract_pulse_delay", ["x"], {"axis": "0", "delay": "2", "overlap": "0"})
+    builder.set_output_names(["y"])
+    runnable = builder.into_model().into_optimized().into_runnable()
+    state = runnable.spawn_state()
+    state.run([numpy.array([1, 2, 3, 4], dtype=numpy.float32)])
+    frozen = state.freeze()
+
+    thawed = runnable.spawn_state()
+    thawed.thaw(frozen)
+    result = thawed.run([numpy.array([5, 6, 7, 8], dtype=numpy.float32)])
+    assert numpy.array_equal(result[0].to_numpy(), [3, 4, 5, 6])
+
+def test_cancellation_token():
+    builder = tract.nnef().with_tract_core().model_builder()
+    builder.add_input("x", "2,f32")
+    builder.wire("y", "add", ["x", "x"])
+    builder.set_output_names(["y"])
+    runnable = builder.into_model().into_optimized().into_runnable()
+    state = runnable.spawn_state()
+    x = numpy.array([1, 2], dtype=numpy.float32)
+    token = tract.CancellationToken()
+    state.set_cancellation_token(token)
+    state.run([x])
+    token.cancel()
+    try:
+        state.run([x])
+        assert False, "run should have been cancelled"
+    except tract.TractDeadlineExceeded:
+        assert False, "run should have been cancelled, not timed out"
+    except tract.TractCancelled:
+        pass
+
+    state = runnable.spawn_state()
+    state.set_cancellation_token(tract.CancellationToken(time_budget=0))
+    try:
+        state.run([x])
+        assert False, "run should have timed out"
+    except tract.TractDeadlineExceeded:
+        pass
@@ -42,14 +42,18 @@ from ctypes import *
 from pathlib import Path
 from typing import Dict, List, Union
 
-from .bindings import check, lib, TractError
+from .bindings import check, lib, TractError, TractCancelled, TractDeadlineExceeded
+from .cancellation_token import CancellationToken
 from .value import Value
 from .fact import Fact, InferenceFact
 from .model import Model
+from .model_builder import ModelBuilder
 from .inference_model import InferenceModel
 from .runnable import Runnable
 from .nnef import Nnef
 from .onnx import Onnx
+from .tflite import Tflite
+from .tensorflow import Tensorflow
 
 def version() -> str:
     """Return the version string of `tract` native library"""
@@ -63,3 +67,11 @@ def onnx() -> Onnx:
     """Return a newly-created ONNX context for loading models"""
     return Onnx()
 
+def tflite() -> Tflite:
+    """Return a newly-created TFLite context for loading and saving models"""
+    return Tflite()
+
+def tensorflow() -> Tensorflow:
+    """Return a newly-created TensorFlow context for loading frozen graphs"""
+    return Tensorflow()
+
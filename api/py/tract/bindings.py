@@ -13,11 +13,28 @@ lib = cdll.LoadLibrary(str(dylib_path))
 lib.tract_version.restype = c_char_p
 lib.tract_get_last_error.restype = c_char_p
 lib.tract_free_cstring.restype = None
+lib.tract_free_bytes.restype = None
 
 class TractError(Exception):
     pass
 
+class TractCancelled(TractError):
+    """Raised by runs interrupted through a `CancellationToken`."""
+    pass
+
+class TractDeadlineExceeded(TractCancelled):
+    """Raised by runs interrupted as they ran past the time budget of their `CancellationToken`."""
+    pass
+
+# TRACT_RESULT codes, see tract.h
+TRACT_RESULT_CANCELLED = 2
+TRACT_RESULT_DEADLINE_EXCEEDED = 3
+
 def check(err):
+    if err == TRACT_RESULT_CANCELLED:
+        raise TractCancelled(str(lib.tract_get_last_error(), "utf-8"))
+    if err == TRACT_RESULT_DEADLINE_EXCEEDED:
+        raise TractDeadlineExceeded(str(lib.tract_get_last_error(), "utf-8"))
     if err != 0:
         raise TractError(str(lib.tract_get_last_error(), "utf-8"))
 
@@ -0,0 +1,36 @@
+from ctypes import *
+from typing import Optional
+from .bindings import check, lib
+
+class CancellationToken:
+    """
+    A flag, with an optional time budget, interrupting the runs of the states it is attached to.
+
+    It can be cancelled from another thread while a run is in progress.
+
+    ```python
+    token = tract.CancellationToken(time_budget=0.5)
+    state.set_cancellation_token(token)
+    try:
+        state.run([input])
+    except tract.TractCancelled:
+        ...
+    ```
+    """
+
+    def __init__(self, time_budget: Optional[float] = None):
+        """Create a token, cancelling each run `time_budget` seconds after it started, if provided."""
+        self.ptr = c_void_p()
+        if time_budget is None:
+            check(lib.tract_cancellation_token_create(byref(self.ptr)))
+        else:
+            budget_ms = c_uint64(max(0, int(time_budget * 1000)))
+            check(lib.tract_cancellation_token_create_with_time_budget(budget_ms, byref(self.ptr)))
+
+    def __del__(self):
+        if self.ptr:
+            check(lib.tract_cancellation_token_destroy(byref(self.ptr)))
+
+    def cancel(self) -> None:
+        """Interrupt the runs checking this token."""
+        check(lib.tract_cancellation_token_cancel(self.ptr))
@@ -0,0 +1,112 @@
+import numpy
+from ctypes import *
+from typing import Dict, List, Union
+from .bindings import check, lib, TractError
+from .value import Value
+from .model import Model
+
+class ModelBuilder:
+    """
+    Build or edit a Model operator by operator.
+
+    A ModelBuilder is obtained from an Nnef context, with `model_builder()` to start from an empty
+    model, or `model_builder_for_model()` to edit an existing one. Operators are designated by
+    their NNEF name, and are resolved using the registries enabled on the context (`with_tract_core()`...).
+
+    Wires are designated by name: inputs and constants by the name they have been given, operator
+    outputs by the name given to `wire()` (or `name:0`, `name:1`... for operators with several
+    outputs).
+
+    ```python
+    builder = tract.nnef().with_tract_core().model_builder()
+    builder.add_input("x", "2,3,f32")
+    builder.add_const("w", numpy.ones((3, 1), dtype=numpy.float32))
+    builder.wire("y", "matmul", ["x", "w"])
+    builder.set_output_names(["y"])
+    model = builder.into_model()
+    ```
+    """
+
+    def __init__(self, ptr):
+        self.ptr = ptr
+
+    def __del__(self):
+        if self.ptr:
+            check(lib.tract_model_builder_destroy(byref(self.ptr)))
+
+    def _valid(self):
+        if self.ptr == None:
+            raise TractError("invalid model builder (maybe already consumed ?)")
+
+    def add_input(self, name: str, fact: str) -> None:
+        """Add an input to the model, with a fact specification like `1,3,224,224,f32`."""
+        self._valid()
+        check(lib.tract_model_builder_add_input(self.ptr, str(name).encode("utf-8"), str(fact).encode("utf-8")))
+
+    def add_const(self, name: str, value: Union[Value, numpy.ndarray]) -> None:
+        """Add a constant to the model."""
+        self._valid()
+        value = self._value(value)
+        check(lib.tract_model_builder_add_const(self.ptr, str(name).encode("utf-8"), value.ptr))
+
+    def replace_const(self, name: str, value: Union[Value, numpy.ndarray]) -> None:
+        """Replace the value of an existing constant, keeping its type and shape."""
+        self._valid()
+        value = self._value(value)
+        check(lib.tract_model_builder_replace_const(self.ptr, str(name).encode("utf-8"), value.ptr))
+
+    def wire(self, name: str, op: str, inputs: List[str], attributes: Dict[str, str] = {}) -> List[str]:
+        """
+        Wire the operator named `op` in NNEF, and return the names of its outputs.
+
+        `inputs` are names of existing wires, passed as positional arguments. `attributes` are
+        passed as named arguments, and their values are NNEF expressions, like `"[1, 1]"` or
+        `"'constant'"`.
+        """
+        self._valid()
+        inputs_str = [str(i).encode("utf-8") for i in inputs]
+        inputs_ptr = (c_char_p * len(inputs_str))(*inputs_str)
+        keys_str = [str(k).encode("utf-8") for k in attributes.keys()]
+        keys_ptr = (c_char_p * len(keys_str))(*keys_str)
+        values_str = [str(v).encode("utf-8") for v in attributes.values()]
+        values_ptr = (c_char_p * len(values_str))(*values_str)
+        outputs = c_size_t()
+        check(lib.tract_model_builder_wire(
+            self.ptr,
+            str(name).encode("utf-8"),
+            str(op).encode("utf-8"),
+            len(inputs_str),
+            inputs_ptr,
+            len(keys_str),
+            keys_ptr,
+            values_ptr,
+            byref(outputs)
+        ))
+        if outputs.value == 1:
+            return [name]
+        return [f"{name}:{ix}" for ix in range(outputs.value)]
+
+    def set_output_names(self, names: List[str]) -> None:
+        """Designate the outputs of the model"""
+        self._valid()
+        nb = len(names)
+        names_str = []
+        names_ptr = (c_char_p * nb)()
+        for ix, n in enumerate(names):
+            names_str.append(str(n).encode("utf-8"))
+            names_ptr[ix] = names_str[ix]
+        check(lib.tract_model_builder_set_output_names(self.ptr, nb, names_ptr))
+
+    def into_model(self) -> Model:
+        """Consume the builder and return the model"""
+        self._valid()
+        model = c_void_p()
+        check(lib.tract_model_builder_into_model(byref(self.ptr), byref(model)))
+        return Model(model)
+
+    def _value(self, value: Union[Value, numpy.ndarray]) -> Value:
+        if isinstance(value, Value):
+            return value
+        elif isinstance(value, numpy.ndarray):
+            return Value.from_numpy(value)
+        raise TractError(f"Constants must be of type tract.Value or numpy.Array, got {value}")
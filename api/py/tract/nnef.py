@@ -3,6 +3,7 @@ from pathlib import Path
 from typing import Dict, List, Union
 from .bindings import check, lib
 from .model import Model
+from .model_builder import ModelBuilder
 
 class Nnef:
     """
@@ -48,6 +49,42 @@ class Nnef:
         check(lib.tract_nnef_model_for_path(self.ptr, path, byref(model)))
         return Model(model)
 
+    def model_for_bytes(self, data: bytes) -> Model:
+        """
+        Load an NNEF model from the content of a `tar` or `tar.gz` archive
+
+        ```python
+        with open("mobilenet_v2_1.0.onnx.nnef.tgz", "rb") as f:
+            model = tract.nnef().model_for_bytes(f.read())
+        ```
+        """
+        self._valid()
+        model = c_void_p()
+        data = bytes(data)
+        check(lib.tract_nnef_model_for_bytes(self.ptr, data, c_size_t(len(data)), byref(model)))
+        return Model(model)
+
+    def model_builder(self) -> ModelBuilder:
+        """
+        Start building a new model, using the operators known to this context.
+        """
+        self._valid()
+        builder = c_void_p()
+        check(lib.tract_nnef_model_builder(self.ptr, byref(builder)))
+        return ModelBuilder(builder)
+
+    def model_builder_for_model(self, model: Model) -> ModelBuilder:
+        """
+        Edit an existing model, using the operators known to this context. The model is consumed.
+        """
+        self._valid()
+        model._valid()
+        if not isinstance(model, Model):
+            raise TractError("Expected a Model, called with " + model);
+        builder = c_void_p()
+        check(lib.tract_nnef_model_builder_for_model(self.ptr, byref(model.ptr), byref(builder)))
+        return ModelBuilder(builder)
+
     def with_tract_core(self) -> "Nnef":
         """
         Enable tract-opl extensions to NNEF to covers tract-core operator set
@@ -1,6 +1,6 @@
 from ctypes import *
 from pathlib import Path
-from typing import Dict, List, Union
+from typing import Dict, List, Optional, Union
 from .bindings import check, lib
 from .inference_model import InferenceModel
 
@@ -43,3 +43,31 @@ class Onnx:
         path = str(path).encode("utf-8")
         check(lib.tract_onnx_model_for_path(self.ptr, path, byref(model)))
         return InferenceModel(model)
+
+    def model_for_bytes(
+        self, data: bytes, external_data: Optional[Dict[str, bytes]] = None
+    ) -> InferenceModel:
+        """
+        Load an ONNX model from the content of an ONNX file as an InferenceModel
+
+        If the model stores some of its tensors as external data, `external_data` must map
+        the locations recorded in the model to the content of the corresponding files.
+        """
+        model = c_void_p()
+        data = bytes(data)
+        external_data = { k: bytes(v) for k, v in (external_data or {}).items() }
+        count = len(external_data)
+        locations = (c_char_p * count)(*[k.encode("utf-8") for k in external_data.keys()])
+        buffers = (c_char_p * count)(*external_data.values())
+        lens = (c_size_t * count)(*[len(v) for v in external_data.values()])
+        check(lib.tract_onnx_model_for_bytes_with_external_data(
+            self.ptr,
+            data,
+            c_size_t(len(data)),
+            c_size_t(count),
+            locations,
+            buffers,
+            lens,
+            byref(model)
+        ))
+        return InferenceModel(model)
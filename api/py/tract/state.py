@@ -1,7 +1,8 @@
 import numpy
 from ctypes import *
-from typing import Dict, List, Union
+from typing import Dict, List, Optional, Union
 from .bindings import check, lib
+from .cancellation_token import CancellationToken
 from .fact import Fact
 from .value import Value
 
@@ -56,28 +57,33 @@ class State:
             result.append(Value(c_void_p(v)))
         return result
 
-    def freeze(self) -> "FrozenState":
-        self._valid()
-        frozen = c_void_p()
-        check(lib.tract_state_freeze(self.ptr, byref(frozen)))
-        return FrozenState(frozen)
+    def freeze(self) -> bytes:
+        """
+        Serialize what the state carries from one run to the next (delay buffers, caches...).
 
-class FrozenState:
-    """
-    The state of a stateful model.
-    """
-    def __init__(self, ptr):
-        self.ptr = ptr
+        The result can be restored with `thaw()` in a state of the same model, possibly in another
+        process.
+        """
+        self._valid()
+        data = POINTER(c_uint8)()
+        length = c_size_t()
+        check(lib.tract_state_freeze(self.ptr, byref(data), byref(length)))
+        result = string_at(data, length.value)
+        lib.tract_free_bytes(data, length)
+        return result
 
-    def __del__(self):
-        check(lib.tract_frozen_state_destroy(byref(self.ptr)))
+    def thaw(self, data: bytes) -> None:
+        """Restore a state serialized with `freeze()`"""
+        self._valid()
+        data = bytes(data)
+        check(lib.tract_state_thaw(self.ptr, data, c_size_t(len(data))))
 
-    def _valid(self):
-        if self.ptr == None:
-            raise TractError("invalid frozen state (maybe already destroyed ?)")
+    def set_cancellation_token(self, token: Optional[CancellationToken]) -> None:
+        """
+        Check `token` between nodes of the following runs, or stop checking if `token` is None.
 
-    def unfreeze(self) -> State:
+        Interrupted runs raise `TractCancelled`, or `TractDeadlineExceeded` past the token time
+        budget, and leave the state to be discarded.
+        """
         self._valid()
-        state = c_void_p()
-        check(lib.tract_frozen_state_unfreeze(self.ptr, byref(state)))
-        return State(state)
+        check(lib.tract_state_set_cancellation_token(self.ptr, token.ptr if token is not None else None))
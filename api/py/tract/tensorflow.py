@@ -0,0 +1,45 @@
+from ctypes import *
+from pathlib import Path
+from typing import Dict, List, Union
+from .bindings import check, lib
+from .inference_model import InferenceModel
+
+class Tensorflow:
+    """
+    Represent the TensorFlow context in tract.
+
+    It allows to load TensorFlow 1.x frozen graphs (`.pb` files). Like ONNX models, they are
+    loaded as an `InferenceModel`, as they often lack shape and element type information.
+
+    ```python
+    model = tract.tensorflow().model_for_path("./mobilenet_v2_1.4_224_frozen.pb")
+    model.set_input_fact(0, "1,224,224,3,f32")
+    model = model.into_optimized().into_runnable()
+    ```
+    """
+
+    def __init__(self):
+        ptr = c_void_p()
+        check(lib.tract_tensorflow_create(byref(ptr)))
+        self.ptr = ptr
+
+    def __del__(self):
+        check(lib.tract_tensorflow_destroy(byref(self.ptr)))
+
+    def model_for_path(self, path: Union[str, Path]) -> InferenceModel:
+        """
+        Load a TensorFlow frozen graph as an InferenceModel
+        """
+        model = c_void_p()
+        path = str(path).encode("utf-8")
+        check(lib.tract_tensorflow_model_for_path(self.ptr, path, byref(model)))
+        return InferenceModel(model)
+
+    def model_for_bytes(self, data: bytes) -> InferenceModel:
+        """
+        Load a TensorFlow frozen graph from the content of a `.pb` file as an InferenceModel
+        """
+        model = c_void_p()
+        data = bytes(data)
+        check(lib.tract_tensorflow_model_for_bytes(self.ptr, data, c_size_t(len(data)), byref(model)))
+        return InferenceModel(model)
@@ -0,0 +1,66 @@
+from ctypes import *
+from pathlib import Path
+from typing import Dict, List, Union
+from .bindings import check, lib, TractError
+from .model import Model
+
+class Tflite:
+    """
+    Represent a TFLite context in tract.
+
+    TFLite models come with full shape and element type information, so they are loaded as a
+    `Model` ready for optimization. A `Model` can also be written back in the TFLite format,
+    provided all its operators have a TFLite counterpart.
+
+    ```python
+    model = (
+        tract.tflite()
+        .model_for_path("mobilenet_v3.tflite")
+        .into_optimized()
+        .into_runnable()
+    )
+    ```
+    """
+
+    def __init__(self):
+        ptr = c_void_p()
+        check(lib.tract_tflite_create(byref(ptr)))
+        self.ptr = ptr
+
+    def __del__(self):
+        check(lib.tract_tflite_destroy(byref(self.ptr)))
+
+    def _valid(self):
+        if self.ptr == None:
+            raise TractError("invalid tflite context (maybe already consumed ?)")
+
+    def model_for_path(self, path: Union[str, Path]) -> Model:
+        """
+        Load a TFLite model from the `.tflite` file at `path`
+        """
+        self._valid()
+        model = c_void_p()
+        path = str(path).encode("utf-8")
+        check(lib.tract_tflite_model_for_path(self.ptr, path, byref(model)))
+        return Model(model)
+
+    def model_for_bytes(self, data: bytes) -> Model:
+        """
+        Load a TFLite model from the content of a `.tflite` file
+        """
+        self._valid()
+        model = c_void_p()
+        data = bytes(data)
+        check(lib.tract_tflite_model_for_bytes(self.ptr, data, c_size_t(len(data)), byref(model)))
+        return Model(model)
+
+    def write_model_to_path(self, model: Model, path: Union[str, Path]) -> None:
+        """
+        Save `model` as a TFLite file in `path`.
+        """
+        self._valid()
+        model._valid()
+        if not isinstance(model, Model):
+            raise TractError("Expected a Model, called with " + model);
+        path = str(path).encode("utf-8")
+        check(lib.tract_tflite_write_model_to_path(self.ptr, path, model.ptr))
@@ -1,17 +1,21 @@
 use std::fmt::{Debug, Display};
+use std::io::Read;
 use std::path::Path;
 use std::sync::Arc;
+use std::time::Duration;
 
 use anyhow::{Context, Result};
 use ndarray::{Data, Dimension, RawData};
 use tract_extra::WithTractExtra;
 use tract_libcli::annotations::Annotations;
 use tract_libcli::profile::BenchLimits;
-use tract_nnef::internal::parse_tdim;
+use tract_nnef::builder;
+use tract_nnef::internal::{parse_tdim, SavedSimpleState};
 use tract_nnef::prelude::{
-    Framework, IntoTValue, SymbolValues, TValue, TVec, Tensor, TractResult, TypedFact, TypedModel,
-    TypedRunnableModel, TypedSimplePlan, TypedSimpleState,
+    Framework, IntoArcTensor, IntoTValue, SymbolValues, TValue, TVec, Tensor, TractResult,
+    TypedFact, TypedModel, TypedRunnableModel, TypedSimplePlan, TypedSimpleState,
 };
+use tract_onnx::data_resolver::InMemoryDataResolver;
 use tract_onnx::prelude::InferenceModelExt;
 use tract_onnx_opl::WithOnnx;
 use tract_pulse::model::{PulsedModel, PulsedModelExt};
@@ -28,6 +32,16 @@ pub fn onnx() -> Result<Onnx> {
     Ok(Onnx(tract_onnx::onnx()))
 }
 
+/// Creates an instance of a TFLite framework and parser that can be used to load and dump TFLite models.
+pub fn tflite() -> Result<Tflite> {
+    Ok(Tflite(tract_tflite::tflite()))
+}
+
+/// Creates an instance of a TensorFlow framework and parser that can be used to load frozen graphs.
+pub fn tensorflow() -> Result<Tensorflow> {
+    Ok(Tensorflow(tract_tensorflow::tensorflow()))
+}
+
 /// tract version tag
 pub fn version() -> &'static str {
     env!("CARGO_PKG_VERSION")
@@ -37,10 +51,19 @@ pub struct Nnef(tract_nnef::internal::Nnef);
 
 impl NnefInterface for Nnef {
     type Model = Model;
+    type ModelBuilder = ModelBuilder;
     fn model_for_path(&self, path: impl AsRef<Path>) -> Result<Model> {
         self.0.model_for_path(path).map(Model)
     }
 
+    fn model_for_bytes(&self, mut bytes: &[u8]) -> Result<Model> {
+        self.0.model_for_read(&mut bytes).map(Model)
+    }
+
+    fn model_for_read(&self, reader: &mut dyn Read) -> Result<Model> {
+        self.0.model_for_read(reader).map(Model)
+    }
+
     fn enable_tract_core(&mut self) -> Result<()> {
         self.0.enable_tract_core();
         Ok(())
@@ -82,6 +105,85 @@ impl NnefInterface for Nnef {
         self.0.write_to_tar(&model.0, gz)?;
         Ok(())
     }
+
+    fn model_builder(&self) -> Result<ModelBuilder> {
+        self.model_builder_for_model(Model(TypedModel::default()))
+    }
+
+    fn model_builder_for_model(&self, model: Model) -> Result<ModelBuilder> {
+        let nnef = tract_nnef::internal::Nnef {
+            registries: self.0.registries.clone(),
+            ..tract_nnef::nnef()
+        };
+        Ok(ModelBuilder { nnef, model: model.0 })
+    }
+}
+
+pub struct ModelBuilder {
+    nnef: tract_nnef::internal::Nnef,
+    model: TypedModel,
+}
+
+impl ModelBuilderInterface for ModelBuilder {
+    type Model = Model;
+    type Value = Value;
+
+    fn add_input(&mut self, name: impl AsRef<str>, fact: impl AsRef<str>) -> Result<()> {
+        let name = name.as_ref();
+        builder::ensure_name_is_free(&self.model, name)?;
+        let fact = tract_libcli::tensor::parse_spec(&self.model.symbol_table, fact.as_ref())?;
+        let fact = tract_onnx::prelude::Fact::to_typed_fact(&fact)?.into_owned();
+        self.model.add_source(name, fact)?;
+        Ok(())
+    }
+
+    fn add_const<V, E>(&mut self, name: impl AsRef<str>, value: V) -> Result<()>
+    where
+        V: TryInto<Value, Error = E>,
+        E: Into<anyhow::Error>,
+    {
+        let name = name.as_ref();
+        builder::ensure_name_is_free(&self.model, name)?;
+        let value: Value = value.try_into().map_err(Into::<anyhow::Error>::into)?;
+        self.model.add_const(name, value.0.into_arc_tensor())?;
+        Ok(())
+    }
+
+    fn replace_const<V, E>(&mut self, name: impl AsRef<str>, value: V) -> Result<()>
+    where
+        V: TryInto<Value, Error = E>,
+        E: Into<anyhow::Error>,
+    {
+        let value: Value = value.try_into().map_err(Into::<anyhow::Error>::into)?;
+        builder::replace_const(&mut self.model, name.as_ref(), value.0.into_arc_tensor())
+    }
+
+    fn wire(
+        &mut self,
+        name: impl AsRef<str>,
+        op: impl AsRef<str>,
+        inputs: &[&str],
+        attributes: &[(&str, &str)],
+    ) -> Result<Vec<String>> {
+        let name = name.as_ref();
+        let outputs =
+            self.nnef.wire_primitive(&mut self.model, name, op.as_ref(), inputs, attributes)?;
+        outputs
+            .iter()
+            .map(|o| Ok(self.model.outlet_label(*o).context("Unlabelled output")?.to_string()))
+            .collect()
+    }
+
+    fn set_output_names(
+        &mut self,
+        outputs: impl IntoIterator<Item = impl AsRef<str>>,
+    ) -> Result<()> {
+        self.model.set_output_names(outputs)
+    }
+
+    fn into_model(self) -> Result<Model> {
+        Ok(Model(self.model))
+    }
 }
 
 pub struct Onnx(tract_onnx::Onnx);
@@ -90,6 +192,46 @@ impl OnnxInterface for Onnx {
     fn model_for_path(&self, path: impl AsRef<Path>) -> Result<Self::InferenceModel> {
         Ok(InferenceModel(self.0.model_for_path(path)?))
     }
+
+    fn model_for_bytes_with_external_data(
+        &self,
+        mut bytes: &[u8],
+        external_data: &[(&str, &[u8])],
+    ) -> Result<InferenceModel> {
+        let resolver = external_data
+            .iter()
+            .fold(InMemoryDataResolver::default(), |r, (loc, data)| r.with(*loc, data.to_vec()));
+        Ok(InferenceModel(self.0.model_for_read_with_external_data(&mut bytes, resolver)?))
+    }
+}
+
+pub struct Tflite(tract_tflite::Tflite);
+impl TfliteInterface for Tflite {
+    type Model = Model;
+    fn model_for_path(&self, path: impl AsRef<Path>) -> Result<Model> {
+        self.0.model_for_path(path).map(Model)
+    }
+
+    fn model_for_bytes(&self, mut bytes: &[u8]) -> Result<Model> {
+        self.0.model_for_read(&mut bytes).map(Model)
+    }
+
+    fn write_model_to_path(&self, path: impl AsRef<Path>, model: &Model) -> Result<()> {
+        let file = std::fs::File::create(path)?;
+        self.0.write(&model.0, file)
+    }
+}
+
+pub struct Tensorflow(tract_tensorflow::Tensorflow);
+impl TensorflowInterface for Tensorflow {
+    type InferenceModel = InferenceModel;
+    fn model_for_path(&self, path: impl AsRef<Path>) -> Result<InferenceModel> {
+        self.0.model_for_path(path).map(InferenceModel)
+    }
+
+    fn model_for_bytes(&self, mut bytes: &[u8]) -> Result<InferenceModel> {
+        self.0.model_for_read(&mut bytes).map(InferenceModel)
+    }
 }
 
 pub struct InferenceModel(tract_onnx::prelude::InferenceModel);
@@ -331,6 +473,7 @@ pub struct State(TypedSimpleState<TypedModel, Arc<TypedSimplePlan<TypedModel>>>)
 
 impl StateInterface for State {
     type Value = Value;
+    type CancellationToken = CancellationToken;
 
     fn input_count(&self) -> Result<usize> {
         Ok(self.0.model().inputs.len())
@@ -350,9 +493,49 @@ impl StateInterface for State {
             .into_iter()
             .map(|i| i.try_into().map_err(|e| e.into()).map(|v| v.0))
             .collect::<Result<_>>()?;
-        let outputs = self.0.run(inputs)?;
+        let outputs = self.0.run(inputs).map_err(|e| {
+            use tract_nnef::prelude::Cancelled as Internal;
+            match e.downcast_ref::<Internal>() {
+                Some(Internal::Requested) => Cancelled::Requested.into(),
+                Some(Internal::DeadlineExceeded) => Cancelled::DeadlineExceeded.into(),
+                None => e,
+            }
+        })?;
         Ok(outputs.into_iter().map(Value).collect())
     }
+
+    fn freeze(&self) -> Result<Vec<u8>> {
+        self.0.freeze().save()?.to_bytes()
+    }
+
+    fn thaw(&mut self, bytes: &[u8]) -> Result<()> {
+        let saved = SavedSimpleState::from_bytes(bytes)?;
+        self.0 = self.0.freeze().load(&saved)?.unfreeze();
+        Ok(())
+    }
+
+    fn set_cancellation_token(&mut self, token: Option<&CancellationToken>) -> Result<()> {
+        self.0.set_cancellation_token(token.map(|t| t.0.clone()));
+        Ok(())
+    }
+}
+
+// CANCELLATION TOKEN
+pub struct CancellationToken(tract_nnef::prelude::CancellationToken);
+
+impl CancellationTokenInterface for CancellationToken {
+    fn new() -> Result<Self> {
+        Ok(CancellationToken(tract_nnef::prelude::CancellationToken::new()))
+    }
+
+    fn with_time_budget(budget: Duration) -> Result<Self> {
+        Ok(CancellationToken(tract_nnef::prelude::CancellationToken::with_time_budget(budget)))
+    }
+
+    fn cancel(&self) -> Result<()> {
+        self.0.cancel();
+        Ok(())
+    }
 }
 
 // VALUE
@@ -1,7 +1,9 @@
 use anyhow::{ensure, Result};
 use boow::Bow;
 use std::fmt::{Debug, Display};
+use std::io::Read;
 use std::path::Path;
+use std::time::Duration;
 
 #[macro_use]
 pub mod macros;
@@ -11,11 +13,22 @@ pub mod macros;
 /// Entry point for NNEF model manipulation: loading from file, dumping to file.
 pub trait NnefInterface: Sized {
     type Model: ModelInterface;
+    type ModelBuilder: ModelBuilderInterface<Model = Self::Model>;
     /// Load a NNEF model from the path into a tract-core model.
     ///
     /// * `path` can point to a directory, a `tar` file or a `tar.gz` file.
     fn model_for_path(&self, path: impl AsRef<Path>) -> Result<Self::Model>;
 
+    /// Load a NNEF model from a `tar` or `tar.gz` archive held in memory.
+    fn model_for_bytes(&self, bytes: &[u8]) -> Result<Self::Model>;
+
+    /// Load a NNEF model from a reader over a `tar` or `tar.gz` archive.
+    fn model_for_read(&self, reader: &mut dyn Read) -> Result<Self::Model> {
+        let mut bytes = vec![];
+        reader.read_to_end(&mut bytes)?;
+        self.model_for_bytes(&bytes)
+    }
+
     /// Allow the framework to use tract_core extensions instead of a stricter NNEF definition.
     fn enable_tract_core(&mut self) -> Result<()>;
 
@@ -77,11 +90,134 @@ pub trait NnefInterface: Sized {
     /// `path` is the archive name 
     fn write_model_to_tar(&self, path: impl AsRef<Path>, model: &Self::Model) -> Result<()>;
     fn write_model_to_tar_gz(&self, path: impl AsRef<Path>, model: &Self::Model) -> Result<()>;
+
+    /// Start building a model from scratch.
+    ///
+    /// Operators are resolved through the registries enabled on this framework object, so
+    /// extensions (`enable_tract_core`...) must be enabled to wire the operators they define.
+    fn model_builder(&self) -> Result<Self::ModelBuilder>;
+
+    /// Start editing an existing model.
+    fn model_builder_for_model(&self, model: Self::Model) -> Result<Self::ModelBuilder>;
+}
+
+/// Programmatic construction and edition of a model.
+///
+/// Wires are designated by name: a source or a constant by the name it was given, the outputs of
+/// a wired operator by its name if it has a single output, `name:0`, `name:1`... otherwise. In a
+/// model being edited, any node output can also be designated by the node name (first output) or
+/// by `node:slot`.
+pub trait ModelBuilderInterface: Sized {
+    type Model: ModelInterface;
+    type Value: ValueInterface;
+
+    /// Add a model input, `fact` being a fact specification like `"1,3,224,224,f32"`.
+    fn add_input(&mut self, name: impl AsRef<str>, fact: impl AsRef<str>) -> Result<()>;
+
+    /// Add a constant.
+    fn add_const<V, E>(&mut self, name: impl AsRef<str>, value: V) -> Result<()>
+    where
+        V: TryInto<Self::Value, Error = E>,
+        E: Into<anyhow::Error>;
+
+    /// Replace the value of an existing constant by a value of the same type and shape.
+    fn replace_const<V, E>(&mut self, name: impl AsRef<str>, value: V) -> Result<()>
+    where
+        V: TryInto<Self::Value, Error = E>,
+        E: Into<anyhow::Error>;
+
+    /// Wire an operator designated by its NNEF name (`conv`, `add`, `tract_core_gelu`...).
+    ///
+    /// `inputs` are wire names, passed as positional arguments. `attributes` are passed as named
+    /// arguments, their values being NNEF expressions (`"[2, 2]"`, `"'constant'"`, `"true"`...)
+    /// which can refer to wires by name.
+    ///
+    /// Returns the names of the operator outputs.
+    fn wire(
+        &mut self,
+        name: impl AsRef<str>,
+        op: impl AsRef<str>,
+        inputs: &[&str],
+        attributes: &[(&str, &str)],
+    ) -> Result<Vec<String>>;
+
+    /// Set the model outputs, designated by wire name.
+    fn set_output_names(
+        &mut self,
+        outputs: impl IntoIterator<Item = impl AsRef<str>>,
+    ) -> Result<()>;
+
+    /// Finish building, getting the model back.
+    fn into_model(self) -> Result<Self::Model>;
 }
 
 pub trait OnnxInterface {
     type InferenceModel: InferenceModelInterface;
     fn model_for_path(&self, path: impl AsRef<Path>) -> Result<Self::InferenceModel>;
+
+    /// Load an ONNX model held in memory, with its external data, if any, given as
+    /// `(location, data)` pairs, `location` being the file name recorded in the model.
+    fn model_for_bytes_with_external_data(
+        &self,
+        bytes: &[u8],
+        external_data: &[(&str, &[u8])],
+    ) -> Result<Self::InferenceModel>;
+
+    /// Load an ONNX model without external data held in memory.
+    fn model_for_bytes(&self, bytes: &[u8]) -> Result<Self::InferenceModel> {
+        self.model_for_bytes_with_external_data(bytes, &[])
+    }
+
+    /// Load an ONNX model without external data from a reader.
+    fn model_for_read(&self, reader: &mut dyn Read) -> Result<Self::InferenceModel> {
+        let mut bytes = vec![];
+        reader.read_to_end(&mut bytes)?;
+        self.model_for_bytes(&bytes)
+    }
+}
+
+/// an implementation of tract's TFLite framework object
+///
+/// Entry point for TFLite model manipulation: loading from file or memory, dumping to file.
+pub trait TfliteInterface: Sized {
+    type Model: ModelInterface;
+    /// Load a TFLite model from the `.tflite` file at `path` into a tract-core model.
+    fn model_for_path(&self, path: impl AsRef<Path>) -> Result<Self::Model>;
+
+    /// Load a TFLite model from the content of a `.tflite` file held in memory.
+    fn model_for_bytes(&self, bytes: &[u8]) -> Result<Self::Model>;
+
+    /// Load a TFLite model from a reader over the content of a `.tflite` file.
+    fn model_for_read(&self, reader: &mut dyn Read) -> Result<Self::Model> {
+        let mut bytes = vec![];
+        reader.read_to_end(&mut bytes)?;
+        self.model_for_bytes(&bytes)
+    }
+
+    /// Dump a TypedModel as a `.tflite` file.
+    ///
+    /// The model is rewritten to the subset of operators and axes conventions TFLite supports
+    /// before being serialized, so this can fail if some operators have no TFLite counterpart.
+    fn write_model_to_path(&self, path: impl AsRef<Path>, model: &Self::Model) -> Result<()>;
+}
+
+/// an implementation of tract's TensorFlow framework object
+///
+/// Entry point for TensorFlow 1.x frozen graph (`.pb`) loading.
+pub trait TensorflowInterface {
+    type InferenceModel: InferenceModelInterface;
+    /// Load a frozen TensorFlow graph from the `.pb` file at `path`.
+    fn model_for_path(&self, path: impl AsRef<Path>) -> Result<Self::InferenceModel>;
+
+    /// Load a frozen TensorFlow graph from the content of a `.pb` file held in memory.
+    fn model_for_bytes(&self, bytes: &[u8]) -> Result<Self::InferenceModel>;
+
+    /// Load a frozen TensorFlow graph from a reader over the content of a `.pb` file.
+    fn model_for_read(&self, reader: &mut dyn Read) -> Result<Self::InferenceModel> {
+        let mut bytes = vec![];
+        reader.read_to_end(&mut bytes)?;
+        self.model_for_bytes(&bytes)
+    }
 }
 
 pub trait InferenceModelInterface: Sized {
@@ -192,6 +328,7 @@ pub trait RunnableInterface {
 
 pub trait StateInterface {
     type Value: ValueInterface;
+    type CancellationToken: CancellationTokenInterface;
 
     fn input_count(&self) -> Result<usize>;
     fn output_count(&self) -> Result<usize>;
@@ -201,8 +338,51 @@ pub trait StateInterface {
         I: IntoIterator<Item = V>,
         V: TryInto<Self::Value, Error = E>,
         E: Into<anyhow::Error>;
+
+    /// Serialize what the state carries from one run to the next (delay buffers, caches...).
+    fn freeze(&self) -> Result<Vec<u8>>;
+
+    /// Restore a serialized state. `self` must be a state of the same model, possibly in another
+    /// process.
+    fn thaw(&mut self, bytes: &[u8]) -> Result<()>;
+
+    /// Check `token` between nodes of the following runs. Interrupted runs fail with a
+    /// [`Cancelled`] error, and leave the state to be discarded.
+    fn set_cancellation_token(&mut self, token: Option<&Self::CancellationToken>) -> Result<()>;
 }
 
+/// A flag, with an optional deadline, interrupting the runs it is attached to.
+///
+/// It can be cancelled from another thread while a run is in progress.
+pub trait CancellationTokenInterface: Sized + Send + Sync {
+    fn new() -> Result<Self>;
+
+    /// A token cancelling each run once `budget` has elapsed since the run started.
+    fn with_time_budget(budget: Duration) -> Result<Self>;
+
+    fn cancel(&self) -> Result<()>;
+}
+
+/// Error returned by runs interrupted through a cancellation token.
+///
+/// It can be recovered from a run error with `downcast_ref::<Cancelled>()`.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Cancelled {
+    Requested,
+    DeadlineExceeded,
+}
+
+impl Display for Cancelled {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Cancelled::Requested => write!(f, "Run cancelled"),
+            Cancelled::DeadlineExceeded => write!(f, "Run deadline exceeded"),
+        }
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
 pub trait ValueInterface: Sized + Clone {
     fn from_bytes(dt: DatumType, shape: &[usize], data: &[u8]) -> Result<Self>;
     fn as_bytes(&self) -> Result<(DatumType, &[usize], &[u8])>;
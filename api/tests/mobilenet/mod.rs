@@ -87,6 +87,42 @@ fn test_nnef() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_nnef_for_bytes() -> anyhow::Result<()> {
+    ensure_models()?;
+    let bytes = std::fs::read("mobilenet_v2_1.0.onnx.nnef.tgz")?;
+    let model = nnef()?.model_for_bytes(&bytes)?.into_optimized()?.into_runnable()?;
+    let result = model.run([grace_hopper()])?;
+    let result = result[0].view::<f32>()?;
+    let best = result
+        .as_slice()
+        .unwrap()
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.partial_cmp(b.1).unwrap())
+        .unwrap();
+    assert_eq!(best.0, 652);
+    Ok(())
+}
+
+#[test]
+fn test_onnx_for_read() -> anyhow::Result<()> {
+    ensure_models()?;
+    let mut file = std::fs::File::open("mobilenetv2-7.onnx")?;
+    let model = onnx()?.model_for_read(&mut file)?.into_optimized()?.into_runnable()?;
+    let result = model.run([grace_hopper()])?;
+    let result = result[0].view::<f32>()?;
+    let best = result
+        .as_slice()
+        .unwrap()
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.partial_cmp(b.1).unwrap())
+        .unwrap();
+    assert_eq!(best.0, 652);
+    Ok(())
+}
+
 #[test]
 fn test_inference_model() -> anyhow::Result<()> {
     ensure_models()?;
@@ -244,6 +280,35 @@ fn test_typed_model_to_nnef_and_back() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_typed_model_to_tflite_and_back() -> anyhow::Result<()> {
+    ensure_models()?;
+    let mut model = onnx()?.model_for_path("mobilenetv2-7.onnx")?;
+    model.set_input_fact(0, "1,3,224,224,f32")?;
+    model.analyse()?;
+    let mut typed = model.into_typed()?;
+    typed.declutter()?;
+    let dir = tempfile::tempdir()?;
+    let tflite = tflite()?;
+
+    let path = dir.path().join("mobilenet.tflite");
+    tflite.write_model_to_path(&path, &typed)?;
+    let reloaded = tflite.model_for_path(&path)?;
+    assert_eq!(reloaded.input_fact(0)?.to_string(), "1,3,224,224,F32");
+    let reloaded = tflite.model_for_bytes(&std::fs::read(&path)?)?;
+    let result = reloaded.into_optimized()?.into_runnable()?.run([grace_hopper()])?;
+    let result = result[0].view::<f32>()?;
+    let best = result
+        .as_slice()
+        .unwrap()
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.partial_cmp(b.1).unwrap())
+        .unwrap();
+    assert_eq!(best.0, 652);
+    Ok(())
+}
+
 #[test]
 fn test_cost() -> anyhow::Result<()> {
     ensure_models()?;
@@ -279,3 +344,71 @@ fn test_profile() -> anyhow::Result<()> {
     assert!(nodes.iter().find_map(|n| n.get("secs_per_iter").and_then(|c| c.as_f64())).is_some());
     Ok(())
 }
+
+#[test]
+fn test_model_builder() -> anyhow::Result<()> {
+    let nnef = nnef()?.with_tract_core()?;
+    let mut builder = nnef.model_builder()?;
+    builder.add_input("x", "2,3,f32")?;
+    builder.add_const("w", ndarray::arr2(&[[1f32], [2.], [3.]]).into_dyn())?;
+    builder.wire("mm", "matmul", &["x", "w"], &[])?;
+    let split = builder.wire("s", "unstack", &["x"], &[("axis", "1")])?;
+    assert_eq!(split, ["s:0", "s:1", "s:2"]);
+    builder.set_output_names(["mm", "s:1"])?;
+    let model = builder.into_model()?;
+
+    let mut builder = nnef.model_builder_for_model(model)?;
+    builder.replace_const("w", ndarray::arr2(&[[1f32], [1.], [1.]]).into_dyn())?;
+    let runnable = builder.into_model()?.into_optimized()?.into_runnable()?;
+    let x = ndarray::arr2(&[[1f32, 2., 3.], [4., 5., 6.]]).into_dyn();
+    let result = runnable.run([x])?;
+    assert_eq!(result[0].as_slice::<f32>()?.1, &[6., 15.]);
+    assert_eq!(result[1].as_slice::<f32>()?.1, &[2., 5.]);
+    Ok(())
+}
+
+#[test]
+fn test_state_freeze_and_thaw() -> anyhow::Result<()> {
+    let mut builder = nnef()?.with_pulse()?.model_builder()?;
+    builder.add_input("x", "4,f32")?;
+    builder.wire(
+        "y",
+        "tract_pulse_delay",
+        &["x"],
+        &[("axis", "0"), ("delay", "2"), ("overlap", "0")],
+    )?;
+    builder.set_output_names(["y"])?;
+    let runnable = builder.into_model()?.into_optimized()?.into_runnable()?;
+    let mut state = runnable.spawn_state()?;
+    state.run([ndarray::arr1(&[1f32, 2., 3., 4.]).into_dyn()])?;
+    let frozen = state.freeze()?;
+
+    let mut thawed = runnable.spawn_state()?;
+    thawed.thaw(&frozen)?;
+    let result = thawed.run([ndarray::arr1(&[5f32, 6., 7., 8.]).into_dyn()])?;
+    assert_eq!(result[0].as_slice::<f32>()?.1, &[3., 4., 5., 6.]);
+    Ok(())
+}
+
+#[test]
+fn test_cancellation_token() -> anyhow::Result<()> {
+    let mut builder = nnef()?.with_tract_core()?.model_builder()?;
+    builder.add_input("x", "2,f32")?;
+    builder.wire("y", "add", &["x", "x"], &[])?;
+    builder.set_output_names(["y"])?;
+    let runnable = builder.into_model()?.into_optimized()?.into_runnable()?;
+    let mut state = runnable.spawn_state()?;
+    let token = CancellationToken::new()?;
+    state.set_cancellation_token(Some(&token))?;
+    state.run([ndarray::arr1(&[1f32, 2.]).into_dyn()])?;
+    token.cancel()?;
+    let err = state.run([ndarray::arr1(&[1f32, 2.]).into_dyn()]).unwrap_err();
+    assert_eq!(err.downcast_ref::<Cancelled>(), Some(&Cancelled::Requested));
+
+    let mut state = runnable.spawn_state()?;
+    let token = CancellationToken::with_time_budget(std::time::Duration::ZERO)?;
+    state.set_cancellation_token(Some(&token))?;
+    let err = state.run([ndarray::arr1(&[1f32, 2.]).into_dyn()]).unwrap_err();
+    assert_eq!(err.downcast_ref::<Cancelled>(), Some(&Cancelled::DeadlineExceeded));
+    Ok(())
+}
@@ -127,7 +127,7 @@ fn main() -> tract_core::anyhow::Result<()> {
 
         .arg(Arg::new("f32-to-f16").long("f32-to-f16").alias("half-floats").long_help("Convert the decluttered network from f32 to f16"))
         .arg(arg!(--"f16-to-f32" "Convert the decluttered network from f16 to f32"))
-        .arg(Arg::new("transform").short('t').long("transform").multiple_occurrences(true).takes_value(true).help("Apply a built-in transformation to the model"))
+        .arg(Arg::new("transform").short('t').long("transform").multiple_occurrences(true).takes_value(true).help("Apply a built-in transformation to the model (quantize-i8=calib.npz or quantize-u8=calib.npz for calibrated post-training quantization)"))
         .arg(Arg::new("set").long("set").multiple_occurrences(true).takes_value(true)
          .long_help("Set a symbol to a concrete value after decluttering"))
 
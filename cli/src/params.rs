@@ -479,6 +479,36 @@ impl Parameters {
         Ok(Self::tensor_values_from_iter(triples.into_iter(), get_values, get_facts))
     }
 
+    fn post_training_quantization(
+        model: &TypedModel,
+        name: &str,
+        calibration: &str,
+    ) -> TractResult<Box<dyn tract_core::transform::ModelTransform>> {
+        let dt = match name {
+            "quantize-i8" => i8::datum_type(),
+            "quantize-u8" => u8::datum_type(),
+            _ => bail!("Could not find calibrated transform named {}", name),
+        };
+        let bundle = Self::parse_npz(calibration, true, false)?;
+        let inputs = model
+            .input_outlets()?
+            .iter()
+            .map(|input| {
+                let name = &model.node(input.node).name;
+                bundle
+                    .iter()
+                    .find(|tv| tv.name.as_deref() == Some(name.as_str()))
+                    .and_then(|tv| tv.values.as_ref())
+                    .with_context(|| format!("No calibration values for input {name}"))
+            })
+            .collect::<TractResult<Vec<_>>>()?;
+        let turns = inputs.iter().map(|values| values.len()).min().unwrap_or(0);
+        let samples = (0..turns)
+            .map(|turn| inputs.iter().map(|values| values[turn].clone()).collect())
+            .collect();
+        Ok(Box::new(tract_core::quantization::PostTrainingQuantization::new(dt, samples)))
+    }
+
     fn parse_tensors(
         matches: &clap::ArgMatches,
         location: &Location,
@@ -732,7 +762,11 @@ impl Parameters {
         if let Some(transform) = matches.values_of("transform") {
             for transform in transform {
                 stage!(transform, typed_model -> typed_model, |m:TypedModel| {
-                    let transform = tract_core::transform::get_transform(transform).with_context(|| format!("Could not find transform named {}", transform))?;
+                    let transform = if let Some((name, calibration)) = transform.split_once('=') {
+                        Self::post_training_quantization(&m, name, calibration)?
+                    } else {
+                        tract_core::transform::get_transform(transform).with_context(|| format!("Could not find transform named {}", transform))?
+                    };
                     transform.transform_into(&m)
                 });
             }
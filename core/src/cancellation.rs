@@ -0,0 +1,185 @@
+//! Cooperative interruption of model runs.
+//!
+//! A `CancellationToken` attached to a `SimpleState` is checked between nodes (and between `Scan`
+//! and `WhileLoop` iterations). Once cancelled, or past its deadline, the run stops with a `Cancelled` error.
+//! The op states are left as they were at the interruption: the state should be reset or
+//! discarded.
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::internal::*;
+
+/// Error stopping a run interrupted through a `CancellationToken`.
+///
+/// It can be recovered from a run error with `downcast_ref::<Cancelled>()`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Cancelled {
+    /// `cancel` was called on the token.
+    Requested,
+    /// The token deadline has passed.
+    DeadlineExceeded,
+}
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Cancelled::Requested => write!(f, "Run cancelled"),
+            Cancelled::DeadlineExceeded => write!(f, "Run deadline exceeded"),
+        }
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+/// Shared cancellation flag, with an optional deadline and time budget.
+///
+/// Clones share the flag: a token can be cancelled from another thread while a run checks it.
+/// The time budget is counted from the start of each run, so a token can be built ahead of time
+/// and reused across runs.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+    deadline: Option<Instant>,
+    budget: Option<Duration>,
+}
+
+impl CancellationToken {
+    pub fn new() -> CancellationToken {
+        CancellationToken::default()
+    }
+
+    /// A token cancelling runs after `deadline`.
+    pub fn with_deadline(deadline: Instant) -> CancellationToken {
+        CancellationToken { deadline: Some(deadline), ..CancellationToken::default() }
+    }
+
+    /// A token cancelling each run once `budget` has elapsed since the run started.
+    pub fn with_time_budget(budget: Duration) -> CancellationToken {
+        CancellationToken { budget: Some(budget), ..CancellationToken::default() }
+    }
+
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    pub fn time_budget(&self) -> Option<Duration> {
+        self.budget
+    }
+
+    /// A token sharing this one's flag, for a run starting now: the time budget becomes a
+    /// deadline. Runs arm their token once, nested bodies inherit the armed token.
+    pub fn armed(&self) -> CancellationToken {
+        let deadline = match (self.deadline, self.budget) {
+            (Some(deadline), Some(budget)) => Some(deadline.min(Instant::now() + budget)),
+            (deadline, budget) => deadline.or_else(|| budget.map(|b| Instant::now() + b)),
+        };
+        CancellationToken { cancelled: self.cancelled.clone(), deadline, budget: None }
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed)
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Fail with `Cancelled` if the token has been cancelled or its deadline has passed.
+    ///
+    /// The time budget only counts once the token is `armed`.
+    pub fn check(&self) -> TractResult<()> {
+        if self.is_cancelled() {
+            return Err(Cancelled::Requested.into());
+        }
+        if self.deadline.is_some_and(|d| Instant::now() >= d) {
+            return Err(Cancelled::DeadlineExceeded.into());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::scan::{InputMapping, OutputMapping, Scan, ScanInfo};
+    use crate::ops::EvalOp;
+
+    fn model() -> TractResult<TypedModel> {
+        let mut model = TypedModel::default();
+        let mut wire = model.add_source("x", f32::fact([1]))?;
+        for i in 0..10 {
+            wire = model.wire_node(format!("add{i}"), crate::ops::math::add(), &[wire, wire])?[0];
+        }
+        model.set_output_outlets(&[wire])?;
+        Ok(model)
+    }
+
+    #[test]
+    fn cancelled_before_run() -> TractResult<()> {
+        let mut state = SimpleState::new(model()?.into_runnable()?)?;
+        let token = CancellationToken::new();
+        state.set_cancellation_token(Some(token.clone()));
+        state.run(tvec!(tensor1(&[1f32]).into()))?;
+        token.cancel();
+        let err = state.run(tvec!(tensor1(&[1f32]).into())).unwrap_err();
+        assert_eq!(err.downcast_ref::<Cancelled>(), Some(&Cancelled::Requested));
+        state.set_cancellation_token(None);
+        state.run(tvec!(tensor1(&[1f32]).into()))?;
+        Ok(())
+    }
+
+    #[test]
+    fn deadline() -> TractResult<()> {
+        let mut state = SimpleState::new(model()?.into_runnable()?)?;
+        state.set_cancellation_token(Some(CancellationToken::with_deadline(Instant::now())));
+        let err = state.run(tvec!(tensor1(&[1f32]).into())).unwrap_err();
+        assert_eq!(err.downcast_ref::<Cancelled>(), Some(&Cancelled::DeadlineExceeded));
+        Ok(())
+    }
+
+    #[test]
+    fn time_budget_starts_with_each_run() -> TractResult<()> {
+        let budget = Duration::from_millis(200);
+        let token = CancellationToken::with_time_budget(budget);
+        std::thread::sleep(budget);
+        let mut state = SimpleState::new(model()?.into_runnable()?)?;
+        state.set_cancellation_token(Some(token.clone()));
+        state.run(tvec!(tensor1(&[1f32]).into()))?;
+        state.run(tvec!(tensor1(&[1f32]).into()))?;
+        state.set_cancellation_token(Some(CancellationToken::with_time_budget(Duration::ZERO)));
+        let err = state.run(tvec!(tensor1(&[1f32]).into())).unwrap_err();
+        assert_eq!(err.downcast_ref::<Cancelled>(), Some(&Cancelled::DeadlineExceeded));
+        Ok(())
+    }
+
+    #[test]
+    fn scan_iterations() -> TractResult<()> {
+        let mut body = TypedModel::default();
+        let x = body.add_source("x", f32::fact([1]))?;
+        let y = body.wire_node("y", crate::ops::math::add(), &[x, x])?;
+        body.set_output_outlets(&y)?;
+        let scan = Scan::new(
+            body,
+            vec![InputMapping::Scan(ScanInfo { axis: 0, chunk: 1 })],
+            vec![OutputMapping {
+                scan: Some((0, ScanInfo { axis: 0, chunk: 1 })),
+                full_dim_hint: None,
+                last_value_slot: None,
+                state: false,
+            }],
+            0,
+        )?;
+        let mut session = SessionState::default();
+        let mut state = scan.state(&mut session, 0)?.context("Expected a state")?;
+        let input = Tensor::zero::<f32>(&[1000])?.into_tvalue();
+        state.eval(&mut session, &scan, tvec!(input.clone()))?;
+        let token = CancellationToken::new();
+        session.cancellation = Some(token.clone());
+        token.cancel();
+        let err = state.eval(&mut session, &scan, tvec!(input)).unwrap_err();
+        assert_eq!(err.downcast_ref::<Cancelled>(), Some(&Cancelled::Requested));
+        Ok(())
+    }
+}
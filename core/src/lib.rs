@@ -82,12 +82,17 @@ pub mod ops;
 
 pub mod axes;
 pub mod broadcast;
+pub mod cancellation;
 pub mod framework;
 pub mod floats;
+pub mod memory_plan;
 pub mod model;
 pub mod optim;
+pub mod parallel_plan;
 pub mod plan;
+pub mod quantization;
 pub mod runtime;
+pub mod saved_state;
 pub mod transform;
 pub mod value;
 
@@ -97,6 +102,7 @@ mod late_bind;
 
 /// This prelude is meant for code using tract.
 pub mod prelude {
+    pub use crate::cancellation::{CancellationToken, Cancelled};
     pub use crate::framework::Framework;
     pub use crate::model::*;
     pub use crate::plan::{SimplePlan, SimpleState};
@@ -119,6 +125,7 @@ pub mod internal {
     pub use crate::ops::element_wise::ElementWiseMiniOp;
     pub use crate::ops::{Cost, EvalOp, FrozenOpState, Op, OpState, Validation};
     pub use crate::plan::SessionState;
+    pub use crate::saved_state::{SavedOpState, SavedSimpleState};
     pub use crate::prelude::*;
     pub use anyhow::{anyhow, bail, ensure, format_err, Context as TractErrorContext};
     pub use dims;
@@ -135,7 +142,7 @@ pub mod internal {
     pub use {args_1, args_2, args_3, args_4, args_5, args_6, args_7, args_8};
     pub use {as_op, impl_op_same_as, not_a_typed_op, op_as_typed_op};
     pub use {bin_to_super_type, element_wise, element_wise_oop};
-    pub use crate::runtime::{Runtime, Runnable, State, DefaultRuntime};
+    pub use crate::runtime::{Runtime, Runnable, State, DefaultRuntime, ParallelRuntime};
 }
 
 #[cfg(test)]
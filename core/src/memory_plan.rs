@@ -0,0 +1,354 @@
+use std::alloc::Layout;
+use std::borrow::Borrow;
+use std::fmt::{Debug, Display};
+use std::ops::Range;
+
+use crate::internal::*;
+use crate::model::{Fact, Graph, OutletId};
+use crate::plan::SimplePlan;
+use tract_data::arena::Preallocated;
+
+/// Alignment of every value placed in an arena.
+pub const ARENA_ALIGNMENT: usize = 128;
+
+/// Placement of an intermediate value in the arena.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ValueMemReq {
+    pub outlet: OutletId,
+    pub datum_type: DatumType,
+    pub shape: TVec<usize>,
+    /// Plan steps during which the value is alive, from the step computing it to the step
+    /// consuming it for the last time (both included).
+    pub lifetime: Range<usize>,
+    pub size: usize,
+    pub offset: usize,
+}
+
+impl ValueMemReq {
+    fn overlaps(&self, other: &ValueMemReq) -> bool {
+        self.lifetime.start < other.lifetime.end && other.lifetime.start < self.lifetime.end
+    }
+}
+
+/// Static placement of the intermediate values of a plan in a single arena.
+///
+/// Values that are produced by a stateless operator, are not a plan output and are not fed to
+/// stateful operators are given an offset such that values alive at the same time never
+/// overlap. Values with unknown shapes, non-copy types or outliving the turn stay allocated on
+/// the heap.
+#[derive(Clone, Debug, Default)]
+pub struct MemoryPlan {
+    pub values: Vec<ValueMemReq>,
+    pub arena_size: usize,
+}
+
+impl MemoryPlan {
+    pub fn new<F, O, M>(plan: &SimplePlan<F, O, M>) -> TractResult<MemoryPlan>
+    where
+        F: Fact + Clone + 'static,
+        O: Debug + Display + AsRef<dyn Op> + AsMut<dyn Op> + Clone + 'static,
+        M: Borrow<Graph<F, O>>,
+    {
+        let model = plan.model();
+        let order = plan.order_without_consts();
+        let mut last_use = vec![order.len(); model.nodes().len()];
+        for (step, flush_list) in plan.flush_lists.iter().enumerate() {
+            for &node in flush_list {
+                last_use[node] = step;
+            }
+        }
+        let mut values = vec![];
+        for (step, &node) in order.iter().enumerate() {
+            let node = model.node(node);
+            if !node.op().is_stateless()
+                || plan.outputs.iter().any(|o| o.node == node.id)
+                || last_use[node.id] >= order.len()
+            {
+                continue;
+            }
+            for (slot, output) in node.outputs.iter().enumerate() {
+                if output.successors.iter().any(|s| !model.node(s.node).op().is_stateless()) {
+                    continue;
+                }
+                let Ok(fact) = output.fact.to_typed_fact() else { continue };
+                let Some(shape) = fact.shape.as_concrete() else { continue };
+                let size = fact.datum_type.size_of() * shape.iter().product::<usize>();
+                if !fact.datum_type.is_copy() || size == 0 {
+                    continue;
+                }
+                values.push(ValueMemReq {
+                    outlet: OutletId::new(node.id, slot),
+                    datum_type: fact.datum_type,
+                    shape: shape.into(),
+                    lifetime: step..last_use[node.id] + 1,
+                    size,
+                    offset: 0,
+                });
+            }
+        }
+        Ok(Self::place(values))
+    }
+
+    // greedy placement: biggest values first, each at the lowest offset that does not
+    // collide with an already placed value alive at the same time
+    fn place(mut values: Vec<ValueMemReq>) -> MemoryPlan {
+        values.sort_by_key(|v| (std::cmp::Reverse(v.size), v.lifetime.start));
+        let mut arena_size = 0;
+        for ix in 0..values.len() {
+            let (placed, todo) = values.split_at_mut(ix);
+            let value = &mut todo[0];
+            let mut busy: Vec<Range<usize>> = placed
+                .iter()
+                .filter(|p| p.overlaps(value))
+                .map(|p| p.offset..p.offset + p.size)
+                .collect();
+            busy.sort_by_key(|r| r.start);
+            let mut offset = 0;
+            for range in busy {
+                if range.start >= offset + value.size {
+                    break;
+                }
+                offset = offset.max(range.end.next_multiple_of(ARENA_ALIGNMENT));
+            }
+            value.offset = offset;
+            arena_size = arena_size.max(offset + value.size);
+        }
+        values.sort_by_key(|v| (v.lifetime.start, v.outlet.slot));
+        MemoryPlan { values, arena_size }
+    }
+
+    /// Bytes needed by the arena to hold all planned values.
+    pub fn peak_memory_size(&self) -> usize {
+        self.arena_size
+    }
+
+    /// Bytes the planned values would use if they were all allocated separately.
+    pub fn total_values_size(&self) -> usize {
+        self.values.iter().map(|v| v.size).sum()
+    }
+
+    pub fn value(&self, outlet: OutletId) -> Option<&ValueMemReq> {
+        self.values.iter().find(|v| v.outlet == outlet)
+    }
+}
+
+/// Memory backing the values of a `MemoryPlan` during the execution of a state.
+///
+/// The buffer is allocated on the first run (see `allocate`), so that a cloned arena shares the
+/// plan but does not allocate until it is actually used.
+#[derive(Debug)]
+pub struct Arena {
+    plan: Arc<MemoryPlan>,
+    layout: Layout,
+    buffer: *mut u8,
+    offers: HashMap<usize, TVec<Preallocated>>,
+}
+
+impl Arena {
+    pub fn new(plan: Arc<MemoryPlan>) -> TractResult<Arena> {
+        let layout = Layout::from_size_align(plan.arena_size.max(1), ARENA_ALIGNMENT)?;
+        Ok(Arena { plan, layout, buffer: std::ptr::null_mut(), offers: HashMap::default() })
+    }
+
+    /// Allocate the buffer, if it is not already.
+    pub fn allocate(&mut self) -> TractResult<()> {
+        if !self.buffer.is_null() {
+            return Ok(());
+        }
+        let buffer = unsafe { std::alloc::alloc(self.layout) };
+        ensure!(!buffer.is_null(), "Failed to allocate a {} bytes arena", self.layout.size());
+        for value in &self.plan.values {
+            self.offers.entry(value.outlet.node).or_default().push(Preallocated {
+                datum_type: value.datum_type,
+                shape: value.shape.clone(),
+                ptr: unsafe { buffer.add(value.offset) },
+            });
+        }
+        self.buffer = buffer;
+        Ok(())
+    }
+
+    pub fn memory_plan(&self) -> &MemoryPlan {
+        &self.plan
+    }
+
+    pub(crate) fn offers_for(&self, node: usize) -> Option<&[Preallocated]> {
+        self.offers.get(&node).map(|o| &**o)
+    }
+
+    fn contains(&self, ptr: *const u8) -> bool {
+        let start = self.buffer as usize;
+        !self.buffer.is_null() && (start..start + self.layout.size()).contains(&(ptr as usize))
+    }
+
+    /// Whether `tensor` lives in this arena.
+    pub fn holds(&self, tensor: &Tensor) -> bool {
+        tensor.len() > 0 && self.contains(unsafe { tensor.as_ptr_unchecked::<u8>() })
+    }
+
+    /// Make sure the only arena-backed outputs of a node are the ones planned for it.
+    ///
+    /// Operators are free to return one of their inputs (or to recycle it in place), which
+    /// would make a value survive in an area of the arena that will be handed over to another
+    /// one. Such outputs are copied: to their planned slot if it was not claimed yet (when
+    /// called while the node offers are active), to the heap otherwise.
+    pub(crate) fn sanitize(&self, node: usize, values: TVec<TValue>) -> TVec<TValue> {
+        values
+            .into_iter()
+            .enumerate()
+            .map(|(slot, v)| {
+                if !self.holds(&v) {
+                    return v;
+                }
+                let ptr = unsafe { v.as_ptr_unchecked::<u8>() };
+                let planned = self
+                    .plan
+                    .value(OutletId::new(node, slot))
+                    .map(|value| unsafe { self.buffer.add(value.offset) as *const u8 });
+                if planned == Some(ptr) {
+                    v
+                } else {
+                    v.deep_clone().into_tvalue()
+                }
+            })
+            .collect()
+    }
+}
+
+impl Clone for Arena {
+    fn clone(&self) -> Self {
+        Arena {
+            plan: self.plan.clone(),
+            layout: self.layout,
+            buffer: std::ptr::null_mut(),
+            offers: HashMap::default(),
+        }
+    }
+}
+
+impl Drop for Arena {
+    fn drop(&mut self) {
+        if !self.buffer.is_null() {
+            unsafe { std::alloc::dealloc(self.buffer, self.layout) }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::math;
+    use tract_data::arena::with_preallocated;
+
+    fn chain(len: usize) -> TractResult<TypedModel> {
+        let mut model = TypedModel::default();
+        let mut wire = model.add_source("source", f32::fact([64]))?;
+        for ix in 0..len {
+            let k = model.add_const(format!("k{ix}"), rctensor1(&[ix as f32; 64]))?;
+            wire = model.wire_node(format!("add{ix}"), math::add(), &[wire, k])?[0];
+        }
+        model.set_output_outlets(&[wire])?;
+        Ok(model)
+    }
+
+    #[test]
+    fn chain_reuses_two_slots() -> TractResult<()> {
+        let plan = chain(6)?.into_runnable()?;
+        let mem = plan.memory_plan()?;
+        // last add is the output, the five others alternate between two slots
+        assert_eq!(mem.values.len(), 5);
+        assert_eq!(mem.total_values_size(), 5 * 256);
+        assert_eq!(mem.peak_memory_size(), 2 * 256);
+        for (a, b) in mem.values.iter().zip(mem.values.iter().skip(1)) {
+            assert!(a.offset != b.offset);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn run_with_arena() -> TractResult<()> {
+        let plan = chain(6)?.into_runnable()?;
+        let input = tensor1(&[1f32; 64]);
+        let expected = plan.run(tvec!(input.clone().into_tvalue()))?;
+        let mut state = SimpleState::new(&plan)?;
+        state.enable_arena()?;
+        for _ in 0..3 {
+            let found = state.run(tvec!(input.clone().into_tvalue()))?;
+            assert_eq!(found, expected);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn failed_run_discards_arena_values() -> TractResult<()> {
+        let plan = chain(6)?.into_runnable()?;
+        let input = tensor1(&[1f32; 64]);
+        let mut state = SimpleState::new(&plan)?;
+        state.enable_arena()?;
+        let failing = |session: &mut SessionState,
+                       op_state: Option<&mut dyn OpState>,
+                       node: &TypedNode,
+                       inputs: TVec<TValue>| {
+            ensure!(node.name != "add4", "failing on purpose");
+            crate::plan::eval(session, op_state, node, inputs)
+        };
+        assert!(state.run_plan_with_eval(tvec!(input.clone().into_tvalue()), failing).is_err());
+        let arena = state.arena().unwrap();
+        assert!(state.values.iter().flatten().flatten().all(|v| !arena.holds(v)));
+        let expected = plan.run(tvec!(input.clone().into_tvalue()))?;
+        assert_eq!(state.run(tvec!(input.into_tvalue()))?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn clone_copies_arena_values() -> TractResult<()> {
+        let plan = chain(6)?.into_runnable()?;
+        let mut state = SimpleState::new(&plan)?;
+        state.enable_arena()?;
+        state.run(tvec!(tensor1(&[1f32; 64]).into_tvalue()))?;
+        // a value of the arena alive in the middle of a turn
+        let add0 = plan.model().node_by_name("add0")?.id;
+        let offers = state.arena().unwrap().offers_for(add0).unwrap();
+        let mut value = unsafe { with_preallocated(offers, || Tensor::zero::<f32>(&[64]))? };
+        value.as_slice_mut::<f32>()?.iter_mut().for_each(|x| *x = 2.);
+        let value = value.into_tvalue();
+        assert!(state.arena().unwrap().holds(&value));
+        state.values[add0] = Some(tvec!(value));
+        let clone = state.clone();
+        assert!(!state.arena().unwrap().holds(&clone.values[add0].as_ref().unwrap()[0]));
+        std::mem::drop(state);
+        assert_eq!(*clone.values[add0].as_ref().unwrap()[0], tensor1(&[2f32; 64]));
+        Ok(())
+    }
+
+    #[test]
+    fn placement_never_overlaps() {
+        let value = |node, lifetime: Range<usize>, size| ValueMemReq {
+            outlet: OutletId::new(node, 0),
+            datum_type: u8::datum_type(),
+            shape: tvec!(size),
+            lifetime,
+            size,
+            offset: 0,
+        };
+        let plan = MemoryPlan::place(vec![
+            value(0, 0..3, 100),
+            value(1, 1..2, 300),
+            value(2, 2..5, 200),
+            value(3, 3..4, 50),
+            value(4, 4..6, 400),
+        ]);
+        for a in &plan.values {
+            assert_eq!(a.offset % ARENA_ALIGNMENT, 0);
+            for b in &plan.values {
+                if a.outlet != b.outlet && a.overlaps(b) {
+                    assert!(
+                        a.offset + a.size <= b.offset || b.offset + b.size <= a.offset,
+                        "{a:?} {b:?}"
+                    );
+                }
+            }
+        }
+        assert!(plan.peak_memory_size() < plan.total_values_size());
+    }
+}
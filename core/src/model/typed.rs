@@ -3,6 +3,7 @@ use crate::model::*;
 use crate::ops;
 use crate::ops::konst::Const;
 use crate::optim::OptimizerSession;
+use crate::parallel_plan::{ParallelPlan, ParallelState};
 use crate::plan::{FrozenSimpleState, SimplePlan, SimpleState};
 use crate::transform::ModelTransform;
 
@@ -20,6 +21,10 @@ pub type TypedRunnableModel<M> = RunnableModel<TypedFact, Box<dyn TypedOp>, M>;
 pub type TypedSimpleState<M, P> = SimpleState<TypedFact, Box<dyn TypedOp>, M, P>;
 /// An execution state for TypedModel, frozen (and Send).
 pub type TypedFrozenSimpleState<M, P> = FrozenSimpleState<TypedFact, Box<dyn TypedOp>, M, P>;
+/// An execution plan for TypedModel, running independent nodes concurrently.
+pub type TypedParallelPlan<M> = ParallelPlan<TypedFact, Box<dyn TypedOp>, M>;
+/// An execution state for TypedParallelPlan.
+pub type TypedParallelState<M, P> = ParallelState<TypedFact, Box<dyn TypedOp>, M, P>;
 
 /// A runnable model with fixed inputs and outputs.
 pub type RunnableModel<F, O, M> = SimplePlan<F, O, M>;
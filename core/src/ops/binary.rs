@@ -167,6 +167,33 @@ impl EvalOp for TypedBinOp {
         true
     }
 
+    fn supports_in_place(&self) -> bool {
+        true
+    }
+
+    fn eval_in_place(
+        &self,
+        _session: &mut SessionState,
+        mut a: Tensor,
+        others: TVec<TValue>,
+    ) -> TractResult<TVec<TValue>> {
+        let b = args_1!(others);
+        ensure!(a.rank() == b.rank());
+        let c_dt = self.output_datum_type(a.datum_type(), b.datum_type())?;
+        // quantized and TDim operands may go through an op specific eval
+        if !c_dt.is_quantized()
+            && c_dt != TDim::datum_type()
+            && c_dt == a.datum_type()
+            && c_dt == b.datum_type()
+            && (a.shape() == b.shape() || b.len() == 1)
+        {
+            self.0.eval_in_a(&mut a, &b)?;
+            Ok(tvec!(a.into_tvalue()))
+        } else {
+            Ok(tvec!(self.0.eval(a.into_tvalue(), b, c_dt)?.into_tvalue()))
+        }
+    }
+
     fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
         let (a, b) = args_2!(inputs);
         ensure!(a.rank() == b.rank());
@@ -372,7 +399,7 @@ macro_rules! bin_to_super_type {
                         $(
                             $(if a.datum_type().unquantized() == <$typ_dt>::datum_type().unquantized() {
                                 let cab: fn(&mut $typ_dt, &$typ_dt, &$typ_dt, i32, f32) -> () = $cab_dt;
-                                let (zp, scale) = a.datum_type().qparams().map(|q| q.zp_scale()).unwrap_or((0, 1.));
+                                let (zp, scale) = a.datum_type().try_zp_scale()?;
                                 let a = a.to_scalar::<$typ_dt>()?;
                                 let b = b.as_slice_mut::<$typ_dt>()?;
                                 unsafe {
@@ -411,7 +438,7 @@ macro_rules! bin_to_super_type {
                         $(
                             $(if a.datum_type().unquantized() == <$typ_dt>::datum_type().unquantized() {
                                 let cab: fn(&mut $typ_dt, &$typ_dt, &$typ_dt, i32, f32) -> () = $cab_dt;
-                                let (zp, scale) = a.datum_type().qparams().map(|q| q.zp_scale()).unwrap_or((0, 1.));
+                                let (zp, scale) = a.datum_type().try_zp_scale()?;
                                 let a = a.as_slice::<$typ_dt>()?;
                                 let b = b.as_slice_mut::<$typ_dt>()?;
                                 unsafe {
@@ -444,7 +471,7 @@ macro_rules! bin_to_super_type {
                         $(
                             $(if a.datum_type().unquantized() == <$typ_dt>::datum_type().unquantized() {
                                 let cab: fn(&mut $typ_dt, &$typ_dt, &$typ_dt, i32, f32) -> () = $cab_dt;
-                                let (zp, scale) = a.datum_type().qparams().map(|q| q.zp_scale()).unwrap_or((0, 1.));
+                                let (zp, scale) = a.datum_type().try_zp_scale()?;
                                 let a = a.to_array_view::<$typ_dt>()?;
                                 let b = b.to_array_view::<$typ_dt>()?;
                                 let mut c = c.to_array_view_mut::<$typ_dt>()?;
@@ -472,7 +499,7 @@ macro_rules! bin_to_super_type {
                     $(
                         $(if a.datum_type().unquantized() == <$typ_dt>::datum_type().unquantized() {
                             let cab: fn(&mut $typ_dt, &$typ_dt, &$typ_dt, i32, f32) -> () = $cab_dt;
-                            let (zp, scale) = a.datum_type().qparams().map(|q| q.zp_scale()).unwrap_or((0, 1.));
+                            let (zp, scale) = a.datum_type().try_zp_scale()?;
                             let mut a = a.to_array_view_mut::<$typ_dt>()?;
                             let b = b.to_array_view::<$typ_dt>()?;
                             $crate::ndarray::Zip::from(&mut a).and_broadcast(b).for_each(|a, b| {
@@ -702,8 +729,25 @@ macro_rules! bin_to_bool {
                     bail!("{} does not support {:?}", self.name(), a.datum_type());
             }
 
-            fn eval_in_a(&self, a: &mut Tensor, _b: &Tensor) -> TractResult<()> {
-                bail!("{} does not support {:?}", self.name(), a.datum_type());
+            fn eval_in_a(&self, a: &mut Tensor, b: &Tensor) -> TractResult<()> {
+                // c is bool, so a can only hold it when both operands are bool
+                $(
+                    $(if $typ::datum_type() == bool::datum_type()
+                        && a.datum_type() == bool::datum_type()
+                        && b.datum_type() == bool::datum_type() {
+                        let cab: fn(&mut bool, &bool, &bool) -> () = $cab;
+                        let b = b.to_array_view::<bool>()?;
+                        let mut a = a.to_array_view_mut::<bool>()?;
+                        ndarray::Zip::from(&mut a).and_broadcast(b).for_each(|a, b| {
+                            let mut c = bool::default();
+                            cab(&mut c, a, b);
+                            *a = c
+                        });
+                        return Ok(())
+                    }
+                    )*
+                 )*
+                bail!("{} does not support {:?} (eval in a)", self.name(), a.datum_type());
             }
 
             fn result_datum_type(&self, _a: DatumType, _b: DatumType) -> TractResult<DatumType> {
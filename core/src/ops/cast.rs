@@ -1,4 +1,5 @@
 use crate::internal::*;
+use num_traits::AsPrimitive;
 
 pub fn cast(to: DatumType) -> Cast {
     Cast { to }
@@ -10,9 +11,48 @@ pub struct Cast {
 }
 
 impl Cast {
-    fn do_eval(&self, input: &Tensor, symbols: &SymbolValues) -> TractResult<TVec<TValue>> {
+    /// Cast `input` in its own buffer, when both types have the same size.
+    fn cast_in_place(&self, input: &mut Tensor) -> bool {
+        unsafe fn cast<S: Datum + AsPrimitive<D>, D: Datum + Copy>(t: &mut Tensor) {
+            let ptr = t.as_ptr_mut_unchecked::<S>();
+            for ix in 0..t.len() {
+                let s = ptr.add(ix).read();
+                ptr.add(ix).cast::<D>().write(s.as_());
+            }
+            t.set_datum_type(D::datum_type());
+        }
+        macro_rules! same_size {
+            ($(($s:ty, $d:ty)),*) => {
+                $(if input.datum_type() == <$s>::datum_type() && self.to == <$d>::datum_type() {
+                    unsafe { cast::<$s, $d>(input) };
+                    return true;
+                })*
+            }
+        }
+        same_size!(
+            (i8, u8),
+            (u8, i8),
+            (i16, u16),
+            (u16, i16),
+            (i32, u32),
+            (u32, i32),
+            (i32, f32),
+            (f32, i32),
+            (u32, f32),
+            (f32, u32),
+            (i64, u64),
+            (u64, i64),
+            (i64, f64),
+            (f64, i64),
+            (u64, f64),
+            (f64, u64)
+        );
+        false
+    }
+
+    fn do_eval(&self, input: TValue, symbols: &SymbolValues) -> TractResult<TVec<TValue>> {
         if input.datum_type() == self.to {
-            Ok(tvec!(input.clone().into_tvalue()))
+            Ok(tvec!(input))
         } else if input.datum_type() == TDim::datum_type() {
             unsafe {
                 let mut tmp = Tensor::uninitialized_dt(i64::datum_type(), input.shape())?;
@@ -21,7 +61,11 @@ impl Cast {
                 {
                     *i = dim.eval(symbols).to_i64()?
                 }
-                Ok(tvec!(tmp.cast_to_dt(self.to)?.into_owned().into_tvalue()))
+                if self.to == i64::datum_type() {
+                    Ok(tvec!(tmp.into_tvalue()))
+                } else {
+                    Ok(tvec!(tmp.cast_to_dt(self.to)?.into_owned().into_tvalue()))
+                }
             }
         } else {
             Ok(tvec!(input.cast_to_dt(self.to)?.into_owned().into_tvalue()))
@@ -42,8 +86,25 @@ impl EvalOp for Cast {
         true
     }
 
-    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
-        self.do_eval(&inputs[0], &Default::default())
+    fn supports_in_place(&self) -> bool {
+        true
+    }
+
+    fn eval_in_place(
+        &self,
+        session: &mut SessionState,
+        mut input: Tensor,
+        _others: TVec<TValue>,
+    ) -> TractResult<TVec<TValue>> {
+        if self.cast_in_place(&mut input) {
+            Ok(tvec!(input.into_tvalue()))
+        } else {
+            self.do_eval(input.into_tvalue(), &session.resolved_symbols)
+        }
+    }
+
+    fn eval(&self, mut inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        self.do_eval(inputs.remove(0), &Default::default())
     }
 
     fn state(
@@ -60,9 +121,9 @@ impl OpState for Cast {
         &mut self,
         session: &mut SessionState,
         _op: &dyn Op,
-        inputs: TVec<TValue>,
+        mut inputs: TVec<TValue>,
     ) -> TractResult<TVec<TValue>> {
-        self.do_eval(&inputs[0], &session.resolved_symbols)
+        self.do_eval(inputs.remove(0), &session.resolved_symbols)
     }
 }
 
@@ -99,8 +160,40 @@ impl TypedOp for Cast {
         _io: InOut,
         change: &AxisOp,
     ) -> TractResult<Option<AxisChangeConsequence>> {
+        // per-axis quantization parameters are bound to an axis index
+        if self.to.is_per_axis_quantized()
+            || model.outlet_fact(node.inputs[0])?.datum_type.is_per_axis_quantized()
+        {
+            return Ok(None);
+        }
         Ok(Some(AxisChangeConsequence::new(model, node, None, change)))
     }
 
     as_op!();
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn same_size_cast_in_place() -> TractResult<()> {
+        let input = tensor1(&[1.5f32, -2., 3.]);
+        let expected = input.cast_to::<i32>()?.into_owned();
+        let ptr = input.as_ptr::<f32>()?;
+        let output =
+            cast(i32::datum_type()).eval_in_place(&mut SessionState::default(), input, tvec!())?;
+        assert_eq!(output[0].as_ptr::<i32>()?, ptr as *const i32);
+        assert_eq!(*output[0], expected);
+        Ok(())
+    }
+
+    #[test]
+    fn widening_cast_allocates() -> TractResult<()> {
+        let input = tensor1(&[1i32, -2, 3]);
+        let output =
+            cast(i64::datum_type()).eval_in_place(&mut SessionState::default(), input, tvec!())?;
+        assert_eq!(*output[0], tensor1(&[1i64, -2, 3]));
+        Ok(())
+    }
+}
@@ -317,7 +317,28 @@ impl AxisOp {
         }
     }
 
+    /// Per-axis quantization parameters follow their axis. Fails if the axis does not survive
+    /// the transformation.
+    pub fn change_datum_type(&self, dt: DatumType) -> TractResult<DatumType> {
+        let Some(params) = dt.qparams().and_then(|qp| qp.per_axis_params()) else { return Ok(dt) };
+        let axis = self.transform_axis(params.axis).with_context(|| {
+            format!("{self:?} does not preserve the quantization axis of {dt:?}")
+        })?;
+        Ok(dt.with_qparams(QParams::per_axis(axis, &params.zero_points, &params.scales)?))
+    }
+
     pub fn change_tensor(&self, tensor: &mut Tensor, broadcasting: bool) -> TractResult<()> {
+        if tensor.datum_type().is_per_axis_quantized() {
+            let dt = self.change_datum_type(tensor.datum_type())?;
+            self.change_tensor_shape(tensor, broadcasting)?;
+            unsafe { tensor.set_datum_type(dt) };
+            Ok(())
+        } else {
+            self.change_tensor_shape(tensor, broadcasting)
+        }
+    }
+
+    fn change_tensor_shape(&self, tensor: &mut Tensor, broadcasting: bool) -> TractResult<()> {
         match self.canonical().as_ref() {
             Add(ix) => {
                 ensure!(*ix <= tensor.rank());
@@ -570,7 +591,7 @@ impl TypedOp for AxisOp {
     fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
         let mut shape = inputs[0].shape.clone();
         self.change_shape(&mut shape, false)?;
-        Ok(tvec!(inputs[0].datum_type.fact(shape)))
+        Ok(tvec!(self.change_datum_type(inputs[0].datum_type)?.fact(shape)))
     }
 
     fn axes_mapping(
@@ -1405,4 +1426,23 @@ mod proptests {
             &[Rm(0), Add(1), r!(3 ; 5,7 => 35 ), Add(4)]
         )
     }
+
+    #[test]
+    fn per_axis_qparams_follow_their_axis() -> TractResult<()> {
+        let dt = DatumType::QI8(QParams::per_axis(1, &[0, 1, 2], &[1., 2., 3.])?);
+        let fact = dt.fact([2, 3]);
+        let moved = Move(1, 0).output_facts(&[&fact])?.remove(0);
+        assert_eq!(moved.datum_type.qparams().unwrap().per_axis_params().unwrap().axis, 0);
+        let added = Add(0).output_facts(&[&fact])?.remove(0);
+        assert_eq!(added.datum_type.qparams().unwrap().per_axis_params().unwrap().axis, 2);
+        assert!(r!(0 ; 2,3 => 6).output_facts(&[&fact]).is_err());
+        assert!(r!(0 ; 2 => 2,1).output_facts(&[&fact]).is_ok());
+
+        let mut t = tensor2(&[[0i8, 1, 2], [3, 4, 5]]);
+        unsafe { t.set_datum_type(dt) };
+        Move(1, 0).change_tensor(&mut t, false)?;
+        assert_eq!(t.shape(), &[3, 2]);
+        assert_eq!(t.datum_type(), moved.datum_type);
+        Ok(())
+    }
 }
@@ -1198,4 +1198,49 @@ mod test {
         assert_eq!(cv.pool_spec.padding, Explicit(tvec![1], tvec![0])); // source + conv
         Ok(())
     }
+
+    #[cfg(feature = "multithread-mm")]
+    #[test]
+    fn multithread_conv_is_bit_identical() -> TractResult<()> {
+        use tract_linalg::multithread::Executor;
+        let mut model = TypedModel::default();
+        let wire = tvec!(model.add_source("source", f32::fact(dims!(2, 4, 12, 12)))?);
+        let kernel = Tensor::from_shape(
+            &[6, 2, 3, 3],
+            &(0..108).map(|i| (i as f32 * 0.37).sin()).collect::<Vec<_>>(),
+        )?;
+        let kernel = model.add_const("kernel", kernel)?;
+        let bias = model.add_const("bias", rctensor0(0.5f32))?;
+        let wire = model.wire_node(
+            "conv",
+            Conv {
+                pool_spec: PoolSpec {
+                    data_format: NCHW,
+                    dilations: None,
+                    strides: None,
+                    kernel_shape: tvec![3, 3],
+                    padding: Explicit(tvec![1, 1], tvec![1, 1]),
+                    input_channels: 4,
+                    output_channels: 6,
+                },
+                kernel_fmt: crate::ops::cnn::KernelFormat::OIHW,
+                group: 2,
+                q_params: None,
+            },
+            &[wire[0], kernel, bias],
+        )?;
+        model.set_output_outlets(&wire)?;
+        let model = model.into_optimized()?;
+        let input = Tensor::from_shape(
+            &[2, 4, 12, 12],
+            &(0..1152).map(|i| (i as f32 * 0.11).cos()).collect::<Vec<_>>(),
+        )?;
+        let single = model.clone().into_runnable()?.run(tvec!(input.clone().into_tvalue()))?;
+        let multi = model
+            .into_runnable()?
+            .with_executor(Executor::multithread(3)?)
+            .run(tvec!(input.into_tvalue()))?;
+        assert_eq!(single[0], multi[0]);
+        Ok(())
+    }
 }
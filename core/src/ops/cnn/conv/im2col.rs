@@ -1,4 +1,5 @@
 use tract_linalg::frame::{MatMatMul, Packer, PackingWriter};
+use tract_linalg::multithread::current_tract_executor;
 
 use crate::internal::*;
 use ndarray::prelude::*;
@@ -156,7 +157,7 @@ impl EvalOp for Im2Col {
         unsafe {
             let mut input = inputs.remove(0).into_tensor();
             let pad_value: Option<&Tensor> = if inputs.len() > 0 { Some(&inputs[0]) } else { None };
-            let mut output = Tensor::uninitialized_aligned_dt(
+            let output = Tensor::uninitialized_aligned_dt(
                 input.datum_type(),
                 &geometry.packed_shape,
                 geometry.b_pack.alignment(),
@@ -168,22 +169,28 @@ impl EvalOp for Im2Col {
             // in the loop, we have normalized the input so that N is
             // always here, and output so that N and G are there.
             if !geometry.pool.output_shape.shape.iter().any(|d| *d == 0) {
-                for i in 0..*geometry.input_shape_with_n.n().unwrap_or(&1) {
+                let n = *geometry.input_shape_with_n.n().unwrap_or(&1);
+                // every (n, g) pair packs to its own disjoint slice of the output: views are
+                // split before dispatch so that each task only gets to write its own.
+                let packs = (0..n * self.group)
+                    .map(|ix| {
+                        let full_prefix = [ix / self.group, ix % self.group];
+                        output.view_at_prefix(&full_prefix[..=(self.group > 1) as usize])
+                    })
+                    .collect::<TractResult<Vec<_>>>()?;
+                current_tract_executor().for_each(0..n * self.group, |ix| {
+                    let (i, g) = (ix / self.group, ix % self.group);
                     let input = input.view_at_prefix(&[i])?;
-                    for g in 0..self.group {
-                        let full_prefix = [i, g];
-                        let actual_prefix = &full_prefix[..=(self.group > 1) as usize];
-                        let mut packed = output.view_at_prefix_mut(actual_prefix)?;
-                        dispatch_copy_by_size!(Patcher::patch(input.datum_type())(
-                            &geometry.patcher,
-                            &geometry,
-                            &input,
-                            &mut packed,
-                            g,
-                            pad_value
-                        ))?
-                    }
-                }
+                    let mut packed = packs[ix].clone();
+                    dispatch_copy_by_size!(Patcher::patch(input.datum_type())(
+                        &geometry.patcher,
+                        &geometry,
+                        &input,
+                        &mut packed,
+                        g,
+                        pad_value
+                    ))
+                })?;
             }
             Ok(tvec!(output.into_tvalue()))
         }
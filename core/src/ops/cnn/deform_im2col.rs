@@ -0,0 +1,207 @@
+use crate::internal::*;
+use crate::ops::cnn::{PatchSpec, PoolSpec};
+use crate::ops::nn::DataFormat;
+use ndarray::prelude::*;
+use num_traits::Float;
+
+/// Im2col for deformable convolutions (NCHW, 2D).
+///
+/// Each kernel tap is displaced by a (y, x) offset learned per output position, and the input
+/// is sampled bilinearly there (zero outside the image), then optionally modulated by a mask.
+/// Inputs are the image, the offsets `[N, offset_group * K * 2, OH, OW]` and an optional mask
+/// `[N, offset_group * K, OH, OW]`, where K is the kernel volume. The output is
+/// `[N, C * K, OH * OW]`, ready to be multiplied by the kernel as in a regular convolution.
+#[derive(Debug, Clone, new, Hash)]
+pub struct DeformIm2Col {
+    pub pool_spec: PoolSpec,
+    pub offset_group: usize,
+}
+
+impl DeformIm2Col {
+    fn kernel_len(&self) -> usize {
+        self.pool_spec.kernel_shape.iter().product()
+    }
+
+    fn eval_t<T: Datum + Float>(
+        &self,
+        input: &Tensor,
+        offsets: &Tensor,
+        mask: Option<&Tensor>,
+    ) -> TractResult<Tensor> {
+        let mut spec = PatchSpec::for_full_shape(DataFormat::NCHW, input.shape())?
+            .with_kernel_shape(self.pool_spec.kernel_shape.clone())
+            .with_padding(self.pool_spec.padding.clone());
+        if let Some(strides) = self.pool_spec.strides.clone() {
+            spec = spec.with_strides(strides);
+        }
+        if let Some(dilations) = self.pool_spec.dilations.clone() {
+            spec = spec.with_dilations(dilations);
+        }
+        let patch = spec.into_patch();
+        let input = input.to_array_view::<T>()?.into_dimensionality::<Ix4>()?;
+        let offsets = offsets.to_array_view::<T>()?.into_dimensionality::<Ix4>()?;
+        let mask = mask
+            .map(|m| -> TractResult<ArrayView4<T>> {
+                Ok(m.to_array_view::<T>()?.into_dimensionality()?)
+            })
+            .transpose()?;
+        let (n, c, _, _) = input.dim();
+        let (oh, ow) = (patch.output_shape[0], patch.output_shape[1]);
+        let k = self.kernel_len();
+        let channels_per_group = c / self.offset_group;
+        let strides = &patch.spec.strides;
+        let mut output = Array3::<T>::zeros((n, c * k, oh * ow));
+        for b in 0..n {
+            for ch in 0..c {
+                let image = input.slice(s![b, ch, .., ..]);
+                let group = ch / channels_per_group;
+                for (tap, field) in patch.data_field.outer_iter().enumerate() {
+                    let offset_channel = (group * k + tap) * 2;
+                    for y in 0..oh {
+                        for x in 0..ow {
+                            let py = (y * strides[0]) as isize + field[0];
+                            let px = (x * strides[1]) as isize + field[1];
+                            let py = T::from(py).unwrap() + offsets[(b, offset_channel, y, x)];
+                            let px = T::from(px).unwrap() + offsets[(b, offset_channel + 1, y, x)];
+                            let mut value = bilinear(image, py, px);
+                            if let Some(mask) = &mask {
+                                value = value * mask[(b, group * k + tap, y, x)];
+                            }
+                            output[(b, ch * k + tap, y * ow + x)] = value;
+                        }
+                    }
+                }
+            }
+        }
+        Ok(output.into_tensor())
+    }
+}
+
+fn bilinear<T: Float>(image: ArrayView2<T>, y: T, x: T) -> T {
+    let (h, w) = image.dim();
+    // written so that NaN coordinates fail the check and sample zero
+    let inside =
+        y > -T::one() && x > -T::one() && y < T::from(h).unwrap() && x < T::from(w).unwrap();
+    if !inside {
+        return T::zero();
+    }
+    let (y0, x0) = (y.floor(), x.floor());
+    let (dy, dx) = (y - y0, x - x0);
+    let (Some(y0), Some(x0)) = (y0.to_isize(), x0.to_isize()) else { return T::zero() };
+    let at = |y: isize, x: isize| {
+        if y >= 0 && x >= 0 && (y as usize) < h && (x as usize) < w {
+            image[(y as usize, x as usize)]
+        } else {
+            T::zero()
+        }
+    };
+    let one = T::one();
+    at(y0, x0) * (one - dy) * (one - dx)
+        + at(y0, x0 + 1) * (one - dy) * dx
+        + at(y0 + 1, x0) * dy * (one - dx)
+        + at(y0 + 1, x0 + 1) * dy * dx
+}
+
+impl Op for DeformIm2Col {
+    fn name(&self) -> Cow<str> {
+        "DeformIm2Col".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        let mut info = self.pool_spec.info();
+        info.push(format!("Offset groups: {}", self.offset_group));
+        Ok(info)
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for DeformIm2Col {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        let dt = inputs[0].datum_type();
+        let output = dispatch_floatlike!(Self::eval_t(dt)(
+            self,
+            &inputs[0],
+            &inputs[1],
+            inputs.get(2).map(|m| &**m)
+        ))?;
+        Ok(tvec!(output.into_tvalue()))
+    }
+}
+
+impl TypedOp for DeformIm2Col {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        ensure!(inputs.len() == 2 || inputs.len() == 3, "DeformIm2Col expects 2 or 3 inputs");
+        ensure!(
+            self.pool_spec.data_format == DataFormat::NCHW && self.pool_spec.rank() == 2,
+            "DeformIm2Col only supports 2D NCHW inputs"
+        );
+        ensure!(inputs[0].rank() == 4 && inputs[1].rank() == 4);
+        ensure!(inputs[0].datum_type.is_float());
+        let k = self.kernel_len();
+        ensure!(inputs[1].shape[1] == (self.offset_group * k * 2).to_dim());
+        if let Some(mask) = inputs.get(2) {
+            ensure!(mask.rank() == 4 && mask.shape[1] == (self.offset_group * k).to_dim());
+        }
+        if let Ok(c) = inputs[0].shape[1].to_usize() {
+            ensure!(c % self.offset_group == 0, "Channels must be divisible by offset groups");
+        }
+        let spatial = self.pool_spec.computed_padding(&inputs[0].shape[2..]);
+        let n = inputs[0].shape[0].clone();
+        let c = inputs[0].shape[1].clone() * k;
+        let len = spatial.into_iter().map(|d| d.convoluted).product::<TDim>();
+        Ok(tvec!(inputs[0].datum_type.fact(&[n, c, len])))
+    }
+
+    as_op!();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::cnn::PaddingSpec;
+
+    fn op(kernel_shape: TVec<usize>, padding: PaddingSpec) -> DeformIm2Col {
+        let pool_spec = PoolSpec::new(DataFormat::NCHW, kernel_shape, padding, None, None, 1, 1);
+        DeformIm2Col::new(pool_spec, 1)
+    }
+
+    #[test]
+    fn zero_offsets_is_im2col() -> TractResult<()> {
+        let op = op(tvec!(2, 2), PaddingSpec::Valid);
+        let input = tensor4(&[[[[1f32, 2., 3.], [4., 5., 6.]]]]);
+        let offsets = Tensor::zero::<f32>(&[1, 8, 1, 2])?;
+        let output = op.eval(tvec!(input.into_tvalue(), offsets.into_tvalue()))?;
+        assert_eq!(*output[0], tensor3(&[[[1f32, 2.], [2., 3.], [4., 5.], [5., 6.]]]));
+        Ok(())
+    }
+
+    #[test]
+    fn fractional_offsets_and_mask() -> TractResult<()> {
+        let op = op(tvec!(1, 1), PaddingSpec::Valid);
+        let input = tensor4(&[[[[1f32, 2.], [3., 4.]]]]);
+        // one output position per pixel: shift (0.5, 0.5), so the last row and column fall
+        // half outside of the image
+        let offsets = tensor1(&[0.5f32; 8]).into_shape(&[1, 2, 2, 2])?;
+        let mask = tensor4(&[[[[1f32, 1.], [1., 0.5]]]]);
+        let output =
+            op.eval(tvec!(input.into_tvalue(), offsets.into_tvalue(), mask.into_tvalue()))?;
+        assert_eq!(*output[0], tensor3(&[[[2.5f32, 1.5, 1.75, 0.5]]]));
+        Ok(())
+    }
+
+    #[test]
+    fn non_finite_offsets_sample_zero() -> TractResult<()> {
+        let op = op(tvec!(1, 1), PaddingSpec::Valid);
+        let input = tensor4(&[[[[1f32, 2.], [3., 4.]]]]);
+        let offsets = tensor1(&[f32::NAN, f32::INFINITY, f32::NEG_INFINITY, 0., 0., 0., 0., 0.])
+            .into_shape(&[1, 2, 2, 2])?;
+        let output = op.eval(tvec!(input.into_tvalue(), offsets.into_tvalue()))?;
+        assert_eq!(*output[0], tensor3(&[[[0f32, 0., 0., 4.]]]));
+        Ok(())
+    }
+}
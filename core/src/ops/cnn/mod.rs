@@ -2,6 +2,7 @@ use crate::internal::*;
 
 pub mod conv;
 pub mod deconv;
+mod deform_im2col;
 mod maxpool;
 mod padding;
 mod patch_axis;
@@ -11,6 +12,7 @@ mod sumpool;
 
 pub use self::conv::{Conv, KernelFormat};
 pub use self::deconv::Deconv;
+pub use self::deform_im2col::DeformIm2Col;
 pub use self::maxpool::MaxPool;
 pub use self::padding::PaddingSpec;
 pub use self::patch_axis::PatchAxis;
@@ -8,11 +8,12 @@ use super::EinSum;
 use crate::internal::*;
 
 pub fn rewrite_einsums_as_matmul(model: &mut TypedModel) -> TractResult<()> {
-    let rules = Rewriter::default().with_rule_for::<EinSum>("einsum-to-matmul", einsum_rules);
+    let rules =
+        Rewriter::default().with_rule_for::<EinSum>("einsum-to-matmul", rewrite_einsum_as_matmul);
     rules.rewrite(&(), model)
 }
 
-fn einsum_rules(
+pub fn rewrite_einsum_as_matmul(
     _ctx: &(),
     model: &TypedModel,
     node: &TypedNode,
@@ -198,13 +199,15 @@ impl EvalOp for BasicMatMul {
         let (a, b) = args_2!(inputs);
         let output_shape = self.output_shape(a.shape(), b.shape());
         if let Some(qp) = self.quantize_output {
+            let (a0, a_scale) = a.datum_type().try_zp_scale()?;
+            let (b0, b_scale) = b.datum_type().try_zp_scale()?;
             let mut acc = Tensor::zero_dt(i32::datum_type(), &output_shape)?;
             let mut a_i32 = a.cast_to::<i32>()?.into_owned();
-            a_i32.as_slice_mut::<i32>()?.iter_mut().for_each(|x| *x -= a.datum_type().zp_scale().0);
+            a_i32.as_slice_mut::<i32>()?.iter_mut().for_each(|x| *x -= a0);
             let mut b_i32 = b.cast_to::<i32>()?.into_owned();
-            b_i32.as_slice_mut::<i32>()?.iter_mut().for_each(|x| *x -= b.datum_type().zp_scale().0);
+            b_i32.as_slice_mut::<i32>()?.iter_mut().for_each(|x| *x -= b0);
             self.mm::<i32>(&mut acc, &a_i32, &b_i32)?;
-            let scale = a.datum_type().zp_scale().1 * b.datum_type().zp_scale().1 / qp.zp_scale().1;
+            let scale = a_scale * b_scale / qp.try_zp_scale()?.1;
             let scaler = Scaler::new(scale, tract_linalg::mmm::RoundingPolicy::Even);
             acc.to_array_view_mut::<i32>()?.iter_mut().for_each(|x| *x = *x * scaler);
             let mut c: Tensor = acc.cast_to_dt(qp.unquantized())?.into_owned();
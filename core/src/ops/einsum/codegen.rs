@@ -225,16 +225,16 @@ fn dequant(
     let mut patch = TypedModelPatch::new("Dequantizing einsum");
 
     let mut taps = patch.taps(model, &node.inputs)?;
-    for ab in [0, 1] {
-        let scale_input = 4 + ab * 2;
-        if !patch.outlet_fact(taps[scale_input])?.shape.volume().is_one() {
-            let q_axis_in_output = op.axes.axis((InOut::In(scale_input), 0))?.outputs[0][0];
+    // per-axis zero points and scales of a and b
+    for q_input in 3..7 {
+        if !patch.outlet_fact(taps[q_input])?.shape.volume().is_one() {
+            let q_axis_in_output = op.axes.axis((InOut::In(q_input), 0))?.outputs[0][0];
             let output_rank = node.outputs[0].fact.rank();
             for i in 1..(output_rank - q_axis_in_output) {
-                taps[scale_input] = patch.wire_node(
-                    format!("{name}.scale_input{ab}_axis_fix_{i}"),
+                taps[q_input] = patch.wire_node(
+                    format!("{name}.q_input{q_input}_axis_fix_{i}"),
                     AxisOp::Add(i),
-                    &[taps[scale_input]],
+                    &[taps[q_input]],
                 )?[0];
             }
         }
@@ -103,18 +103,17 @@ pub fn eval_q(expr: &AxesMapping, qp: DatumType, inputs: TVec<TValue>) -> TractR
         bail!("Expect exactly 9 inputs")
     };
 
-    let mut a = a.cast_to::<i32>()?.into_owned();
-    let a0 = a0.cast_to_scalar::<i32>()?;
-    a.as_slice_mut::<i32>()?.iter_mut().for_each(|x| *x -= a0);
-    let mut b = b.cast_to::<i32>()?.into_owned();
-    let b0 = b0.cast_to_scalar::<i32>()?;
-    b.as_slice_mut::<i32>()?.iter_mut().for_each(|x| *x -= b0);
+    let mut a = a.cast_to::<i32>()?.into_owned().into_array::<i32>()?;
+    a -= &q_param::<i32>(expr, 3, InOut::In(0), a.ndim(), a0)?;
+    let mut b = b.cast_to::<i32>()?.into_owned().into_array::<i32>()?;
+    b -= &q_param::<i32>(expr, 5, InOut::In(1), b.ndim(), b0)?;
 
     let mut output =
         eval_t::<i32>(expr, tvec!(a.into_tvalue(), b.into_tvalue()))?.into_array::<i32>()?;
-    let scale = a_scale.cast_to_scalar::<f32>()? * b_scale.cast_to_scalar::<f32>()?
+    let rank = output.ndim();
+    let scale = q_param::<f32>(expr, 4, InOut::Out(0), rank, a_scale)?
+        * q_param::<f32>(expr, 6, InOut::Out(0), rank, b_scale)?
         / c_scale.cast_to_scalar::<f32>()?;
-    let scale = Scaler::new(scale, tract_linalg::mmm::RoundingPolicy::Even);
     let c0 = c0.cast_to_scalar::<i32>()?;
 
     if bias.rank() == 0 {
@@ -126,8 +125,10 @@ pub fn eval_q(expr: &AxesMapping, qp: DatumType, inputs: TVec<TValue>) -> TractR
         output = output + bias;
     }
 
-    output.mapv_inplace(|x| x * scale);
-    output.mapv_inplace(|x| x + c0);
+    // one scaler per tensor or per channel, broadcast over the output
+    let scalers = scale.mapv(|s| Scaler::new(s, tract_linalg::mmm::RoundingPolicy::Even));
+    let scalers = scalers.broadcast(output.shape()).context("Incompatible quantization scales")?;
+    tract_ndarray::Zip::from(&mut output).and(&scalers).for_each(|x, s| *x = *x * *s + c0);
 
     if qp.unquantized() == i8::datum_type() {
         output.mapv_inplace(|x| x.clamp(i8::MIN as _, i8::MAX as _))
@@ -136,3 +137,28 @@ pub fn eval_q(expr: &AxesMapping, qp: DatumType, inputs: TVec<TValue>) -> TractR
     }
     Ok(output.into_tensor().cast_to_dt(qp)?.into_owned())
 }
+
+// zero points and scales can be per-axis: they are then rank 1 and linked to an axis of
+// the tensor they apply to
+fn q_param<T: Datum + Copy>(
+    expr: &AxesMapping,
+    slot: usize,
+    io: InOut,
+    rank: usize,
+    param: &Tensor,
+) -> TractResult<tract_ndarray::ArrayD<T>> {
+    let param = param.cast_to::<T>()?;
+    let mut shape = tvec!(1; rank);
+    if param.rank() == 1 {
+        let axis = expr.axis((InOut::In(slot), 0))?;
+        let positions = match io {
+            InOut::In(ix) => &axis.inputs[ix],
+            InOut::Out(ix) => &axis.outputs[ix],
+        };
+        let position = positions
+            .first()
+            .with_context(|| format!("Quantization parameter #{slot} is not linked to {io:?}"))?;
+        shape[*position] = param.len();
+    }
+    Ok(param.to_array_view::<T>()?.into_shape(&*shape)?.to_owned())
+}
@@ -16,7 +16,7 @@ mod codegen;
 #[cfg(test)]
 mod proptest;
 
-pub use as_matmul::{rewrite_einsums_as_matmul, BasicMatMul};
+pub use as_matmul::{rewrite_einsum_as_matmul, rewrite_einsums_as_matmul, BasicMatMul};
 
 #[derive(Clone, Hash)]
 pub struct EinSum {
@@ -328,3 +328,42 @@ impl TypedOp for EinSum {
 
     as_op!();
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn q_per_channel_scale() -> TractResult<()> {
+        let a_dt = i8::datum_type().with_zp_scale(1, 0.5);
+        let c_dt = i8::datum_type().with_zp_scale(-2, 0.25);
+        let mut a = tensor2(&[[3i8, -1, 2], [0, 4, -3]]);
+        unsafe { a.set_datum_type(a_dt) };
+        let mut model = TypedModel::default();
+        let inputs = [
+            model.add_source("a", a_dt.fact([2, 3]))?,
+            model.add_const("b", tensor2(&[[1i8, 2, -1], [-2, 0, 3]]))?,
+            model.add_const("bias", tensor1(&[1i32, -1]))?,
+            model.add_const("a0", tensor0(1i32))?,
+            model.add_const("a_scale", tensor0(0.5f32))?,
+            model.add_const("b0", tensor0(0i32))?,
+            model.add_const("b_scale", tensor1(&[1f32, 0.5]))?,
+            model.add_const("c0", tensor0(-2i32))?,
+            model.add_const("c_scale", tensor0(0.25f32))?,
+        ];
+        let op = EinSum {
+            axes: "mk,nk,n,,,,n,,->mn".parse()?,
+            operating_dt: i32::datum_type(),
+            q_params: Some(c_dt),
+        };
+        let output = model.wire_node("einsum", op, &inputs)?;
+        model.set_output_outlets(&output)?;
+        // (a - 1) . b + bias = [[-2, -2], [10, -11]], scaled by [2, 1] and shifted by -2
+        let mut expected = tensor2(&[[-6i8, -4], [18, -13]]);
+        unsafe { expected.set_datum_type(c_dt) };
+        let found = model.clone().into_runnable()?.run(tvec!(a.clone().into_tvalue()))?.remove(0);
+        found.close_enough(&expected, Approximation::Exact)?;
+        let found = model.into_optimized()?.into_runnable()?.run(tvec!(a.into_tvalue()))?.remove(0);
+        found.close_enough(&expected, Approximation::Exact)
+    }
+}
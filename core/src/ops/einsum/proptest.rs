@@ -8,6 +8,7 @@ use tract_ndarray::ArrayD;
 use crate::axes::AxesMapping;
 
 use super::EinSum;
+use crate::ops::matmul::mir_quant::{link_per_axis_qparams, wire_zp_scale};
 
 #[derive(Clone)]
 struct BinEinsumProblem {
@@ -175,3 +176,36 @@ fn unicast_2() {
     .check()
     .unwrap()
 }
+
+#[test]
+fn q_per_axis_datum_type() -> TractResult<()> {
+    let a_dt = i8::datum_type().with_zp_scale(1, 0.5);
+    let b_dt = i8::datum_type().quantize(QParams::per_axis(0, &[0, 1], &[1., 0.5]).unwrap());
+    let c_dt = i8::datum_type().with_zp_scale(-2, 0.25);
+    let mut a = tensor2(&[[3i8, -1, 2], [0, 4, -3]]);
+    unsafe { a.set_datum_type(a_dt) };
+    let mut b = tensor2(&[[1i8, 2, -1], [-2, 0, 3]]);
+    unsafe { b.set_datum_type(b_dt) };
+    let mut model = TypedModel::default();
+    let a_wire = model.add_source("a", a_dt.fact([2, 3]))?;
+    let b_wire = model.add_const("b", b)?;
+    let bias = Tensor::zero_scalar_dt(i32::datum_type().with_zp_scale(0, 0.5))?;
+    let bias = model.add_const("bias", bias)?;
+    let (a0, a_scale) = wire_zp_scale(&mut model, "a", a_dt)?;
+    let (b0, b_scale) = wire_zp_scale(&mut model, "b", b_dt)?;
+    let (c0, c_scale) = wire_zp_scale(&mut model, "c", c_dt)?;
+    let axes = "mk,nk,,,,,,,->mn".parse()?;
+    let axes = link_per_axis_qparams(axes, b_dt, 1, [5, 6])?;
+    assert_eq!(axes.to_string(), "mk,nk,,,,n,n,,->mn");
+    let op = EinSum { axes, operating_dt: i32::datum_type(), q_params: Some(c_dt) };
+    let inputs = [a_wire, b_wire, bias, a0, a_scale, b0, b_scale, c0, c_scale];
+    let output = model.wire_node("einsum", op, &inputs)?;
+    model.set_output_outlets(&output)?;
+    // (a - 1) . (b - [0, 1]) = [[-3, -2], [9, -8]], scaled by [2, 1] and shifted by -2
+    let mut expected = tensor2(&[[-8i8, -4], [16, -10]]);
+    unsafe { expected.set_datum_type(c_dt) };
+    let found = model.clone().into_runnable()?.run(tvec!(a.clone().into_tvalue()))?.remove(0);
+    found.close_enough(&expected, Approximation::Exact)?;
+    let found = model.into_optimized()?.into_runnable()?.run(tvec!(a.into_tvalue()))?.remove(0);
+    found.close_enough(&expected, Approximation::Exact)
+}
@@ -89,6 +89,23 @@ impl EvalOp for ElementWiseOp {
         true
     }
 
+    fn supports_in_place(&self) -> bool {
+        true
+    }
+
+    fn eval_in_place(
+        &self,
+        _session: &mut SessionState,
+        mut input: Tensor,
+        _others: TVec<TValue>,
+    ) -> TractResult<TVec<TValue>> {
+        if self.0.output_type(input.datum_type()).is_some() {
+            return Ok(tvec!(self.0.eval_out_of_place(&input, self.1)?.into_tvalue()));
+        }
+        self.0.eval_in_place(&mut input, self.1)?;
+        Ok(tvec!(input.into_tvalue()))
+    }
+
     fn eval(&self, mut inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
         if let Some(_dt) = self.0.output_type(inputs[0].datum_type()) {
             Ok(tvec!(self.0.eval_out_of_place(&inputs[0], self.1)?.into_tvalue()))
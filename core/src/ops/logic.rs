@@ -2,7 +2,9 @@
 #![allow(clippy::unnecessary_cast)]
 
 mod ite;
+mod while_loop;
 pub use ite::IfThenElse;
+pub use while_loop::{WhileLoop, WhileLoopState};
 
 use ndarray::*;
 
@@ -0,0 +1,428 @@
+use crate::internal::*;
+use crate::ops::scan::{InputMapping, OutputMapping, Scan, ScanInfo};
+use crate::ops::source::TypedSource;
+use crate::ops::OpStateFreeze;
+
+/// Run a body while a condition holds, up to a maximum number of iterations.
+///
+/// Inputs are the maximum trip count (i64 scalar), the initial condition (bool scalar), the
+/// initial values of the `carried` loop dependencies, then the values the body closes over.
+///
+/// The body takes the same inputs, except the trip count is replaced by the iteration number.
+/// It returns the condition for the next iteration, the new carried values, then any number of
+/// scan outputs.
+///
+/// The op outputs the last carried values, then the scan outputs of all iterations
+/// concatenated along their first axis. As the iteration count is only known at runtime, it is
+/// represented by the `iterations` symbol in output facts.
+#[derive(Debug, Clone)]
+pub struct WhileLoop {
+    pub body: TypedModel,
+    pub carried: usize,
+    pub iterations: Symbol,
+    pub optimized: bool,
+}
+
+impl WhileLoop {
+    pub fn new(body: TypedModel, carried: usize, iterations: Symbol) -> TractResult<WhileLoop> {
+        body.check_consistency()?;
+        ensure!(body.inputs.len() >= 2 + carried, "Loop body misses inputs");
+        ensure!(body.outputs.len() > carried, "Loop body misses outputs");
+        ensure!(*body.input_fact(0)? == i64::scalar_fact(), "Loop iteration must be a i64 scalar");
+        ensure!(
+            *body.input_fact(1)? == bool::scalar_fact(),
+            "Loop condition must be a bool scalar"
+        );
+        ensure!(
+            body.output_fact(0)?.without_value() == bool::scalar_fact(),
+            "Loop body must return its condition as a bool scalar"
+        );
+        Ok(WhileLoop { body, carried, iterations, optimized: false })
+    }
+
+    fn scan_outputs(&self) -> TractResult<usize> {
+        ensure!(
+            self.body.outputs.len() > self.carried,
+            "Loop body must output the condition and {} carried values, it has {} outputs",
+            self.carried,
+            self.body.outputs.len()
+        );
+        Ok(self.body.outputs.len() - 1 - self.carried)
+    }
+
+    /// Number of iterations if the loop is known to always run to its maximum trip count.
+    pub fn fixed_trip_count(&self, inputs: &[&TypedFact]) -> TractResult<Option<usize>> {
+        let Some(max) = &inputs[0].konst else { return Ok(None) };
+        let Some(cond) = &inputs[1].konst else { return Ok(None) };
+        let body_cond = self.body.outputs[0];
+        let body_cond_is_true = body_cond == self.body.inputs[1]
+            || self
+                .body
+                .outlet_fact(body_cond)?
+                .konst
+                .as_ref()
+                .map(|k| k.cast_to_scalar::<bool>())
+                .transpose()?
+                == Some(true);
+        let max = max.cast_to_scalar::<i64>()?;
+        if !cond.cast_to_scalar::<bool>()? || !body_cond_is_true || max > i32::MAX as i64 {
+            return Ok(None);
+        }
+        Ok(Some(max.max(0) as usize))
+    }
+
+    /// Wire the loop as a `Scan` over the iteration numbers, if it always runs to its maximum
+    /// trip count and its carried values keep the same type and shape from one iteration to
+    /// the next.
+    pub fn wire_as_scan(
+        &self,
+        target: &mut TypedModel,
+        name: &str,
+        inputs: &[OutletId],
+    ) -> TractResult<Option<TVec<OutletId>>> {
+        let facts: TVec<TypedFact> =
+            inputs.iter().map(|i| target.outlet_fact(*i).cloned()).collect::<TractResult<_>>()?;
+        let facts: TVec<&TypedFact> = facts.iter().collect();
+        let Some(trip_count) = self.fixed_trip_count(&facts)? else { return Ok(None) };
+        if trip_count == 0 {
+            return Ok(None);
+        }
+        for ix in 0..self.carried {
+            if self.body.input_fact(2 + ix)? != self.body.output_fact(1 + ix)? {
+                return Ok(None);
+            }
+        }
+
+        // the iteration number comes as a chunk of one element from a scanned range
+        let mut body = self.body.clone();
+        let iteration = body.inputs[0];
+        let chunk_fact = i64::fact([1]);
+        body.node_mut(iteration.node).op = Box::new(TypedSource::new(chunk_fact.clone()));
+        body.set_outlet_fact(iteration, chunk_fact)?;
+        let successors = body.outlet_successors(iteration).to_vec();
+        let scalar = body.wire_node(
+            format!("{}.as_scalar", body.node(iteration.node).name),
+            AxisOp::Rm(0),
+            &[iteration],
+        )?[0];
+        for succ in successors {
+            body.add_edge(scalar, succ)?;
+        }
+        for output in &mut body.outputs {
+            if *output == iteration {
+                *output = scalar;
+            }
+        }
+
+        let mut input_mapping =
+            vec![InputMapping::Scan(ScanInfo { axis: 0, chunk: 1 }), InputMapping::Full];
+        input_mapping.extend((0..self.carried).map(|_| InputMapping::State));
+        input_mapping.extend((2 + self.carried..inputs.len()).map(|_| InputMapping::Full));
+        let mut output_mapping = vec![OutputMapping::default()];
+        for ix in 0..self.carried {
+            output_mapping.push(OutputMapping {
+                state: true,
+                last_value_slot: Some(ix),
+                ..OutputMapping::default()
+            });
+        }
+        for ix in 0..self.scan_outputs()? {
+            output_mapping.push(OutputMapping {
+                scan: Some((self.carried + ix, ScanInfo { axis: 0, chunk: 1 })),
+                ..OutputMapping::default()
+            });
+        }
+        let range = tract_ndarray::Array1::from_iter(0..trip_count as i64);
+        let range = target.add_const(format!("{name}.iterations"), range.into_tensor())?;
+        let mut scan_inputs: TVec<OutletId> = tvec!(range);
+        scan_inputs.extend(inputs.iter().skip(1).cloned());
+        let scan = Scan::new(body, input_mapping, output_mapping, 0)?;
+        Ok(Some(target.wire_node(name, scan, &scan_inputs)?))
+    }
+}
+
+impl Op for WhileLoop {
+    fn name(&self) -> Cow<str> {
+        "WhileLoop".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("carried: {} scan outputs: {}", self.carried, self.scan_outputs()?)])
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for WhileLoop {
+    fn is_stateless(&self) -> bool {
+        false
+    }
+
+    fn state(
+        &self,
+        _session: &mut SessionState,
+        _node_id: usize,
+    ) -> TractResult<Option<Box<dyn OpState>>> {
+        Ok(Some(Box::new(self.body_state()?)))
+    }
+}
+
+/// Keeps the plan and the state of the body from one evaluation to the next.
+#[derive(Debug, Clone)]
+pub struct WhileLoopState(TypedSimpleState<TypedModel, Arc<TypedSimplePlan<TypedModel>>>);
+
+impl WhileLoop {
+    pub fn body_state(&self) -> TractResult<WhileLoopState> {
+        let plan = SimplePlan::new(self.body.clone())?;
+        Ok(WhileLoopState(TypedSimpleState::new(Arc::new(plan))?))
+    }
+}
+
+impl OpState for WhileLoopState {
+    fn eval(
+        &mut self,
+        session: &mut SessionState,
+        op: &dyn Op,
+        inputs: TVec<TValue>,
+    ) -> TractResult<TVec<TValue>> {
+        let op = op.downcast_ref::<WhileLoop>().context("Wrong op")?;
+        let max = inputs[0].cast_to_scalar::<i64>()?;
+        let mut cond = inputs[1].cast_to_scalar::<bool>()?;
+        let mut carried: TVec<TValue> = inputs[2..2 + op.carried].into();
+        let closures = &inputs[2 + op.carried..];
+        // every evaluation starts with fresh body op states
+        let state = &mut self.0;
+        state.reset_op_states()?;
+        state.session_state.cancellation = session.cancellation.clone();
+        let mut scans: Vec<Vec<TValue>> = vec![vec![]; op.scan_outputs()?];
+        let mut iteration = 0i64;
+        while iteration < max && cond {
+            session.check_cancellation()?;
+            let mut body_inputs = tvec!(tensor0(iteration).into_tvalue(), tensor0(cond).into());
+            body_inputs.extend(carried.drain(..));
+            body_inputs.extend(closures.iter().cloned());
+            let mut outputs = state.run(body_inputs)?;
+            for (scan, value) in scans.iter_mut().zip(outputs.drain(1 + op.carried..)) {
+                scan.push(value);
+            }
+            carried.extend(outputs.drain(1..));
+            cond = outputs[0].cast_to_scalar::<bool>()?;
+            iteration += 1;
+        }
+        let mut outputs = carried;
+        for (ix, values) in scans.into_iter().enumerate() {
+            let tensor = if values.len() > 0 {
+                Tensor::stack_tensors(0, &values)?
+            } else {
+                let fact = op.body.output_fact(1 + op.carried + ix)?;
+                let mut shape: TVec<usize> = fact
+                    .shape
+                    .as_concrete()
+                    .context("Empty loop scan output with symbolic shape")?
+                    .into();
+                shape[0] = 0;
+                Tensor::zero_dt(fact.datum_type, &shape)?
+            };
+            outputs.push(tensor.into_tvalue());
+        }
+        Ok(outputs)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct FrozenWhileLoopState(TypedFrozenSimpleState<TypedModel, Arc<TypedSimplePlan<TypedModel>>>);
+
+impl OpStateFreeze for WhileLoopState {
+    fn freeze(&self) -> Box<dyn FrozenOpState> {
+        Box::new(FrozenWhileLoopState(self.0.freeze()))
+    }
+}
+
+// the body state is reset by every evaluation, nothing outlives a turn
+impl FrozenOpState for FrozenWhileLoopState {
+    fn unfreeze(&self) -> Box<dyn OpState> {
+        Box::new(WhileLoopState(self.0.unfreeze()))
+    }
+
+    fn save(&self) -> TractResult<SavedOpState> {
+        Ok(SavedOpState::default())
+    }
+
+    fn load(&self, _saved: &SavedOpState) -> TractResult<Box<dyn FrozenOpState>> {
+        Ok(Box::new(self.clone()))
+    }
+}
+
+impl TypedOp for WhileLoop {
+    as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        ensure!(inputs.len() == self.body.inputs.len());
+        ensure!(inputs[0].datum_type == i64::datum_type() && inputs[0].rank() == 0);
+        ensure!(inputs[1].datum_type == bool::datum_type() && inputs[1].rank() == 0);
+        let iterations = if let Some(n) = self.fixed_trip_count(inputs)? {
+            n.to_dim()
+        } else {
+            self.iterations.to_dim()
+        };
+        let scan_outputs = self.scan_outputs()?;
+        let mut facts = tvec!();
+        for ix in 0..self.carried {
+            let fact = self.body.output_fact(1 + ix)?;
+            ensure!(fact.datum_type == inputs[2 + ix].datum_type);
+            facts.push(fact.datum_type.fact(fact.shape.clone()));
+        }
+        for ix in 0..scan_outputs {
+            let fact = self.body.output_fact(1 + self.carried + ix)?;
+            ensure!(fact.rank() > 0, "Loop scan outputs must have a leading axis");
+            let mut shape = fact.shape.clone();
+            shape.set(0, shape[0].clone() * &iterations);
+            facts.push(fact.datum_type.fact(shape));
+        }
+        Ok(facts)
+    }
+
+    fn declutter(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        let mut patch = TypedModelPatch::default();
+        let inputs = patch.taps(model, &node.inputs)?;
+        if let Some(outputs) = self.wire_as_scan(&mut patch, &node.name, &inputs)? {
+            for (ix, output) in outputs.into_iter().enumerate() {
+                patch.shunt_outside(model, OutletId::new(node.id, ix), output)?;
+            }
+            return Ok(Some(patch));
+        }
+        Ok(None)
+    }
+
+    fn concretize_dims(
+        &self,
+        _source: &TypedModel,
+        node: &TypedNode,
+        target: &mut TypedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+        values: &SymbolValues,
+    ) -> TractResult<TVec<OutletId>> {
+        let inputs = node.inputs.iter().map(|o| mapping[o]).collect::<TVec<_>>();
+        let op = WhileLoop { body: self.body.concretize_dims(values)?, ..self.clone() };
+        target.wire_node(&node.name, op, &inputs)
+    }
+
+    fn codegen(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        if self.optimized {
+            return Ok(None);
+        }
+        let op = WhileLoop {
+            body: self.body.clone().into_optimized()?,
+            optimized: true,
+            ..self.clone()
+        };
+        Ok(Some(TypedModelPatch::replace_single_op(model, node, &node.inputs, op)?))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::math;
+
+    // sums 1.. while the sum stays below a limit, also outputting the partial sums
+    fn sum_body() -> TractResult<TypedModel> {
+        let mut body = TypedModel::default();
+        let iteration = body.add_source("iteration", i64::scalar_fact())?;
+        let _cond = body.add_source("cond", bool::scalar_fact())?;
+        let sum = body.add_source("sum", i64::scalar_fact())?;
+        let limit = body.add_source("limit", i64::scalar_fact())?;
+        let one = body.add_const("one", tensor0(1i64))?;
+        let next = body.wire_node("next", math::add(), &[iteration, one])?[0];
+        let sum = body.wire_node("new_sum", math::add(), &[sum, next])?[0];
+        let cond = body.wire_node("cond_out", crate::ops::logic::less(), &[sum, limit])?[0];
+        let partial = body.wire_node("partial", AxisOp::Add(0), &[sum])?[0];
+        body.set_output_outlets(&[cond, sum, partial])?;
+        Ok(body)
+    }
+
+    #[test]
+    fn eval_while_loop() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let op = WhileLoop::new(sum_body()?, 1, model.symbol_table.sym("n"))?;
+        let inputs = tvec!(
+            model.add_const("max", tensor0(100i64))?,
+            model.add_const("cond", tensor0(true))?,
+            model.add_source("init", i64::scalar_fact())?,
+            model.add_const("limit", tensor0(10i64))?,
+        );
+        let outputs = model.wire_node("loop", op, &inputs)?;
+        model.set_output_outlets(&outputs)?;
+        assert!(model.output_fact(1)?.shape[0].to_i64().is_err());
+        let result = model.into_runnable()?.run(tvec!(tensor0(0i64).into()))?;
+        assert_eq!(*result[0], tensor0(10i64));
+        assert_eq!(*result[1], tensor1(&[1i64, 3, 6, 10]));
+        Ok(())
+    }
+
+    #[test]
+    fn endless_loop_is_cancellable() -> TractResult<()> {
+        use crate::cancellation::{CancellationToken, Cancelled};
+        let mut body = sum_body()?;
+        let cond = body.inputs[1];
+        body.outputs[0] = cond;
+        let op = WhileLoop::new(body, 1, SymbolTable::default().sym("n"))?;
+        let mut session = SessionState::default();
+        let mut state = op.state(&mut session, 0)?.context("Expected a state")?;
+        let budget = std::time::Duration::from_millis(50);
+        session.cancellation = Some(CancellationToken::with_time_budget(budget).armed());
+        let inputs = tvec!(
+            tensor0(i64::MAX).into(),
+            tensor0(true).into(),
+            tensor0(0i64).into(),
+            tensor0(0i64).into()
+        );
+        let err = state.eval(&mut session, &op, inputs).unwrap_err();
+        assert_eq!(err.downcast_ref::<Cancelled>(), Some(&Cancelled::DeadlineExceeded));
+        Ok(())
+    }
+
+    #[test]
+    fn body_missing_carried_outputs_is_an_error() -> TractResult<()> {
+        let mut op = WhileLoop::new(sum_body()?, 1, SymbolTable::default().sym("n"))?;
+        op.body.outputs.truncate(1);
+        let facts =
+            [i64::scalar_fact(), bool::scalar_fact(), i64::scalar_fact(), i64::scalar_fact()];
+        assert!(op.output_facts(&facts.iter().collect::<TVec<_>>()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn fixed_trip_count_loop_becomes_scan() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let mut body = sum_body()?;
+        let cond = body.inputs[1];
+        body.outputs[0] = cond;
+        let op = WhileLoop::new(body, 1, model.symbol_table.sym("n"))?;
+        let inputs = tvec!(
+            model.add_const("max", tensor0(5i64))?,
+            model.add_const("cond", tensor0(true))?,
+            model.add_source("init", i64::scalar_fact())?,
+            model.add_const("limit", tensor0(10i64))?,
+        );
+        let outputs = model.wire_node("loop", op, &inputs)?;
+        model.set_output_outlets(&outputs)?;
+        assert_eq!(model.output_fact(1)?.shape[0], 5.to_dim());
+        let expected = model.clone().into_runnable()?.run(tvec!(tensor0(0i64).into()))?;
+        assert_eq!(*expected[1], tensor1(&[1i64, 3, 6, 10, 15]));
+        let decluttered = model.into_decluttered()?;
+        assert!(decluttered.nodes().iter().all(|n| !n.op_is::<WhileLoop>()));
+        assert!(decluttered.nodes().iter().any(|n| n.op_is::<Scan>()));
+        let found = decluttered.into_runnable()?.run(tvec!(tensor0(0i64).into()))?;
+        assert_eq!(found, expected);
+        Ok(())
+    }
+}
@@ -218,6 +218,8 @@ macro_rules! assert_close {
     });
 }
 
+/// Freeze, save and load for states holding nothing that outlives a turn: saving them records
+/// nothing, and loading gives back a fresh state.
 #[macro_export]
 macro_rules! trivial_op_state_freeeze {
     ($state:ty) => {
@@ -225,6 +227,17 @@ macro_rules! trivial_op_state_freeeze {
             fn unfreeze(&self) -> Box<dyn OpState> {
                 Box::new(self.clone())
             }
+
+            fn save(&self) -> TractResult<$crate::saved_state::SavedOpState> {
+                Ok(Default::default())
+            }
+
+            fn load(
+                &self,
+                _saved: &$crate::saved_state::SavedOpState,
+            ) -> TractResult<Box<dyn $crate::ops::FrozenOpState>> {
+                Ok(Box::new(self.clone()))
+            }
         }
         impl $crate::ops::OpStateFreeze for $state {
             fn freeze(&self) -> Box<dyn $crate::ops::FrozenOpState> {
@@ -642,7 +642,8 @@ element_wise!(tanh, Tanh,
  [f32] => |_, xs| { (tract_linalg::ops().tanh_f32)().run(xs) },
  [f64] => |_, xs| { xs.iter_mut().for_each(|x| *x = x.tanh()); Ok(()) };
  q: [i8, u8, i32] => f32::tanh;
- cost: |dt| {tvec!((Cost::FMA(dt), 11), (Cost::Div(dt), 1))}
+ cost: |dt| {tvec!((Cost::FMA(dt), 11), (Cost::Div(dt), 1))};
+ declutter: crate::ops::nn::detect_tanh_gelu
 );
 
 element_wise!(erf, Erf,
@@ -653,7 +654,8 @@ element_wise!(erf, Erf,
      xs.iter_mut().zip(f32s.into_iter()).for_each(|(x, f)| *x = f16::from_f32(f));
      Ok(())
 };
- cost: |dt| {tvec!((Cost::FMA(dt), 11), (Cost::Div(dt), 1))}
+ cost: |dt| {tvec!((Cost::FMA(dt), 11), (Cost::Div(dt), 1))};
+ declutter: crate::ops::nn::detect_erf_gelu
 );
 
 element_wise!(acosh, Acosh, [f16, f32, f64] => |_, xs| {
@@ -748,4 +750,57 @@ mod tests {
         assert!(op.0.downcast_ref::<ShiftRight>().is_some());
         Ok(())
     }
+
+    #[test]
+    fn sub_recycles_a_in_place() -> TractResult<()> {
+        let a = tensor2(&[[1f32, 2.], [3., 4.]]);
+        let b = tensor2(&[[1f32, 1.], [1., 1.]]).into_tvalue();
+        let shared_b = b.clone();
+        let a_ptr = a.as_ptr::<f32>()?;
+        let c = TypedBinOp(Box::new(Sub), None)
+            .eval_in_place(&mut SessionState::default(), a, tvec!(b))?
+            .remove(0);
+        assert_eq!(c.as_ptr::<f32>()?, a_ptr);
+        assert_eq!(*c, tensor2(&[[0f32, 1.], [2., 3.]]));
+        assert_eq!(*shared_b, tensor2(&[[1f32, 1.], [1., 1.]]));
+        Ok(())
+    }
+
+    #[test]
+    fn comparison_recycles_a_in_place() -> TractResult<()> {
+        let a = tensor1(&[true, false, true]);
+        let b = tensor1(&[true, true, false]).into_tvalue();
+        let a_ptr = a.as_ptr::<bool>()?;
+        let c = TypedBinOp(Box::new(crate::ops::logic::Equals), None).eval_in_place(
+            &mut SessionState::default(),
+            a,
+            tvec!(b),
+        )?;
+        assert_eq!(c[0].as_ptr::<bool>()?, a_ptr);
+        assert_eq!(*c[0], tensor1(&[true, false, false]));
+        Ok(())
+    }
+
+    #[test]
+    fn comparison_on_numbers_does_not_recycle_a() -> TractResult<()> {
+        let a = tensor1(&[1f32, 2., 3.]);
+        let b = tensor1(&[1f32, 1., 3.]).into_tvalue();
+        let c = TypedBinOp(Box::new(crate::ops::logic::Equals), None).eval_in_place(
+            &mut SessionState::default(),
+            a,
+            tvec!(b),
+        )?;
+        assert_eq!(*c[0], tensor1(&[true, false, true]));
+        Ok(())
+    }
+
+    #[test]
+    fn abs_recycles_its_input_in_place() -> TractResult<()> {
+        let a = tensor1(&[-1f32, 2., -3.]);
+        let a_ptr = a.as_ptr::<f32>()?;
+        let c = abs().eval_in_place(&mut SessionState::default(), a, tvec!())?;
+        assert_eq!(c[0].as_ptr::<f32>()?, a_ptr);
+        assert_eq!(*c[0], tensor1(&[1f32, 2., 3.]));
+        Ok(())
+    }
 }
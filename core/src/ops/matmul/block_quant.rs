@@ -0,0 +1,277 @@
+use crate::internal::*;
+use crate::ops::einsum::EinSum;
+use crate::ops::matmul::lir_unary::{
+    AddMatMulGeometry, LirMatMulUnary, MapOutputAxisToInput, ProtoFusedSpec,
+};
+use crate::ops::matmul::pack::MatMatMulPack;
+use crate::transform::ModelTransform;
+pub use tract_linalg::frame::BlockQuant;
+use tract_ndarray::Array2;
+
+/// Product of activations by block-quantized constant weights.
+///
+/// Inputs are the weights, a `[m, format.row_bytes(k)]` u8 matrix as produced by
+/// `BlockQuant::quant_f32`, and the f32 or f16 activations `[..., k]`. The output is `[..., m]`.
+/// Once optimized, weights are dequantized panel by panel inside the matrix multiplication loop.
+#[derive(Clone, Debug, new, PartialEq, Eq, Hash)]
+pub struct BlockQuantMatMul {
+    pub format: BlockQuant,
+    pub m: usize,
+    pub k: usize,
+}
+
+impl Op for BlockQuantMatMul {
+    fn name(&self) -> Cow<str> {
+        "BlockQuantMatMul".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("{} m:{} k:{}", self.format, self.m, self.k)])
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for BlockQuantMatMul {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        let (weights, input) = args_2!(inputs);
+        let weights = self.format.dequant_f32(weights.as_slice::<u8>()?)?;
+        let weights = Array2::from_shape_vec((self.m, self.k), weights)?;
+        let dt = input.datum_type();
+        let mut shape: TVec<usize> = input.shape().into();
+        let input = input.cast_to::<f32>()?;
+        let n = input.len() / self.k;
+        let output = input.to_array_view::<f32>()?.into_shape((n, self.k))?.dot(&weights.t());
+        *shape.last_mut().unwrap() = self.m;
+        let output = output.into_tensor().into_shape(&shape)?;
+        Ok(tvec!(output.cast_to_dt(dt)?.into_owned().into_tvalue()))
+    }
+}
+
+impl TypedOp for BlockQuantMatMul {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        ensure!(inputs.len() == 2);
+        let (weights, input) = (inputs[0], inputs[1]);
+        ensure!(weights.datum_type == u8::datum_type());
+        ensure!(*weights.shape == [self.m.to_dim(), self.format.row_bytes(self.k).to_dim()]);
+        ensure!(input.datum_type.is_float());
+        ensure!(input.rank() >= 1 && input.shape[input.rank() - 1] == self.k.to_dim());
+        let mut shape = input.shape.to_tvec();
+        *shape.last_mut().unwrap() = self.m.to_dim();
+        Ok(tvec!(input.datum_type.fact(shape)))
+    }
+
+    fn cost(&self, inputs: &[&TypedFact]) -> TractResult<TVec<(Cost, TDim)>> {
+        let input = inputs[1];
+        let n = input.shape[..input.rank() - 1].iter().product::<TDim>();
+        Ok(tvec!((Cost::FMA(input.datum_type), n * self.m * self.k)))
+    }
+
+    fn codegen(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        let input_fact = model.outlet_fact(node.inputs[1])?;
+        let dt = input_fact.datum_type;
+        let rank = input_fact.rank();
+        let prefix: TVec<TDim> = input_fact.shape[..rank - 1].into();
+        let n = prefix.iter().product::<TDim>();
+        let Some(mmm) =
+            tract_linalg::ops().mmm(dt, dt, dt, Some(self.m), Some(self.k), n.to_usize().ok())
+        else {
+            return Ok(None);
+        };
+        let name = &node.name;
+        let mut patch = TypedModelPatch::new("BlockQuantMatMul to LirMatMulUnary");
+        let weights = patch.tap_model(model, node.inputs[0])?;
+        let mut input = patch.tap_model(model, node.inputs[1])?;
+        // activations as a [n, k] matrix
+        if rank == 1 {
+            input = patch.wire_node(format!("{name}.add_n"), AxisOp::Add(0), &[input])?[0];
+        } else if rank > 2 {
+            let reshape = AxisOp::Reshape(0, prefix.clone(), tvec!(n.clone()));
+            input = patch.wire_node(format!("{name}.flatten_n"), reshape, &[input])?[0];
+        }
+        let packer = mmm.b_pack();
+        let output_shape_fact =
+            MatMatMulPack::output_shape(&[n.clone(), self.k.to_dim()], &packer, 0, 1);
+        let pack_b = MatMatMulPack { packer, k_axis: 1, mn_axis: 0, output_shape_fact };
+        let pb = patch.wire_node(format!("{name}.pack_b"), pack_b, &[input])?[0];
+        let geo = AddMatMulGeometry {
+            k: self.k.to_dim(),
+            a_storage: Some(unsafe { mmm.a_block_quant(self.format, self.k)? }),
+            b_storage: Some(unsafe { mmm.b_packed(dt.size_of(), self.k) }),
+            mmm: mmm.clone(),
+            c_to_a_axis_mapping: MapOutputAxisToInput(tvec!()),
+            c_to_b_axis_mapping: MapOutputAxisToInput(tvec!()),
+        };
+        let c_fact = dt.fact([n.clone(), self.m.to_dim()]);
+        let output = unsafe { mmm.c_view(1, 0) };
+        let lir = LirMatMulUnary::new(
+            mmm,
+            c_fact,
+            1,
+            0,
+            vec![ProtoFusedSpec::AddMatMul(geo, 0, 1), ProtoFusedSpec::Store(output)],
+        )
+        .context("Creating LirMatMulUnary")?;
+        let mut output = patch.wire_node(format!("{name}.matmul"), lir, &[weights, pb])?[0];
+        if rank == 1 {
+            output = patch.wire_node(name, AxisOp::Rm(0), &[output])?[0];
+        } else if rank > 2 {
+            output = patch.wire_node(name, AxisOp::Reshape(0, tvec!(n), prefix), &[output])?[0];
+        }
+        patch.shunt_outside(model, node.id.into(), output)?;
+        Ok(Some(patch))
+    }
+
+    as_op!();
+}
+
+/// Weight-only quantization: replaces EinSum nodes multiplying activations by a constant 2D
+/// weight matrix by BlockQuantMatMul, the weights being quantized to `format`.
+///
+/// Only "linear" products are converted: k must be the activations last axis and a multiple of
+/// the block length, m the output last axis, all other axes going through untouched.
+#[derive(Clone, Debug, new)]
+pub struct BlockQuantTransform {
+    pub format: BlockQuant,
+}
+
+impl ModelTransform for BlockQuantTransform {
+    fn name(&self) -> Cow<str> {
+        format!("block-quant-{}", self.format).into()
+    }
+
+    fn transform(&self, model: &mut TypedModel) -> TractResult<()> {
+        for node in model.eval_order()? {
+            if let Some(patch) = self
+                .quantize_einsum(model, model.node(node))
+                .with_context(|| format!("Quantizing weights of {}", model.node(node)))?
+            {
+                patch.apply(model)?;
+            }
+        }
+        model.compact()
+    }
+}
+
+impl BlockQuantTransform {
+    fn quantize_einsum(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        let Some(op) = node.op_as::<EinSum>() else { return Ok(None) };
+        if op.q_params.is_some() || node.inputs.len() != 2 {
+            return Ok(None);
+        }
+        let facts = model.node_input_facts(node.id)?;
+        let Some(w_slot) =
+            (0..2).find(|&s| facts[s].konst.is_some() && facts[1 - s].konst.is_none())
+        else {
+            return Ok(None);
+        };
+        let (weights, input) = (facts[w_slot], facts[1 - w_slot]);
+        let rank = input.rank();
+        let dt = input.datum_type;
+        if weights.rank() != 2
+            || rank == 0
+            || !dt.is_float()
+            || op.operating_dt != dt
+            || node.outputs[0].fact.datum_type != dt
+            || node.outputs[0].fact.rank() != rank
+        {
+            return Ok(None);
+        }
+        let axes = &op.axes;
+        let k_axis = axes.axis((InOut::In(1 - w_slot), rank - 1))?;
+        let m_axis = axes.axis((InOut::Out(0), rank - 1))?;
+        let batch_axes_ok = (0..rank - 1).all(|ix| {
+            axes.axis((InOut::In(1 - w_slot), ix))
+                .is_ok_and(|axis| *axis.outputs[0] == [ix] && axis.inputs[w_slot].is_empty())
+        });
+        if !batch_axes_ok
+            || k_axis.inputs[w_slot].len() != 1
+            || !k_axis.outputs[0].is_empty()
+            || m_axis.inputs[w_slot].len() != 1
+            || !m_axis.inputs[1 - w_slot].is_empty()
+        {
+            return Ok(None);
+        }
+        let k_pos = k_axis.inputs[w_slot][0];
+        let k = weights.shape[k_pos].to_usize()?;
+        let m = weights.shape[1 - k_pos].to_usize()?;
+        if k % self.format.block_len() != 0 {
+            return Ok(None);
+        }
+        let mut weights = weights.konst.as_ref().unwrap().cast_to::<f32>()?.into_owned();
+        if k_pos == 0 {
+            weights = weights.permute_axes(&[1, 0])?;
+        }
+        let quantized = self.format.quant_f32(weights.as_slice::<f32>()?, k)?;
+        let quantized = Tensor::from_shape(&[m, self.format.row_bytes(k)], &quantized)?;
+
+        let mut patch = TypedModelPatch::new(format!("Block quantizing {}", node.name));
+        let input = patch.tap_model(model, node.inputs[1 - w_slot])?;
+        let weights =
+            patch.add_const(format!("{}.weights_{}", node.name, self.format), quantized)?;
+        let output = patch.wire_node(
+            &node.name,
+            BlockQuantMatMul::new(self.format, m, k),
+            &[weights, input],
+        )?;
+        patch.shunt_outside(model, node.id.into(), output[0])?;
+        Ok(Some(patch))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn linear_model(expr: &str, weights_shape: [usize; 2]) -> TractResult<TypedModel> {
+        let mut model = TypedModel::default();
+        let x = model.add_source("x", f32::fact([2, 3, 64]))?;
+        let len = weights_shape[0] * weights_shape[1];
+        // multiples of 1/8 with -1 in each block: exactly representable in q4_0
+        let weights = (0..len).map(|x| ((x * 7) % 16) as f32 / 8. - 1.).collect::<Vec<_>>();
+        let weights = model.add_const("w", tensor1(&weights).into_shape(&weights_shape)?)?;
+        let y =
+            model.wire_node("y", EinSum::new(expr.parse()?, f32::datum_type()), &[x, weights])?;
+        model.set_output_outlets(&y)?;
+        Ok(model)
+    }
+
+    fn check(format: BlockQuant, expr: &str, weights_shape: [usize; 2]) -> TractResult<()> {
+        let model = linear_model(expr, weights_shape)?;
+        let mut quantized = model.clone();
+        BlockQuantTransform::new(format).transform(&mut quantized)?;
+        assert!(quantized.nodes().iter().any(|n| n.op_is::<BlockQuantMatMul>()));
+        assert!(!quantized.nodes().iter().any(|n| n.op_is::<EinSum>()));
+        let data = (0..2 * 3 * 64).map(|x| ((x * 5) % 11) as f32 / 5. - 1.).collect::<Vec<_>>();
+        let input = tvec!(tensor1(&data).into_shape(&[2, 3, 64])?.into_tvalue());
+        let expected = model.into_runnable()?.run(input.clone())?.remove(0);
+        let found = quantized.clone().into_runnable()?.run(input.clone())?.remove(0);
+        found.close_enough(&expected, Approximation::SuperApproximate)?;
+        let optimized = quantized.clone().into_optimized()?;
+        assert!(optimized.nodes().iter().any(|n| n.op_is::<LirMatMulUnary>()));
+        let optimized = optimized.into_runnable()?.run(input)?.remove(0);
+        optimized.close_enough(&found, Approximation::Approximate)
+    }
+
+    #[test]
+    fn q8_0() -> TractResult<()> {
+        check(BlockQuant::Q8_0, "btk,mk->btm", [5, 64])
+    }
+
+    #[test]
+    fn q4_0_transposed_weights() -> TractResult<()> {
+        check(BlockQuant::Q4_0, "btk,km->btm", [64, 5])
+    }
+}
@@ -62,6 +62,50 @@ pub fn wire_ensure_q8_flavour(
     Ok(())
 }
 
+/// Zero point and scale of a quantized datum type: scalars, or 1-D tensors for per-axis
+/// quantization parameters (see `link_per_axis_qparams`).
+pub fn zp_scale_tensors(dt: DatumType) -> (Tensor, Tensor) {
+    if let Some(params) = dt.qparams().and_then(|qp| qp.per_axis_params()) {
+        (tensor1(&params.zero_points), tensor1(&params.scales))
+    } else {
+        let (zp, scale) = dt.zp_scale();
+        (tensor0(zp), tensor0(scale))
+    }
+}
+
+/// Wires the zero point and scale of a quantized datum type as constants.
+pub fn wire_zp_scale(
+    model: &mut TypedModel,
+    prefix: &str,
+    dt: DatumType,
+) -> TractResult<(OutletId, OutletId)> {
+    let (zp, scale) = zp_scale_tensors(dt);
+    let zp = model.add_const(format!("{prefix}0"), zp)?;
+    let scale = model.add_const(format!("{prefix}_scale"), scale)?;
+    Ok((zp, scale))
+}
+
+/// Links the zero point and scale inputs of a quantized EinSum to the quantization axis of
+/// their operand, when it is per-axis quantized.
+pub fn link_per_axis_qparams(
+    axes: AxesMapping,
+    dt: DatumType,
+    operand: usize,
+    zp_scale_slots: [usize; 2],
+) -> TractResult<AxesMapping> {
+    let Some(params) = dt.qparams().and_then(|qp| qp.per_axis_params()) else { return Ok(axes) };
+    let axis = axes.axis((InOut::In(operand), params.axis))?;
+    ensure!(
+        axis.outputs[0].len() == 1,
+        "Per-axis quantization along axis {} which does not appear in output",
+        axis.repr
+    );
+    let repr = axis.repr;
+    zp_scale_slots
+        .iter()
+        .try_fold(axes, |axes, slot| axes.with_extra_axis_occurency(repr, InOut::In(*slot), 0))
+}
+
 pub(crate) fn combine_scales(
     model: &mut TypedModel,
     name: &str,
@@ -192,6 +236,13 @@ pub(crate) fn clamp_and_cast_to(
     if dt == i32::datum_type() {
         return Ok(wire);
     }
+    // the accumulator may carry the bias quantization: only its raw values are relevant here
+    let acc_dt = model.outlet_fact(wire)?.datum_type;
+    let wire = if acc_dt.is_quantized() {
+        model.wire_node(format!("{name}.raw"), ops::cast::cast(acc_dt.unquantized()), &[wire])?[0]
+    } else {
+        wire
+    };
     let rank = model.outlet_fact(wire)?.rank();
     let inf = dt
         .unquantized()
@@ -0,0 +1,260 @@
+use crate::internal::*;
+use crate::ops::OpStateFreeze;
+
+/// Key/value cache for autoregressive decoding.
+///
+/// Each evaluation appends its input to the tensors seen during the previous turns along `axis`
+/// and outputs the whole sequence. The cached length is exposed to the rest of the model as the
+/// `past` symbol, so the output length along `axis` is `past + input length`.
+///
+/// The cache lives in the op state: it is dropped by `SimpleState::reset_kv_caches` (or
+/// `reset_op_states`) and can be rolled back with `SimpleState::truncate_kv_caches`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct KvCache {
+    pub axis: usize,
+    pub past: Symbol,
+}
+
+impl KvCache {
+    pub fn new(axis: usize, past: Symbol) -> KvCache {
+        KvCache { axis, past }
+    }
+}
+
+impl Op for KvCache {
+    fn name(&self) -> Cow<str> {
+        "KvCache".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("axis: {} past: {}", self.axis, self.past)])
+    }
+
+    impl_op_same_as!();
+    op_as_typed_op!();
+}
+
+impl EvalOp for KvCache {
+    fn is_stateless(&self) -> bool {
+        false
+    }
+
+    fn state(
+        &self,
+        _session: &mut SessionState,
+        _node_id: usize,
+    ) -> TractResult<Option<Box<dyn OpState>>> {
+        Ok(Some(Box::new(KvCacheState { axis: self.axis, cache: None })))
+    }
+}
+
+impl TypedOp for KvCache {
+    as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        ensure!(inputs.len() == 1, "KvCache expects a single input");
+        ensure!(
+            self.axis < inputs[0].rank(),
+            "KvCache axis {} is invalid for input {:?}",
+            self.axis,
+            inputs[0]
+        );
+        let mut shape = inputs[0].shape.to_tvec();
+        shape[self.axis] = TDim::from(self.past.clone()) + &shape[self.axis];
+        Ok(tvec!(inputs[0].datum_type.fact(shape)))
+    }
+
+    fn change_axes(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+        _io: InOut,
+        change: &AxisOp,
+    ) -> TractResult<Option<AxisChangeConsequence>> {
+        if let Some(axis) = change.transform_axis(self.axis) {
+            let op = (axis != self.axis).then(|| Box::new(Self { axis, ..self.clone() }) as _);
+            Ok(Some(AxisChangeConsequence::new(model, node, op, change)))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+/// Inner state of a KvCache: the concatenation of all the inputs seen so far.
+///
+/// Each step builds a new tensor from the cache and the new input, and shares it with the op
+/// output instead of copying it a second time. The whole cache is still copied at every step,
+/// so decoding `n` positions copies O(n²) elements overall: tensors can not be views on a
+/// larger buffer, so growing the cache in place would not spare the copy to the output.
+#[derive(Clone, Debug)]
+pub struct KvCacheState {
+    pub axis: usize,
+    pub cache: Option<Arc<Tensor>>,
+}
+
+impl KvCacheState {
+    /// Number of positions currently cached along the sequence axis.
+    pub fn len(&self) -> usize {
+        self.cache.as_ref().map(|t| t.shape()[self.axis]).unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drop the cached content.
+    pub fn reset(&mut self) {
+        self.cache = None;
+    }
+
+    /// Keep only the first `len` cached positions.
+    pub fn truncate(&mut self, len: usize) -> TractResult<()> {
+        ensure!(
+            len <= self.len(),
+            "Can not truncate KvCache to {len}, it only contains {} positions",
+            self.len()
+        );
+        if len == 0 {
+            self.reset()
+        } else if let Some(cache) = self.cache.as_mut() {
+            *cache = cache.slice(self.axis, 0, len)?.into_arc_tensor();
+        }
+        Ok(())
+    }
+}
+
+impl OpState for KvCacheState {
+    fn eval(
+        &mut self,
+        session: &mut SessionState,
+        op: &dyn Op,
+        inputs: TVec<TValue>,
+    ) -> TractResult<TVec<TValue>> {
+        let op = op.downcast_ref::<KvCache>().context("Wrong op for KvCacheState")?;
+        let input = args_1!(inputs);
+        session.resolved_symbols.set(&op.past, self.len() as i64);
+        let output = if let Some(cache) = self.cache.take() {
+            ensure!(
+                cache.datum_type() == input.datum_type()
+                    && cache.rank() == input.rank()
+                    && (0..cache.rank())
+                        .all(|ax| ax == op.axis || cache.shape()[ax] == input.shape()[ax]),
+                "KvCache input {:?} is incompatible with cached {:?}",
+                input,
+                cache
+            );
+            Tensor::stack_tensors(op.axis, &[&*cache, &*input])?.into_arc_tensor()
+        } else {
+            input.into_arc_tensor()
+        };
+        self.cache = Some(output.clone());
+        Ok(tvec!(TValue::Const(output)))
+    }
+}
+
+impl OpStateFreeze for KvCacheState {
+    fn freeze(&self) -> Box<dyn FrozenOpState> {
+        Box::new(self.clone())
+    }
+}
+
+impl FrozenOpState for KvCacheState {
+    fn unfreeze(&self) -> Box<dyn OpState> {
+        Box::new(self.clone())
+    }
+
+    fn save(&self) -> TractResult<SavedOpState> {
+        Ok(SavedOpState {
+            tensors: vec![self.cache.as_deref().cloned()],
+            ..SavedOpState::default()
+        })
+    }
+
+    fn load(&self, saved: &SavedOpState) -> TractResult<Box<dyn FrozenOpState>> {
+        Ok(Box::new(KvCacheState { axis: self.axis, cache: saved.tensor(0)?.map(Arc::new) }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn model() -> TractResult<(TypedModel, Symbol)> {
+        let mut model = TypedModel::default();
+        let s = model.symbol_table.sym("S");
+        let p = model.symbol_table.sym("P");
+        let x = model.add_source("x", f32::fact(dims!(1, s, 2)))?;
+        let y = model.wire_node("cache", KvCache::new(1, p.clone()), &[x])?;
+        model.set_output_outlets(&y)?;
+        Ok((model, p))
+    }
+
+    fn step(
+        state: &mut TypedSimpleState<TypedModel, Arc<TypedSimplePlan<TypedModel>>>,
+        values: &[f32],
+    ) -> TractResult<Tensor> {
+        let input = tensor1(values).into_shape(&[1, values.len() / 2, 2])?;
+        Ok(state.run(tvec!(input.into_tvalue()))?.remove(0).into_tensor())
+    }
+
+    #[test]
+    fn grows_along_axis() -> TractResult<()> {
+        let (model, p) = model()?;
+        let fact = model.outlet_fact(model.output_outlets()?[0])?;
+        assert_eq!(fact.shape[1], TDim::from(p.clone()) + model.symbol_table.sym("S"));
+        let mut state = SimpleState::new(Arc::new(model.into_runnable()?))?;
+        step(&mut state, &[1., 2., 3., 4.])?;
+        let output = step(&mut state, &[5., 6.])?;
+        assert_eq!(output, tensor1(&[1f32, 2., 3., 4., 5., 6.]).into_shape(&[1, 3, 2])?);
+        assert_eq!(state.session_state.resolved_symbols[&p], Some(2));
+        Ok(())
+    }
+
+    #[test]
+    fn output_shares_cache() -> TractResult<()> {
+        let (model, _) = model()?;
+        let mut state = SimpleState::new(Arc::new(model.into_runnable()?))?;
+        let input = tensor1(&[1f32, 2.]).into_shape(&[1, 1, 2])?;
+        let output = state.run(tvec!(input.into_tvalue()))?.remove(0);
+        let cache = state.states[1]
+            .as_ref()
+            .and_then(|s| s.downcast_ref::<KvCacheState>())
+            .and_then(|s| s.cache.as_ref())
+            .unwrap();
+        assert_eq!(cache.as_ptr::<f32>()?, output.as_ptr::<f32>()?);
+        Ok(())
+    }
+
+    #[test]
+    fn truncate_and_reset() -> TractResult<()> {
+        let (model, _) = model()?;
+        let mut state = SimpleState::new(Arc::new(model.into_runnable()?))?;
+        step(&mut state, &[1., 2., 3., 4.])?;
+        state.truncate_kv_caches(1)?;
+        let output = step(&mut state, &[5., 6.])?;
+        assert_eq!(output, tensor1(&[1f32, 2., 5., 6.]).into_shape(&[1, 2, 2])?);
+        let frozen = state.freeze();
+        state.reset_kv_caches()?;
+        let output = step(&mut state, &[7., 8.])?;
+        assert_eq!(output, tensor1(&[7f32, 8.]).into_shape(&[1, 1, 2])?);
+        let mut state = frozen.unfreeze();
+        let output = step(&mut state, &[7., 8.])?;
+        assert_eq!(output, tensor1(&[1f32, 2., 5., 6., 7., 8.]).into_shape(&[1, 3, 2])?);
+        assert!(state.truncate_kv_caches(4).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn save_and_load() -> TractResult<()> {
+        let (model, _) = model()?;
+        let plan = Arc::new(model.into_runnable()?);
+        let mut state = SimpleState::new(plan.clone())?;
+        step(&mut state, &[1., 2., 3., 4.])?;
+        let bytes = state.freeze().save()?.to_bytes()?;
+        let saved = SavedSimpleState::from_bytes(&bytes)?;
+        let mut state = SimpleState::new(plan)?.freeze().load(&saved)?.unfreeze();
+        let output = step(&mut state, &[5., 6.])?;
+        assert_eq!(output, tensor1(&[1f32, 2., 3., 4., 5., 6.]).into_shape(&[1, 3, 2])?);
+        Ok(())
+    }
+}
@@ -70,6 +70,17 @@ impl Cost {
 
 pub trait FrozenOpState: fmt::Debug + dyn_clone::DynClone + Send + 'static {
     fn unfreeze(&self) -> Box<dyn OpState>;
+
+    /// Extract the state content for serialization.
+    fn save(&self) -> TractResult<SavedOpState> {
+        bail!("{self:?} can not be saved")
+    }
+
+    /// Rebuild a state from saved content. `self` is a state from the same op, providing what is
+    /// not saved (op parameters, nested plans...).
+    fn load(&self, saved: &SavedOpState) -> TractResult<Box<dyn FrozenOpState>> {
+        bail!("{self:?} can not be loaded from {saved:?}")
+    }
 }
 
 pub trait OpStateFreeze {
@@ -105,6 +116,27 @@ pub trait EvalOp {
     }
 
     fn is_stateless(&self) -> bool;
+
+    /// Whether `eval_in_place` can recycle the buffer of the first input for an output.
+    fn supports_in_place(&self) -> bool {
+        false
+    }
+
+    /// Evaluate the op, owning its first input.
+    ///
+    /// Plans call it instead of `eval` (or of the op state `eval`) when `supports_in_place` is
+    /// true and nothing else refers to the first input, so that its buffer can be overwritten
+    /// with the output instead of allocating a new one.
+    #[allow(unused_variables)]
+    fn eval_in_place(
+        &self,
+        session: &mut SessionState,
+        input: Tensor,
+        mut others: TVec<TValue>,
+    ) -> TractResult<TVec<TValue>> {
+        others.insert(0, input.into_tvalue());
+        self.eval(others)
+    }
 }
 
 /// A base operation
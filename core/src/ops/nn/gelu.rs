@@ -0,0 +1,231 @@
+use super::layer_norm::{bin_op_is, only_successor, uniform_f32};
+use crate::internal::*;
+use crate::ops::element_wise::ElementWiseOp;
+use crate::ops::math::{Add, Mul, Pow, Square};
+
+element_wise!(gelu, Gelu { approximate: bool },
+ [f16] => |op, xs| {
+     if op.approximate {
+         (tract_linalg::ops().gelu_tanh_f16)().run(xs)
+     } else {
+         (tract_linalg::ops().gelu_f16)().run(xs)
+     }
+ },
+ [f32] => |op, xs| {
+     if op.approximate {
+         (tract_linalg::ops().gelu_tanh_f32)().run(xs)
+     } else {
+         (tract_linalg::ops().gelu_f32)().run(xs)
+     }
+ };
+ cost: |dt| {tvec!((Cost::FMA(dt), 15), (Cost::Div(dt), 1))}
+);
+
+fn is_uniform(model: &TypedModel, outlet: OutletId, value: f32) -> TractResult<bool> {
+    Ok(uniform_f32(model, outlet)?.is_some_and(|v| (v - value).abs() <= 1e-4 * value.abs()))
+}
+
+/// If outlet is `x * k` (or `k * x`), returns x.
+fn scaled_by(model: &TypedModel, outlet: OutletId, k: f32) -> TractResult<Option<OutletId>> {
+    let node = model.node(outlet.node);
+    if !bin_op_is::<Mul>(node) {
+        return Ok(None);
+    }
+    for slot in 0..2 {
+        if is_uniform(model, node.inputs[1 - slot], k)? {
+            return Ok(Some(node.inputs[slot]));
+        }
+    }
+    Ok(None)
+}
+
+fn is_cube_of(model: &TypedModel, outlet: OutletId, x: OutletId) -> TractResult<bool> {
+    let node = model.node(outlet.node);
+    if bin_op_is::<Pow>(node) {
+        return Ok(node.inputs[0] == x && is_uniform(model, node.inputs[1], 3.0)?);
+    }
+    if !bin_op_is::<Mul>(node) {
+        return Ok(false);
+    }
+    let is_square = |outlet: OutletId| {
+        let node = model.node(outlet.node);
+        node.op_as::<ElementWiseOp>().is_some_and(|ew| ew.0.is::<Square>()) && node.inputs[0] == x
+            || bin_op_is::<Mul>(node) && node.inputs[0] == x && node.inputs[1] == x
+    };
+    Ok((node.inputs[0] == x && is_square(node.inputs[1]))
+        || (node.inputs[1] == x && is_square(node.inputs[0])))
+}
+
+/// Called from Erf declutter. Recognizes `0.5 * x * (1 + erf(x / sqrt(2)))`.
+pub(crate) fn detect_erf_gelu(
+    model: &TypedModel,
+    node: &TypedNode,
+) -> TractResult<Option<TypedModelPatch>> {
+    let Some(x) = scaled_by(model, node.inputs[0], std::f32::consts::FRAC_1_SQRT_2)? else {
+        return Ok(None);
+    };
+    wire_gelu(model, node, x, false)
+}
+
+/// Called from Tanh declutter. Recognizes
+/// `0.5 * x * (1 + tanh(sqrt(2 / pi) * (x + 0.044715 * x^3)))`.
+pub(crate) fn detect_tanh_gelu(
+    model: &TypedModel,
+    node: &TypedNode,
+) -> TractResult<Option<TypedModelPatch>> {
+    let Some(inner) = scaled_by(model, node.inputs[0], (2.0 / std::f32::consts::PI).sqrt())? else {
+        return Ok(None);
+    };
+    let add = model.node(inner.node);
+    if !bin_op_is::<Add>(add) {
+        return Ok(None);
+    }
+    for slot in 0..2 {
+        let x = add.inputs[slot];
+        if let Some(cube) = scaled_by(model, add.inputs[1 - slot], 0.044715)? {
+            if is_cube_of(model, cube, x)? {
+                return wire_gelu(model, node, x, true);
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Checks what follows erf or tanh is `0.5 * x * (1 + ...)`, with any product association.
+fn wire_gelu(
+    model: &TypedModel,
+    activation: &TypedNode,
+    x: OutletId,
+    approximate: bool,
+) -> TractResult<Option<TypedModelPatch>> {
+    let x_fact = model.outlet_fact(x)?;
+    // Gelu only has f16 and f32 kernels
+    if !matches!(x_fact.datum_type, DatumType::F16 | DatumType::F32) {
+        return Ok(None);
+    }
+    let Some(add) = only_successor(model, activation) else { return Ok(None) };
+    if !bin_op_is::<Add>(add) {
+        return Ok(None);
+    }
+    let one_slot = if add.inputs[0].node == activation.id { 1 } else { 0 };
+    if !is_uniform(model, add.inputs[one_slot], 1.0)? {
+        return Ok(None);
+    }
+    let Some(mul) = only_successor(model, add) else { return Ok(None) };
+    if !bin_op_is::<Mul>(mul) {
+        return Ok(None);
+    }
+    let other = mul.inputs[if mul.inputs[0].node == add.id { 1 } else { 0 }];
+    let output = if scaled_by(model, other, 0.5)? == Some(x) {
+        mul
+    } else {
+        let Some(last) = only_successor(model, mul) else { return Ok(None) };
+        if !bin_op_is::<Mul>(last) {
+            return Ok(None);
+        }
+        let last_other = last.inputs[if last.inputs[0].node == mul.id { 1 } else { 0 }];
+        if (other == x && is_uniform(model, last_other, 0.5)?)
+            || (last_other == x && is_uniform(model, other, 0.5)?)
+        {
+            last
+        } else {
+            return Ok(None);
+        }
+    };
+    if output.outputs[0].fact.shape != x_fact.shape
+        || output.outputs[0].fact.datum_type != x_fact.datum_type
+    {
+        return Ok(None);
+    }
+    let mut patch = TypedModelPatch::default();
+    let wire = patch.tap_model(model, x)?;
+    let wire = patch.wire_node(&output.name, gelu(approximate), &[wire])?[0];
+    patch.shunt_outside(model, output.id.into(), wire)?;
+    Ok(Some(patch))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::binary::wire_with_rank_broadcast;
+    use crate::ops::math::{add, erf, mul, pow, tanh};
+
+    fn konst(model: &mut TypedModel, name: &str, v: f32) -> TractResult<OutletId> {
+        model.add_const(name, tensor0(v))
+    }
+
+    fn bin(
+        model: &mut TypedModel,
+        name: &str,
+        op: crate::ops::binary::TypedBinOp,
+        a: OutletId,
+        b: OutletId,
+    ) -> TractResult<OutletId> {
+        Ok(wire_with_rank_broadcast(name, model, op, &[a, b])?[0])
+    }
+
+    fn check(model: TypedModel, approximate: bool) -> TractResult<()> {
+        let decluttered = model.clone().into_decluttered()?;
+        assert_eq!(decluttered.nodes().len(), 2);
+        let op = decluttered.node(1).op_as::<ElementWiseOp>().unwrap();
+        assert_eq!(op.0.downcast_ref::<Gelu>().unwrap().approximate, approximate);
+        let input = tensor1(&(-20..20).map(|x| x as f32 / 4.).collect::<Vec<_>>());
+        let expected = model.into_runnable()?.run(tvec!(input.clone().into_tvalue()))?;
+        let found = decluttered.into_runnable()?.run(tvec!(input.into_tvalue()))?;
+        found[0].close_enough(&expected[0], Approximation::Approximate)
+    }
+
+    fn erf_gelu_model(dt: DatumType) -> TractResult<TypedModel> {
+        let mut model = TypedModel::default();
+        let x = model.add_source("x", dt.fact([40]))?;
+        let mut konst = |name: &str, v: f32| -> TractResult<OutletId> {
+            model.add_const(name, tensor0(v).cast_to_dt(dt)?.into_owned())
+        };
+        let k = konst("k", std::f32::consts::FRAC_1_SQRT_2)?;
+        let one = konst("one", 1.0)?;
+        let half = konst("half", 0.5)?;
+        let scaled = bin(&mut model, "scaled", mul(), x, k)?;
+        let erf = model.wire_node("erf", erf(), &[scaled])?[0];
+        let plus_one = bin(&mut model, "plus_one", add(), erf, one)?;
+        let half_x = bin(&mut model, "half_x", mul(), x, half)?;
+        let y = bin(&mut model, "y", mul(), half_x, plus_one)?;
+        model.set_output_outlets(&[y])?;
+        Ok(model)
+    }
+
+    #[test]
+    fn detect_erf_gelu() -> TractResult<()> {
+        check(erf_gelu_model(f32::datum_type())?, false)
+    }
+
+    #[test]
+    fn no_gelu_without_kernel() -> TractResult<()> {
+        let decluttered = erf_gelu_model(f64::datum_type())?.into_decluttered()?;
+        assert!(decluttered
+            .nodes()
+            .iter()
+            .all(|n| n.op_as::<ElementWiseOp>().map_or(true, |ew| !ew.0.is::<Gelu>())));
+        Ok(())
+    }
+
+    #[test]
+    fn detect_tanh_gelu() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let x = model.add_source("x", f32::fact([40]))?;
+        let three = konst(&mut model, "three", 3.0)?;
+        let c = konst(&mut model, "c", 0.044715)?;
+        let k = konst(&mut model, "k", (2.0 / std::f32::consts::PI).sqrt())?;
+        let one = konst(&mut model, "one", 1.0)?;
+        let half = konst(&mut model, "half", 0.5)?;
+        let cube = bin(&mut model, "cube", pow(), x, three)?;
+        let cube = bin(&mut model, "cube_c", mul(), c, cube)?;
+        let inner = bin(&mut model, "inner", add(), x, cube)?;
+        let inner = bin(&mut model, "inner_k", mul(), inner, k)?;
+        let tanh = model.wire_node("tanh", tanh(), &[inner])?[0];
+        let plus_one = bin(&mut model, "plus_one", add(), one, tanh)?;
+        let y = bin(&mut model, "x_plus_one", mul(), x, plus_one)?;
+        let y = bin(&mut model, "y", mul(), y, half)?;
+        model.set_output_outlets(&[y])?;
+        check(model, true)
+    }
+}
@@ -0,0 +1,343 @@
+use crate::internal::*;
+use crate::ops::binary::{BinMiniOp, TypedBinOp};
+use crate::ops::element_wise::ElementWiseOp;
+use crate::ops::math::{Add, Mul, Rsqrt, Sub};
+use crate::ops::nn::{Reduce, Reducer};
+use num_traits::Float;
+use tract_ndarray::{ArrayViewMut, Axis, Dimension};
+
+/// Layer normalization: `(x - mean(x)) / sqrt(var(x) + eps)` over `axes`.
+///
+/// Scale and bias are left out and wired as regular binary operators.
+#[derive(Clone, Debug, new, PartialEq)]
+pub struct LayerNorm {
+    pub axes: TVec<usize>,
+    pub eps: f32,
+}
+
+/// Root mean square normalization: `x / sqrt(mean(x²) + eps)` over `axes`.
+#[derive(Clone, Debug, new, PartialEq)]
+pub struct RmsNorm {
+    pub axes: TVec<usize>,
+    pub eps: f32,
+}
+
+macro_rules! norm_op {
+    ($op: ident, $name: expr, $center: expr) => {
+        impl Op for $op {
+            fn name(&self) -> Cow<str> {
+                $name.into()
+            }
+
+            fn info(&self) -> TractResult<Vec<String>> {
+                Ok(vec![format!("axes: {:?} eps: {:?}", self.axes, self.eps)])
+            }
+
+            op_as_typed_op!();
+        }
+
+        impl EvalOp for $op {
+            fn is_stateless(&self) -> bool {
+                true
+            }
+
+            fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+                let input = args_1!(inputs);
+                let output = match input.datum_type() {
+                    DatumType::F64 => normalize::<f64>(&self.axes, self.eps, $center, input)?,
+                    DatumType::F32 => normalize::<f32>(&self.axes, self.eps, $center, input)?,
+                    DatumType::F16 => normalize::<f16>(&self.axes, self.eps, $center, input)?,
+                    dt => bail!("Unsupported type {dt:?}"),
+                };
+                Ok(tvec!(output.into_tvalue()))
+            }
+        }
+
+        impl TypedOp for $op {
+            fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+                let dt = inputs[0].datum_type;
+                ensure!(dt.is_float(), "Unsupported datum type in {}: {:?}", $name, dt);
+                ensure!(self.axes.iter().all(|&ax| ax < inputs[0].rank()));
+                Ok(tvec!(dt.fact(inputs[0].shape.clone())))
+            }
+
+            fn axes_mapping(
+                &self,
+                inputs: &[&TypedFact],
+                outputs: &[&TypedFact],
+            ) -> TractResult<AxesMapping> {
+                // normalized axes can not be tracked from input to output, just like reduced axes
+                Reduce { axes: self.axes.clone(), reducer: Reducer::Sum }
+                    .axes_mapping(inputs, outputs)
+            }
+
+            fn change_axes(
+                &self,
+                model: &TypedModel,
+                node: &TypedNode,
+                _io: InOut,
+                change: &AxisOp,
+            ) -> TractResult<Option<AxisChangeConsequence>> {
+                let axes: Option<TVec<usize>> =
+                    self.axes.iter().map(|it| change.transform_axis(*it)).collect();
+                if let Some(mut axes) = axes {
+                    axes.sort();
+                    Ok(Some(AxisChangeConsequence::new(
+                        model,
+                        node,
+                        Some(Box::new($op { axes, ..self.clone() })),
+                        change,
+                    )))
+                } else {
+                    Ok(None)
+                }
+            }
+
+            as_op!();
+        }
+    };
+}
+
+norm_op!(LayerNorm, "LayerNorm", true);
+norm_op!(RmsNorm, "RmsNorm", false);
+
+fn normalize<T: Float + Datum>(
+    axes: &[usize],
+    eps: f32,
+    center: bool,
+    input: TValue,
+) -> TractResult<Tensor> {
+    let mut output = input.into_tensor();
+    if output.len() == 0 {
+        return Ok(output);
+    }
+    let iterating_shape: TVec<usize> = output
+        .shape()
+        .iter()
+        .enumerate()
+        .map(|(ix, d)| if axes.contains(&ix) { 1 } else { *d })
+        .collect();
+    let mut view = output.to_array_view_mut::<T>()?;
+    for it_coords in tract_ndarray::indices(&*iterating_shape) {
+        let mut view = view.view_mut();
+        for ix in 0..iterating_shape.len() {
+            if !axes.contains(&ix) {
+                view.collapse_axis(Axis(ix), it_coords[ix]);
+            }
+        }
+        if let Some(slice) = view.as_slice_mut().filter(|_| T::datum_type() == f32::datum_type()) {
+            let slice: &mut [f32] = unsafe { std::mem::transmute(slice) };
+            normalize_slice_f32(slice, eps, center)?;
+        } else if let Some(slice) =
+            view.as_slice_mut().filter(|_| T::datum_type() == f16::datum_type())
+        {
+            let slice: &mut [f16] = unsafe { std::mem::transmute(slice) };
+            normalize_slice_f16(slice, eps, center)?;
+        } else {
+            normalize_inner(view, eps, center);
+        }
+    }
+    Ok(output)
+}
+
+fn normalize_slice_f32(slice: &mut [f32], eps: f32, center: bool) -> TractResult<()> {
+    let recip_len = (slice.len() as f32).recip();
+    if center {
+        let mean = (tract_linalg::ops().mean_f32)().run_with_params(slice, recip_len)?;
+        (tract_linalg::ops().add_by_scalar_f32)().run_with_params(slice, -mean)?;
+    }
+    let var = (tract_linalg::ops().mean_of_squares_f32)().run_with_params(slice, recip_len)?;
+    let scale = (var + eps).sqrt().recip();
+    (tract_linalg::ops().mul_by_scalar_f32)().run_with_params(slice, scale)?;
+    Ok(())
+}
+
+fn normalize_slice_f16(slice: &mut [f16], eps: f32, center: bool) -> TractResult<()> {
+    let recip_len = (slice.len() as f32).recip();
+    if center {
+        let mean = (tract_linalg::ops().mean_f16)().run_with_params(slice, recip_len)?;
+        (tract_linalg::ops().add_by_scalar_f16)().run_with_params(slice, -mean)?;
+    }
+    let var = (tract_linalg::ops().mean_of_squares_f16)().run_with_params(slice, recip_len)?;
+    let scale = f16::from_f32((var.to_f32() + eps).sqrt().recip());
+    (tract_linalg::ops().mul_by_scalar_f16)().run_with_params(slice, scale)?;
+    Ok(())
+}
+
+fn normalize_inner<T: Float, D: Dimension>(mut view: ArrayViewMut<T, D>, eps: f32, center: bool) {
+    let len = T::from(view.len()).unwrap();
+    if center {
+        let mean = view.iter().fold(T::zero(), |acc, x| acc + *x) / len;
+        view.mapv_inplace(|x| x - mean);
+    }
+    let var = view.iter().fold(T::zero(), |acc, x| acc + *x * *x) / len;
+    let scale = (var + T::from(eps).unwrap()).sqrt().recip();
+    view.mapv_inplace(|x| x * scale);
+}
+
+pub(super) fn bin_op_is<B: BinMiniOp>(node: &TypedNode) -> bool {
+    node.op_as::<TypedBinOp>().is_some_and(|op| op.0.is::<B>())
+}
+
+pub(super) fn only_successor<'m>(model: &'m TypedModel, node: &TypedNode) -> Option<&'m TypedNode> {
+    if node.outputs.len() != 1 || node.outputs[0].successors.len() != 1 {
+        return None;
+    }
+    Some(model.node(node.outputs[0].successors[0].node))
+}
+
+pub(super) fn uniform_f32(model: &TypedModel, outlet: OutletId) -> TractResult<Option<f32>> {
+    let fact = model.outlet_fact(outlet)?;
+    let uniform = fact.uniform.clone().or_else(|| fact.konst.as_ref()?.as_uniform().map(Arc::new));
+    uniform.map(|u| u.cast_to_scalar::<f32>()).transpose()
+}
+
+/// Called from MeanOfSquares reduction declutter. Recognizes the decomposed form
+/// `z * rsqrt(mean_of_squares(z) + eps)`, where z is either `x - mean(x)` (LayerNorm) or
+/// x itself (RmsNorm).
+pub(crate) fn detect_norm(
+    model: &TypedModel,
+    node: &TypedNode,
+    reduce: &Reduce,
+) -> TractResult<Option<TypedModelPatch>> {
+    let z = node.inputs[0];
+    let z_fact = model.outlet_fact(z)?;
+    if !z_fact.datum_type.is_float() {
+        return Ok(None);
+    }
+    let Some(add) = only_successor(model, node) else { return Ok(None) };
+    if !bin_op_is::<Add>(add) || add.outputs[0].fact.shape != node.outputs[0].fact.shape {
+        return Ok(None);
+    }
+    let eps_slot = if add.inputs[0] == node.id.into() { 1 } else { 0 };
+    let Some(eps) = uniform_f32(model, add.inputs[eps_slot])? else { return Ok(None) };
+    let Some(rsqrt) = only_successor(model, add) else { return Ok(None) };
+    if !rsqrt.op_as::<ElementWiseOp>().is_some_and(|ew| ew.0.is::<Rsqrt>()) {
+        return Ok(None);
+    }
+    let Some(mul) = only_successor(model, rsqrt) else { return Ok(None) };
+    if !bin_op_is::<Mul>(mul)
+        || !mul.inputs.contains(&z)
+        || mul.outputs[0].fact.shape != z_fact.shape
+        || mul.outputs[0].fact.datum_type != z_fact.datum_type
+    {
+        return Ok(None);
+    }
+    let mut patch = TypedModelPatch::default();
+    let wire = if let Some(x) = centered_input(model, z, &reduce.axes)? {
+        let x = patch.tap_model(model, x)?;
+        patch.wire_node(&mul.name, LayerNorm::new(reduce.axes.clone(), eps), &[x])?[0]
+    } else {
+        let z = patch.tap_model(model, z)?;
+        patch.wire_node(&mul.name, RmsNorm::new(reduce.axes.clone(), eps), &[z])?[0]
+    };
+    patch.shunt_outside(model, mul.id.into(), wire)?;
+    Ok(Some(patch))
+}
+
+/// Looks for `x - sum(x) * 1/N` behind z, returning x.
+fn centered_input(
+    model: &TypedModel,
+    z: OutletId,
+    axes: &[usize],
+) -> TractResult<Option<OutletId>> {
+    let sub = model.node(z.node);
+    if !bin_op_is::<Sub>(sub) {
+        return Ok(None);
+    }
+    let x = sub.inputs[0];
+    let x_fact = model.outlet_fact(x)?;
+    if x_fact.shape != model.outlet_fact(z)?.shape {
+        return Ok(None);
+    }
+    let mean = model.node(sub.inputs[1].node);
+    if !bin_op_is::<Mul>(mean) {
+        return Ok(None);
+    }
+    let norm: TDim = axes.iter().map(|&ax| &x_fact.shape[ax]).product();
+    let Some(norm) = norm.as_i64().filter(|n| *n > 0) else { return Ok(None) };
+    for slot in 0..2 {
+        let sum = model.node(mean.inputs[slot].node);
+        let Some(sum_op) = sum.op_as::<Reduce>() else { continue };
+        if sum_op.reducer != Reducer::Sum || *sum_op.axes != *axes || sum.inputs[0] != x {
+            continue;
+        }
+        let Some(recip) = uniform_f32(model, mean.inputs[1 - slot])? else { continue };
+        if tensor0(recip)
+            .close_enough(&tensor0((norm as f32).recip()), Approximation::Close)
+            .is_ok()
+        {
+            return Ok(Some(x));
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::binary::wire_with_rank_broadcast;
+    use crate::ops::math::{add, div, mul, rsqrt, square, sub};
+
+    fn decomposed(center: bool) -> TractResult<TypedModel> {
+        let mut model = TypedModel::default();
+        let x = model.add_source("x", f32::fact([2, 3, 8]))?;
+        let axes = tvec!(2);
+        let len = model.add_const("len", tensor0(8f32))?;
+        let z = if center {
+            let sum = model.wire_node("sum", Reduce::new(axes.clone(), Reducer::Sum), &[x])?;
+            let mean = wire_with_rank_broadcast("mean", &mut model, div(), &[sum[0], len])?;
+            model.wire_node("z", sub(), &[x, mean[0]])?[0]
+        } else {
+            x
+        };
+        let sqr = model.wire_node("sqr", square(), &[z])?;
+        let sum_sqr = model.wire_node("sum_sqr", Reduce::new(axes, Reducer::Sum), &sqr)?;
+        let var = wire_with_rank_broadcast("var", &mut model, div(), &[sum_sqr[0], len])?;
+        let eps = model.add_const("eps", tensor0(1e-5f32))?;
+        let var_eps = wire_with_rank_broadcast("var_eps", &mut model, add(), &[var[0], eps])?;
+        let inv = model.wire_node("inv", rsqrt(), &var_eps)?;
+        let y = model.wire_node("y", mul(), &[z, inv[0]])?;
+        model.set_output_outlets(&y)?;
+        Ok(model)
+    }
+
+    fn check_detection(center: bool) -> TractResult<()> {
+        let model = decomposed(center)?;
+        let decluttered = model.clone().into_decluttered()?;
+        if center {
+            assert!(decluttered.nodes().iter().any(|n| n.op_is::<LayerNorm>()));
+        } else {
+            assert!(decluttered.nodes().iter().any(|n| n.op_is::<RmsNorm>()));
+        }
+        assert!(!decluttered.nodes().iter().any(|n| n.op_is::<Reduce>()));
+        let input = Tensor::from_shape(
+            &[2, 3, 8],
+            &(0..48).map(|x| ((x * 7) % 11) as f32 - 4.).collect::<Vec<_>>(),
+        )?;
+        let expected = model.into_runnable()?.run(tvec!(input.clone().into_tvalue()))?;
+        let found = decluttered.into_runnable()?.run(tvec!(input.into_tvalue()))?;
+        found[0].close_enough(&expected[0], Approximation::Approximate)
+    }
+
+    #[test]
+    fn detect_layer_norm() -> TractResult<()> {
+        check_detection(true)
+    }
+
+    #[test]
+    fn detect_rms_norm() -> TractResult<()> {
+        check_detection(false)
+    }
+
+    #[test]
+    fn layer_norm_non_contiguous_axes() -> TractResult<()> {
+        let input =
+            tensor1(&(0..12).map(|x| x as f32).collect::<Vec<_>>()).into_shape(&[2, 3, 2])?;
+        let output = LayerNorm::new(tvec!(0, 1), 0.).eval(tvec!(input.into_tvalue()))?;
+        let s = (35f32 / 3.).sqrt().recip();
+        let centered = [-5f32, -5., -3., -3., -1., -1., 1., 1., 3., 3., 5., 5.];
+        let expected: Vec<f32> = centered.iter().map(|x| x * s).collect();
+        output[0].close_enough(&tensor1(&expected).into_shape(&[2, 3, 2])?, Approximation::Close)
+    }
+}
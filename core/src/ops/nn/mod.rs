@@ -1,9 +1,16 @@
 mod data_formats;
+mod gelu;
+mod layer_norm;
 mod reduce;
+mod sdpa;
 mod softmax;
 
 pub use self::data_formats::{BaseDataShape, DataFormat, DataShape, SymDataShape};
+pub use self::gelu::{gelu, Gelu};
+pub(crate) use self::gelu::{detect_erf_gelu, detect_tanh_gelu};
+pub use self::layer_norm::{LayerNorm, RmsNorm};
 pub use self::reduce::{Reduce, Reducer, expand_mean_of_squares};
+pub use self::sdpa::ScaledDotProductAttention;
 pub use self::softmax::{Softmax, SoftmaxExp};
 
 pub use crate::internal::*;
@@ -319,6 +319,9 @@ impl TypedOp for Reduce {
         model: &TypedModel,
         node: &TypedNode,
     ) -> TractResult<Option<TypedModelPatch>> {
+        if self.reducer == Reducer::MeanOfSquares {
+            return super::layer_norm::detect_norm(model, node, self);
+        }
         if self.reducer == Reducer::Sum {
             let Some(prec) = model.single_prec(node.id)? else { return Ok(None) };
             let Some(prec_ew) = prec.op_as::<ElementWiseOp>() else { return Ok(None) };
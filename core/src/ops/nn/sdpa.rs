@@ -0,0 +1,347 @@
+use super::layer_norm::{bin_op_is, only_successor, uniform_f32};
+use super::Softmax;
+use crate::internal::*;
+use crate::ops::einsum::EinSum;
+use crate::ops::math::{Add, Mul};
+use tract_linalg::attention::{AttentionGeometry, AttentionMask};
+
+/// `softmax(q.k^T * scale + mask) . v`, over the two last axes.
+///
+/// Inputs are q `[..., q_len, d]`, k `[..., kv_len, d]`, v `[..., kv_len, dv]` and an optional
+/// additive mask broadcastable to `[..., q_len, kv_len]`. Leading axes must match between q, k
+/// and v. With `causal`, query i only sees keys up to `i + kv_len - q_len`.
+#[derive(Clone, Debug, new, PartialEq)]
+pub struct ScaledDotProductAttention {
+    pub scale: f32,
+    pub causal: bool,
+}
+
+impl Op for ScaledDotProductAttention {
+    fn name(&self) -> Cow<str> {
+        "ScaledDotProductAttention".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("scale: {:?} causal: {:?}", self.scale, self.causal)])
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for ScaledDotProductAttention {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        let dt = inputs[0].datum_type();
+        let inputs = inputs
+            .iter()
+            .map(|t| t.cast_to::<f32>())
+            .collect::<TractResult<TVec<Cow<Tensor>>>>()?;
+        let output =
+            self.eval_f32(&inputs[0], &inputs[1], &inputs[2], inputs.get(3).map(|m| &**m))?;
+        Ok(tvec!(output.cast_to_dt(dt)?.into_owned().into_tvalue()))
+    }
+}
+
+impl ScaledDotProductAttention {
+    fn eval_f32(
+        &self,
+        q: &Tensor,
+        k: &Tensor,
+        v: &Tensor,
+        mask: Option<&Tensor>,
+    ) -> TractResult<Tensor> {
+        let rank = q.rank();
+        let geo = AttentionGeometry::new(
+            q.shape()[rank - 2],
+            k.shape()[rank - 2],
+            q.shape()[rank - 1],
+            v.shape()[rank - 1],
+        );
+        let batch_shape = &q.shape()[..rank - 2];
+        let mut output_shape: TVec<usize> = q.shape().into();
+        output_shape[rank - 1] = geo.dv;
+        let mut output = Tensor::zero::<f32>(&output_shape)?;
+        // mask strides, with zeroes on broadcast axes
+        let mask_strides: TVec<usize> = if let Some(mask) = mask {
+            mask.shape()
+                .iter()
+                .zip(mask.strides())
+                .map(|(&dim, &stride)| if dim == 1 { 0 } else { stride as usize })
+                .collect()
+        } else {
+            tvec!()
+        };
+        let (q, k, v) = (q.as_slice::<f32>()?, k.as_slice::<f32>()?, v.as_slice::<f32>()?);
+        let mask_data = mask.map(|m| m.as_slice::<f32>()).transpose()?;
+        let attention = (tract_linalg::ops().attention_f32)();
+        let out = output.as_slice_mut::<f32>()?;
+        for (head, coords) in tract_ndarray::indices(batch_shape).into_iter().enumerate() {
+            let mask = mask_data.map(|data| {
+                let offset: usize = (0..rank - 2).map(|ax| coords[ax] * mask_strides[ax]).sum();
+                AttentionMask::new(&data[offset..], mask_strides[rank - 2], mask_strides[rank - 1])
+            });
+            attention.run(
+                &geo,
+                &q[head * geo.q_len * geo.d..][..geo.q_len * geo.d],
+                &k[head * geo.kv_len * geo.d..][..geo.kv_len * geo.d],
+                &v[head * geo.kv_len * geo.dv..][..geo.kv_len * geo.dv],
+                mask,
+                self.scale,
+                self.causal,
+                &mut out[head * geo.q_len * geo.dv..][..geo.q_len * geo.dv],
+            )?;
+        }
+        Ok(output)
+    }
+}
+
+impl TypedOp for ScaledDotProductAttention {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        ensure!(inputs.len() == 3 || inputs.len() == 4);
+        let (q, k, v) = (inputs[0], inputs[1], inputs[2]);
+        let rank = q.rank();
+        ensure!(rank >= 2 && k.rank() == rank && v.rank() == rank);
+        ensure!(q.datum_type.is_float());
+        ensure!(k.datum_type == q.datum_type && v.datum_type == q.datum_type);
+        ensure!(q.shape[..rank - 2] == k.shape[..rank - 2]);
+        ensure!(q.shape[..rank - 2] == v.shape[..rank - 2]);
+        ensure!(q.shape[rank - 1] == k.shape[rank - 1]);
+        ensure!(k.shape[rank - 2] == v.shape[rank - 2]);
+        if let Some(mask) = inputs.get(3) {
+            ensure!(mask.rank() == rank && mask.datum_type == q.datum_type);
+        }
+        let mut shape = q.shape.to_tvec();
+        shape[rank - 1] = v.shape[rank - 1].clone();
+        Ok(tvec!(q.datum_type.fact(shape)))
+    }
+
+    fn cost(&self, inputs: &[&TypedFact]) -> TractResult<TVec<(Cost, TDim)>> {
+        let (q, v) = (inputs[0], inputs[2]);
+        let rank = q.rank();
+        let scores = q.shape[..rank - 1].iter().product::<TDim>() * &v.shape[rank - 2];
+        let fma = scores * (q.shape[rank - 1].clone() + &v.shape[rank - 1]);
+        Ok(tvec!((Cost::FMA(q.datum_type), fma)))
+    }
+
+    as_op!();
+}
+
+/// Checks that an einsum is `[batch.., m, c] x [batch.., n, c] -> [batch.., m, n]`, or the same
+/// with the second operand transposed as `[batch.., c, n]`. Returns the operand slots (left, right)
+/// and whether the right one is transposed.
+fn matmul_by_transposed(einsum: &EinSum) -> Option<(usize, usize, bool)> {
+    if einsum.q_params.is_some() || einsum.axes.input_count() != 2 {
+        return None;
+    }
+    let (inputs, outputs) = einsum.axes.to_strs();
+    let output: Vec<char> = outputs[0].chars().collect();
+    let rank = output.len();
+    if rank < 2 {
+        return None;
+    }
+    let (batch, m, n) = (&output[..rank - 2], output[rank - 2], output[rank - 1]);
+    let inputs: Vec<Vec<char>> = inputs.iter().map(|s| s.chars().collect()).collect();
+    let left = inputs.iter().position(|i| i.contains(&m))?;
+    let right = 1 - left;
+    let (a, b) = (&inputs[left], &inputs[right]);
+    if a.len() != rank || b.len() != rank || a[..rank - 2] != *batch || a[rank - 2] != m {
+        return None;
+    }
+    let c = a[rank - 1];
+    if output.contains(&c) || b[..rank - 2] != *batch {
+        return None;
+    }
+    if b[rank - 2] == n && b[rank - 1] == c {
+        Some((left, right, false))
+    } else if b[rank - 2] == c && b[rank - 1] == n {
+        Some((left, right, true))
+    } else {
+        None
+    }
+}
+
+/// Same as matmul_by_transposed, for `[batch.., m, c] x [batch.., c, n] -> [batch.., m, n]`.
+fn matmul(einsum: &EinSum) -> Option<(usize, usize, bool)> {
+    let (left, right, transposed) = matmul_by_transposed(einsum)?;
+    Some((left, right, !transposed))
+}
+
+/// Called from Softmax declutter. Recognizes
+/// `einsum(softmax(einsum(q, k) [* scale] [+ mask]), v)` with softmax on the key axis.
+pub(crate) fn detect_sdpa(
+    model: &TypedModel,
+    node: &TypedNode,
+    softmax: &Softmax,
+) -> TractResult<Option<TypedModelPatch>> {
+    let fact = model.outlet_fact(node.inputs[0])?;
+    let rank = fact.rank();
+    if rank < 2
+        || softmax.quant_output_dt.is_some()
+        || *softmax.axes != [rank - 1]
+        || !fact.datum_type.is_float()
+    {
+        return Ok(None);
+    }
+    let mut scores = model.node(node.inputs[0].node);
+    let mut mask = None;
+    if bin_op_is::<Add>(scores) {
+        for slot in 0..2 {
+            let other = model.outlet_fact(scores.inputs[1 - slot])?;
+            let pred = model.node(scores.inputs[slot].node);
+            if other.rank() == rank
+                && other.datum_type == fact.datum_type
+                && (pred.op_is::<EinSum>() || bin_op_is::<Mul>(pred))
+            {
+                mask = Some(scores.inputs[1 - slot]);
+                scores = pred;
+                break;
+            }
+        }
+        if mask.is_none() {
+            return Ok(None);
+        }
+    }
+    let mut scale = 1.0;
+    if bin_op_is::<Mul>(scores) {
+        let Some(slot) =
+            (0..2).find(|&slot| model.node(scores.inputs[slot].node).op_is::<EinSum>())
+        else {
+            return Ok(None);
+        };
+        let Some(s) = uniform_f32(model, scores.inputs[1 - slot])? else { return Ok(None) };
+        scale = s;
+        scores = model.node(scores.inputs[slot].node);
+    }
+    let Some(qk) = scores.op_as::<EinSum>() else { return Ok(None) };
+    if scores.outputs[0].fact.shape != fact.shape {
+        return Ok(None);
+    }
+    let Some((q_slot, k_slot, k_transposed)) = matmul_by_transposed(qk) else { return Ok(None) };
+    let Some(pv_node) = only_successor(model, node) else { return Ok(None) };
+    let Some(pv) = pv_node.op_as::<EinSum>() else { return Ok(None) };
+    let Some((p_slot, v_slot, v_transposed)) = matmul(pv) else { return Ok(None) };
+    if pv_node.inputs[p_slot] != node.id.into() {
+        return Ok(None);
+    }
+    let (q, k, v) = (scores.inputs[q_slot], scores.inputs[k_slot], pv_node.inputs[v_slot]);
+    for input in [q, k, v] {
+        let input = model.outlet_fact(input)?;
+        if input.datum_type != fact.datum_type || input.shape[..rank - 2] != fact.shape[..rank - 2]
+        {
+            return Ok(None);
+        }
+    }
+    if qk.operating_dt != fact.datum_type || pv.operating_dt != fact.datum_type {
+        return Ok(None);
+    }
+    if let Some(mask) = mask {
+        let mask_shape = &model.outlet_fact(mask)?.shape;
+        if mask_shape.iter().zip(fact.shape.iter()).any(|(m, s)| !m.is_one() && m != s) {
+            return Ok(None);
+        }
+    }
+    let mut patch = TypedModelPatch::default();
+    let name = &pv_node.name;
+    let mut inputs = tvec!(patch.tap_model(model, q)?);
+    for (input, transposed, label) in [(k, k_transposed, "k"), (v, v_transposed, "v")] {
+        let mut wire = patch.tap_model(model, input)?;
+        if transposed {
+            wire = patch.wire_node(
+                format!("{name}.{label}_transposed"),
+                AxisOp::Move(rank - 1, rank - 2),
+                &[wire],
+            )?[0];
+        }
+        inputs.push(wire);
+    }
+    if let Some(mask) = mask {
+        inputs.push(patch.tap_model(model, mask)?);
+    }
+    let wire = patch.wire_node(name, ScaledDotProductAttention::new(scale, false), &inputs)?[0];
+    patch.shunt_outside(model, pv_node.id.into(), wire)?;
+    Ok(Some(patch))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::binary::wire_with_rank_broadcast;
+    use crate::ops::math::{add, mul};
+
+    fn attention_model(masked: bool, transposed_k: bool) -> TractResult<TypedModel> {
+        let mut model = TypedModel::default();
+        let q = model.add_source("q", f32::fact([2, 3, 5, 4]))?;
+        let k_shape = if transposed_k { [2, 3, 4, 7] } else { [2, 3, 7, 4] };
+        let k = model.add_source("k", f32::fact(k_shape))?;
+        let v = model.add_source("v", f32::fact([2, 3, 7, 6]))?;
+        let qk_expr = if transposed_k { "bhqd,bhdk->bhqk" } else { "bhqd,bhkd->bhqk" };
+        let qk =
+            model.wire_node("qk", EinSum::new(qk_expr.parse()?, f32::datum_type()), &[q, k])?;
+        let scale = model.add_const("scale", tensor0(0.5f32))?;
+        let mut scores = wire_with_rank_broadcast("scaled", &mut model, mul(), &[qk[0], scale])?;
+        if masked {
+            let mask = model.add_source("mask", f32::fact([1, 1, 5, 7]))?;
+            scores = model.wire_node("masked", add(), &[scores[0], mask])?;
+        }
+        let probs =
+            model.wire_node("probs", Softmax::new(tvec!(3), None, Default::default()), &scores)?;
+        let output = model.wire_node(
+            "output",
+            EinSum::new("bhqk,bhkv->bhqv".parse()?, f32::datum_type()),
+            &[probs[0], v],
+        )?;
+        model.set_output_outlets(&output)?;
+        Ok(model)
+    }
+
+    fn check(masked: bool, transposed_k: bool) -> TractResult<()> {
+        let model = attention_model(masked, transposed_k)?;
+        let decluttered = model.clone().into_decluttered()?;
+        let sdpa = decluttered.nodes().iter().find_map(|n| n.op_as::<ScaledDotProductAttention>());
+        assert_eq!(sdpa, Some(&ScaledDotProductAttention::new(0.5, false)));
+        assert!(!decluttered.nodes().iter().any(|n| n.op_is::<Softmax>()));
+        let inputs: TVec<TValue> = model
+            .input_outlets()?
+            .iter()
+            .enumerate()
+            .map(|(ix, i)| {
+                let shape = model.outlet_fact(*i)?.shape.as_concrete().unwrap().to_vec();
+                let len = shape.iter().product::<usize>();
+                let data =
+                    (0..len).map(|x| ((x * 7 + ix) % 13) as f32 / 6. - 1.).collect::<Vec<_>>();
+                Ok(tensor1(&data).into_shape(&shape)?.into_tvalue())
+            })
+            .collect::<TractResult<_>>()?;
+        let expected = model.into_runnable()?.run(inputs.clone())?;
+        let found = decluttered.into_runnable()?.run(inputs)?;
+        found[0].close_enough(&expected[0], Approximation::Approximate)
+    }
+
+    #[test]
+    fn detect_plain() -> TractResult<()> {
+        check(false, false)
+    }
+
+    #[test]
+    fn detect_masked_transposed() -> TractResult<()> {
+        check(true, true)
+    }
+
+    #[test]
+    fn causal() -> TractResult<()> {
+        let q = tensor3(&[[[1f32], [2.]]]);
+        let k = tensor3(&[[[1f32], [0.], [-1.]]]);
+        let v = tensor3(&[[[3f32], [1.], [2.]]]);
+        let op = ScaledDotProductAttention::new(1.0, true);
+        let found = op.eval(tvec!(q.into_tvalue(), k.into_tvalue(), v.into_tvalue()))?;
+        // first query sees the first two keys only
+        let e = [1f32.exp(), 1.];
+        let first = (3. * e[0] + e[1]) / (e[0] + e[1]);
+        let e = [2f32.exp(), 1., (-2f32).exp()];
+        let second = (3. * e[0] + e[1] + 2. * e[2]) / (e[0] + e[1] + e[2]);
+        found[0].close_enough(&tensor3(&[[[first], [second]]]), Approximation::Approximate)
+    }
+}
@@ -67,6 +67,14 @@ impl TypedOp for Softmax {
         Ok(tvec!(fact))
     }
 
+    fn declutter(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        super::sdpa::detect_sdpa(model, node, self)
+    }
+
     fn axes_mapping(
         &self,
         inputs: &[&TypedFact],
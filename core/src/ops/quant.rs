@@ -390,6 +390,16 @@ pub fn scale() -> TypedBinOp {
     TypedBinOp(Box::new(Scale), None)
 }
 
+/// Shifts the zero point(s) of quantization parameters by `offset`.
+pub(crate) fn offset_qparams(qp: QParams, offset: i32) -> TractResult<QParams> {
+    if let Some(params) = qp.per_axis_params() {
+        params.map(|zp, scale| (zp + offset, scale))
+    } else {
+        let (zp, scale) = qp.zp_scale();
+        Ok(QParams::ZpScale { zero_point: zp + offset, scale })
+    }
+}
+
 /// Offsets i8 integers as u8 integers.
 pub(crate) fn offset_i8_as_u8_elementwise(x: i8) -> u8 {
     (x as u8).wrapping_add(128)
@@ -403,8 +413,7 @@ impl ElementWiseMiniOp for OffsetI8asU8 {
     }
     fn output_type(&self, input_type: DatumType) -> Option<DatumType> {
         Some(if let DatumType::QI8(qp) = input_type {
-            let (zp, scale) = qp.zp_scale();
-            DatumType::QU8(QParams::ZpScale { zero_point: zp + 128, scale })
+            DatumType::QU8(offset_qparams(qp, 128).ok()?)
         } else if input_type == DatumType::I8 {
             DatumType::U8
         } else {
@@ -443,8 +452,7 @@ impl ElementWiseMiniOp for OffsetU8asI8 {
     }
     fn output_type(&self, input_type: DatumType) -> Option<DatumType> {
         Some(if let DatumType::QU8(qp) = input_type {
-            let (zp, scale) = qp.zp_scale();
-            DatumType::QI8(QParams::ZpScale { zero_point: zp - 128, scale })
+            DatumType::QI8(offset_qparams(qp, -128).ok()?)
         } else if input_type == DatumType::U8 {
             DatumType::I8
         } else {
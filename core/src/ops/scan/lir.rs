@@ -102,6 +102,35 @@ impl FrozenOpState for FrozenState {
             model_state: self.model_state.unfreeze(),
         })
     }
+
+    fn save(&self) -> TractResult<SavedOpState> {
+        Ok(SavedOpState {
+            counters: vec![self.position as i64],
+            tensors: self.hidden_state.iter().cloned().map(Some).collect(),
+            nested: vec![self.model_state.save()?],
+        })
+    }
+
+    fn load(&self, saved: &SavedOpState) -> TractResult<Box<dyn FrozenOpState>> {
+        let hidden_state: TVec<Tensor> = saved
+            .tensors
+            .iter()
+            .map(|t| t.clone().context("Missing hidden state"))
+            .collect::<TractResult<_>>()?;
+        // an empty hidden state is initialized from the inputs at the next turn
+        let states = self.op.input_mapping.iter().filter(|m| m.is_state()).count();
+        ensure!(
+            hidden_state.is_empty() || hidden_state.len() == states,
+            "Expected {states} hidden states for scan, found {}",
+            hidden_state.len()
+        );
+        Ok(Box::new(FrozenState {
+            op: self.op.clone(),
+            position: saved.counter(0)? as usize,
+            hidden_state,
+            model_state: self.model_state.load(saved.nested(0)?)?,
+        }))
+    }
 }
 
 impl State {
@@ -216,7 +245,9 @@ impl OpState for State {
         outputs.sort_by_key(|a| a.0);
         let mut outputs: TVec<Tensor> = outputs.into_iter().map(|(_slot, v)| v).collect();
 
+        model_state.session_state.cancellation = session.cancellation.clone();
         for i in 0..iters {
+            session.check_cancellation()?;
             *position += 1;
             if *position <= op.skip {
                 continue;
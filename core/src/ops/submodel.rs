@@ -175,6 +175,14 @@ impl FrozenOpState for FrozenSubmodelOpState {
     fn unfreeze(&self) -> Box<dyn OpState> {
         Box::new(self.unfreeze())
     }
+
+    fn save(&self) -> TractResult<SavedOpState> {
+        Ok(SavedOpState { nested: vec![self.save()?], ..SavedOpState::default() })
+    }
+
+    fn load(&self, saved: &SavedOpState) -> TractResult<Box<dyn FrozenOpState>> {
+        Ok(Box::new(self.load(saved.nested(0)?)?))
+    }
 }
 
 impl OpStateFreeze for TypedModelOpState {
@@ -0,0 +1,453 @@
+use std::borrow::Borrow;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fmt::{Debug, Display};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::mpsc;
+use std::sync::Mutex;
+use std::thread::JoinHandle;
+
+use crate::internal::*;
+use crate::model::{Fact, Graph};
+use crate::plan::{SimplePlan, SimpleState};
+
+type StepResult = TractResult<TVec<Arc<Tensor>>>;
+type Job = Box<dyn FnOnce() + Send>;
+
+/// Worker threads living as long as the plan, fed through a shared job queue.
+#[derive(Debug)]
+struct WorkerPool {
+    jobs: Option<mpsc::Sender<Job>>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl WorkerPool {
+    fn new(threads: usize) -> TractResult<WorkerPool> {
+        let (jobs, queue) = mpsc::channel::<Job>();
+        let queue = Arc::new(Mutex::new(queue));
+        let workers = (0..threads)
+            .map(|ix| {
+                let queue = queue.clone();
+                std::thread::Builder::new().name(format!("tract-parallel-plan-{ix}")).spawn(
+                    move || loop {
+                        let Ok(job) = queue.lock().unwrap().recv() else { break };
+                        job();
+                    },
+                )
+            })
+            .collect::<std::io::Result<Vec<_>>>()?;
+        Ok(WorkerPool { jobs: Some(jobs), workers })
+    }
+
+    fn execute(&self, job: Job) -> TractResult<()> {
+        self.jobs
+            .as_ref()
+            .unwrap()
+            .send(job)
+            .map_err(|_| format_err!("ParallelPlan workers are gone"))
+    }
+}
+
+impl Drop for WorkerPool {
+    fn drop(&mut self) {
+        // closing the queue stops the workers once they are done with their current job
+        self.jobs.take();
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// A plan evaluating independent branches of a model concurrently.
+///
+/// The linear order computed by a `SimplePlan` is turned into a dependency graph: a step
+/// becomes ready as soon as all the nodes it consumes are computed. Ready stateless nodes are
+/// dispatched to a pool of worker threads, while stateful nodes (sources, scans, memories...)
+/// run on the calling thread, in their original relative order, as they need the session
+/// state. The worker threads are spawned with the plan, and shared by all its states.
+#[derive(Debug, Clone)]
+pub struct ParallelPlan<F, O, M>
+where
+    F: Fact + Clone + 'static,
+    O: Debug + Display + AsRef<dyn Op> + AsMut<dyn Op> + Clone + 'static,
+    M: Borrow<Graph<F, O>>,
+{
+    plan: Arc<SimplePlan<F, O, M>>,
+    threads: usize,
+    pool: Arc<WorkerPool>,
+    /// for each step, the steps that can only start once it is done
+    successors: Vec<TVec<usize>>,
+    /// for each step, the number of steps it waits for
+    precursors: Vec<usize>,
+    /// for each node, the number of steps consuming its outputs
+    consumers: Vec<usize>,
+    /// nodes whose value can be dropped once all their consumers are done
+    flushable: Vec<bool>,
+}
+
+impl<F, O, M> ParallelPlan<F, O, M>
+where
+    F: Fact + Clone + 'static,
+    O: Debug + Display + AsRef<dyn Op> + AsMut<dyn Op> + Clone + 'static,
+    M: Borrow<Graph<F, O>>,
+{
+    /// Build a parallel plan on top of a simple plan, using `threads` worker threads.
+    pub fn new(plan: SimplePlan<F, O, M>, threads: usize) -> TractResult<ParallelPlan<F, O, M>> {
+        ensure!(threads > 0, "ParallelPlan needs at least one thread");
+        let model = plan.model();
+        let order = plan.order_without_consts();
+        let mut step_of = vec![None; model.nodes().len()];
+        for (step, &node) in order.iter().enumerate() {
+            step_of[node] = Some(step);
+        }
+        let mut successors: Vec<TVec<usize>> = vec![tvec!(); order.len()];
+        let mut precursors = vec![0; order.len()];
+        let mut consumers = vec![0; model.nodes().len()];
+        let mut previous_stateful = None;
+        for (step, &node) in order.iter().enumerate() {
+            let mut deps: TVec<usize> = model.node(node).inputs.iter().map(|i| i.node).collect();
+            deps.sort();
+            deps.dedup();
+            for &dep in &deps {
+                consumers[dep] += 1;
+            }
+            let mut deps: TVec<usize> = deps.iter().filter_map(|&dep| step_of[dep]).collect();
+            // side effects on the session state must happen in the sequential order
+            if !model.node(node).op().is_stateless() {
+                deps.extend(previous_stateful);
+                previous_stateful = Some(step);
+            }
+            deps.sort();
+            deps.dedup();
+            for &dep in &deps {
+                successors[dep].push(step);
+            }
+            precursors[step] = deps.len();
+        }
+        // the last flush list is for the values the plan outputs, they must be kept
+        let mut flushable = vec![false; model.nodes().len()];
+        for flush_list in &plan.flush_lists[..order.len()] {
+            for &node in flush_list {
+                flushable[node] = true;
+            }
+        }
+        Ok(ParallelPlan {
+            plan: Arc::new(plan),
+            threads,
+            pool: Arc::new(WorkerPool::new(threads)?),
+            successors,
+            precursors,
+            consumers,
+            flushable,
+        })
+    }
+
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    pub fn simple_plan(&self) -> &Arc<SimplePlan<F, O, M>> {
+        &self.plan
+    }
+
+    pub fn model(&self) -> &Graph<F, O> {
+        self.plan.model()
+    }
+}
+
+impl<F, O, M> ParallelPlan<F, O, M>
+where
+    F: Fact + Clone + Send + Sync + 'static,
+    O: Debug + Display + AsRef<dyn Op> + AsMut<dyn Op> + Clone + Send + Sync + 'static,
+    M: Borrow<Graph<F, O>> + Send + Sync + 'static,
+{
+    pub fn run(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        let mut state = ParallelState::new(self)?;
+        state.run(inputs)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ParallelState<F, O, M, P>
+where
+    F: Fact + Clone + 'static,
+    O: Debug + Display + AsRef<dyn Op> + AsMut<dyn Op> + Clone + 'static,
+    M: Borrow<Graph<F, O>>,
+    P: Borrow<ParallelPlan<F, O, M>>,
+{
+    plan: P,
+    pub state: SimpleState<F, O, M, Arc<SimplePlan<F, O, M>>>,
+}
+
+impl<F, O, M, P> ParallelState<F, O, M, P>
+where
+    F: Fact + Clone + Send + Sync + 'static,
+    O: Debug + Display + AsRef<dyn Op> + AsMut<dyn Op> + Clone + Send + Sync + 'static,
+    M: Borrow<Graph<F, O>> + Send + Sync + 'static,
+    P: Borrow<ParallelPlan<F, O, M>> + Clone,
+{
+    pub fn new(plan: P) -> TractResult<ParallelState<F, O, M, P>> {
+        let state = SimpleState::new(plan.borrow().plan.clone())?;
+        Ok(ParallelState { plan, state })
+    }
+
+    pub fn plan(&self) -> &ParallelPlan<F, O, M> {
+        self.plan.borrow()
+    }
+
+    pub fn model(&self) -> &Graph<F, O> {
+        self.plan().model()
+    }
+
+    pub fn run(&mut self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        // values crossing threads must be Arc-backed, converting inputs now is free when they
+        // are not shared
+        let inputs = inputs.into_iter().map(|t| TValue::Const(t.into_arc_tensor())).collect();
+        self.state.set_inputs(inputs)?;
+        let result = self.exec();
+        let outputs = result.and_then(|_| self.state.outputs());
+        self.state.reset_turn()?;
+        outputs
+    }
+
+    pub fn exec(&mut self) -> TractResult<()> {
+        let token = self.state.session_state.arm_cancellation();
+        let result = self.do_exec();
+        self.state.session_state.cancellation = token;
+        result
+    }
+
+    fn do_exec(&mut self) -> TractResult<()> {
+        let ParallelState { plan, state } = self;
+        let plan: &ParallelPlan<F, O, M> = (*plan).borrow();
+        let model = plan.model();
+        let order = plan.plan.order_without_consts();
+        let mut precursors = plan.precursors.clone();
+        let mut consumers = plan.consumers.clone();
+        let stateful: Vec<bool> = order.iter().map(|&n| state.states[n].is_some()).collect();
+        let mut ready_stateless = BinaryHeap::new();
+        let mut ready_stateful = BinaryHeap::new();
+        let ready = |step: usize, stateless: &mut BinaryHeap<_>, stateful_: &mut BinaryHeap<_>| {
+            if stateful[step] {
+                stateful_.push(Reverse(step));
+            } else {
+                stateless.push(Reverse(step));
+            }
+        };
+        for (step, count) in precursors.iter().enumerate() {
+            if *count == 0 {
+                ready(step, &mut ready_stateless, &mut ready_stateful);
+            }
+        }
+        let (done_tx, done_rx) = mpsc::channel::<(usize, StepResult)>();
+        let mut done = 0;
+        let mut in_flight = 0;
+        while done < order.len() {
+            state.session_state.check_cancellation()?;
+            while let Some(Reverse(step)) = ready_stateless.pop() {
+                let inputs = Self::gather_inputs(state, model.node(order[step]))?;
+                let simple_plan = plan.plan.clone();
+                let done_tx = done_tx.clone();
+                plan.pool.execute(Box::new(move || {
+                    let node = simple_plan.model().node(simple_plan.order_without_consts()[step]);
+                    let inputs = inputs.into_iter().map(TValue::Const).collect();
+                    // a panicking op must still report, or the dispatch loop would wait forever
+                    let outputs = catch_unwind(AssertUnwindSafe(|| node.op().eval(inputs)))
+                        .unwrap_or_else(|panic| {
+                            let msg = panic
+                                .downcast_ref::<&str>()
+                                .map(|s| s.to_string())
+                                .or_else(|| panic.downcast_ref::<String>().cloned())
+                                .unwrap_or_default();
+                            bail!("Panicked: {msg}")
+                        })
+                        .with_context(|| format!("Evaluating {node}"))
+                        .map(|vs| vs.into_iter().map(|v| v.into_arc_tensor()).collect());
+                    let _ = done_tx.send((step, outputs));
+                }))?;
+                in_flight += 1;
+            }
+            let (step, outputs) = if let Some(Reverse(step)) = ready_stateful.pop() {
+                let node = model.node(order[step]);
+                let inputs =
+                    Self::gather_inputs(state, node)?.into_iter().map(TValue::Const).collect();
+                let SimpleState { session_state, states, .. } = state;
+                let op_state = states[node.id].as_deref_mut().unwrap();
+                let outputs = op_state
+                    .eval(session_state, node.op(), inputs)
+                    .with_context(|| format!("Evaluating {node}"))
+                    .map(|vs| vs.into_iter().map(|v| v.into_arc_tensor()).collect());
+                (step, outputs)
+            } else if in_flight > 0 {
+                in_flight -= 1;
+                done_rx.recv()?
+            } else {
+                bail!("ParallelPlan is stuck, no node is ready while the model is not done")
+            };
+            let node = model.node(order[step]);
+            let outputs = outputs?;
+            if plan.plan.has_unresolved_symbols {
+                for (o, v) in node.outputs.iter().zip(outputs.iter()) {
+                    if let Ok(f) = o.fact.to_typed_fact() {
+                        for (dim_abstract, dim_concrete) in f.shape.iter().zip(v.shape()) {
+                            SimpleState::<F, O, M, Arc<SimplePlan<F, O, M>>>::resolve(
+                                &mut state.session_state.resolved_symbols,
+                                dim_abstract,
+                                *dim_concrete as i64,
+                            );
+                        }
+                    }
+                }
+            }
+            state.values[node.id] = Some(outputs.into_iter().map(TValue::Const).collect());
+            done += 1;
+            for &succ in &plan.successors[step] {
+                precursors[succ] -= 1;
+                if precursors[succ] == 0 {
+                    ready(succ, &mut ready_stateless, &mut ready_stateful);
+                }
+            }
+            let mut inputs: TVec<usize> = node.inputs.iter().map(|i| i.node).collect();
+            inputs.sort();
+            inputs.dedup();
+            for input in inputs {
+                consumers[input] -= 1;
+                if consumers[input] == 0 && plan.flushable[input] {
+                    trace!("  Ran {} can now flush {}", node, model.node(input));
+                    state.values[input] = None;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn gather_inputs(
+        state: &SimpleState<F, O, M, Arc<SimplePlan<F, O, M>>>,
+        node: &Node<F, O>,
+    ) -> TractResult<TVec<Arc<Tensor>>> {
+        node.inputs
+            .iter()
+            .map(|i| {
+                let prec = state.values[i.node].as_ref().ok_or_else(|| {
+                    format_err!("Computing {}, precursor {} not done", node, i.node)
+                })?;
+                Ok(prec[i.slot].clone().into_arc_tensor())
+            })
+            .collect()
+    }
+
+    /// Reset op inner state.
+    pub fn reset_op_states(&mut self) -> TractResult<()> {
+        self.state.reset_op_states()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::math;
+
+    fn branchy_model() -> TractResult<TypedModel> {
+        let mut model = TypedModel::default();
+        let source = model.add_source("source", f32::fact([4, 16]))?;
+        let mut branches = tvec!();
+        for b in 0..6 {
+            let k = model.add_const(format!("k{b}"), rctensor2(&[[b as f32 + 1.0]]))?;
+            let mut wire = model.wire_node(format!("mul{b}"), math::mul(), &[source, k])?;
+            for l in 0..3 {
+                wire = model.wire_node(format!("add{b}.{l}"), math::add(), &[wire[0], source])?;
+            }
+            branches.push(wire[0]);
+        }
+        let mut wire = branches[0];
+        for (ix, b) in branches.iter().enumerate().skip(1) {
+            wire = model.wire_node(format!("sum{ix}"), math::add(), &[wire, *b])?[0];
+        }
+        model.set_output_outlets(&[wire, branches[2]])?;
+        Ok(model)
+    }
+
+    #[test]
+    fn parallel_plan_matches_simple_plan() -> TractResult<()> {
+        let model = branchy_model()?;
+        let input =
+            Tensor::from_shape(&[4, 16], &(0..64).map(|i| i as f32 / 7.0).collect::<Vec<_>>())?;
+        let expected = model.clone().into_runnable()?.run(tvec!(input.clone().into_tvalue()))?;
+        for threads in [1, 2, 4] {
+            let plan = ParallelPlan::new(model.clone().into_runnable()?, threads)?;
+            let mut state = ParallelState::new(&plan)?;
+            for _ in 0..2 {
+                let found = state.run(tvec!(input.clone().into_tvalue()))?;
+                assert_eq!(found, expected);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn parallel_plan_reports_errors() -> TractResult<()> {
+        let model = branchy_model()?;
+        let plan = ParallelPlan::new(model.into_runnable()?, 2)?;
+        let input = Tensor::zero::<f32>(&[4, 17])?;
+        assert!(plan.run(tvec!(input.into_tvalue())).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn parallel_plan_checks_cancellation() -> TractResult<()> {
+        let plan = ParallelPlan::new(branchy_model()?.into_runnable()?, 2)?;
+        let mut state = ParallelState::new(&plan)?;
+        let token = CancellationToken::new();
+        state.state.set_cancellation_token(Some(token.clone()));
+        let input = Tensor::zero::<f32>(&[4, 16])?;
+        state.run(tvec!(input.clone().into_tvalue()))?;
+        token.cancel();
+        let err = state.run(tvec!(input.into_tvalue())).unwrap_err();
+        assert_eq!(err.downcast_ref::<Cancelled>(), Some(&Cancelled::Requested));
+        Ok(())
+    }
+
+    #[derive(Debug, Clone, Hash)]
+    struct Panic;
+
+    impl Op for Panic {
+        fn name(&self) -> Cow<str> {
+            "Panic".into()
+        }
+
+        op_as_typed_op!();
+    }
+
+    impl EvalOp for Panic {
+        fn is_stateless(&self) -> bool {
+            true
+        }
+
+        fn eval(&self, _inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+            panic!("expected panic")
+        }
+    }
+
+    impl TypedOp for Panic {
+        fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+            Ok(tvec!(inputs[0].clone()))
+        }
+
+        as_op!();
+    }
+
+    #[test]
+    fn parallel_plan_reports_panics() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let source = model.add_source("source", f32::fact([2]))?;
+        let left = model.wire_node("left", Panic, &[source])?;
+        let right = model.wire_node("right", math::add(), &[source, source])?;
+        model.set_output_outlets(&[left[0], right[0]])?;
+        let plan = ParallelPlan::new(model.into_runnable()?, 2)?;
+        for _ in 0..2 {
+            let err = plan.run(tvec!(tensor1(&[1f32, 2.]).into_tvalue())).unwrap_err();
+            assert!(format!("{err:?}").contains("expected panic"));
+        }
+        Ok(())
+    }
+}
@@ -3,10 +3,14 @@ use std::fmt::{Debug, Display};
 use std::marker::PhantomData;
 
 use crate::internal::*;
+use crate::memory_plan::{Arena, MemoryPlan};
 use crate::model::order::eval_order_for_nodes;
 use crate::model::{Fact, Graph, OutletId};
 use crate::ops::konst::Const;
+use crate::ops::kv_cache::KvCacheState;
 use crate::ops::FrozenOpState;
+use tract_data::arena::with_preallocated;
+use tract_linalg::multithread::{multithread_tract_scope, Executor};
 
 #[derive(Default)]
 pub struct SessionState {
@@ -14,6 +18,7 @@ pub struct SessionState {
     pub resolved_symbols: SymbolValues,
     pub tensors: HashMap<String, Tensor>,
     pub cached_mmm_scratch_space: Option<Box<dyn tract_linalg::mmm::ScratchSpace>>,
+    pub cancellation: Option<CancellationToken>,
 }
 
 impl Clone for SessionState {
@@ -23,10 +28,26 @@ impl Clone for SessionState {
             resolved_symbols: self.resolved_symbols.clone(),
             tensors: self.tensors.clone(),
             cached_mmm_scratch_space: None,
+            cancellation: self.cancellation.clone(),
         }
     }
 }
 
+impl SessionState {
+    /// Fail with `Cancelled` if the run has been cancelled or is past its deadline.
+    pub fn check_cancellation(&self) -> TractResult<()> {
+        self.cancellation.as_ref().map(|c| c.check()).unwrap_or(Ok(()))
+    }
+
+    /// Arm the cancellation token for a run starting now. Returns the token to restore once the
+    /// run is over.
+    pub fn arm_cancellation(&mut self) -> Option<CancellationToken> {
+        let token = self.cancellation.clone();
+        self.cancellation = token.as_ref().map(|t| t.armed());
+        token
+    }
+}
+
 impl Debug for SessionState {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "SessionState({:?})", self.resolved_symbols)
@@ -41,10 +62,11 @@ where
     M: Borrow<Graph<F, O>>,
 {
     model: M,
-    outputs: Vec<OutletId>,
+    pub(crate) outputs: Vec<OutletId>,
     order: Vec<usize>,
-    flush_lists: Vec<TVec<usize>>,
-    has_unresolved_symbols: bool,
+    pub(crate) flush_lists: Vec<TVec<usize>>,
+    pub(crate) has_unresolved_symbols: bool,
+    executor: Option<Executor>,
     _casper: PhantomData<(F, O)>,
 }
 
@@ -109,10 +131,27 @@ where
             flush_lists,
             outputs: outputs.to_vec(),
             has_unresolved_symbols: !symbols.is_empty(),
+            executor: None,
             _casper: PhantomData,
         })
     }
 
+    /// Run states spawned from this plan on the given executor instead of the process-wide
+    /// default one.
+    pub fn with_executor(mut self, executor: Executor) -> Self {
+        self.executor = Some(executor);
+        self
+    }
+
+    pub fn executor(&self) -> Option<&Executor> {
+        self.executor.as_ref()
+    }
+
+    /// Compute a static placement of the intermediate values of this plan in a single arena.
+    pub fn memory_plan(&self) -> TractResult<MemoryPlan> {
+        MemoryPlan::new(self)
+    }
+
     pub fn order_without_consts(&self) -> &[usize] {
         &self.order
     }
@@ -127,7 +166,7 @@ where
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Debug)]
 pub struct SimpleState<F, O, M, P>
 where
     F: Fact + Clone + 'static,
@@ -139,9 +178,54 @@ where
     pub states: Vec<Option<Box<dyn OpState>>>,
     pub session_state: SessionState,
     pub values: Vec<Option<TVec<TValue>>>,
+    pub executor: Option<Executor>,
+    // must stay after values: tensors living in the arena are dropped first
+    arena: Option<Arena>,
     _phantom: PhantomData<(M, F, O)>,
 }
 
+impl<F, O, M, P> Clone for SimpleState<F, O, M, P>
+where
+    F: Fact + Clone + 'static,
+    O: Debug + Display + AsRef<dyn Op> + AsMut<dyn Op> + Clone + 'static,
+    M: Borrow<Graph<F, O>>,
+    P: Borrow<SimplePlan<F, O, M>> + Clone,
+{
+    /// Values living in the arena are copied to the heap: the clone gets an arena of its own,
+    /// and must not depend on the lifetime of the original.
+    fn clone(&self) -> Self {
+        let values = if let Some(arena) = &self.arena {
+            self.values
+                .iter()
+                .map(|vs| {
+                    vs.as_ref().map(|vs| {
+                        vs.iter()
+                            .map(|v| {
+                                if arena.holds(v) {
+                                    v.deep_clone().into_tvalue()
+                                } else {
+                                    v.clone()
+                                }
+                            })
+                            .collect()
+                    })
+                })
+                .collect()
+        } else {
+            self.values.clone()
+        };
+        SimpleState {
+            plan: self.plan.clone(),
+            states: self.states.clone(),
+            session_state: self.session_state.clone(),
+            values,
+            executor: self.executor.clone(),
+            arena: self.arena.clone(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
 impl<F, O, M, P> SimpleState<F, O, M, P>
 where
     F: Fact + Clone + 'static,
@@ -154,8 +238,15 @@ where
         let session = SessionState::default();
         let model = plan.borrow().model();
         let states: Vec<Option<Box<dyn OpState>>> = vec![None; model.nodes.len()];
-        let mut state =
-            SimpleState { plan, states, session_state: session, values, _phantom: PhantomData };
+        let mut state = SimpleState {
+            plan,
+            states,
+            session_state: session,
+            values,
+            executor: None,
+            arena: None,
+            _phantom: PhantomData,
+        };
         state.populate_consts();
         state.reset_op_states()?;
         Ok(state)
@@ -187,6 +278,21 @@ where
         Ok(())
     }
 
+    /// Empty all the key/value caches, starting a new sequence.
+    pub fn reset_kv_caches(&mut self) -> TractResult<()> {
+        self.truncate_kv_caches(0)
+    }
+
+    /// Roll back all the key/value caches to their first `len` positions.
+    pub fn truncate_kv_caches(&mut self, len: usize) -> TractResult<()> {
+        for state in self.states.iter_mut().flatten() {
+            if let Some(cache) = state.downcast_mut::<KvCacheState>() {
+                cache.truncate(len)?;
+            }
+        }
+        Ok(())
+    }
+
     pub fn run(&mut self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
         self.run_plan_with_eval(inputs, self::eval)
     }
@@ -216,7 +322,62 @@ where
         Ok(outputs)
     }
 
-    pub fn exec_plan_with_eval<Eval, E>(&mut self, mut eval: Eval) -> TractResult<()>
+    /// Run this state on the given executor, overriding the plan and process-wide settings.
+    pub fn set_executor(&mut self, executor: Executor) {
+        self.executor = Some(executor);
+    }
+
+    /// Serve the intermediate values of the plan from a single pre-allocated arena.
+    ///
+    /// The arena is sized and laid out by the plan `memory_plan()`, once, so that repeated
+    /// runs do not go through the allocator for these values. Cloning the state copies the
+    /// values living in the arena, and a run failing midway discards them.
+    pub fn enable_arena(&mut self) -> TractResult<()> {
+        let memory_plan = self.plan().memory_plan()?;
+        self.reset_turn()?;
+        self.arena = Some(Arena::new(Arc::new(memory_plan))?);
+        Ok(())
+    }
+
+    pub fn arena(&self) -> Option<&Arena> {
+        self.arena.as_ref()
+    }
+
+    /// Check `token` between nodes of the following runs, aborting them with `Cancelled`.
+    pub fn set_cancellation_token(&mut self, token: Option<CancellationToken>) {
+        self.session_state.cancellation = token;
+    }
+
+    pub fn exec_plan_with_eval<Eval, E>(&mut self, eval: Eval) -> TractResult<()>
+    where
+        Eval: for<'a, 'b, 'c> FnMut(
+            &'a mut SessionState,
+            Option<&'b mut (dyn OpState + 'static)>,
+            &'c Node<F, O>,
+            TVec<TValue>,
+        ) -> Result<TVec<TValue>, E>,
+        E: Into<anyhow::Error> + Send + Sync + 'static,
+    {
+        if let Some(arena) = &mut self.arena {
+            arena.allocate()?;
+        }
+        let token = self.session_state.arm_cancellation();
+        let result = if let Some(executor) =
+            self.executor.as_ref().or(self.plan.borrow().executor.as_ref()).cloned()
+        {
+            multithread_tract_scope(executor, || self.do_exec_plan_with_eval(eval))
+        } else {
+            self.do_exec_plan_with_eval(eval)
+        };
+        self.session_state.cancellation = token;
+        if result.is_err() && self.arena.is_some() {
+            // do not leave values pointing to arena slots the next turn will overwrite
+            self.reset_turn()?;
+        }
+        result
+    }
+
+    fn do_exec_plan_with_eval<Eval, E>(&mut self, mut eval: Eval) -> TractResult<()>
     where
         Eval: for<'a, 'b, 'c> FnMut(
             &'a mut SessionState,
@@ -232,11 +393,13 @@ where
                 ref mut session_state,
                 ref mut states,
                 ref mut values,
+                ref arena,
                 ..
             } = self;
             let plan = plan.borrow();
             let model = plan.model();
             for (step, n) in plan.order.iter().enumerate() {
+                session_state.check_cancellation()?;
                 let node = model.node(*n);
                 trace!("Running step {}, node {}", step, node);
                 let mut inputs: TVec<TValue> = tvec![];
@@ -277,8 +440,26 @@ where
                     }
                 }
 
-                let vs = eval(session_state, states[node.id].as_deref_mut(), node, inputs)
-                    .map_err(|e| e.into())?;
+                let vs = if let Some(arena) = arena {
+                    // an input living in the arena must not be recycled for the output, as its
+                    // slot may be planned for another value: keep it shared, so that the op
+                    // computes its output in its own planned slot instead
+                    let _pinned = inputs
+                        .first()
+                        .filter(|i| node.op().supports_in_place() && arena.holds(i))
+                        .cloned();
+                    let offers = arena.offers_for(node.id).unwrap_or(&[]);
+                    unsafe {
+                        with_preallocated(offers, || {
+                            eval(session_state, states[node.id].as_deref_mut(), node, inputs)
+                                .map(|vs| arena.sanitize(node.id, vs))
+                        })
+                    }
+                    .map_err(|e| e.into())?
+                } else {
+                    eval(session_state, states[node.id].as_deref_mut(), node, inputs)
+                        .map_err(|e| e.into())?
+                };
 
                 if plan.has_unresolved_symbols {
                     for (o, v) in node.outputs.iter().zip(vs.iter()) {
@@ -338,7 +519,7 @@ where
         Ok(())
     }
 
-    fn resolve(symbols: &mut SymbolValues, expected: &TDim, provided: i64) {
+    pub(crate) fn resolve(symbols: &mut SymbolValues, expected: &TDim, provided: i64) {
         match expected {
             TDim::Sym(s) => {
                 info!("Determined symbol {s}={provided}");
@@ -541,7 +722,7 @@ pub fn eval<F, O>(
     session_state: &mut SessionState,
     mut state: Option<&mut (dyn OpState + 'static)>,
     node: &Node<F, O>,
-    input: TVec<TValue>,
+    mut input: TVec<TValue>,
 ) -> TractResult<TVec<TValue>>
 where
     F: Fact + Clone + 'static,
@@ -549,6 +730,10 @@ where
 {
     // eprint!("{node} {input:?}");
     let r = match state {
+        _ if node.op().supports_in_place() && input.first().is_some_and(|i| i.is_exclusive()) => {
+            let first = input.remove(0).into_tensor();
+            node.op().eval_in_place(session_state, first, input)
+        }
         Some(ref mut state) => state.eval(session_state, node.op(), input),
         None => node.op().eval(input),
     }
@@ -589,6 +774,7 @@ where
                 resolved_symbols: self.resolved_symbols.clone(),
                 tensors: self.tensors.clone(),
                 cached_mmm_scratch_space: None,
+                cancellation: None,
             },
             states: self.states.iter().map(|s| s.as_ref().map(|s| s.unfreeze())).collect(),
             values: self
@@ -596,11 +782,61 @@ where
                 .iter()
                 .map(|t| t.as_ref().map(|t| t.iter().map(|t| t.clone().into_tvalue()).collect()))
                 .collect(),
+            executor: None,
+            arena: None,
             _phantom: PhantomData,
         };
         state.populate_consts();
         state
     }
+
+    /// Extract the content of the state outliving a turn, for serialization.
+    pub fn save(&self) -> TractResult<SavedSimpleState> {
+        let model = self.plan.borrow().model();
+        let mut states = vec![];
+        for (ix, state) in self.states.iter().enumerate() {
+            let saved = if let Some(state) = state {
+                let saved = state.save().with_context(|| format!("Saving {}", model.node(ix)))?;
+                Some(saved)
+            } else {
+                None
+            };
+            states.push(saved);
+        }
+        Ok(SavedSimpleState { tensors: self.tensors.clone(), states })
+    }
+
+    /// Rebuild a state from saved content. `self` is a state of the same plan, providing what is
+    /// not saved.
+    pub fn load(&self, saved: &SavedSimpleState) -> TractResult<Self> {
+        let model = self.plan.borrow().model();
+        ensure!(
+            saved.states.len() == self.states.len(),
+            "Saved state has {} nodes, model has {}",
+            saved.states.len(),
+            self.states.len()
+        );
+        let mut states = vec![];
+        for (ix, (fresh, saved)) in self.states.iter().zip(saved.states.iter()).enumerate() {
+            let loaded = match (fresh, saved) {
+                (Some(fresh), Some(saved)) => Some(
+                    fresh.load(saved).with_context(|| format!("Loading {}", model.node(ix)))?,
+                ),
+                (None, None) => None,
+                _ => bail!("Saved state does not match model at {}", model.node(ix)),
+            };
+            states.push(loaded);
+        }
+        Ok(FrozenSimpleState {
+            plan: self.plan.clone(),
+            inputs: HashMap::default(),
+            resolved_symbols: self.resolved_symbols.clone(),
+            tensors: saved.tensors.clone(),
+            states,
+            values: self.values.clone(),
+            _phantom: PhantomData,
+        })
+    }
 }
 
 #[cfg(test)]
@@ -633,4 +869,35 @@ mod test {
     fn frozen_type_state_is_send() {
         is_send::<TypedFrozenSimpleState<TypedModel, TypedSimplePlan<TypedModel>>>();
     }
+
+    #[test]
+    fn run_recycles_exclusive_inputs() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let source = model.add_source("source", f32::fact([4]))?;
+        let k = model.add_const("k", rctensor1(&[1f32; 4]))?;
+        let add = model.wire_node("add", crate::ops::math::add(), &[source, k])?;
+        let abs = model.wire_node("abs", crate::ops::math::abs(), &add)?;
+        let cast = model.wire_node("cast", crate::ops::cast::cast(i32::datum_type()), &abs)?;
+        model.set_output_outlets(&cast)?;
+        let plan = model.into_runnable()?;
+        let mut state = SimpleState::new(&plan)?;
+        let mut pointers = HashMap::<String, (usize, usize)>::default();
+        let output = state.run_plan_with_eval(
+            tvec!(tensor1(&[-3f32, -2., 0., 1.]).into_tvalue()),
+            |session, op_state, node, inputs| {
+                let input = inputs.first().map(|i| unsafe { i.as_ptr_unchecked::<u8>() } as usize);
+                let outputs = eval(session, op_state, node, inputs)?;
+                if let Some(input) = input {
+                    let output = unsafe { outputs[0].as_ptr_unchecked::<u8>() } as usize;
+                    pointers.insert(node.name.clone(), (input, output));
+                }
+                TractResult::Ok(outputs)
+            },
+        )?;
+        assert_eq!(*output[0], tensor1(&[2i32, 1, 1, 2]));
+        for name in ["abs", "cast"] {
+            assert_eq!(pointers[name].0, pointers[name].1, "{name} allocated");
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,381 @@
+use crate::internal::translator::Translate;
+use crate::internal::*;
+use crate::ops::cast::{cast, Cast};
+use crate::ops::cnn::Conv;
+use crate::ops::einsum::EinSum;
+use crate::ops::matmul::mir_quant::{link_per_axis_qparams, wire_zp_scale};
+use crate::transform::ModelTransform;
+
+/// Post-training static quantization.
+///
+/// Activation ranges are collected by running the float model on the calibration inputs, then
+/// convolutions and matrix products with constant weights are rewritten as their quantized
+/// counterparts: activations are quantized to `activations_dt` (I8 or U8) with the calibrated
+/// ranges, weights are quantized symmetrically to I8 with one scale per output channel.
+/// Quantized operators are surrounded by casts from and to f32, so the rest of the model is left
+/// untouched. Casts between consecutive quantized operators cancel out.
+///
+/// Calibration inputs are given in the order of the model inputs. The instances returned by
+/// `get_transform("quantize-i8")` and `"quantize-u8"` have none, and fail to transform.
+#[derive(Debug, Clone)]
+pub struct PostTrainingQuantization {
+    pub activations_dt: DatumType,
+    pub calibration: Vec<TVec<TValue>>,
+}
+
+impl PostTrainingQuantization {
+    pub fn new(activations_dt: DatumType, calibration: Vec<TVec<TValue>>) -> Self {
+        PostTrainingQuantization { activations_dt, calibration }
+    }
+
+    /// Run the model on the calibration inputs and collect the (min, max) range of the inputs
+    /// and outputs of the quantizable nodes.
+    pub fn calibrate(&self, model: &TypedModel) -> TractResult<HashMap<OutletId, (f32, f32)>> {
+        ensure!(
+            !self.calibration.is_empty(),
+            "{} requires calibration inputs (`-t {}=calibration.npz` in the cli)",
+            self.name(),
+            self.name()
+        );
+        let mut outlets: Vec<OutletId> = vec![];
+        for node in model.nodes() {
+            if quantizable(model, node)?.is_some() {
+                outlets.push(node.inputs[0]);
+                outlets.push(node.inputs[1]);
+                outlets.push(node.id.into());
+            }
+        }
+        outlets.retain(|o| model.outlet_fact(*o).map(|f| f.konst.is_none()).unwrap_or(false));
+        outlets.sort();
+        outlets.dedup();
+        let mut ranges = HashMap::new();
+        if outlets.is_empty() {
+            return Ok(ranges);
+        }
+        let plan = SimplePlan::new_for_outputs(model, &outlets)?;
+        for inputs in &self.calibration {
+            let values = plan.run(inputs.clone())?;
+            for (outlet, value) in outlets.iter().zip(values.iter()) {
+                let value = value.cast_to::<f32>()?;
+                let range = ranges.entry(*outlet).or_insert((f32::MAX, f32::MIN));
+                for x in value.as_slice::<f32>()? {
+                    range.0 = range.0.min(*x);
+                    range.1 = range.1.max(*x);
+                }
+            }
+        }
+        Ok(ranges)
+    }
+}
+
+impl ModelTransform for PostTrainingQuantization {
+    fn name(&self) -> Cow<str> {
+        format!("quantize-{:?}", self.activations_dt).to_lowercase().into()
+    }
+
+    fn transform(&self, model: &mut TypedModel) -> TractResult<()> {
+        ensure!(
+            self.activations_dt == i8::datum_type() || self.activations_dt == u8::datum_type(),
+            "Activations can only be quantized to i8 or u8"
+        );
+        let ranges = self.calibrate(model)?;
+        let translator = QuantizationTranslator { activations_dt: self.activations_dt, ranges };
+        *model = translator.translate_model(model)?.into_compact()?;
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct QuantizationTranslator {
+    activations_dt: DatumType,
+    ranges: HashMap<OutletId, (f32, f32)>,
+}
+
+/// Slot of the activation and of the constant weights of a quantizable node.
+fn quantizable(model: &TypedModel, node: &TypedNode) -> TractResult<Option<(usize, usize)>> {
+    let facts = model.node_input_facts(node.id)?;
+    if let Some(conv) = node.op_as::<Conv>() {
+        if conv.q_params.is_none()
+            && facts[0].datum_type == f32::datum_type()
+            && facts[1].konst.is_some()
+            && facts[2].konst.is_some()
+        {
+            return Ok(Some((0, 1)));
+        }
+    } else if let Some(op) = node.op_as::<EinSum>() {
+        if op.q_params.is_some()
+            || op.operating_dt != f32::datum_type()
+            || facts.len() != 2
+            || facts.iter().any(|f| f.datum_type != f32::datum_type())
+        {
+            return Ok(None);
+        }
+        let k_axes = op
+            .axes
+            .iter_all_axes()
+            .filter(|a| a.outputs[0].is_empty())
+            .filter(|a| a.inputs[0].len() == 1 && a.inputs[1].len() == 1)
+            .count();
+        let sum_axes = op.axes.iter_all_axes().filter(|a| a.outputs[0].is_empty()).count();
+        if k_axes != 1 || sum_axes != 1 {
+            return Ok(None);
+        }
+        match (facts[0].konst.is_some(), facts[1].konst.is_some()) {
+            (false, true) => return Ok(Some((0, 1))),
+            (true, false) => return Ok(Some((1, 0))),
+            _ => (),
+        }
+    }
+    Ok(None)
+}
+
+fn activation_dt(dt: DatumType, (min, max): (f32, f32)) -> DatumType {
+    let (min, max) = (min.min(0.), max.max(0.));
+    let (qmin, qmax) = if dt == i8::datum_type() { (-128, 127) } else { (0, 255) };
+    let scale = if max > min { (max - min) / (qmax - qmin) as f32 } else { 1. };
+    let zero_point = (qmin as f32 - min / scale).round() as i32;
+    dt.with_zp_scale(zero_point.clamp(qmin, qmax), scale)
+}
+
+/// Symmetric I8 quantization parameters of weights, with one scale per slice along `axis` if
+/// it is specified.
+fn weights_qparams(weights: &Tensor, axis: Option<usize>) -> TractResult<QParams> {
+    let weights = weights.cast_to::<f32>()?;
+    let weights = weights.to_array_view::<f32>()?;
+    let scale = |max_abs: f32| if max_abs > 0. { max_abs / 127. } else { 1. };
+    if let Some(axis) = axis {
+        let scales: Vec<f32> = weights
+            .axis_iter(tract_ndarray::Axis(axis))
+            .map(|slice| scale(slice.iter().fold(0f32, |acc, x| acc.max(x.abs()))))
+            .collect();
+        QParams::per_axis(axis, &vec![0; scales.len()], &scales)
+    } else {
+        let max_abs = weights.iter().fold(0f32, |acc, x| acc.max(x.abs()));
+        Ok(QParams::ZpScale { zero_point: 0, scale: scale(max_abs) })
+    }
+}
+
+impl QuantizationTranslator {
+    fn quantize_activation(
+        &self,
+        source: &TypedModel,
+        target: &mut TypedModel,
+        name: &str,
+        outlet: OutletId,
+        wire: OutletId,
+    ) -> TractResult<(OutletId, DatumType)> {
+        let range = self.ranges.get(&outlet).context("Missing calibrated range")?;
+        let dt = activation_dt(self.activations_dt, *range);
+        // a dequantizing cast from the same quantization cancels out
+        let producer = target.node(wire.node);
+        if producer.op_is::<Cast>() && target.outlet_fact(producer.inputs[0])?.datum_type == dt {
+            return Ok((producer.inputs[0], dt));
+        }
+        debug_assert!(source.outlet_fact(outlet)?.datum_type == f32::datum_type());
+        Ok((target.wire_node(name, cast(dt), &[wire])?[0], dt))
+    }
+
+    fn translate_conv(
+        &self,
+        source: &TypedModel,
+        node: &TypedNode,
+        conv: &Conv,
+        target: &mut TypedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+    ) -> TractResult<TVec<OutletId>> {
+        let name = &node.name;
+        let facts = source.node_input_facts(node.id)?;
+        let (x, x_dt) = self.quantize_activation(
+            source,
+            target,
+            &format!("{name}.quantize_input"),
+            node.inputs[0],
+            mapping[&node.inputs[0]],
+        )?;
+        let range = self.ranges.get(&node.id.into()).context("Missing calibrated range")?;
+        let y_dt = activation_dt(self.activations_dt, *range);
+        let kernel = facts[1].konst.as_ref().unwrap();
+        let o_axis = conv.kernel_fmt.o_axis(kernel.shape());
+        let k_qp = weights_qparams(kernel, Some(o_axis).filter(|_| conv.group == 1))?;
+        let k_dt = i8::datum_type().quantize(k_qp);
+        let kernel = kernel.cast_to_dt(k_dt)?.cast_to_dt(i8::datum_type())?.into_owned();
+        let (_, x_scale) = x_dt.zp_scale();
+        let k_scales = if let Some(params) = k_qp.per_axis_params() {
+            params.scales.clone()
+        } else {
+            vec![k_qp.zp_scale().1; conv.output_channels()]
+        };
+        let bias = facts[2].konst.as_ref().unwrap().cast_to::<f32>()?.into_owned();
+        let bias = bias.as_slice::<f32>()?;
+        let bias: Vec<i32> = (0..conv.output_channels())
+            .map(|c| (bias[c.min(bias.len() - 1)] / (x_scale * k_scales[c])).round() as i32)
+            .collect();
+        let kernel = target.add_const(format!("{name}.kernel"), kernel)?;
+        let bias = target.add_const(format!("{name}.bias"), tensor1(&bias))?;
+        let (x0, x_scale) = wire_zp_scale(target, &format!("{name}.x"), x_dt)?;
+        let (k0, k_scale) = wire_zp_scale(target, &format!("{name}.k"), k_dt)?;
+        let (y0, y_scale) = wire_zp_scale(target, &format!("{name}.y"), y_dt)?;
+        let op = Conv { q_params: Some(y_dt), ..conv.clone() };
+        let wire = target.wire_node(
+            name,
+            op,
+            &[x, kernel, bias, x0, x_scale, k0, k_scale, y0, y_scale],
+        )?;
+        target.wire_node(format!("{name}.dequantize"), cast(f32::datum_type()), &wire)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn translate_einsum(
+        &self,
+        source: &TypedModel,
+        node: &TypedNode,
+        op: &EinSum,
+        (act, weights): (usize, usize),
+        target: &mut TypedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+    ) -> TractResult<TVec<OutletId>> {
+        let name = &node.name;
+        let facts = source.node_input_facts(node.id)?;
+        let (x, x_dt) = self.quantize_activation(
+            source,
+            target,
+            &format!("{name}.quantize_input"),
+            node.inputs[act],
+            mapping[&node.inputs[act]],
+        )?;
+        let range = self.ranges.get(&node.id.into()).context("Missing calibrated range")?;
+        let y_dt = activation_dt(self.activations_dt, *range);
+        let w = facts[weights].konst.as_ref().unwrap();
+        // one scale per channel along the only weights axis that is not shared with the
+        // activation
+        let channel_axes: TVec<usize> = op
+            .axes
+            .axes(InOut::In(weights))
+            .filter(|a| a.inputs[act].is_empty() && a.outputs[0].len() == 1)
+            .map(|a| a.inputs[weights][0])
+            .collect();
+        let w_qp = weights_qparams(w, channel_axes.first().copied().filter(|_| channel_axes.len() == 1))?;
+        let w_dt = i8::datum_type().quantize(w_qp);
+        let w = w.cast_to_dt(w_dt)?.cast_to_dt(i8::datum_type())?.into_owned();
+        let w = target.add_const(format!("{name}.weights"), w)?;
+
+        let mut axes = op.axes.clone();
+        for slot in 2..9 {
+            axes = axes.with_extra_input(slot)?;
+        }
+        let (a_dt, b_dt) = if act == 0 { (x_dt, w_dt) } else { (w_dt, x_dt) };
+        axes = link_per_axis_qparams(axes, a_dt, 0, [3, 4])?;
+        axes = link_per_axis_qparams(axes, b_dt, 1, [5, 6])?;
+        let bias = target.add_const(format!("{name}.bias"), tensor0(0i32))?;
+        let (a0, a_scale) = wire_zp_scale(target, &format!("{name}.a"), a_dt)?;
+        let (b0, b_scale) = wire_zp_scale(target, &format!("{name}.b"), b_dt)?;
+        let (c0, c_scale) = wire_zp_scale(target, &format!("{name}.c"), y_dt)?;
+        let (a, b) = if act == 0 { (x, w) } else { (w, x) };
+        let op = EinSum { axes, operating_dt: i32::datum_type(), q_params: Some(y_dt) };
+        let wire =
+            target.wire_node(name, op, &[a, b, bias, a0, a_scale, b0, b_scale, c0, c_scale])?;
+        target.wire_node(format!("{name}.dequantize"), cast(f32::datum_type()), &wire)
+    }
+}
+
+impl Translate<TypedFact, Box<dyn TypedOp>, TypedFact, Box<dyn TypedOp>>
+    for QuantizationTranslator
+{
+    fn translate_node(
+        &self,
+        source: &TypedModel,
+        node: &TypedNode,
+        target: &mut TypedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+    ) -> TractResult<TVec<OutletId>> {
+        if let Some(slots) = quantizable(source, node)? {
+            if let Some(conv) = node.op_as::<Conv>() {
+                return self.translate_conv(source, node, conv, target, mapping);
+            } else if let Some(op) = node.op_as::<EinSum>() {
+                return self.translate_einsum(source, node, op, slots, target, mapping);
+            }
+        }
+        let inputs = node.inputs.iter().map(|i| mapping[i]).collect::<TVec<_>>();
+        target.wire_node(&node.name, node.op.clone(), &inputs)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::cnn::{KernelFormat, PaddingSpec, PoolSpec};
+    use crate::ops::nn::DataFormat;
+
+    #[test]
+    fn conv_and_matmul() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let x = model.add_source("x", f32::fact([1, 2, 5]))?;
+        let kernel = Tensor::from_shape(
+            &[3, 2, 3],
+            &[
+                0.5f32, -0.2, 0.1, 0.3, 0.8, -0.6, -0.4, 0.2, 0.9, 0.1, -0.7, 0.3, 0.2, 0.2, -0.1,
+                0.05, 0.6, -0.3,
+            ],
+        )?;
+        let kernel = model.add_const("kernel", kernel)?;
+        let bias = model.add_const("bias", tensor1(&[0.1f32, -0.2, 0.05]))?;
+        let pool_spec = PoolSpec {
+            data_format: DataFormat::NCHW,
+            kernel_shape: tvec!(3),
+            padding: PaddingSpec::Valid,
+            dilations: None,
+            strides: None,
+            input_channels: 2,
+            output_channels: 3,
+        };
+        let conv = Conv { pool_spec, kernel_fmt: KernelFormat::OIHW, group: 1, q_params: None };
+        let wire = model.wire_node("conv", conv, &[x, kernel, bias])?[0];
+        let w = model.add_const("w", tensor2(&[[0.2f32, -0.5, 0.3], [0.7, 0.1, -0.4]]))?;
+        let op = EinSum {
+            axes: "nct,dc->ndt".parse()?,
+            operating_dt: f32::datum_type(),
+            q_params: None,
+        };
+        let wire = model.wire_node("mm", op, &[wire, w])?;
+        model.set_output_outlets(&wire)?;
+
+        let input = |phase: f32| {
+            let values: Vec<f32> = (0..10).map(|i| (i as f32 * 0.7 + phase).sin()).collect();
+            tvec!(Tensor::from_shape(&[1, 2, 5], &values).unwrap().into_tvalue())
+        };
+        let calibration = (0..8).map(|i| input(i as f32)).collect();
+        let ptq = PostTrainingQuantization::new(i8::datum_type(), calibration);
+        let quantized = ptq.transform_into(&model)?;
+        assert!(quantized
+            .nodes()
+            .iter()
+            .any(|n| n.op_as::<Conv>().is_some_and(|c| c.q_params.is_some())));
+        assert!(quantized
+            .nodes()
+            .iter()
+            .any(|n| n.op_as::<EinSum>().is_some_and(|e| e.q_params.is_some())));
+        // the conv output is quantized only once
+        assert_eq!(quantized.nodes().iter().filter(|n| n.op_is::<Cast>()).count(), 2);
+
+        let reference = model.into_runnable()?.run(input(0.5))?.remove(0);
+        let found = quantized.clone().into_runnable()?.run(input(0.5))?.remove(0);
+        found.close_enough(&reference, Approximation::SuperApproximate)?;
+        let found = quantized.into_optimized()?.into_runnable()?.run(input(0.5))?.remove(0);
+        found.close_enough(&reference, Approximation::SuperApproximate)
+    }
+
+    #[test]
+    fn registered_without_calibration() -> TractResult<()> {
+        let transform = crate::transform::get_transform("quantize-i8").unwrap();
+        assert_eq!(transform.name(), "quantize-i8");
+        let mut model = TypedModel::default();
+        let x = model.add_source("x", f32::fact([2, 3]))?;
+        let w = model.add_const("w", tensor2(&[[0.2f32, -0.5], [0.7, 0.1], [0.3, -0.4]]))?;
+        let op =
+            EinSum { axes: "mk,kn->mn".parse()?, operating_dt: f32::datum_type(), q_params: None };
+        let wire = model.wire_node("mm", op, &[x, w])?;
+        model.set_output_outlets(&wire)?;
+        assert!(transform.transform(&mut model).is_err());
+        Ok(())
+    }
+}
@@ -42,3 +42,40 @@ impl State for TypedSimpleState<TypedModel, Arc<TypedRunnableModel<TypedModel>>>
         self.run(inputs)
     }
 }
+
+/// Runs independent branches of the optimized model concurrently, see `ParallelPlan`.
+#[derive(Debug)]
+pub struct ParallelRuntime {
+    pub threads: usize,
+}
+
+impl Default for ParallelRuntime {
+    fn default() -> Self {
+        ParallelRuntime {
+            threads: std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1),
+        }
+    }
+}
+
+impl Runtime for ParallelRuntime {
+    fn name(&self) -> Cow<str> {
+        Cow::Borrowed("parallel")
+    }
+
+    fn prepare(&self, model: TypedModel) -> TractResult<Box<dyn Runnable>> {
+        let plan = TypedParallelPlan::new(model.into_optimized()?.into_runnable()?, self.threads)?;
+        Ok(Box::new(Arc::new(plan)))
+    }
+}
+
+impl Runnable for Arc<TypedParallelPlan<TypedModel>> {
+    fn spawn(&self) -> TractResult<Box<dyn State>> {
+        Ok(Box::new(TypedParallelState::new(self.clone())?))
+    }
+}
+
+impl State for TypedParallelState<TypedModel, Arc<TypedParallelPlan<TypedModel>>> {
+    fn run(&mut self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        self.run(inputs)
+    }
+}
@@ -0,0 +1,383 @@
+//! Serializable form of frozen model states.
+//!
+//! A `FrozenSimpleState` can be saved as a `SavedSimpleState`, encoded to bytes, then decoded and
+//! loaded in another process running the same model. Only what outlives a turn is saved: op
+//! states (delay buffers, caches, scan hidden states...) and the session tensors used by memory
+//! ops. Turn inputs, intermediate values and resolved symbols are recomputed by the next run.
+use std::io::{Read, Write};
+
+use crate::internal::*;
+
+const MAGIC: &[u8; 8] = b"tractst\0";
+const VERSION: u32 = 1;
+
+/// Saved content of a `FrozenOpState`.
+///
+/// Each state kind defines its own layout, and is responsible for reading it back in
+/// `FrozenOpState::load`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SavedOpState {
+    pub counters: Vec<i64>,
+    pub tensors: Vec<Option<Tensor>>,
+    pub nested: Vec<SavedSimpleState>,
+}
+
+impl SavedOpState {
+    pub fn counter(&self, ix: usize) -> TractResult<i64> {
+        self.counters.get(ix).copied().with_context(|| format!("Missing counter #{ix} in {self:?}"))
+    }
+
+    pub fn tensor(&self, ix: usize) -> TractResult<Option<Tensor>> {
+        self.tensors.get(ix).cloned().with_context(|| format!("Missing tensor #{ix} in {self:?}"))
+    }
+
+    pub fn nested(&self, ix: usize) -> TractResult<&SavedSimpleState> {
+        self.nested.get(ix).with_context(|| format!("Missing nested state #{ix} in {self:?}"))
+    }
+}
+
+/// Saved content of a `FrozenSimpleState`, indexed by node id.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SavedSimpleState {
+    pub tensors: HashMap<String, Tensor>,
+    pub states: Vec<Option<SavedOpState>>,
+}
+
+impl SavedSimpleState {
+    pub fn to_bytes(&self) -> TractResult<Vec<u8>> {
+        let mut bytes = vec![];
+        self.write(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> TractResult<SavedSimpleState> {
+        Self::read(&mut &*bytes)
+    }
+
+    pub fn write(&self, w: &mut impl Write) -> TractResult<()> {
+        w.write_all(MAGIC)?;
+        write_u32(w, VERSION)?;
+        write_simple_state(w, self)
+    }
+
+    pub fn read(r: &mut impl Read) -> TractResult<SavedSimpleState> {
+        let mut magic = [0u8; 8];
+        r.read_exact(&mut magic)?;
+        ensure!(&magic == MAGIC, "Not a tract saved state");
+        let version = read_u32(r)?;
+        ensure!(version == VERSION, "Unsupported saved state version {version}");
+        read_simple_state(r)
+    }
+}
+
+fn write_u32(w: &mut impl Write, v: u32) -> TractResult<()> {
+    w.write_all(&v.to_le_bytes())?;
+    Ok(())
+}
+
+fn write_u64(w: &mut impl Write, v: u64) -> TractResult<()> {
+    w.write_all(&v.to_le_bytes())?;
+    Ok(())
+}
+
+fn write_str(w: &mut impl Write, s: &str) -> TractResult<()> {
+    write_u64(w, s.len() as u64)?;
+    w.write_all(s.as_bytes())?;
+    Ok(())
+}
+
+fn write_tensor(w: &mut impl Write, t: &Tensor) -> TractResult<()> {
+    ensure!(t.datum_type().is_copy(), "Can not save tensors of type {:?}", t.datum_type());
+    write_datum_type(w, t.datum_type())?;
+    write_u64(w, t.rank() as u64)?;
+    for d in t.shape() {
+        write_u64(w, *d as u64)?;
+    }
+    let bytes = unsafe { t.as_bytes() };
+    if cfg!(target_endian = "little") {
+        w.write_all(bytes)?;
+    } else {
+        let mut bytes = bytes.to_vec();
+        swap_endianness(t.datum_type(), &mut bytes);
+        w.write_all(&bytes)?;
+    }
+    Ok(())
+}
+
+/// Write a datum type as its unquantized name followed by its quantization parameters, if any:
+/// a tag, then the fields of each `QParams` variant.
+fn write_datum_type(w: &mut impl Write, dt: DatumType) -> TractResult<()> {
+    write_str(w, &format!("{:?}", dt.unquantized()))?;
+    match dt.qparams() {
+        None => w.write_all(&[0])?,
+        Some(QParams::MinMax { min, max }) => {
+            w.write_all(&[1])?;
+            w.write_all(&min.to_le_bytes())?;
+            w.write_all(&max.to_le_bytes())?;
+        }
+        Some(QParams::ZpScale { zero_point, scale }) => {
+            w.write_all(&[2])?;
+            w.write_all(&zero_point.to_le_bytes())?;
+            w.write_all(&scale.to_le_bytes())?;
+        }
+        Some(QParams::PerAxis(params)) => {
+            w.write_all(&[3])?;
+            write_u64(w, params.axis as u64)?;
+            write_u64(w, params.len() as u64)?;
+            for (zp, scale) in params.zero_points.iter().zip(params.scales.iter()) {
+                w.write_all(&zp.to_le_bytes())?;
+                w.write_all(&scale.to_le_bytes())?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Reverse the bytes of each scalar in a tensor payload, turning native byte order to
+/// little-endian (and back) on big-endian targets.
+fn swap_endianness(dt: DatumType, bytes: &mut [u8]) {
+    #[cfg(feature = "complex")]
+    let scalar_size = if dt.is_complex() { dt.size_of() / 2 } else { dt.size_of() };
+    #[cfg(not(feature = "complex"))]
+    let scalar_size = dt.size_of();
+    bytes.chunks_mut(scalar_size).for_each(|scalar| scalar.reverse());
+}
+
+fn write_simple_state(w: &mut impl Write, state: &SavedSimpleState) -> TractResult<()> {
+    let mut tensors: Vec<_> = state.tensors.iter().collect();
+    tensors.sort_by_key(|(name, _)| *name);
+    write_u64(w, tensors.len() as u64)?;
+    for (name, tensor) in tensors {
+        write_str(w, name)?;
+        write_tensor(w, tensor)?;
+    }
+    write_u64(w, state.states.len() as u64)?;
+    for op_state in &state.states {
+        if let Some(op_state) = op_state {
+            w.write_all(&[1])?;
+            write_op_state(w, op_state)?;
+        } else {
+            w.write_all(&[0])?;
+        }
+    }
+    Ok(())
+}
+
+fn write_op_state(w: &mut impl Write, state: &SavedOpState) -> TractResult<()> {
+    write_u64(w, state.counters.len() as u64)?;
+    for c in &state.counters {
+        w.write_all(&c.to_le_bytes())?;
+    }
+    write_u64(w, state.tensors.len() as u64)?;
+    for t in &state.tensors {
+        if let Some(t) = t {
+            w.write_all(&[1])?;
+            write_tensor(w, t)?;
+        } else {
+            w.write_all(&[0])?;
+        }
+    }
+    write_u64(w, state.nested.len() as u64)?;
+    for nested in &state.nested {
+        write_simple_state(w, nested)?;
+    }
+    Ok(())
+}
+
+fn read_u8(r: &mut impl Read) -> TractResult<u8> {
+    let mut buf = [0u8; 1];
+    r.read_exact(&mut buf)?;
+    Ok(buf[0])
+}
+
+fn read_u32(r: &mut impl Read) -> TractResult<u32> {
+    let mut buf = [0u8; 4];
+    r.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_u64(r: &mut impl Read) -> TractResult<u64> {
+    let mut buf = [0u8; 8];
+    r.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+fn read_flag(r: &mut impl Read) -> TractResult<bool> {
+    match read_u8(r)? {
+        0 => Ok(false),
+        1 => Ok(true),
+        other => bail!("Invalid flag {other} in saved state"),
+    }
+}
+
+fn read_bytes(r: &mut impl Read, len: u64) -> TractResult<Vec<u8>> {
+    let mut bytes = vec![];
+    r.take(len).read_to_end(&mut bytes)?;
+    ensure!(bytes.len() as u64 == len, "Truncated saved state");
+    Ok(bytes)
+}
+
+fn read_str(r: &mut impl Read) -> TractResult<String> {
+    let len = read_u64(r)?;
+    Ok(String::from_utf8(read_bytes(r, len)?)?)
+}
+
+fn read_i32(r: &mut impl Read) -> TractResult<i32> {
+    let mut buf = [0u8; 4];
+    r.read_exact(&mut buf)?;
+    Ok(i32::from_le_bytes(buf))
+}
+
+fn read_f32(r: &mut impl Read) -> TractResult<f32> {
+    let mut buf = [0u8; 4];
+    r.read_exact(&mut buf)?;
+    Ok(f32::from_le_bytes(buf))
+}
+
+fn read_datum_type(r: &mut impl Read) -> TractResult<DatumType> {
+    let dt: DatumType = read_str(r)?.parse()?;
+    ensure!(!dt.is_quantized(), "Invalid datum type {dt:?} in saved state");
+    let qparams = match read_u8(r)? {
+        0 => return Ok(dt),
+        1 => QParams::MinMax { min: read_f32(r)?, max: read_f32(r)? },
+        2 => QParams::ZpScale { zero_point: read_i32(r)?, scale: read_f32(r)? },
+        3 => {
+            let axis = read_u64(r)? as usize;
+            let len = read_u64(r)?;
+            let (mut zero_points, mut scales) = (vec![], vec![]);
+            for _ in 0..len {
+                zero_points.push(read_i32(r)?);
+                scales.push(read_f32(r)?);
+            }
+            QParams::per_axis(axis, &zero_points, &scales)?
+        }
+        other => bail!("Invalid quantization tag {other} in saved state"),
+    };
+    ensure!(
+        matches!(dt, DatumType::I8 | DatumType::U8 | DatumType::I32),
+        "Can not quantize {dt:?}"
+    );
+    Ok(dt.quantize(qparams))
+}
+
+fn read_tensor(r: &mut impl Read) -> TractResult<Tensor> {
+    let dt = read_datum_type(r)?;
+    ensure!(dt.is_copy(), "Can not load tensors of type {dt:?}");
+    let rank = read_u64(r)?;
+    let shape = (0..rank).map(|_| Ok(read_u64(r)? as usize)).collect::<TractResult<TVec<_>>>()?;
+    let len = shape
+        .iter()
+        .try_fold(dt.size_of(), |acc, d| acc.checked_mul(*d))
+        .context("Overflow in saved tensor size")?;
+    let mut bytes = read_bytes(r, len as u64)?;
+    if cfg!(target_endian = "big") {
+        swap_endianness(dt, &mut bytes);
+    }
+    unsafe { Tensor::from_raw_dt(dt, &shape, &bytes) }
+}
+
+fn read_simple_state(r: &mut impl Read) -> TractResult<SavedSimpleState> {
+    let mut state = SavedSimpleState::default();
+    for _ in 0..read_u64(r)? {
+        let name = read_str(r)?;
+        let tensor = read_tensor(r)?;
+        state.tensors.insert(name, tensor);
+    }
+    for _ in 0..read_u64(r)? {
+        let op_state = if read_flag(r)? { Some(read_op_state(r)?) } else { None };
+        state.states.push(op_state);
+    }
+    Ok(state)
+}
+
+fn read_op_state(r: &mut impl Read) -> TractResult<SavedOpState> {
+    let mut state = SavedOpState::default();
+    for _ in 0..read_u64(r)? {
+        let mut buf = [0u8; 8];
+        r.read_exact(&mut buf)?;
+        state.counters.push(i64::from_le_bytes(buf));
+    }
+    for _ in 0..read_u64(r)? {
+        let tensor = if read_flag(r)? { Some(read_tensor(r)?) } else { None };
+        state.tensors.push(tensor);
+    }
+    for _ in 0..read_u64(r)? {
+        state.nested.push(read_simple_state(r)?);
+    }
+    Ok(state)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn roundtrip() -> TractResult<()> {
+        let nested = SavedSimpleState {
+            tensors: HashMap::new(),
+            states: vec![None, Some(SavedOpState { counters: vec![-3], ..Default::default() })],
+        };
+        let state = SavedSimpleState {
+            tensors: [("mem".to_string(), tensor1(&[1u8, 2, 3]))].into_iter().collect(),
+            states: vec![
+                None,
+                Some(SavedOpState {
+                    counters: vec![12, 7],
+                    tensors: vec![None, Some(tensor2(&[[1f32, 2.], [3., 4.]]))],
+                    nested: vec![nested],
+                }),
+            ],
+        };
+        let bytes = state.to_bytes()?;
+        assert_eq!(SavedSimpleState::from_bytes(&bytes)?, state);
+        assert!(SavedSimpleState::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(SavedSimpleState::from_bytes(&bytes[1..]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn little_endian_payload() -> TractResult<()> {
+        let mut bytes = vec![];
+        write_tensor(&mut bytes, &tensor1(&[1f32, -2.]))?;
+        let payload: Vec<u8> = [1f32, -2.].iter().flat_map(|x| x.to_le_bytes()).collect();
+        assert!(bytes.ends_with(&payload));
+        let mut swapped = payload.clone();
+        swap_endianness(f32::datum_type(), &mut swapped);
+        assert_eq!(swapped, [1f32, -2.].iter().flat_map(|x| x.to_be_bytes()).collect::<Vec<_>>());
+        Ok(())
+    }
+
+    #[test]
+    fn quantized_datum_type() -> TractResult<()> {
+        let dt = i8::datum_type().with_zp_scale(3, 0.25);
+        let mut t = tensor1(&[1i8, -2]);
+        unsafe { t.set_datum_type(dt) };
+        let state = SavedSimpleState {
+            tensors: HashMap::new(),
+            states: vec![Some(SavedOpState { tensors: vec![Some(t)], ..Default::default() })],
+        };
+        let loaded = SavedSimpleState::from_bytes(&state.to_bytes()?)?;
+        assert_eq!(
+            loaded.states[0].as_ref().unwrap().tensors[0].as_ref().unwrap().datum_type(),
+            dt
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn min_max_and_per_axis_datum_types() -> TractResult<()> {
+        let min_max = u8::datum_type().quantize(QParams::MinMax { min: -1.3, max: 2.7 });
+        let per_axis = i8::datum_type().quantize(QParams::per_axis(1, &[0, -3], &[0.5, 0.125])?);
+        let mut tensors = HashMap::new();
+        for (name, dt) in [("min_max", min_max), ("per_axis", per_axis)] {
+            let mut t = tensor2(&[[1i8, -2]]);
+            unsafe { t.set_datum_type(dt) };
+            tensors.insert(name.to_string(), t);
+        }
+        let state = SavedSimpleState { tensors, states: vec![] };
+        let loaded = SavedSimpleState::from_bytes(&state.to_bytes()?)?;
+        assert_eq!(loaded.tensors["min_max"].datum_type(), min_max);
+        assert_eq!(loaded.tensors["per_axis"].datum_type(), per_axis);
+        Ok(())
+    }
+}
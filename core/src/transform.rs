@@ -5,9 +5,12 @@ use std::borrow::Cow;
 use std::fmt::Debug;
 
 use tract_data::TractResult;
+use tract_linalg::frame::BlockQuant;
 
 use crate::floats::FloatPrecisionTranslator;
+use crate::ops::matmul::block_quant::BlockQuantTransform;
 use crate::ops::nn::{Softmax, SoftmaxExp, TypedModel};
+use crate::quantization::PostTrainingQuantization;
 
 pub fn get_transform(name: &str) -> Option<Box<dyn ModelTransform>> {
     match name {
@@ -16,6 +19,10 @@ pub fn get_transform(name: &str) -> Option<Box<dyn ModelTransform>> {
         "f32-to-f16" => Some(Box::<FloatPrecisionTranslator<f32, f16>>::default()),
         "f16-to-f32" => Some(Box::<FloatPrecisionTranslator<f16, f32>>::default()),
         "softmax-fast-compact" => Some(Box::new(SoftmaxFastCompact)),
+        "block-quant-q8_0" => Some(Box::new(BlockQuantTransform::new(BlockQuant::Q8_0))),
+        "block-quant-q4_0" => Some(Box::new(BlockQuantTransform::new(BlockQuant::Q4_0))),
+        "quantize-i8" => Some(Box::new(PostTrainingQuantization::new(i8::datum_type(), vec![]))),
+        "quantize-u8" => Some(Box::new(PostTrainingQuantization::new(u8::datum_type(), vec![]))),
         _ => None,
     }
 }
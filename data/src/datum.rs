@@ -3,6 +3,7 @@ use crate::dim::TDim;
 use crate::tensor::litteral::*;
 use crate::tensor::Tensor;
 use crate::TVec;
+use anyhow::Context;
 use half::f16;
 #[cfg(feature = "complex")]
 use num_complex::Complex;
@@ -35,10 +36,66 @@ impl std::str::FromStr for Blob {
     }
 }
 
+/// Upper bound on the number of channels held by interned per-axis quantization parameters.
+pub const MAX_INTERNED_PER_AXIS_CHANNELS: usize = 1 << 22;
+
 #[derive(Copy, Clone, PartialEq)]
 pub enum QParams {
     MinMax { min: f32, max: f32 },
     ZpScale { zero_point: i32, scale: f32 },
+    PerAxis(&'static PerAxisQParams),
+}
+
+/// Per-axis (per-channel) linear quantization: one zero point and one scale for each
+/// slice of the tensor along `axis`.
+///
+/// Instances are interned (see `QParams::per_axis`) so that `QParams` and `DatumType` stay
+/// `Copy`.
+#[derive(Clone, Debug)]
+pub struct PerAxisQParams {
+    pub axis: usize,
+    pub zero_points: Vec<i32>,
+    pub scales: Vec<f32>,
+}
+
+impl PerAxisQParams {
+    pub fn len(&self) -> usize {
+        self.scales.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scales.is_empty()
+    }
+
+    pub fn zp_scale(&self, channel: usize) -> (i32, f32) {
+        (self.zero_points[channel], self.scales[channel])
+    }
+
+    /// Map each channel's zero point and scale, re-interning the result.
+    pub fn map(&self, f: impl Fn(i32, f32) -> (i32, f32)) -> anyhow::Result<QParams> {
+        let (zero_points, scales): (Vec<i32>, Vec<f32>) =
+            self.zero_points.iter().zip(self.scales.iter()).map(|(zp, s)| f(*zp, *s)).unzip();
+        QParams::per_axis(self.axis, &zero_points, &scales)
+    }
+}
+
+impl PartialEq for PerAxisQParams {
+    fn eq(&self, other: &Self) -> bool {
+        self.axis == other.axis
+            && self.zero_points == other.zero_points
+            && self.scales.len() == other.scales.len()
+            && self.scales.iter().zip(other.scales.iter()).all(|(a, b)| a.to_bits() == b.to_bits())
+    }
+}
+
+impl Eq for PerAxisQParams {}
+
+impl Hash for PerAxisQParams {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.axis.hash(state);
+        self.zero_points.hash(state);
+        self.scales.iter().for_each(|s| s.to_bits().hash(state));
+    }
 }
 
 impl Eq for QParams {}
@@ -49,6 +106,18 @@ impl Ord for QParams {
         match (self, other) {
             (MinMax { .. }, ZpScale { .. }) => std::cmp::Ordering::Less,
             (ZpScale { .. }, MinMax { .. }) => std::cmp::Ordering::Greater,
+            (PerAxis(_), MinMax { .. } | ZpScale { .. }) => std::cmp::Ordering::Greater,
+            (MinMax { .. } | ZpScale { .. }, PerAxis(_)) => std::cmp::Ordering::Less,
+            (PerAxis(a), PerAxis(b)) => a.axis.cmp(&b.axis).then_with(|| {
+                a.zero_points.cmp(&b.zero_points).then_with(|| {
+                    a.scales
+                        .iter()
+                        .zip(b.scales.iter())
+                        .map(|(a, b)| a.total_cmp(b))
+                        .find(|o| o.is_ne())
+                        .unwrap_or_else(|| a.scales.len().cmp(&b.scales.len()))
+                })
+            }),
             (MinMax { min: min1, max: max1 }, MinMax { min: min2, max: max2 }) => {
                 min1.total_cmp(min2).then_with(|| max1.total_cmp(max2))
             }
@@ -86,11 +155,71 @@ impl Hash for QParams {
                 zero_point.hash(state);
                 scale.to_bits().hash(state);
             }
+            QParams::PerAxis(params) => {
+                2.hash(state);
+                params.hash(state);
+            }
         }
     }
 }
 
 impl QParams {
+    /// Build per-axis quantization parameters. Collapses to `ZpScale` when all the channels
+    /// share the same zero point and scale.
+    ///
+    /// Distinct per-axis parameters are interned for the process lifetime, in a table holding
+    /// at most `MAX_INTERNED_PER_AXIS_CHANNELS` channels overall. Fails if the lengths of
+    /// `zero_points` and `scales` differ or are zero, or if the table is full.
+    pub fn per_axis(axis: usize, zero_points: &[i32], scales: &[f32]) -> anyhow::Result<QParams> {
+        anyhow::ensure!(
+            zero_points.len() == scales.len() && !scales.is_empty(),
+            "Per-axis quantization expects as many zero points as scales, and at least one ({} and {})",
+            zero_points.len(),
+            scales.len()
+        );
+        if zero_points.iter().all(|zp| *zp == zero_points[0])
+            && scales.iter().all(|s| s.to_bits() == scales[0].to_bits())
+        {
+            return Ok(QParams::ZpScale { zero_point: zero_points[0], scale: scales[0] });
+        }
+        #[derive(Default)]
+        struct Interned {
+            params: std::collections::HashSet<&'static PerAxisQParams>,
+            channels: usize,
+        }
+        lazy_static::lazy_static! {
+            static ref INTERNED: std::sync::Mutex<Interned> = Default::default();
+        }
+        let params =
+            PerAxisQParams { axis, zero_points: zero_points.to_vec(), scales: scales.to_vec() };
+        let mut interned = INTERNED.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(it) = interned.params.get(&params) {
+            return Ok(QParams::PerAxis(it));
+        }
+        anyhow::ensure!(
+            interned.channels + params.len() <= MAX_INTERNED_PER_AXIS_CHANNELS,
+            "Too many distinct per-axis quantization parameters (more than {MAX_INTERNED_PER_AXIS_CHANNELS} channels overall)"
+        );
+        interned.channels += params.len();
+        let it: &'static PerAxisQParams = Box::leak(Box::new(params));
+        interned.params.insert(it);
+        Ok(QParams::PerAxis(it))
+    }
+
+    pub fn per_axis_params(&self) -> Option<&'static PerAxisQParams> {
+        if let QParams::PerAxis(params) = self {
+            Some(params)
+        } else {
+            None
+        }
+    }
+
+    pub fn is_per_axis(&self) -> bool {
+        self.per_axis_params().is_some()
+    }
+
+    /// Zero point and scale. For per-axis parameters, this is the first channel's: use
+    /// `try_zp_scale` where per-axis parameters must not be mistaken for a single pair.
     pub fn zp_scale(&self) -> (i32, f32) {
         match self {
             QParams::MinMax { min, max } => {
@@ -98,9 +227,18 @@ impl QParams {
                 ((-(min + max) / 2. / scale) as i32, scale)
             }
             QParams::ZpScale { zero_point, scale } => (*zero_point, *scale),
+            QParams::PerAxis(params) => params.zp_scale(0),
         }
     }
 
+    /// Zero point and scale, failing on per-axis parameters.
+    pub fn try_zp_scale(&self) -> anyhow::Result<(i32, f32)> {
+        if self.is_per_axis() {
+            anyhow::bail!("No single zero point and scale for per-axis quantization {self:?}")
+        }
+        Ok(self.zp_scale())
+    }
+
     pub fn q(&self, f: f32) -> i32 {
         let (zp, scale) = self.zp_scale();
         (f / scale) as i32 + zp
@@ -114,6 +252,9 @@ impl QParams {
 
 impl std::fmt::Debug for QParams {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let QParams::PerAxis(params) = self {
+            return write!(f, "A:{} Z:{:?} S:{:?}", params.axis, params.zero_points, params.scales);
+        }
         let (zp, scale) = self.zp_scale();
         write!(f, "Z:{zp} S:{scale}")
     }
@@ -286,6 +427,10 @@ impl DatumType {
         self.qparams().is_some()
     }
 
+    pub fn is_per_axis_quantized(&self) -> bool {
+        self.qparams().is_some_and(|q| q.is_per_axis())
+    }
+
     pub fn qparams(&self) -> Option<QParams> {
         match self {
             DatumType::QI8(qparams) | DatumType::QU8(qparams) | DatumType::QI32(qparams) => {
@@ -298,7 +443,7 @@ impl DatumType {
     pub fn with_qparams(&self, qparams: QParams) -> DatumType {
         match self {
             DatumType::QI8(_) => DatumType::QI8(qparams),
-            DatumType::QU8(_) => DatumType::QI8(qparams),
+            DatumType::QU8(_) => DatumType::QU8(qparams),
             DatumType::QI32(_) => DatumType::QI32(qparams),
             _ => *self,
         }
@@ -321,6 +466,11 @@ impl DatumType {
         self.qparams().map(|q| q.zp_scale()).unwrap_or((0, 1.))
     }
 
+    /// Zero point and scale, (0, 1) for non quantized types. Fails on per-axis quantization.
+    pub fn try_zp_scale(&self) -> anyhow::Result<(i32, f32)> {
+        self.qparams().map(|q| q.try_zp_scale()).unwrap_or(Ok((0, 1.)))
+    }
+
     #[inline(always)]
     pub fn with_zp_scale(&self, zero_point: i32, scale: f32) -> DatumType {
         self.quantize(QParams::ZpScale { zero_point, scale })
@@ -415,6 +565,15 @@ impl std::str::FromStr for DatumType {
             Ok(DatumType::QI8(QParams::ZpScale { zero_point: z, scale: s }))
         } else if let Ok((z, s)) = scan_fmt!(s, "QI32(Z:{d} S:{f})", i32, f32) {
             Ok(DatumType::QI32(QParams::ZpScale { zero_point: z, scale: s }))
+        } else if let Some((dt, params)) = s.strip_suffix(')').and_then(|s| s.split_once('(')) {
+            let qp = parse_per_axis_qparams(params)
+                .with_context(|| format!("Parsing datum type {s:?}"))?;
+            match dt {
+                "QU8" => Ok(DatumType::QU8(qp)),
+                "QI8" => Ok(DatumType::QI8(qp)),
+                "QI32" => Ok(DatumType::QI32(qp)),
+                _ => anyhow::bail!("Unknown type {}", s),
+            }
         } else {
             match s {
                 "I8" | "i8" => Ok(DatumType::I8),
@@ -450,6 +609,29 @@ impl std::str::FromStr for DatumType {
     }
 }
 
+fn parse_per_axis_qparams(s: &str) -> anyhow::Result<QParams> {
+    fn list<T: std::str::FromStr>(s: &str) -> anyhow::Result<Vec<T>>
+    where
+        T::Err: std::error::Error + Send + Sync + 'static,
+    {
+        let s = s
+            .trim()
+            .strip_prefix('[')
+            .and_then(|s| s.strip_suffix(']'))
+            .with_context(|| format!("Expected a list, got {s:?}"))?;
+        Ok(s.split(',').map(|s| s.trim().parse()).collect::<Result<_, _>>()?)
+    }
+    let (axis, rest) = s
+        .strip_prefix("A:")
+        .and_then(|s| s.split_once(" Z:"))
+        .with_context(|| format!("Expected per-axis quantization parameters, got {s:?}"))?;
+    let (zps, scales) =
+        rest.split_once(" S:").with_context(|| format!("Expected scales in {s:?}"))?;
+    let zero_points: Vec<i32> = list(zps)?;
+    let scales: Vec<f32> = list(scales)?;
+    QParams::per_axis(axis.parse()?, &zero_points, &scales)
+}
+
 const TOINT: f32 = 1.0f32 / std::f32::EPSILON;
 
 pub fn round_ties_to_even(x: f32) -> f32 {
@@ -592,4 +774,32 @@ mod tests {
             DatumType::QU8(QParams::ZpScale { zero_point: 128, scale: 0.01 })
         );
     }
+
+    #[test]
+    fn test_cast_per_axis() {
+        let dt = DatumType::QI8(QParams::per_axis(1, &[0, 2], &[0.5, 0.25]).unwrap());
+        let t = tensor2(&[[1f32, 1.], [2., -1.]]);
+        let q = t.cast_to_dt(dt).unwrap().into_owned();
+        assert_eq!(q.datum_type(), dt);
+        assert_eq!(*q.cast_to_dt(DatumType::I8).unwrap(), tensor2(&[[2i8, 6], [4, -2]]));
+        assert_eq!(*q.cast_to::<f32>().unwrap(), t);
+    }
+
+    #[test]
+    fn test_per_axis_qparams() {
+        let qp = QParams::per_axis(1, &[0, 2], &[0.5, 0.25]).unwrap();
+        assert!(qp.is_per_axis());
+        assert!(qp.try_zp_scale().is_err());
+        assert_eq!(qp, QParams::per_axis(1, &[0, 2], &[0.5, 0.25]).unwrap());
+        assert_eq!(
+            QParams::per_axis(0, &[3, 3], &[0.5, 0.5]).unwrap(),
+            QParams::ZpScale { zero_point: 3, scale: 0.5 }
+        );
+        assert!(QParams::per_axis(0, &[], &[]).is_err());
+        assert!(QParams::per_axis(0, &[0], &[0.5, 0.25]).is_err());
+        assert!(QParams::per_axis(0, &[0; 2], &[0.5; 2]).unwrap().try_zp_scale().is_ok());
+        let dt = DatumType::QI8(qp);
+        assert_eq!(format!("{dt:?}"), "QI8(A:1 Z:[0, 2] S:[0.5, 0.25])");
+        assert_eq!(format!("{dt:?}").parse::<DatumType>().unwrap(), dt);
+    }
 }
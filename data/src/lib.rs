@@ -16,7 +16,7 @@ pub type TractError = anyhow::Error;
 pub type TractResult<T> = anyhow::Result<T>;
 
 pub mod prelude {
-    pub use crate::datum::{round_ties_to_even, Blob, Datum, DatumType, QParams};
+    pub use crate::datum::{round_ties_to_even, Blob, Datum, DatumType, PerAxisQParams, QParams};
     pub use crate::dim::{Symbol, SymbolTable, SymbolValues, TDim, ToDim};
     pub use crate::tensor::litteral::*;
     pub use crate::tensor::{ natural_strides, IntoArcTensor, IntoTensor, Tensor };
@@ -51,6 +51,7 @@ pub mod internal {
 pub use anyhow;
 pub use dim::UndeterminedSymbol;
 pub use half;
+pub use tensor::arena;
 
 mod datum;
 mod dim;
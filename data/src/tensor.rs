@@ -1,5 +1,7 @@
 //! `Tensor`, tract main data object of interest.
-use crate::datum::{round_ties_to_even, scale_by, Blob, ClampCast, Datum, DatumType, QParams};
+use crate::datum::{
+    round_ties_to_even, scale_by, Blob, ClampCast, Datum, DatumType, PerAxisQParams, QParams,
+};
 use crate::dim::TDim;
 use crate::TVec;
 use anyhow::{ensure, Context};
@@ -17,6 +19,7 @@ use std::mem::{align_of, size_of};
 use std::ops::Range;
 use std::sync::Arc;
 
+pub mod arena;
 pub mod litteral;
 pub mod view;
 
@@ -62,6 +65,7 @@ pub struct Tensor {
     len: usize,
     layout: alloc::Layout,
     data: *mut u8,
+    preallocated: bool,
 }
 
 unsafe impl Send for Tensor {}
@@ -148,7 +152,7 @@ impl Drop for Tensor {
                     .for_each(|s| std::ptr::drop_in_place(s as *mut TDim));
             }
         }
-        if !self.data.is_null() && self.layout.size() > 0 {
+        if !self.data.is_null() && self.layout.size() > 0 && !self.preallocated {
             unsafe { alloc::dealloc(self.data, self.layout) }
         }
     }
@@ -197,14 +201,26 @@ impl Tensor {
     ) -> anyhow::Result<Tensor> {
         let bytes = shape.iter().cloned().product::<usize>() * dt.size_of();
         let layout = alloc::Layout::from_size_align(bytes, alignment)?;
+        let mut preallocated = false;
         let data = if bytes == 0 {
             std::ptr::null()
+        } else if let Some(ptr) = arena::claim(dt, shape, alignment) {
+            preallocated = true;
+            ptr
         } else {
             let ptr = alloc::alloc(layout);
             assert!(!ptr.is_null());
             ptr
         } as *mut u8;
-        let mut tensor = Tensor { strides: tvec!(), layout, dt, shape: shape.into(), data, len: 0 };
+        let mut tensor = Tensor {
+            strides: tvec!(),
+            layout,
+            dt,
+            shape: shape.into(),
+            data,
+            len: 0,
+            preallocated,
+        };
         tensor.update_strides_and_len();
         if !data.is_null() {
             if dt == String::datum_type() || dt == Blob::datum_type() {
@@ -302,7 +318,9 @@ impl Tensor {
         shape: &[usize],
         alignment: usize,
     ) -> anyhow::Result<Tensor> {
-        if dt.is_quantized() {
+        if dt.is_per_axis_quantized() {
+            Ok(Tensor::zero::<f32>(shape)?.cast_to_dt(dt)?.into_owned())
+        } else if dt.is_quantized() {
             unsafe {
                 let mut t = Tensor::uninitialized_dt(dt, shape)?;
                 let zp = dt.zp_scale().0;
@@ -759,7 +777,15 @@ impl Tensor {
     /// `force_full` will force the tensor to be dump in full even if it is big.
     pub fn dump(&self, force_full: bool) -> anyhow::Result<String> {
         unsafe fn dump_t<D: Datum>(tensor: &Tensor, n: usize) -> String {
-            if let Some(qp) = tensor.datum_type().qparams() {
+            if tensor.datum_type().is_per_axis_quantized() {
+                let integers = tensor.cast_to::<i32>().unwrap();
+                let floats = tensor.cast_to::<f32>().unwrap();
+                integers.as_slice_unchecked::<i32>()[0..n]
+                    .iter()
+                    .zip(floats.as_slice_unchecked::<f32>())
+                    .map(|(x, f)| format!("[{x}]({f})"))
+                    .join(", ")
+            } else if let Some(qp) = tensor.datum_type().qparams() {
                 let integers = tensor.cast_to::<i32>().unwrap();
                 integers.as_slice_unchecked::<i32>()[0..n]
                     .iter()
@@ -1051,6 +1077,45 @@ impl Tensor {
         Ok(())
     }
 
+    /// Casts involving a per-axis quantized type go through f32, channel by channel.
+    fn cast_per_axis_q(&self, dst_dt: DatumType) -> anyhow::Result<Tensor> {
+        fn per_axis(
+            t: &mut Tensor,
+            params: &PerAxisQParams,
+            f: impl Fn(f32, i32, f32) -> f32,
+        ) -> anyhow::Result<()> {
+            ensure!(
+                params.axis < t.rank() && t.shape()[params.axis] == params.len(),
+                "Per-axis quantization {:?} incompatible with shape {:?}",
+                params,
+                t.shape()
+            );
+            let mut view = t.to_array_view_mut::<f32>()?;
+            for (c, mut slice) in view.axis_iter_mut(Axis(params.axis)).enumerate() {
+                let (zp, scale) = params.zp_scale(c);
+                slice.mapv_inplace(|x| f(x, zp, scale));
+            }
+            Ok(())
+        }
+        let mut values = if let Some(params) = self.dt.qparams().and_then(|q| q.per_axis_params()) {
+            let mut values = self.cast_to_dt(self.dt.unquantized())?.cast_to::<f32>()?.into_owned();
+            per_axis(&mut values, params, |x, zp, scale| (x - zp as f32) * scale)?;
+            values
+        } else {
+            self.cast_to::<f32>()?.into_owned()
+        };
+        if let Some(params) = dst_dt.qparams().and_then(|q| q.per_axis_params()) {
+            per_axis(&mut values, params, |x, zp, scale| {
+                round_ties_to_even(x / scale) + zp as f32
+            })?;
+            let mut result = values.cast_to_dt(dst_dt.unquantized())?.into_owned();
+            unsafe { result.set_datum_type(dst_dt) };
+            Ok(result)
+        } else {
+            Ok(values.cast_to_dt(dst_dt)?.into_owned())
+        }
+    }
+
     unsafe fn cast_to_string<Source: Datum>(&self, other: &mut Tensor) {
         for (s, d) in self
             .as_slice_unchecked::<Source>()
@@ -1146,6 +1211,12 @@ impl Tensor {
                 n!(f32);
                 n!(f64);
             } else {
+                if (self.dt.is_per_axis_quantized() && (dst_dt.is_float() || dst_dt.is_quantized()))
+                    || (dst_dt.is_per_axis_quantized()
+                        && (self.dt.is_float() || self.dt.is_quantized()))
+                {
+                    return Ok(Cow::Owned(self.cast_per_axis_q(dst_dt)?));
+                }
                 let (s_zp, s_scale) = self.datum_type().zp_scale();
                 let (d_zp, d_scale) = dst_dt.zp_scale();
                 if self.datum_type().is_quantized() && dst_dt.is_float() {
@@ -1328,7 +1399,15 @@ impl Tensor {
             let vec = it.into_raw_vec().into_boxed_slice();
             let data = Box::into_raw(vec) as *mut u8;
             let mut t =
-                Tensor { dt: T::datum_type(), shape, layout, data, strides: tvec!(), len: 0 };
+                Tensor {
+                    dt: T::datum_type(),
+                    shape,
+                    layout,
+                    data,
+                    strides: tvec!(),
+                    len: 0,
+                    preallocated: false,
+                };
             t.update_strides_and_len();
             return t;
         }
@@ -1372,6 +1451,7 @@ impl Tensor {
                 data: data as *mut u8,
                 shape: self.shape.clone(),
                 strides: self.strides.clone(),
+                preallocated: false,
                 ..*self
             }
         } else if self.dt == DatumType::TDim {
@@ -1382,6 +1462,7 @@ impl Tensor {
                 data: data as *mut u8,
                 shape: self.shape.clone(),
                 strides: self.strides.clone(),
+                preallocated: false,
                 ..*self
             }
         } else {
@@ -0,0 +1,86 @@
+//! Serving tensor allocations from caller-provided memory.
+//!
+//! An execution plan that knows in advance the type and shape of the tensor an operator is
+//! about to produce can offer a slice of a pre-allocated arena for it. While `with_preallocated`
+//! runs, the first tensor allocation on the current thread matching one of the offered buffers
+//! exactly (datum type and shape) will use it instead of calling the allocator. Such tensors
+//! do not free their memory on drop: the caller must make sure they do not outlive the arena,
+//! and that no two live tensors share the same region.
+
+use crate::datum::DatumType;
+use crate::TVec;
+use std::cell::RefCell;
+
+#[derive(Clone, Debug)]
+pub struct Preallocated {
+    pub datum_type: DatumType,
+    pub shape: TVec<usize>,
+    pub ptr: *mut u8,
+}
+
+thread_local! {
+    static OFFERS: RefCell<TVec<Preallocated>> = RefCell::new(TVec::new());
+}
+
+/// Run `f`, offering `buffers` to the tensor allocations it performs on the current thread.
+///
+/// # Safety
+///
+/// Every buffer must be valid for writes of its datum type and shape, and stay valid and
+/// otherwise unused as long as the tensor that claims it is alive.
+pub unsafe fn with_preallocated<R>(buffers: &[Preallocated], f: impl FnOnce() -> R) -> R {
+    let previous =
+        OFFERS.with(|offers| std::mem::replace(&mut *offers.borrow_mut(), buffers.into()));
+    struct Restore(Option<TVec<Preallocated>>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let previous = self.0.take().unwrap();
+            OFFERS.with(|offers| *offers.borrow_mut() = previous);
+        }
+    }
+    let _restore = Restore(Some(previous));
+    f()
+}
+
+pub(crate) fn claim(datum_type: DatumType, shape: &[usize], alignment: usize) -> Option<*mut u8> {
+    if !datum_type.is_copy() {
+        return None;
+    }
+    OFFERS.with(|offers| {
+        let mut offers = offers.borrow_mut();
+        let ix = offers.iter().position(|offer| {
+            offer.datum_type == datum_type
+                && &*offer.shape == shape
+                && offer.ptr as usize % alignment == 0
+        })?;
+        Some(offers.remove(ix).ptr)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::prelude::*;
+
+    #[test]
+    fn claim_matching_allocation_once() {
+        let mut buffer = vec![0f32; 8];
+        let offer = Preallocated {
+            datum_type: f32::datum_type(),
+            shape: tvec!(2, 4),
+            ptr: buffer.as_mut_ptr() as *mut u8,
+        };
+        unsafe {
+            with_preallocated(&[offer], || {
+                let other = Tensor::zero::<f32>(&[4, 2]).unwrap();
+                assert!(other.as_ptr::<f32>().unwrap() != buffer.as_ptr());
+                let claimed = Tensor::zero::<f32>(&[2, 4]).unwrap();
+                assert_eq!(claimed.as_ptr::<f32>().unwrap(), buffer.as_ptr());
+                let again = Tensor::zero::<f32>(&[2, 4]).unwrap();
+                assert!(again.as_ptr::<f32>().unwrap() != buffer.as_ptr());
+            })
+        }
+        let after = unsafe { Tensor::uninitialized::<f32>(&[2, 4]).unwrap() };
+        assert!(after.as_ptr::<f32>().unwrap() != buffer.as_ptr());
+    }
+}
@@ -1,5 +1,5 @@
 use tract_nnef::internal::*;
-use tract_nnef::tract_core::trivial_op_state_freeeze;
+use tract_nnef::tract_core::ops::OpStateFreeze;
 use tract_pulse::model::PulsedModel;
 use tract_pulse::ops::OpPulsifier;
 use tract_pulse::PulsedOp;
@@ -96,7 +96,33 @@ pub struct ExpUnitNormState {
     hidden: Option<Tensor>,
     index: usize,
 }
-trivial_op_state_freeeze!(ExpUnitNormState);
+
+impl OpStateFreeze for ExpUnitNormState {
+    fn freeze(&self) -> Box<dyn FrozenOpState> {
+        Box::new(self.clone())
+    }
+}
+
+impl FrozenOpState for ExpUnitNormState {
+    fn unfreeze(&self) -> Box<dyn OpState> {
+        Box::new(self.clone())
+    }
+
+    fn save(&self) -> TractResult<SavedOpState> {
+        Ok(SavedOpState {
+            counters: vec![self.index as i64],
+            tensors: vec![self.hidden.clone()],
+            ..SavedOpState::default()
+        })
+    }
+
+    fn load(&self, saved: &SavedOpState) -> TractResult<Box<dyn FrozenOpState>> {
+        Ok(Box::new(ExpUnitNormState {
+            hidden: saved.tensor(0)?,
+            index: saved.counter(0)? as usize,
+        }))
+    }
+}
 
 impl Op for ExpUnitNorm {
     fn name(&self) -> Cow<str> {
@@ -102,4 +102,17 @@ mod tests {
             .into_runnable()?;
         run(tfd)
     }
+
+    #[test]
+    fn round_trip() -> TractResult<()> {
+        let tflite = tract_tflite::tflite();
+        let model = tflite
+            .model_for_path(mobilenet_v2())?
+            .with_input_fact(0, input_dt().fact([1, 224, 224, 3]))?
+            .into_decluttered()?;
+        let mut buffer = vec![];
+        tflite.write(&model, &mut buffer)?;
+        let tfd = tflite.model_for_read(&mut &*buffer)?.into_optimized()?.into_runnable()?;
+        run(tfd)
+    }
 }
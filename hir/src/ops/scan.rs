@@ -93,7 +93,7 @@ impl InferenceScan {
         )?))
     }
 
-    fn unify_scanning_tensor_fact(
+    pub fn unify_scanning_tensor_fact(
         outer: &mut InferenceFact,
         inner: &mut InferenceFact,
         outer_scan_axis: usize,
@@ -1,13 +1,20 @@
 #[macro_use]
 pub mod element_wise;
 
+#[macro_use]
+pub mod attention;
+
 #[macro_use]
 pub mod by_scalar;
 #[macro_use]
+pub mod gelu;
+#[macro_use]
 pub mod lut;
 #[macro_use]
 pub mod max;
 #[macro_use]
+pub mod mean;
+#[macro_use]
 pub mod mmm;
 pub mod pack;
 #[macro_use]
@@ -22,6 +29,7 @@ pub mod softmax;
 pub mod tanh;
 pub mod element_wise_helper;
 
+pub use pack::BlockQuant;
 pub use pack::Packer;
 pub use pack::PackingWriter;
 
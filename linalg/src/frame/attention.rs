@@ -0,0 +1,177 @@
+use std::fmt::Debug;
+use tract_data::internal::*;
+
+/// Sizes of one attention head: q is `q_len x d`, k is `kv_len x d`, v is `kv_len x dv`,
+/// and the output is `q_len x dv`. All are contiguous, row-major.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, new)]
+pub struct AttentionGeometry {
+    pub q_len: usize,
+    pub kv_len: usize,
+    pub d: usize,
+    pub dv: usize,
+}
+
+/// Additive mask, logically `q_len x kv_len`. Strides are in items and can be zero to
+/// broadcast a row or column.
+#[derive(Clone, Copy, Debug, new)]
+pub struct AttentionMask<'a> {
+    pub data: &'a [f32],
+    pub row_stride: usize,
+    pub col_stride: usize,
+}
+
+/// Computes `softmax(q.k^T * scale + mask) . v` for one head without materializing the
+/// `q_len x kv_len` score matrix.
+///
+/// With `causal`, query i sees keys up to `i + kv_len - q_len`, so the last query sees all the
+/// keys (this is the usual layout when keys include a cache of past positions).
+pub trait Attention: Send + Sync + Debug + dyn_clone::DynClone {
+    fn name(&self) -> &'static str;
+    #[allow(clippy::too_many_arguments)]
+    fn run(
+        &self,
+        geo: &AttentionGeometry,
+        q: &[f32],
+        k: &[f32],
+        v: &[f32],
+        mask: Option<AttentionMask>,
+        scale: f32,
+        causal: bool,
+        output: &mut [f32],
+    ) -> TractResult<()>;
+}
+
+dyn_clone::clone_trait_object!(Attention);
+
+#[cfg(test)]
+#[macro_use]
+pub mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[macro_export]
+    macro_rules! attention_frame_tests {
+        ($cond:expr, $att: expr) => {
+            proptest::proptest! {
+                #[test]
+                fn prop(pb in proptest::prelude::any::<$crate::frame::attention::test::AttentionProblem>()) {
+                    if $cond {
+                        pb.check(&$att).unwrap()
+                    }
+                }
+            }
+
+            #[test]
+            fn causal_shorter_query() {
+                if $cond {
+                    $crate::frame::attention::test::AttentionProblem {
+                        geo: $crate::frame::attention::AttentionGeometry::new(2, 3, 1, 1),
+                        q: vec![1.0, 2.0],
+                        k: vec![1.0, 0.0, -1.0],
+                        v: vec![3.0, 1.0, 2.0],
+                        mask: None,
+                        scale: 1.0,
+                        causal: true,
+                    }
+                    .check(&$att)
+                    .unwrap()
+                }
+            }
+        };
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct AttentionProblem {
+        pub geo: AttentionGeometry,
+        pub q: Vec<f32>,
+        pub k: Vec<f32>,
+        pub v: Vec<f32>,
+        pub mask: Option<Vec<f32>>,
+        pub scale: f32,
+        pub causal: bool,
+    }
+
+    impl Arbitrary for AttentionProblem {
+        type Parameters = ();
+        type Strategy = BoxedStrategy<AttentionProblem>;
+
+        fn arbitrary_with(_: ()) -> Self::Strategy {
+            (1usize..5, 1usize..150, 1usize..9, 1usize..9, any::<bool>(), any::<bool>())
+                .prop_flat_map(|(q_len, kv_len, d, dv, masked, causal)| {
+                    let q_len = q_len.min(kv_len);
+                    let geo = AttentionGeometry::new(q_len, kv_len, d, dv);
+                    let values = |n| proptest::collection::vec(-2f32..2f32, n);
+                    let mask = if masked {
+                        proptest::option::of(values(q_len * kv_len)).boxed()
+                    } else {
+                        Just(None).boxed()
+                    };
+                    (
+                        Just(geo),
+                        values(q_len * d),
+                        values(kv_len * d),
+                        values(kv_len * dv),
+                        mask,
+                        0.1f32..1.0,
+                        Just(causal),
+                    )
+                })
+                .prop_map(|(geo, q, k, v, mask, scale, causal)| AttentionProblem {
+                    geo,
+                    q,
+                    k,
+                    v,
+                    mask,
+                    scale,
+                    causal,
+                })
+                .boxed()
+        }
+    }
+
+    impl AttentionProblem {
+        pub fn reference(&self) -> Vec<f32> {
+            let AttentionGeometry { q_len, kv_len, d, dv } = self.geo;
+            let mut output = vec![0f32; q_len * dv];
+            for i in 0..q_len {
+                let visible = if self.causal { i + 1 + kv_len - q_len } else { kv_len };
+                let scores: Vec<f32> = (0..visible)
+                    .map(|j| {
+                        let dot: f32 = (0..d).map(|c| self.q[i * d + c] * self.k[j * d + c]).sum();
+                        dot * self.scale
+                            + self.mask.as_ref().map(|m| m[i * kv_len + j]).unwrap_or(0.)
+                    })
+                    .collect();
+                let max = scores.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+                let exps: Vec<f32> = scores.iter().map(|s| (s - max).exp()).collect();
+                let sum: f32 = exps.iter().sum();
+                for c in 0..dv {
+                    output[i * dv + c] =
+                        exps.iter().enumerate().map(|(j, e)| e * self.v[j * dv + c]).sum::<f32>()
+                            / sum;
+                }
+            }
+            output
+        }
+
+        pub fn check(&self, att: &dyn Attention) -> TractResult<()> {
+            let mut found = vec![0f32; self.geo.q_len * self.geo.dv];
+            let mask = self.mask.as_ref().map(|m| AttentionMask::new(m, self.geo.kv_len, 1));
+            att.run(
+                &self.geo,
+                &self.q,
+                &self.k,
+                &self.v,
+                mask,
+                self.scale,
+                self.causal,
+                &mut found,
+            )?;
+            let shape = [self.geo.q_len, self.geo.dv];
+            tensor1(&found).into_shape(&shape)?.close_enough(
+                &tensor1(&self.reference()).into_shape(&shape)?,
+                Approximation::Approximate,
+            )
+        }
+    }
+}
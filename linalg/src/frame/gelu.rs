@@ -0,0 +1,76 @@
+#[cfg(test)]
+#[macro_use]
+pub mod test {
+    use crate::frame::element_wise::*;
+    use crate::LADatum;
+    use num_traits::{AsPrimitive, Float};
+    use proptest::test_runner::TestCaseResult;
+
+    #[macro_export]
+    macro_rules! gelu_frame_tests {
+        ($cond:expr, $t:ty, $ker:ty, $tanh: expr) => {
+            proptest::proptest! {
+                #[test]
+                fn gelu(xs in proptest::collection::vec(-25f32..25.0, 0..100)) {
+                    if $cond {
+                        $crate::frame::gelu::test::test_gelu::<$ker, $t>(&*xs, $tanh).unwrap()
+                    }
+                }
+            }
+
+            #[test]
+            fn gelu_4_magic() {
+                if $cond {
+                    $crate::frame::gelu::test::test_gelu::<$ker, $t>(
+                        &[0f32, -20.0, 20.0, 0.5],
+                        $tanh,
+                    )
+                    .unwrap()
+                }
+            }
+
+            #[test]
+            fn gelu_18_ones() {
+                if $cond {
+                    $crate::frame::gelu::test::test_gelu::<$ker, $t>(&[1.0; 18], $tanh).unwrap();
+                }
+            }
+        };
+    }
+
+    // power series, good enough in f64 for |x| < 5. erf(5) == 1 in f32.
+    fn erf(x: f64) -> f64 {
+        if x.abs() > 5.0 {
+            return x.signum();
+        }
+        let mut term = x;
+        let mut sum = x;
+        for n in 1..100 {
+            term *= -x * x / n as f64;
+            sum += term / (2 * n + 1) as f64;
+        }
+        sum * 2.0 / std::f64::consts::PI.sqrt()
+    }
+
+    pub fn test_gelu<K: ElementWiseKer<T>, T: LADatum + Float>(
+        values: &[f32],
+        tanh: bool,
+    ) -> TestCaseResult
+    where
+        f32: AsPrimitive<T>,
+        T: AsPrimitive<f32>,
+    {
+        crate::setup_test_logger();
+        let values: Vec<T> = values.iter().copied().map(|x| x.as_()).collect();
+        crate::frame::element_wise::test::test_element_wise::<K, _, _>(&values, |x| {
+            let x = x.as_() as f64;
+            let y = if tanh {
+                let inner = (2.0 / std::f64::consts::PI).sqrt() * (x + 0.044715 * x.powi(3));
+                0.5 * x * (1.0 + inner.tanh())
+            } else {
+                0.5 * x * (1.0 + erf(x / std::f64::consts::SQRT_2))
+            };
+            (y as f32).as_()
+        })
+    }
+}
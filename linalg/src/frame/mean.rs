@@ -0,0 +1,51 @@
+#[cfg(test)]
+#[macro_use]
+pub mod test {
+    use crate::frame::reduce::ReduceKer;
+    use crate::LADatum;
+    use num_traits::{AsPrimitive, Float};
+    use proptest::test_runner::{TestCaseError, TestCaseResult};
+    use tract_data::internal::*;
+
+    #[macro_export]
+    macro_rules! mean_frame_tests {
+        ($cond:expr, $t: ty, $ker:ty, $squares: expr) => {
+            proptest::proptest! {
+                #[test]
+                fn prop(xs in proptest::collection::vec(-25f32..25.0, 1..100)) {
+                    if $cond {
+                        $crate::frame::mean::test::test_mean::<$ker, $t>(&*xs, $squares).unwrap()
+                    }
+                }
+            }
+
+            #[test]
+            fn empty() {
+                if $cond {
+                    $crate::frame::mean::test::test_mean::<$ker, $t>(&[], $squares).unwrap()
+                }
+            }
+        };
+    }
+
+    pub fn test_mean<K: ReduceKer<T, f32>, T: LADatum + Float>(
+        values: &[f32],
+        squares: bool,
+    ) -> TestCaseResult
+    where
+        f32: AsPrimitive<T>,
+        T: AsPrimitive<f32>,
+    {
+        crate::setup_test_logger();
+        let values: Vec<T> = values.iter().copied().map(|x| x.as_()).collect();
+        let recip_len = (values.len().max(1) as f32).recip();
+        let expected =
+            values.iter().map(|x| if squares { x.as_() * x.as_() } else { x.as_() }).sum::<f32>()
+                * recip_len;
+        let found = K::red().run_with_params(&values, recip_len).unwrap();
+        tensor0(found)
+            .close_enough(&tensor0::<T>(expected.as_()), Approximation::Approximate)
+            .map_err(|e| TestCaseError::fail(e.root_cause().to_string()))?;
+        Ok(())
+    }
+}
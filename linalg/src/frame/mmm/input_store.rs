@@ -2,6 +2,8 @@ use std::alloc::Layout;
 use std::fmt::Debug;
 use tract_data::internal::*;
 
+use crate::frame::{BlockQuant, Packer};
+
 pub trait InputStoreSpec: dyn_clone::DynClone + Debug + Send + Sync {
     fn wrap(&self, view: &TensorView) -> Box<dyn InputStore>;
 }
@@ -39,3 +41,152 @@ impl InputStore for Prepacked {
         unsafe { self.ptr.offset(self.panel_bytes * i as isize) }
     }
 }
+
+/// Block-quantized weights, dequantized one panel at a time into the scratch panel buffer as
+/// the multiplication loop reaches them. The wrapped tensor is the `row_bytes(k)`-wide u8 matrix
+/// produced by `BlockQuant::quant_f32`.
+///
+/// The scratch space remembers which panel its buffer holds, so a panel is dequantized once for
+/// all the tiles of its row, not once per tile.
+#[derive(Debug, Clone)]
+pub struct BlockQuantSpec {
+    format: BlockQuant,
+    packer: Packer,
+    k: usize,
+    dt: DatumType,
+}
+
+impl BlockQuantSpec {
+    pub fn new(format: BlockQuant, packer: Packer, k: usize, dt: DatumType) -> TractResult<Self> {
+        ensure!(
+            dt == f32::datum_type() || dt == f16::datum_type(),
+            "Block-quantized weights can only be dequantized to f32 or f16, not {dt:?}"
+        );
+        Ok(BlockQuantSpec { format, packer, k, dt })
+    }
+}
+
+impl InputStoreSpec for BlockQuantSpec {
+    fn wrap(&self, view: &TensorView) -> Box<dyn InputStore> {
+        let ptr = unsafe { view.as_ptr_unchecked() };
+        Box::new(BlockQuantStore { spec: self.clone(), ptr, mn: view.shape()[0] })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BlockQuantStore {
+    spec: BlockQuantSpec,
+    ptr: *const u8,
+    mn: usize,
+}
+
+impl BlockQuantStore {
+    unsafe fn dequant_panel<T: Datum + Copy>(
+        &self,
+        i: usize,
+        buffer: *mut T,
+        f: impl Fn(f32) -> T,
+    ) {
+        let BlockQuantSpec { format, packer, k, .. } = &self.spec;
+        let r = packer.r;
+        let panel = std::slice::from_raw_parts_mut(buffer, packer.single_panel_len(*k));
+        let rows = r.min(self.mn.saturating_sub(i * r));
+        // only the padding rows of the last panel are not overwritten
+        if rows < r {
+            panel.iter_mut().for_each(|x| *x = f(0.0));
+        }
+        let row_bytes = format.row_bytes(*k);
+        let mut block = [0f32; 32];
+        let block = &mut block[..format.block_len()];
+        for row in 0..rows {
+            let bytes =
+                std::slice::from_raw_parts(self.ptr.add((i * r + row) * row_bytes), row_bytes);
+            for (b, bytes) in bytes.chunks(format.block_bytes()).enumerate() {
+                format.dequant_block(bytes, block);
+                for (ix, x) in block.iter().enumerate() {
+                    panel[(b * format.block_len() + ix) * r + row] = f(*x);
+                }
+            }
+        }
+    }
+}
+
+impl InputStore for BlockQuantStore {
+    fn scratch_panel_buffer_layout(&self) -> Option<Layout> {
+        Some(self.spec.packer.single_panel_layout(self.spec.k, self.spec.dt.size_of()))
+    }
+
+    fn panel(&self, i: usize, buffer: Option<*mut u8>) -> *const u8 {
+        let buffer = buffer.unwrap();
+        unsafe {
+            if self.spec.dt == f16::datum_type() {
+                self.dequant_panel(i, buffer as *mut f16, f16::from_f32)
+            } else {
+                self.dequant_panel(i, buffer as *mut f32, |x| x)
+            }
+        }
+        buffer
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::frame::mmm::FusedSpec;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[derive(Debug, Clone)]
+    struct CountingStore(Box<dyn InputStore>, Arc<AtomicUsize>);
+
+    impl InputStore for CountingStore {
+        fn scratch_panel_buffer_layout(&self) -> Option<Layout> {
+            self.0.scratch_panel_buffer_layout()
+        }
+
+        fn panel(&self, i: usize, buffer: Option<*mut u8>) -> *const u8 {
+            self.1.fetch_add(1, Ordering::SeqCst);
+            self.0.panel(i, buffer)
+        }
+    }
+
+    #[test]
+    fn block_quant_spec_refuses_integers() {
+        let mmm = crate::ops()
+            .mmm(i8::datum_type(), i8::datum_type(), i32::datum_type(), None, None, None)
+            .unwrap();
+        assert!(unsafe { mmm.a_block_quant(BlockQuant::Q8_0, 32) }.is_err());
+    }
+
+    #[test]
+    fn block_quant_panels_are_dequantized_once_per_row_of_tiles() -> TractResult<()> {
+        let (m, k, n) = (33, 64, 40);
+        let mmm = crate::ops()
+            .mmm(f32::datum_type(), f32::datum_type(), f32::datum_type(), None, None, None)
+            .unwrap();
+        let weights: Vec<f32> = (0..m * k).map(|i| ((i * 7 % 13) as f32 - 6.) / 4.).collect();
+        let q = BlockQuant::Q8_0.quant_f32(&weights, k)?;
+        let q = tensor1(&q).into_shape(&[m, BlockQuant::Q8_0.row_bytes(k)])?;
+        let b =
+            Tensor::from_shape(&[k, n], &(0..k * n).map(|i| (i % 5) as f32).collect::<Vec<_>>())?;
+        let mut c = Tensor::zero::<f32>(&[m, n])?;
+        let count = Arc::new(AtomicUsize::new(0));
+        unsafe {
+            let b_pack = mmm.b_pack();
+            let mut packed_b =
+                Tensor::uninitialized_aligned::<f32>(&[b_pack.len(k, n)], b_pack.alignment())?;
+            b_pack.pack(packed_b.view_mut(), b.view(), 0, 1);
+            let a = mmm.a_block_quant(BlockQuant::Q8_0, k)?.wrap(&q.view());
+            let a = Box::new(CountingStore(a, count.clone()));
+            let b = mmm.b_packed(4, k).wrap(&packed_b.view());
+            let c_store = mmm.c_view(0, 1).wrap(&c.view_mut());
+            mmm.run(m, n, &[FusedSpec::AddMatMul { k, a, b }, FusedSpec::Store(c_store)])?;
+        }
+        assert_eq!(count.load(Ordering::SeqCst), m.divceil(mmm.mr()));
+        let weights = BlockQuant::Q8_0.dequant_f32(q.as_slice::<u8>()?)?;
+        let weights = tract_ndarray::Array2::from_shape_vec((m, k), weights)?;
+        let expected =
+            weights.dot(&b.to_array_view::<f32>()?.into_dimensionality::<tract_ndarray::Ix2>()?);
+        c.close_enough(&expected.into_tensor(), true)
+    }
+}
@@ -1,6 +1,7 @@
 use super::ScratchSpaceImpl;
 use super::*;
-use crate::frame::Packer;
+use crate::frame::{BlockQuant, Packer};
+use crate::multithread::{current_tract_executor, Executor, Unchecked};
 use crate::LADatum;
 use anyhow::Context;
 use std::fmt;
@@ -24,6 +25,14 @@ pub trait MatMatMul:
     unsafe fn a_packed(&self, item_size: usize, k: usize) -> Box<dyn InputStoreSpec>;
 
     unsafe fn b_packed(&self, item_size: usize, k: usize) -> Box<dyn InputStoreSpec>;
+
+    /// Weights block-quantized to `format`, dequantized on the fly. Only f32 and f16 products
+    /// support it.
+    unsafe fn a_block_quant(
+        &self,
+        format: BlockQuant,
+        k: usize,
+    ) -> TractResult<Box<dyn InputStoreSpec>>;
 //    unsafe fn b_virtual_input(&self, func: Box<dyn VirtualInputSpec>, k: usize) -> InputStoreSpec;
 
     unsafe fn c_view(&self, m_axis: usize, n_axis: usize) -> OutputStoreSpec;
@@ -43,6 +52,10 @@ pub trait MatMatMul:
 
     unsafe fn allocate_scratch_space(&self) -> Box<dyn ScratchSpace>;
     unsafe fn can_use_scratch_space(&self, scratch: &dyn ScratchSpace) -> bool;
+    /// Run the product, reusing `scratch` across calls.
+    ///
+    /// When the current executor is multithreaded and the product spans several tiles, `scratch`
+    /// is ignored: each worker allocates and prepares a scratch space of its own.
     unsafe fn run_with_scratch_space(
         &self,
         m: usize,
@@ -152,6 +165,14 @@ where
         Box::new(PrepackedSpec { panel_bytes })
     }
 
+    unsafe fn a_block_quant(
+        &self,
+        format: BlockQuant,
+        k: usize,
+    ) -> TractResult<Box<dyn InputStoreSpec>> {
+        Ok(Box::new(BlockQuantSpec::new(format, self.a_pack(), k, TI::datum_type())?))
+    }
+
     /*
     unsafe fn b_virtual_input(&self, func: Box<dyn VirtualInputSpec>, k: usize) -> InputStoreSpec {
         InputStoreSpec::VirtualPacking { packer: self.b_pack(), func, k }
@@ -193,6 +214,10 @@ where
     ) -> anyhow::Result<()> {
         let scratch =
             scratch.downcast_mut::<ScratchSpaceImpl<TI>>().context("Wrong scratch space type")?;
+        let executor = current_tract_executor();
+        if executor.is_multithread() && m.divceil(K::mr()) * n.divceil(K::nr()) > 1 {
+            return self.run_with_executor(&executor, m, n, non_linear);
+        }
         scratch.prepare::<K>(m, n, non_linear)?;
         if n == 1 && K::nr() == 1 {
             self.run_with_scratch_space_vec(m, scratch, non_linear);
@@ -206,6 +231,43 @@ where
 }
 
 impl<TI: LADatum, K: MatMatMulKer<TI>> MatMatMulImpl<K, TI> {
+    // Tiles are independent: each worker gets its own scratch space and computes a full
+    // row (or column) of tiles exactly as the sequential loops would.
+    unsafe fn run_with_executor(
+        &self,
+        executor: &Executor,
+        m: usize,
+        n: usize,
+        non_linear: &[FusedSpec],
+    ) -> anyhow::Result<()> {
+        let specs = Unchecked(non_linear);
+        let prepare = || -> TractResult<Unchecked<ScratchSpaceImpl<TI>>> {
+            let mut scratch = ScratchSpaceImpl::<TI>::default();
+            scratch.prepare::<K>(m, n, specs.get())?;
+            Ok(Unchecked(scratch))
+        };
+        if n == 1 && K::nr() == 1 {
+            executor.for_each_init(0..m.divceil(K::mr()), prepare, |scratch, ia| {
+                scratch.0.run::<K>(specs.get(), ia, 0);
+                Ok(())
+            })
+        } else if non_linear.iter().any(|f| f.prefer_col_outer()) || m <= K::mr() {
+            executor.for_each_init(0..n.divceil(K::nr()), prepare, |scratch, ib| {
+                for ia in 0..m.divceil(K::mr()) {
+                    scratch.0.run::<K>(specs.get(), ia, ib);
+                }
+                Ok(())
+            })
+        } else {
+            executor.for_each_init(0..m.divceil(K::mr()), prepare, |scratch, ia| {
+                for ib in 0..n.divceil(K::nr()) {
+                    scratch.0.run::<K>(specs.get(), ia, ib);
+                }
+                Ok(())
+            })
+        }
+    }
+
     unsafe fn run_with_scratch_space_vec(
         &self,
         m: usize,
@@ -80,6 +80,22 @@ macro_rules! mmm_frame_tests {
                 }
             }
 
+            #[cfg(feature = "multithread-mm")]
+            #[test]
+            fn mat_mul_multithread() {
+                if $cond {
+                    let (m, k, n) = (67, 13, 45);
+                    let a = tensor1(&(0..m * k).map(|i| (i % 7) as i32).collect::<Vec<_>>())
+                        .into_shape(&[m, k]).unwrap().cast_to::<$ta>().unwrap().into_owned();
+                    let b = tensor1(&(0..k * n).map(|i| (i % 5) as i32).collect::<Vec<_>>())
+                        .into_shape(&[k, n]).unwrap().cast_to::<$tb>().unwrap().into_owned();
+                    let executor = $crate::multithread::Executor::multithread(4).unwrap();
+                    $crate::multithread::multithread_tract_scope(executor, || {
+                        test_mat_mat_mul_prep::<$ker, $ta, $tb, $tc, $ti>(m, k, n, &a, &b)
+                    }).unwrap()
+                }
+            }
+
             #[test]
             fn mat_vec_1() {
                 if $cond {
@@ -364,6 +364,124 @@ unsafe fn pack_mn_major<Chunk: Copy>(
     }
 }
 
+/// Block-quantized weight formats, storing weights as blocks of `block_len()` consecutive values
+/// along k, each block made of a f16 scale followed by the packed quantized values.
+///
+/// A m×k matrix is stored row major: each row is `k / block_len()` blocks, so a row takes
+/// `row_bytes(k)` bytes. Q8_0 and Q4_0 match the ggml block layouts.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum BlockQuant {
+    Q8_0,
+    Q4_0,
+}
+
+impl BlockQuant {
+    #[inline]
+    pub fn block_len(&self) -> usize {
+        32
+    }
+
+    #[inline]
+    pub fn block_bytes(&self) -> usize {
+        match self {
+            BlockQuant::Q8_0 => 2 + 32,
+            BlockQuant::Q4_0 => 2 + 16,
+        }
+    }
+
+    #[inline]
+    pub fn row_bytes(&self, k: usize) -> usize {
+        k / self.block_len() * self.block_bytes()
+    }
+
+    pub fn quant_block(&self, input: &[f32], output: &mut [u8]) {
+        debug_assert_eq!(input.len(), self.block_len());
+        debug_assert_eq!(output.len(), self.block_bytes());
+        match self {
+            BlockQuant::Q8_0 => {
+                let amax = input.iter().fold(0f32, |acc, x| acc.max(x.abs()));
+                let d = amax / 127.0;
+                let id = if d == 0.0 { 0.0 } else { d.recip() };
+                output[0..2].copy_from_slice(&f16::from_f32(d).to_bits().to_le_bytes());
+                for (o, x) in output[2..].iter_mut().zip(input) {
+                    *o = (x * id).round() as i8 as u8;
+                }
+            }
+            BlockQuant::Q4_0 => {
+                let max =
+                    input.iter().fold(0f32, |acc, &x| if x.abs() > acc.abs() { x } else { acc });
+                let d = max / -8.0;
+                let id = if d == 0.0 { 0.0 } else { d.recip() };
+                output[0..2].copy_from_slice(&f16::from_f32(d).to_bits().to_le_bytes());
+                let q = |x: f32| ((x * id + 8.5) as u8).min(15);
+                for j in 0..16 {
+                    output[2 + j] = q(input[j]) | q(input[j + 16]) << 4;
+                }
+            }
+        }
+    }
+
+    pub fn dequant_block(&self, input: &[u8], output: &mut [f32]) {
+        debug_assert_eq!(input.len(), self.block_bytes());
+        debug_assert_eq!(output.len(), self.block_len());
+        let d = f16::from_bits(u16::from_le_bytes([input[0], input[1]])).to_f32();
+        match self {
+            BlockQuant::Q8_0 => {
+                for (o, q) in output.iter_mut().zip(&input[2..]) {
+                    *o = *q as i8 as f32 * d;
+                }
+            }
+            BlockQuant::Q4_0 => {
+                for j in 0..16 {
+                    output[j] = ((input[2 + j] & 0xF) as i8 - 8) as f32 * d;
+                    output[j + 16] = ((input[2 + j] >> 4) as i8 - 8) as f32 * d;
+                }
+            }
+        }
+    }
+
+    /// Quantize a row-major m×k matrix. k must be a multiple of `block_len()`.
+    pub fn quant_f32(&self, input: &[f32], k: usize) -> TractResult<Vec<u8>> {
+        ensure!(k % self.block_len() == 0, "k ({k}) must be a multiple of {}", self.block_len());
+        ensure!(input.len() % k == 0);
+        let mut output = vec![0u8; input.len() / self.block_len() * self.block_bytes()];
+        for (i, o) in input.chunks(self.block_len()).zip(output.chunks_mut(self.block_bytes())) {
+            self.quant_block(i, o);
+        }
+        Ok(output)
+    }
+
+    /// Dequantize a row-major matrix, as produced by `quant_f32`.
+    pub fn dequant_f32(&self, input: &[u8]) -> TractResult<Vec<f32>> {
+        ensure!(input.len() % self.block_bytes() == 0);
+        let mut output = vec![0f32; input.len() / self.block_bytes() * self.block_len()];
+        for (i, o) in input.chunks(self.block_bytes()).zip(output.chunks_mut(self.block_len())) {
+            self.dequant_block(i, o);
+        }
+        Ok(output)
+    }
+}
+
+impl std::fmt::Display for BlockQuant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BlockQuant::Q8_0 => write!(f, "q8_0"),
+            BlockQuant::Q4_0 => write!(f, "q4_0"),
+        }
+    }
+}
+
+impl std::str::FromStr for BlockQuant {
+    type Err = TractError;
+    fn from_str(s: &str) -> TractResult<BlockQuant> {
+        match s {
+            "q8_0" => Ok(BlockQuant::Q8_0),
+            "q4_0" => Ok(BlockQuant::Q4_0),
+            _ => bail!("Unknown block quantization format {s}"),
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use std::ops::Range;
@@ -526,4 +644,26 @@ mod test {
     fn range_b_5() {
         PackProblem { k: 1, mn: 7, is_a: false, r: 6, k_range: 0..1, mn_range: 1..7 }.check();
     }
+
+    #[test]
+    fn block_quant_q8_0() {
+        let input: Vec<f32> = (0..64).map(|i| (i as f32 - 20.0) / 7.0).collect();
+        let q = super::BlockQuant::Q8_0.quant_f32(&input, 32).unwrap();
+        assert_eq!(q.len(), 2 * 34);
+        let output = super::BlockQuant::Q8_0.dequant_f32(&q).unwrap();
+        for (i, o) in input.iter().zip(output.iter()) {
+            assert!((i - o).abs() < 0.03, "{i} {o}");
+        }
+    }
+
+    #[test]
+    fn block_quant_q4_0() {
+        let input: Vec<f32> = (0..64).map(|i| (i as f32 - 20.0) / 7.0).collect();
+        let q = super::BlockQuant::Q4_0.quant_f32(&input, 32).unwrap();
+        assert_eq!(q.len(), 2 * 18);
+        let output = super::BlockQuant::Q4_0.dequant_f32(&q).unwrap();
+        for (i, o) in input.iter().zip(output.iter()) {
+            assert!((i - o).abs() < 0.4, "{i} {o}");
+        }
+    }
 }
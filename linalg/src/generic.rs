@@ -1,18 +1,24 @@
+pub mod attention;
 pub mod by_scalar;
 pub mod erf;
+pub mod gelu;
 pub mod leaky_relu;
 pub mod lut;
 pub mod max;
+pub mod mean;
 pub mod mmm;
 pub mod rounding;
 pub mod sigmoid;
 pub mod softmax;
 pub mod tanh;
 
-pub use self::by_scalar::{HMulByScalar8, SMulByScalar4};
+pub use self::attention::TiledAttention;
+pub use self::by_scalar::{HAddByScalar8, HMulByScalar8, SAddByScalar4, SMulByScalar4};
 pub use self::erf::SErf4;
+pub use self::gelu::{HGelu8, HGeluTanh8, SGelu4, SGeluTanh4};
 pub use self::leaky_relu::{HLeakyRelu8, SLeakyRelu4};
 pub use self::lut::GenericLut8;
+pub use self::mean::{HMean8, HMeanOfSquares8, SMean4, SMeanOfSquares4};
 pub use self::mmm::GenericMmm4x1;
 pub use self::mmm::GenericMmm4x4;
 pub use self::rounding::{ScaleShiftAndRound, Scaler};
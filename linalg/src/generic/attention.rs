@@ -0,0 +1,82 @@
+use crate::frame::attention::{Attention, AttentionGeometry, AttentionMask};
+use tract_data::internal::*;
+
+/// Online-softmax attention, going through keys and values by tiles of `kv_tile` rows.
+#[derive(Clone, Debug)]
+pub struct TiledAttention {
+    pub kv_tile: usize,
+}
+
+impl Default for TiledAttention {
+    fn default() -> TiledAttention {
+        TiledAttention { kv_tile: 64 }
+    }
+}
+
+impl Attention for TiledAttention {
+    fn name(&self) -> &'static str {
+        "generic"
+    }
+
+    fn run(
+        &self,
+        geo: &AttentionGeometry,
+        q: &[f32],
+        k: &[f32],
+        v: &[f32],
+        mask: Option<AttentionMask>,
+        scale: f32,
+        causal: bool,
+        output: &mut [f32],
+    ) -> TractResult<()> {
+        let AttentionGeometry { q_len, kv_len, d, dv } = *geo;
+        ensure!(q.len() == q_len * d && k.len() == kv_len * d && v.len() == kv_len * dv);
+        ensure!(output.len() == q_len * dv);
+        ensure!(!causal || q_len <= kv_len, "Causal attention expects q_len <= kv_len");
+        let mut scores = vec![0f32; self.kv_tile];
+        for i in 0..q_len {
+            let q_row = &q[i * d..][..d];
+            let acc = &mut output[i * dv..][..dv];
+            acc.iter_mut().for_each(|x| *x = 0.);
+            let visible = if causal { i + 1 + kv_len - q_len } else { kv_len };
+            let mut max = f32::NEG_INFINITY;
+            let mut sum = 0f32;
+            for start in (0..visible).step_by(self.kv_tile) {
+                let len = self.kv_tile.min(visible - start);
+                let scores = &mut scores[..len];
+                for (jx, s) in scores.iter_mut().enumerate() {
+                    let j = start + jx;
+                    let k_row = &k[j * d..][..d];
+                    *s = q_row.iter().zip(k_row).map(|(a, b)| a * b).sum::<f32>() * scale;
+                    if let Some(mask) = &mask {
+                        *s += mask.data[i * mask.row_stride + j * mask.col_stride];
+                    }
+                }
+                let tile_max = scores.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+                let new_max = max.max(tile_max);
+                if new_max == f32::NEG_INFINITY {
+                    continue;
+                }
+                let correction = (max - new_max).exp();
+                sum *= correction;
+                acc.iter_mut().for_each(|x| *x *= correction);
+                for (jx, s) in scores.iter().enumerate() {
+                    let p = (s - new_max).exp();
+                    sum += p;
+                    let v_row = &v[(start + jx) * dv..][..dv];
+                    acc.iter_mut().zip(v_row).for_each(|(a, v)| *a += p * v);
+                }
+                max = new_max;
+            }
+            let recip = sum.recip();
+            acc.iter_mut().for_each(|x| *x *= recip);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[macro_use]
+pub mod test {
+    attention_frame_tests!(true, crate::generic::attention::TiledAttention { kv_tile: 16 });
+}
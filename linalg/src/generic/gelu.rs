@@ -0,0 +1,149 @@
+use super::erf::serf;
+use super::sigmoid::ssigmoid;
+use crate::frame::element_wise::ElementWiseKer;
+use tract_data::internal::*;
+
+/// 0.5 * x * (1 + erf(x / sqrt(2)))
+pub fn sgelu(x: f32) -> f32 {
+    let mut erf = x * std::f32::consts::FRAC_1_SQRT_2;
+    serf(&mut erf);
+    0.5 * x * (1.0 + erf)
+}
+
+/// 0.5 * x * (1 + tanh(sqrt(2 / pi) * (x + 0.044715 * x^3))), computed as x * sigmoid(2 * ...)
+pub fn sgelu_tanh(x: f32) -> f32 {
+    const TWO_SQRT_2_OVER_PI: f32 = 1.595_769_1;
+    x * ssigmoid(TWO_SQRT_2_OVER_PI * (x + 0.044715 * x * x * x))
+}
+
+#[derive(Clone, Debug)]
+pub struct SGelu4;
+
+impl ElementWiseKer<f32> for SGelu4 {
+    fn name() -> &'static str {
+        "generic"
+    }
+
+    fn alignment_bytes() -> usize {
+        16
+    }
+
+    fn alignment_items() -> usize {
+        4
+    }
+
+    fn nr() -> usize {
+        4
+    }
+
+    fn run(x: &mut [f32], _: ()) {
+        debug_assert!(x.len() % Self::nr() == 0);
+        debug_assert!(x.as_ptr() as usize % Self::alignment_bytes() == 0);
+        x.iter_mut().for_each(|px| *px = sgelu(*px))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct HGelu8;
+
+impl ElementWiseKer<f16> for HGelu8 {
+    fn name() -> &'static str {
+        "generic"
+    }
+
+    fn alignment_bytes() -> usize {
+        16
+    }
+
+    fn alignment_items() -> usize {
+        8
+    }
+
+    fn nr() -> usize {
+        8
+    }
+
+    fn run(x: &mut [f16], _: ()) {
+        debug_assert!(x.len() % Self::nr() == 0);
+        debug_assert!(x.as_ptr() as usize % Self::alignment_bytes() == 0);
+        x.iter_mut().for_each(|px| *px = f16::from_f32(sgelu(px.to_f32())))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SGeluTanh4;
+
+impl ElementWiseKer<f32> for SGeluTanh4 {
+    fn name() -> &'static str {
+        "generic"
+    }
+
+    fn alignment_bytes() -> usize {
+        16
+    }
+
+    fn alignment_items() -> usize {
+        4
+    }
+
+    fn nr() -> usize {
+        4
+    }
+
+    fn run(x: &mut [f32], _: ()) {
+        debug_assert!(x.len() % Self::nr() == 0);
+        debug_assert!(x.as_ptr() as usize % Self::alignment_bytes() == 0);
+        x.iter_mut().for_each(|px| *px = sgelu_tanh(*px))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct HGeluTanh8;
+
+impl ElementWiseKer<f16> for HGeluTanh8 {
+    fn name() -> &'static str {
+        "generic"
+    }
+
+    fn alignment_bytes() -> usize {
+        16
+    }
+
+    fn alignment_items() -> usize {
+        8
+    }
+
+    fn nr() -> usize {
+        8
+    }
+
+    fn run(x: &mut [f16], _: ()) {
+        debug_assert!(x.len() % Self::nr() == 0);
+        debug_assert!(x.as_ptr() as usize % Self::alignment_bytes() == 0);
+        x.iter_mut().for_each(|px| *px = f16::from_f32(sgelu_tanh(px.to_f32())))
+    }
+}
+
+#[cfg(test)]
+#[macro_use]
+pub mod s {
+    gelu_frame_tests!(true, f32, crate::generic::gelu::SGelu4, false);
+}
+
+#[cfg(test)]
+#[macro_use]
+pub mod h {
+    gelu_frame_tests!(true, tract_data::internal::f16, crate::generic::gelu::HGelu8, false);
+}
+
+#[cfg(test)]
+#[macro_use]
+pub mod s_tanh {
+    gelu_frame_tests!(true, f32, crate::generic::gelu::SGeluTanh4, true);
+}
+
+#[cfg(test)]
+#[macro_use]
+pub mod h_tanh {
+    gelu_frame_tests!(true, tract_data::internal::f16, crate::generic::gelu::HGeluTanh8, true);
+}
@@ -0,0 +1,153 @@
+use tract_data::internal::f16;
+
+use crate::frame::reduce::ReduceKer;
+
+#[derive(Clone, Debug)]
+pub struct SMean4;
+
+impl ReduceKer<f32, f32> for SMean4 {
+    fn name() -> &'static str {
+        "generic"
+    }
+
+    fn alignment_items() -> usize {
+        4
+    }
+
+    fn nr() -> usize {
+        4
+    }
+
+    fn neutral() -> f32 {
+        0.
+    }
+
+    fn reduce_two(a: f32, b: f32) -> f32 {
+        a + b
+    }
+
+    fn run(x: &[f32], recip_len: f32) -> f32 {
+        debug_assert!(x.len() % Self::nr() == 0);
+        debug_assert!(x.as_ptr() as usize % Self::alignment_bytes() == 0);
+        x.iter().sum::<f32>() * recip_len
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct HMean8;
+
+impl ReduceKer<f16, f32> for HMean8 {
+    fn name() -> &'static str {
+        "generic"
+    }
+
+    fn alignment_items() -> usize {
+        8
+    }
+
+    fn nr() -> usize {
+        8
+    }
+
+    fn neutral() -> f16 {
+        f16::from_f32(0.)
+    }
+
+    fn reduce_two(a: f16, b: f16) -> f16 {
+        a + b
+    }
+
+    fn run(x: &[f16], recip_len: f32) -> f16 {
+        debug_assert!(x.len() % Self::nr() == 0);
+        debug_assert!(x.as_ptr() as usize % Self::alignment_bytes() == 0);
+        f16::from_f32(x.iter().map(|x| x.to_f32()).sum::<f32>() * recip_len)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SMeanOfSquares4;
+
+impl ReduceKer<f32, f32> for SMeanOfSquares4 {
+    fn name() -> &'static str {
+        "generic"
+    }
+
+    fn alignment_items() -> usize {
+        4
+    }
+
+    fn nr() -> usize {
+        4
+    }
+
+    fn neutral() -> f32 {
+        0.
+    }
+
+    fn reduce_two(a: f32, b: f32) -> f32 {
+        a + b
+    }
+
+    fn run(x: &[f32], recip_len: f32) -> f32 {
+        debug_assert!(x.len() % Self::nr() == 0);
+        debug_assert!(x.as_ptr() as usize % Self::alignment_bytes() == 0);
+        x.iter().map(|x| x * x).sum::<f32>() * recip_len
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct HMeanOfSquares8;
+
+impl ReduceKer<f16, f32> for HMeanOfSquares8 {
+    fn name() -> &'static str {
+        "generic"
+    }
+
+    fn alignment_items() -> usize {
+        8
+    }
+
+    fn nr() -> usize {
+        8
+    }
+
+    fn neutral() -> f16 {
+        f16::from_f32(0.)
+    }
+
+    fn reduce_two(a: f16, b: f16) -> f16 {
+        a + b
+    }
+
+    fn run(x: &[f16], recip_len: f32) -> f16 {
+        debug_assert!(x.len() % Self::nr() == 0);
+        debug_assert!(x.as_ptr() as usize % Self::alignment_bytes() == 0);
+        f16::from_f32(x.iter().map(|x| x.to_f32() * x.to_f32()).sum::<f32>() * recip_len)
+    }
+}
+
+#[cfg(test)]
+#[macro_use]
+pub mod s {
+    mean_frame_tests!(true, f32, crate::generic::mean::SMean4, false);
+}
+
+#[cfg(test)]
+#[macro_use]
+pub mod s_squares {
+    mean_frame_tests!(true, f32, crate::generic::mean::SMeanOfSquares4, true);
+}
+
+#[cfg(test)]
+#[macro_use]
+pub mod h {
+    use super::*;
+    mean_frame_tests!(true, f16, crate::generic::mean::HMean8, false);
+}
+
+#[cfg(test)]
+#[macro_use]
+pub mod h_squares {
+    use super::*;
+    mean_frame_tests!(true, f16, crate::generic::mean::HMeanOfSquares8, true);
+}
@@ -18,6 +18,7 @@ include!(concat!(env!("OUT_DIR"), "/extern_kernel_macro.rs"));
 #[macro_use]
 pub mod frame;
 pub mod generic;
+pub mod multithread;
 use frame::element_wise::ElementWiseKer;
 use frame::reduce::{MapReduceKer, ReduceKer};
 use frame::{reduce, MatMatMul};
@@ -31,7 +32,7 @@ pub mod arm64;
 #[cfg(any(target_arch = "arm", target_arch = "armv7"))]
 pub mod arm32;
 
-pub use self::frame::{element_wise, lut, mmm};
+pub use self::frame::{attention, element_wise, lut, mmm};
 
 use crate::frame::mmm::kernel::MatMatMulKer;
 use tract_data::prelude::*;
@@ -63,17 +64,32 @@ pub struct Ops {
         Box<dyn Fn() -> Box<dyn element_wise::ElementWise<f32, f32>> + Send + Sync>,
     pub mul_by_scalar_f16:
         Box<dyn Fn() -> Box<dyn element_wise::ElementWise<f16, f16>> + Send + Sync>,
+    pub add_by_scalar_f32:
+        Box<dyn Fn() -> Box<dyn element_wise::ElementWise<f32, f32>> + Send + Sync>,
+    pub add_by_scalar_f16:
+        Box<dyn Fn() -> Box<dyn element_wise::ElementWise<f16, f16>> + Send + Sync>,
 
     pub sigmoid_f16: Box<dyn Fn() -> Box<dyn element_wise::ElementWise<f16>> + Send + Sync>,
     pub sigmoid_f32: Box<dyn Fn() -> Box<dyn element_wise::ElementWise<f32>> + Send + Sync>,
     pub tanh_f16: Box<dyn Fn() -> Box<dyn element_wise::ElementWise<f16>> + Send + Sync>,
     pub tanh_f32: Box<dyn Fn() -> Box<dyn element_wise::ElementWise<f32>> + Send + Sync>,
     pub erf_f32: Box<dyn Fn() -> Box<dyn element_wise::ElementWise<f32>> + Send + Sync>,
+    pub gelu_f16: Box<dyn Fn() -> Box<dyn element_wise::ElementWise<f16>> + Send + Sync>,
+    pub gelu_f32: Box<dyn Fn() -> Box<dyn element_wise::ElementWise<f32>> + Send + Sync>,
+    pub gelu_tanh_f16: Box<dyn Fn() -> Box<dyn element_wise::ElementWise<f16>> + Send + Sync>,
+    pub gelu_tanh_f32: Box<dyn Fn() -> Box<dyn element_wise::ElementWise<f32>> + Send + Sync>,
     pub lut_u8: Box<dyn Fn(&[u8]) -> Box<dyn lut::Lut> + Send + Sync>,
 
     pub max_f16: Box<dyn Fn() -> Box<dyn reduce::Reduce<f16>> + Send + Sync>,
     pub max_f32: Box<dyn Fn() -> Box<dyn reduce::Reduce<f32>> + Send + Sync>,
 
+    pub mean_f16: Box<dyn Fn() -> Box<dyn reduce::Reduce<f16, f32>> + Send + Sync>,
+    pub mean_f32: Box<dyn Fn() -> Box<dyn reduce::Reduce<f32, f32>> + Send + Sync>,
+    pub mean_of_squares_f16: Box<dyn Fn() -> Box<dyn reduce::Reduce<f16, f32>> + Send + Sync>,
+    pub mean_of_squares_f32: Box<dyn Fn() -> Box<dyn reduce::Reduce<f32, f32>> + Send + Sync>,
+
+    pub attention_f32: Box<dyn Fn() -> Box<dyn attention::Attention> + Send + Sync>,
+
     pub softmax2_fastcompact_f16: Box<dyn Fn() -> Box<dyn reduce::MapReduce<f16, f16>> + Send + Sync>,
     pub softmax2_fastcompact_f32: Box<dyn Fn() -> Box<dyn reduce::MapReduce<f32, f32>> + Send + Sync>,
 }
@@ -129,17 +145,28 @@ pub fn generic() -> Ops {
         leaky_relu_f32: Box::new(|| generic::SLeakyRelu4::ew()),
         mul_by_scalar_f16: Box::new(|| generic::HMulByScalar8::ew()),
         mul_by_scalar_f32: Box::new(|| generic::SMulByScalar4::ew()),
+        add_by_scalar_f16: Box::new(|| generic::HAddByScalar8::ew()),
+        add_by_scalar_f32: Box::new(|| generic::SAddByScalar4::ew()),
         sigmoid_f16: Box::new(|| generic::HSigmoid8::ew()),
         sigmoid_f32: Box::new(|| generic::SSigmoid4::ew()),
         tanh_f16: Box::new(|| generic::HTanh8::ew()),
         tanh_f32: Box::new(|| generic::STanh4::ew()),
         erf_f32: Box::new(|| generic::SErf4::ew()),
+        gelu_f16: Box::new(|| generic::HGelu8::ew()),
+        gelu_f32: Box::new(|| generic::SGelu4::ew()),
+        gelu_tanh_f16: Box::new(|| generic::HGeluTanh8::ew()),
+        gelu_tanh_f32: Box::new(|| generic::SGeluTanh4::ew()),
         lut_u8: Box::new(|table: &[u8]| Box::new(lut::LutImpl::<generic::GenericLut8>::new(table))),
         max_f16: Box::new(|| generic::max::HMax8::red()),
         max_f32: Box::new(|| generic::max::SMax4::red()),
+        mean_f16: Box::new(|| generic::mean::HMean8::red()),
+        mean_f32: Box::new(|| generic::mean::SMean4::red()),
+        mean_of_squares_f16: Box::new(|| generic::mean::HMeanOfSquares8::red()),
+        mean_of_squares_f32: Box::new(|| generic::mean::SMeanOfSquares4::red()),
         /*
         activation_f32: Box::new(|microcode| generic::SActivation::new(microcode))
         */
+        attention_f32: Box::new(|| Box::<generic::TiledAttention>::default()),
         softmax2_fastcompact_f16: Box::new(|| generic::softmax::HSoftMaxL2::red()),
         softmax2_fastcompact_f32: Box::new(|| generic::softmax::SSoftMaxL2::red()),
     }
@@ -0,0 +1,181 @@
+//! Intra-op parallelism.
+//!
+//! By default, tract runs every operator on the calling thread. With the
+//! `multithread-mm` feature enabled, an `Executor` backed by a rayon thread
+//! pool can be installed globally (`set_default_executor`) or for the
+//! duration of a closure (`multithread_tract_scope`). Kernels that know how to
+//! split their work (matrix multiplication tiles, im2col packing) will then
+//! dispatch it across the pool. Each unit of work is computed exactly as in the
+//! single-threaded case, so results are bit-identical.
+
+use std::cell::RefCell;
+use std::ops::Range;
+use std::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(feature = "multithread-mm")]
+use std::sync::Arc;
+use std::sync::Mutex;
+
+#[cfg(feature = "multithread-mm")]
+use rayon::prelude::*;
+#[cfg(feature = "multithread-mm")]
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use tract_data::TractResult;
+
+#[derive(Debug, Clone, Default)]
+pub enum Executor {
+    #[default]
+    SingleThread,
+    #[cfg(feature = "multithread-mm")]
+    MultiThread(Arc<ThreadPool>),
+}
+
+impl Executor {
+    #[cfg(feature = "multithread-mm")]
+    pub fn multithread(n: usize) -> TractResult<Executor> {
+        let pool = ThreadPoolBuilder::new().num_threads(n).build()?;
+        Ok(Executor::MultiThread(Arc::new(pool)))
+    }
+
+    pub fn is_multithread(&self) -> bool {
+        !matches!(self, Executor::SingleThread)
+    }
+
+    /// Call `f` once for every index in `range`, possibly in parallel.
+    ///
+    /// `init` is called once per worker batch to build some scratch state that is handed
+    /// mutably to `f`.
+    pub fn for_each_init<I, Init, F>(
+        &self,
+        range: Range<usize>,
+        init: Init,
+        f: F,
+    ) -> TractResult<()>
+    where
+        Init: Fn() -> TractResult<I> + Send + Sync,
+        F: Fn(&mut I, usize) -> TractResult<()> + Send + Sync,
+    {
+        match self {
+            Executor::SingleThread => {
+                let mut state = init()?;
+                range.into_iter().try_for_each(|ix| f(&mut state, ix))
+            }
+            #[cfg(feature = "multithread-mm")]
+            Executor::MultiThread(pool) => pool.install(|| {
+                range.into_par_iter().try_for_each_init(init, |state, ix| match state {
+                    Ok(state) => f(state, ix),
+                    Err(e) => Err(tract_data::anyhow::anyhow!("{e:?}")),
+                })
+            }),
+        }
+    }
+
+    /// Call `f` once for every index in `range`, possibly in parallel.
+    pub fn for_each<F>(&self, range: Range<usize>, f: F) -> TractResult<()>
+    where
+        F: Fn(usize) -> TractResult<()> + Send + Sync,
+    {
+        self.for_each_init(range, || Ok(()), |_, ix| f(ix))
+    }
+}
+
+static DEFAULT_EXECUTOR: Mutex<Executor> = Mutex::new(Executor::SingleThread);
+// bumped every time the default executor is set, zero as long as it never was
+static DEFAULT_EXECUTOR_GENERATION: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    static TLS_EXECUTOR_OVERRIDE: RefCell<Option<Executor>> = const { RefCell::new(None) };
+    // copy of the default executor, and the generation it was taken at
+    static TLS_DEFAULT_EXECUTOR: RefCell<(usize, Executor)> =
+        const { RefCell::new((0, Executor::SingleThread)) };
+}
+
+/// The executor kernels should use on the current thread.
+///
+/// This is called for every matrix multiplication: the process-wide default is only read
+/// under its lock when it has changed since the last call on this thread.
+pub fn current_tract_executor() -> Executor {
+    if let Some(over_ride) = TLS_EXECUTOR_OVERRIDE.with(|tls| tls.borrow().clone()) {
+        return over_ride;
+    }
+    let generation = DEFAULT_EXECUTOR_GENERATION.load(Ordering::Acquire);
+    if generation == 0 {
+        return Executor::SingleThread;
+    }
+    TLS_DEFAULT_EXECUTOR.with(|tls| {
+        let mut cached = tls.borrow_mut();
+        if cached.0 != generation {
+            *cached = (generation, DEFAULT_EXECUTOR.lock().unwrap().clone());
+        }
+        cached.1.clone()
+    })
+}
+
+/// Set the process-wide executor, used when no scope override is active.
+pub fn set_default_executor(executor: Executor) {
+    let mut default = DEFAULT_EXECUTOR.lock().unwrap();
+    *default = executor;
+    DEFAULT_EXECUTOR_GENERATION.fetch_add(1, Ordering::Release);
+}
+
+/// Run `f` with `executor` as the current executor for the calling thread.
+pub fn multithread_tract_scope<R, F: FnOnce() -> R>(executor: Executor, f: F) -> R {
+    let previous = TLS_EXECUTOR_OVERRIDE.with(|tls| tls.replace(Some(executor)));
+    struct Restore(Option<Executor>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let previous = self.0.take();
+            TLS_EXECUTOR_OVERRIDE.with(|tls| *tls.borrow_mut() = previous);
+        }
+    }
+    let _restore = Restore(previous);
+    f()
+}
+
+/// Raw pointers and non-Sync structures holding them can not cross threads. Work split by
+/// the executor only ever touch disjoint memory regions, so we vouch for them explicitly.
+#[derive(Copy, Clone, Debug)]
+pub struct Unchecked<T>(pub T);
+unsafe impl<T> Send for Unchecked<T> {}
+unsafe impl<T> Sync for Unchecked<T> {}
+
+impl<T: Copy> Unchecked<T> {
+    // going through a method makes closures capture the wrapper, not the wrapped field
+    pub fn get(self) -> T {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn scope_overrides_and_restores() {
+        assert!(!current_tract_executor().is_multithread());
+        let n = multithread_tract_scope(Executor::SingleThread, || {
+            let n = std::sync::atomic::AtomicUsize::new(0);
+            current_tract_executor()
+                .for_each(0..10, |ix| {
+                    n.fetch_add(ix, std::sync::atomic::Ordering::Relaxed);
+                    Ok(())
+                })
+                .unwrap();
+            n.into_inner()
+        });
+        assert_eq!(n, 45);
+    }
+
+    #[cfg(feature = "multithread-mm")]
+    #[test]
+    fn multithread_for_each() {
+        let executor = Executor::multithread(4).unwrap();
+        let n = std::sync::atomic::AtomicUsize::new(0);
+        executor
+            .for_each(0..100, |ix| {
+                n.fetch_add(ix, std::sync::atomic::Ordering::Relaxed);
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(n.into_inner(), 4950);
+    }
+}
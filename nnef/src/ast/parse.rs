@@ -31,6 +31,11 @@ pub fn parse_parameters(doc: &str) -> TractResult<Vec<Parameter>> {
     all_consuming(parameter_list)(doc).map(|pair| pair.1).map_err(translate_error)
 }
 
+#[inline(never)]
+pub fn parse_rvalue(doc: &str) -> TractResult<RValue> {
+    all_consuming(rvalue)(doc).map(|pair| pair.1).map_err(translate_error)
+}
+
 // <document> ::= <version> <extension>* <fragmentdefinition>* <graph-definition>
 fn document(i: &str) -> IResult<&str, Document> {
     map(
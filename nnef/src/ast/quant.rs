@@ -5,6 +5,7 @@ use nom::character::complete::digit1;
 use nom::combinator::{map_res, recognize};
 use nom::sequence::{delimited, pair};
 use tract_core::internal::*;
+use tract_itertools::Itertools;
 
 use nom::branch::alt;
 use nom::{bytes::complete::*, multi::*};
@@ -50,14 +51,36 @@ fn qparam(i: &str) -> IResult<&str, QuantFormat> {
             (i, QParams::MinMax { min, max }, bits, true)
         }
         "zero_point_linear_quantize" => {
-            let (i, (zero_point, scale, bits, signed, _)) = permutation((
-                arg("zero_point", integer_numeric),
-                arg("scale", float),
+            let (i, (zero_points, scales, bits, signed, _, axis)) = permutation((
+                arg("zero_point", scalar_or_list(integer_numeric)),
+                arg("scale", scalar_or_list(float)),
                 arg("bits", integer_numeric),
                 arg("signed", logical_literal),
                 opt(arg("symmetric", logical_literal)),
+                opt(arg("axis", integer_numeric::<usize>)),
             ))(i)?;
-            (i, QParams::ZpScale { zero_point, scale }, bits, signed)
+            let params = if let Some(axis) = axis {
+                let zero_points = if zero_points.len() == 1 {
+                    vec![zero_points[0]; scales.len()]
+                } else {
+                    zero_points
+                };
+                let Ok(params) = QParams::per_axis(axis, &zero_points, &scales) else {
+                    return Err(nom::Err::Failure(nom::error::Error::new(
+                        i,
+                        nom::error::ErrorKind::Verify,
+                    )));
+                };
+                params
+            } else if zero_points.len() == 1 && scales.len() == 1 {
+                QParams::ZpScale { zero_point: zero_points[0], scale: scales[0] }
+            } else {
+                return Err(nom::Err::Failure(nom::error::Error::new(
+                    i,
+                    nom::error::ErrorKind::Verify,
+                )));
+            };
+            (i, params, bits, signed)
         }
         _ => unreachable!(),
     };
@@ -65,6 +88,19 @@ fn qparam(i: &str) -> IResult<&str, QuantFormat> {
     let (i, _) = stag(")")(i)?;
     Ok((i, QuantFormat::Linear { params, bits, signed }))
 }
+// <scalar_or_list>(<f>) ::= <f> | "[" <f> ("," <f>)* "]"
+fn scalar_or_list<'s, T, F>(f: F) -> impl Fn(&'s str) -> IResult<&'s str, Vec<T>>
+where
+    F: Fn(&'s str) -> IResult<&'s str, T> + Copy,
+{
+    move |i: &str| {
+        alt((
+            delimited(stag("["), separated_list1(stag(","), f), stag("]")),
+            nom::combinator::map(f, |x| vec![x]),
+        ))(i)
+    }
+}
+
 // <arg>(<id>, <f>) ::= <id> "=" <f> ","
 fn arg<'s, T, F>(name: &'static str, f: F) -> impl Fn(&'s str) -> IResult<&'s str, T>
 where
@@ -93,6 +129,14 @@ pub(crate) fn write_quant_format(
         QuantFormat::Linear {
             params: QParams::MinMax {min, max}, bits, signed: _
         } => writeln!(w, ": linear_quantize(max = {max:.9}, min = {min:.9}, bits = {bits});")?,
+        QuantFormat::Linear {
+            params: QParams::PerAxis(params), bits, signed
+        } => {
+            let zero_points = params.zero_points.iter().join(", ");
+            let scales = params.scales.iter().map(|s| format!("{s:.9}")).join(", ");
+            let symmetric = params.zero_points.iter().all(|zp| *zp == 0);
+            writeln!(w, ": zero_point_linear_quantize(zero_point = [{zero_points}], scale = [{scales}], bits = {bits}, signed = {signed}, symmetric = {symmetric}, axis = {});", params.axis)?
+        }
     }
     Ok(())
 }
@@ -131,6 +175,27 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_per_axis_qparam() {
+        let format = QuantFormat::Linear {
+            params: QParams::per_axis(0, &[0, 0, 0], &[0.5, 0.25, 0.125]).unwrap(),
+            bits: 8,
+            signed: true,
+        };
+        assert_eq!(
+            p(
+                qparam,
+                "zero_point_linear_quantize(zero_point = 0, scale = [0.5, 0.25, 0.125], bits = 8, signed = true, axis = 0)"
+            ),
+            format
+        );
+        let mut buf = vec![];
+        write_quant_format(&mut buf, &"t".into(), format.clone(), false).unwrap();
+        let (id, parsed) = p(quantization, std::str::from_utf8(&buf).unwrap());
+        assert_eq!(id, "t".into());
+        assert_eq!(parsed, format);
+    }
+
     #[test]
     fn test_quantization() {
         assert_eq!(
@@ -0,0 +1,292 @@
+//! Programmatic edition of typed models.
+//!
+//! Operators are designated by the name they have in NNEF documents and resolved through the
+//! framework registries, exactly like the deserializer does: any operator that tract can load from
+//! NNEF can be wired this way.
+use tract_core::ops::konst::Const;
+
+use crate::ast::parse::parse_rvalue;
+use crate::ast::*;
+use crate::internal::*;
+
+/// Find an outlet by name.
+///
+/// `name` can be an outlet label, a `node:slot` pair, or a node name, designating its first
+/// output.
+pub fn outlet_by_name(model: &TypedModel, name: &str) -> TractResult<OutletId> {
+    if let Some(outlet) = model.find_outlet_label(name) {
+        return Ok(outlet);
+    }
+    if let Some((node, slot)) = name.rsplit_once(':') {
+        if let (Ok(node), Ok(slot)) = (model.node_id_by_name(node), slot.parse::<usize>()) {
+            if slot < model.node(node).outputs.len() {
+                return Ok(OutletId::new(node, slot));
+            }
+        }
+    }
+    if let Ok(node) = model.node_id_by_name(name) {
+        if model.node(node).outputs.len() > 0 {
+            return Ok(node.into());
+        }
+    }
+    bail!("No wire named {name:?} in model")
+}
+
+/// Check `name` designates neither a node nor an outlet of the model.
+pub fn ensure_name_is_free(model: &TypedModel, name: &str) -> TractResult<()> {
+    ensure!(
+        model.find_outlet_label(name).is_none() && model.nodes().iter().all(|n| n.name != name),
+        "Name {name:?} is already in use in model"
+    );
+    Ok(())
+}
+
+/// Replace the value of the constant `name`.
+///
+/// The new value must have the same datum type and shape as the previous one, so the facts in the
+/// rest of the model stay valid.
+pub fn replace_const(model: &mut TypedModel, name: &str, value: Arc<Tensor>) -> TractResult<()> {
+    let outlet = outlet_by_name(model, name)?;
+    let node = model.node(outlet.node);
+    ensure!(node.op_is::<Const>(), "{name:?} is not a constant, found {}", node.op.name());
+    let fact = model.outlet_fact(outlet)?;
+    ensure!(
+        fact.datum_type == value.datum_type() && fact.shape.as_concrete() == Some(value.shape()),
+        "Replacing {name:?} with a value of a different type: expected {fact:?}, got {value:?}"
+    );
+    model.node_mut(outlet.node).op = Box::new(Const::new(value.clone()));
+    model.set_outlet_fact(outlet, TypedFact::from(value))
+}
+
+impl Nnef {
+    /// Wire the operator known as `op` to NNEF in `model`.
+    ///
+    /// `inputs` are the names of existing wires (see [`outlet_by_name`]), passed as positional
+    /// arguments. `attributes` are passed as named arguments: their values are NNEF expressions
+    /// (`"[1, 1]"`, `"'constant'"`, `"true"`...) which can refer to wires by name.
+    ///
+    /// The outputs are labelled `name` if the operator has a single one, `name:0`, `name:1`...
+    /// otherwise.
+    pub fn wire_primitive(
+        &self,
+        model: &mut TypedModel,
+        name: &str,
+        op: &str,
+        inputs: &[&str],
+        attributes: &[(&str, &str)],
+    ) -> TractResult<TVec<OutletId>> {
+        ensure_name_is_free(model, name)?;
+        let mut arguments = vec![];
+        for input in inputs {
+            arguments.push(Argument { id: None, rvalue: RValue::Identifier((*input).into()) });
+        }
+        for (key, value) in attributes {
+            let rvalue = parse_rvalue(value)
+                .with_context(|| format!("Parsing attribute {key} = {value}"))?;
+            arguments.push(Argument { id: Some((*key).into()), rvalue });
+        }
+        // only the wires the invocation refers to are put in scope
+        let mut scope = HashMap::new();
+        for input in inputs {
+            scope.insert(Identifier::from(*input), Value::Wire(outlet_by_name(model, input)?));
+        }
+        let mut identifiers = vec![];
+        for arg in &arguments {
+            collect_identifiers(&arg.rvalue, &mut identifiers);
+        }
+        for id in identifiers {
+            if !scope.contains_key(id) {
+                if let Ok(outlet) = outlet_by_name(model, &id.0) {
+                    scope.insert(id.clone(), Value::Wire(outlet));
+                }
+            }
+        }
+        let invocation = Invocation { id: op.into(), generic_type_name: None, arguments };
+
+        let proto = ProtoModel {
+            doc: Document {
+                version: "1.0".into(),
+                extension: vec![],
+                fragments: vec![],
+                graph_def: GraphDef {
+                    id: "network".into(),
+                    parameters: vec![],
+                    results: vec![],
+                    body: vec![],
+                },
+            },
+            tensors: HashMap::new(),
+            quantization: None,
+            resources: HashMap::new(),
+        };
+        let node_count = model.nodes().len();
+        let mut builder = ModelBuilder::new(self, &proto, &model.symbol_table);
+        builder.registries = self.registries.iter().map(|r| r.id.clone()).collect();
+        builder.model = std::mem::take(model);
+        builder.scopes.push(scope);
+        builder.naming_scopes.push(name.into());
+        let outputs = builder
+            .wire_invocation(&invocation, &[])
+            .and_then(|value| value.to::<TVec<OutletId>>(&mut builder));
+        *model = builder.model;
+        if outputs.is_err() {
+            truncate(model, node_count);
+        }
+        let outputs = outputs.with_context(|| format!("Wiring {name:?} as {op:?}"))?;
+
+        if let [output] = &*outputs {
+            model.set_outlet_label(*output, name.to_string())?;
+        } else {
+            for (ix, output) in outputs.iter().enumerate() {
+                model.set_outlet_label(*output, format!("{name}:{ix}"))?;
+            }
+        }
+        Ok(outputs)
+    }
+}
+
+fn collect_identifiers<'a>(rvalue: &'a RValue, ids: &mut Vec<&'a Identifier>) {
+    match rvalue {
+        RValue::Identifier(id) => ids.push(id),
+        RValue::Literal(_) => (),
+        RValue::Binary(left, _, right) => {
+            collect_identifiers(left, ids);
+            collect_identifiers(right, ids);
+        }
+        RValue::Unary(_, rv) => collect_identifiers(rv, ids),
+        RValue::Tuple(rvs) | RValue::Array(rvs) => {
+            rvs.iter().for_each(|rv| collect_identifiers(rv, ids))
+        }
+        RValue::Subscript(rv, sub) => {
+            collect_identifiers(rv, ids);
+            match &**sub {
+                Subscript::Single(rv) => collect_identifiers(rv, ids),
+                Subscript::Range(from, to) => {
+                    from.iter().chain(to.iter()).for_each(|rv| collect_identifiers(rv, ids))
+                }
+            }
+        }
+        RValue::Comprehension(comp) => {
+            comp.loop_iters.iter().for_each(|(_, rv)| collect_identifiers(rv, ids));
+            comp.filter.iter().for_each(|rv| collect_identifiers(rv, ids));
+            collect_identifiers(&comp.yields, ids);
+        }
+        RValue::IfThenElse(ite) => {
+            collect_identifiers(&ite.cond, ids);
+            collect_identifiers(&ite.then, ids);
+            collect_identifiers(&ite.otherwise, ids);
+        }
+        RValue::Invocation(inv) => {
+            inv.arguments.iter().for_each(|arg| collect_identifiers(&arg.rvalue, ids))
+        }
+    }
+}
+
+/// Remove the nodes past the first `len` ones, and any reference to them.
+fn truncate(model: &mut TypedModel, len: usize) {
+    model.nodes.truncate(len);
+    for node in &mut model.nodes {
+        for output in &mut node.outputs {
+            output.successors.retain(|inlet| inlet.node < len);
+        }
+    }
+    model.outlet_labels.retain(|outlet, _| outlet.node < len);
+    model.inputs.retain(|outlet| outlet.node < len);
+    model.outputs.retain(|outlet| outlet.node < len);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn nnef() -> Nnef {
+        use crate::ops::tract_core;
+        let mut nnef = crate::nnef();
+        nnef.registries.push(tract_core());
+        nnef
+    }
+
+    #[test]
+    fn build_and_run() -> TractResult<()> {
+        let nnef = nnef();
+        let mut model = TypedModel::default();
+        model.add_source("x", f32::fact([2, 3]))?;
+        model.add_const("w", rctensor2(&[[1f32], [2.], [3.]]))?;
+        nnef.wire_primitive(&mut model, "mm", "matmul", &["x", "w"], &[])?;
+        nnef.wire_primitive(&mut model, "y", "add", &["mm", "mm"], &[])?;
+        nnef.wire_primitive(&mut model, "s", "unstack", &["x"], &[("axis", "1")])?;
+        model.set_output_names(["y", "s:1"])?;
+        let outputs =
+            model.into_runnable()?.run(tvec!(tensor2(&[[1f32, 1., 1.], [1., 0., 1.]]).into()))?;
+        assert_eq!(*outputs[0], tensor2(&[[12f32], [8.]]));
+        assert_eq!(*outputs[1], tensor1(&[1f32, 0.]));
+        Ok(())
+    }
+
+    #[test]
+    fn attribute_referring_to_wires() -> TractResult<()> {
+        let nnef = nnef();
+        let mut model = TypedModel::default();
+        model.add_source("a", f32::fact([2]))?;
+        model.add_source("b", f32::fact([2]))?;
+        nnef.wire_primitive(
+            &mut model,
+            "c",
+            "concat",
+            &[],
+            &[("values", "[a, b]"), ("axis", "0")],
+        )?;
+        model.set_output_names(["c"])?;
+        let outputs = model
+            .into_runnable()?
+            .run(tvec!(tensor1(&[1f32, 2.]).into(), tensor1(&[3f32, 4.]).into()))?;
+        assert_eq!(*outputs[0], tensor1(&[1f32, 2., 3., 4.]));
+        Ok(())
+    }
+
+    #[test]
+    fn errors() -> TractResult<()> {
+        let nnef = nnef();
+        let mut model = TypedModel::default();
+        model.add_source("x", f32::fact([2]))?;
+        assert!(nnef.wire_primitive(&mut model, "x", "relu", &["x"], &[]).is_err());
+        assert!(nnef.wire_primitive(&mut model, "y", "relu", &["z"], &[]).is_err());
+        assert!(nnef.wire_primitive(&mut model, "y", "not_an_op", &["x"], &[]).is_err());
+        assert_eq!(model.nodes().len(), 1);
+        nnef.wire_primitive(&mut model, "y", "relu", &["x"], &[])?;
+        Ok(())
+    }
+
+    #[test]
+    fn failing_fragment_leaves_model_untouched() -> TractResult<()> {
+        let nnef = nnef();
+        let mut model = TypedModel::default();
+        model.add_source("x", f32::fact([2, 3]))?;
+        model.add_const("w", rctensor2(&[[1f32, 2., 3.]]))?;
+        model.add_const("b", rctensor2(&[[0f32; 2]; 3]))?;
+        assert!(nnef.wire_primitive(&mut model, "y", "linear", &["x", "w", "b"], &[]).is_err());
+        assert_eq!(model.nodes().len(), 3);
+        assert!(model.nodes().iter().all(|n| n.outputs[0].successors.is_empty()));
+        nnef.wire_primitive(&mut model, "y", "linear", &["x", "w"], &[])?;
+        model.set_output_names(["y"])?;
+        let outputs =
+            model.into_runnable()?.run(tvec!(tensor2(&[[1f32, 1., 1.], [1., 0., 1.]]).into()))?;
+        assert_eq!(*outputs[0], tensor2(&[[6f32], [4.]]));
+        Ok(())
+    }
+
+    #[test]
+    fn replace_constant() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let x = model.add_source("x", f32::fact([2]))?;
+        let w = model.add_const("w", rctensor1(&[1f32, 2.]))?;
+        let y = model.wire_node("y", tract_core::ops::math::add(), &[x, w])?;
+        model.set_output_outlets(&y)?;
+        assert!(replace_const(&mut model, "w", rctensor1(&[1f32, 2., 3.])).is_err());
+        assert!(replace_const(&mut model, "x", rctensor1(&[1f32, 2.])).is_err());
+        replace_const(&mut model, "w", rctensor1(&[10f32, 20.]))?;
+        let outputs = model.into_runnable()?.run(tvec!(tensor1(&[1f32, 2.]).into()))?;
+        assert_eq!(*outputs[0], tensor1(&[11f32, 22.]));
+        Ok(())
+    }
+}
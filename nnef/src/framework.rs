@@ -3,6 +3,7 @@ use tract_core::tract_data::itertools::Itertools;
 
 use crate::ast::quant::write_quant_format;
 use crate::ast::{Document, Identifier, ProtoModel, QuantFormat};
+use crate::tensors::BlockQuantDat;
 use crate::{internal::*, nnef};
 use std::io::Read;
 #[cfg(target_family = "unix")]
@@ -135,14 +136,14 @@ impl Nnef {
             ar.append(&header, &mut &*quant_data).context("Appending graph.quant")?;
         }
 
-        for (label, t) in &proto_model.tensors {
-            let mut label = label.0.to_string() + ".dat";
+        for (label_id, t) in &proto_model.tensors {
+            let mut label = label_id.0.to_string() + ".dat";
             if label.starts_with('/') {
                 label.insert(0, '.');
             }
             let filename = std::path::Path::new(&label);
             let mut data = vec![];
-            crate::tensors::write_tensor(&mut data, t)
+            write_dat(&proto_model.resources, label_id, &mut data, t)
                 .with_context(|| format!("Serializing tensor {filename:?}: {t:?}"))?;
 
             let mut header = tar::Header::new_gnu();
@@ -204,17 +205,30 @@ impl Nnef {
             }
         }
 
-        for (label, t) in &proto_model.tensors {
-            let label = label.0.to_string() + ".dat";
+        for (label_id, t) in &proto_model.tensors {
+            let label = label_id.0.to_string() + ".dat";
             std::fs::create_dir_all(path.join(&label).parent().unwrap())?;
             let filename = path.join(label);
             let mut file = std::fs::File::create(filename)?;
-            crate::tensors::write_tensor(&mut file, t)?;
+            write_dat(&proto_model.resources, label_id, &mut file, t)?;
         }
         Ok(())
     }
 }
 
+fn write_dat(
+    resources: &HashMap<String, Arc<dyn Resource>>,
+    label: &Identifier,
+    w: &mut impl std::io::Write,
+    tensor: &Tensor,
+) -> TractResult<()> {
+    if let Some(bq) = resources.get(&label.0).and_then(|r| r.downcast_ref::<BlockQuantDat>()) {
+        crate::tensors::write_block_quant_tensor(w, tensor, bq.format, bq.k)
+    } else {
+        crate::tensors::write_tensor(w, tensor)
+    }
+}
+
 impl tract_core::prelude::Framework<ProtoModel, TypedModel> for Nnef {
     fn model_for_path(&self, p: impl AsRef<Path>) -> TractResult<TypedModel> {
         let proto = self.proto_model_for_path(p)?;
@@ -3,6 +3,7 @@
 extern crate log;
 
 pub mod ast;
+pub mod builder;
 pub mod deser;
 pub mod framework;
 pub mod ops;
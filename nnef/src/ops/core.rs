@@ -1,16 +1,21 @@
 use crate::internal::*;
 use tract_core::ops;
 
+mod block_quant;
 mod broadcast;
 mod cast;
 #[cfg(feature = "complex")]
 mod complex;
+mod deform_im2col;
 mod downsample;
 mod dyn_slice;
 mod einsum;
 mod fft;
 mod force_eval;
 mod gather;
+mod gelu;
+mod kv_cache;
+mod layer_norm;
 mod load;
 mod matmul;
 mod one_hot;
@@ -20,6 +25,7 @@ mod range;
 mod reduce;
 mod scan;
 mod scatter;
+mod sdpa;
 mod shape_of;
 mod softmax;
 mod source;
@@ -27,6 +33,7 @@ mod store;
 mod submodel;
 mod topk;
 mod trilu;
+mod while_loop;
 
 pub fn register(registry: &mut Registry) {
     registry.register_unit_element_wise("tract_core_round_even", &ops::math::RoundHalfToEven {});
@@ -41,16 +48,21 @@ pub fn register(registry: &mut Registry) {
 
     registry.register_binary("tract_shl", &ops::math::ShiftLeft);
     registry.register_binary("tract_shr", &ops::math::ShiftRight);
+    block_quant::register(registry);
     broadcast::register(registry);
     cast::register(registry);
     #[cfg(feature = "complex")]
     complex::register(registry);
+    deform_im2col::register(registry);
     downsample::register(registry);
     dyn_slice::register(registry);
     einsum::register(registry);
     fft::register(registry);
     force_eval::register(registry);
     gather::register(registry);
+    gelu::register(registry);
+    kv_cache::register(registry);
+    layer_norm::register(registry);
     load::register(registry);
     matmul::register(registry);
     one_hot::register(registry);
@@ -59,6 +71,7 @@ pub fn register(registry: &mut Registry) {
     reduce::register(registry);
     scan::register(registry);
     scatter::register(registry);
+    sdpa::register(registry);
     shape_of::register(registry);
     softmax::register(registry);
     source::register(registry);
@@ -67,4 +80,5 @@ pub fn register(registry: &mut Registry) {
     range::register(registry);
     topk::register(registry);
     trilu::register(registry);
+    while_loop::register(registry);
 }
@@ -0,0 +1,109 @@
+use crate::internal::*;
+use crate::ser::*;
+use crate::tensors::BlockQuantDat;
+use tract_core::ops::matmul::block_quant::{BlockQuant, BlockQuantMatMul};
+
+pub fn register(registry: &mut Registry) {
+    registry.register_dumper(ser_block_quant_matmul);
+    registry.register_primitive(
+        "tract_core_block_quant_matmul",
+        &[
+            TypeName::Integer.tensor().named("weights"),
+            TypeName::Scalar.tensor().named("input"),
+            TypeName::String.named("format"),
+            TypeName::Integer.named("m"),
+            TypeName::Integer.named("k"),
+        ],
+        &[("output", TypeName::Scalar.tensor())],
+        de_block_quant_matmul,
+    );
+}
+
+fn ser_block_quant_matmul(
+    ast: &mut IntoAst,
+    node: &TypedNode,
+    op: &BlockQuantMatMul,
+) -> TractResult<Option<Arc<RValue>>> {
+    let weights = ast.mapping[&node.inputs[0]].clone();
+    let input = ast.mapping[&node.inputs[1]].clone();
+    // constant weights go to a .dat file recording their block format
+    if let Some(konst) = &ast.model.outlet_fact(node.inputs[0])?.konst {
+        if let Some(label) =
+            ast.tensors.iter().find(|(_, t)| Arc::ptr_eq(t, konst) || t == &konst).map(|p| p.0)
+        {
+            let dat = BlockQuantDat { format: op.format, k: op.k };
+            ast.resources.insert(label.0.clone(), Arc::new(dat));
+        }
+    }
+    Ok(Some(invocation(
+        "tract_core_block_quant_matmul",
+        &[weights, input],
+        &[("format", string(op.format.to_string())), ("m", numeric(op.m)), ("k", numeric(op.k))],
+    )))
+}
+
+fn de_block_quant_matmul(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<Value> {
+    let weights = invocation.named_arg_as(builder, "weights")?;
+    let input = invocation.named_arg_as(builder, "input")?;
+    let format: String = invocation.named_arg_as(builder, "format")?;
+    let format: BlockQuant = format.parse()?;
+    let m = invocation.named_arg_as::<i64>(builder, "m")? as usize;
+    let k = invocation.named_arg_as::<i64>(builder, "k")? as usize;
+    builder.wire(BlockQuantMatMul::new(format, m, k), &[weights, input])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::Read;
+
+    fn model(format: BlockQuant) -> TractResult<TypedModel> {
+        let (m, k) = (3, 64);
+        let weights: Vec<f32> = (0..m * k).map(|i| ((i * 5 % 11) as f32 - 5.) / 3.).collect();
+        let weights =
+            tensor1(&format.quant_f32(&weights, k)?).into_shape(&[m, format.row_bytes(k)])?;
+        let mut model = TypedModel::default();
+        let input = model.add_source("input", f32::fact([2, k]))?;
+        let weights = model.add_const("weights", weights)?;
+        let output =
+            model.wire_node("matmul", BlockQuantMatMul::new(format, m, k), &[weights, input])?;
+        model.set_output_outlets(&output)?;
+        Ok(model)
+    }
+
+    #[test]
+    fn round_trip() -> TractResult<()> {
+        let nnef = crate::nnef().with_tract_core();
+        for format in [BlockQuant::Q8_0, BlockQuant::Q4_0] {
+            let model = model(format)?;
+            let buffer = nnef.write_to_tar(&model, vec![])?;
+            // the weights header records the [m, k] matrix they encode
+            let mut archive = tar::Archive::new(&*buffer);
+            let mut entry = archive
+                .entries()?
+                .map(|e| e.unwrap())
+                .find(|e| e.path().unwrap().to_str() == Some("weights.dat"))
+                .context("No weights.dat")?;
+            let mut dat = vec![];
+            entry.read_to_end(&mut dat)?;
+            assert_eq!(&dat[12..20], &[3, 0, 0, 0, 64, 0, 0, 0]);
+
+            let reloaded = nnef.model_for_read(&mut &*buffer)?;
+            let reloaded_op = reloaded
+                .nodes()
+                .iter()
+                .find_map(|n| n.op_as::<BlockQuantMatMul>())
+                .context("BlockQuantMatMul not reloaded")?;
+            assert_eq!(reloaded_op, &BlockQuantMatMul::new(format, 3, 64));
+            let input = tensor1(&(0..128).map(|i| (i as f32 * 0.3).sin()).collect::<Vec<_>>())
+                .into_shape(&[2, 64])?;
+            let expected = model.into_runnable()?.run(tvec!(input.clone().into()))?;
+            let found = reloaded.into_runnable()?.run(tvec!(input.into()))?;
+            assert_eq!(found, expected);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,155 @@
+use crate::internal::*;
+use crate::ser::*;
+use tract_core::ops::cnn::{DeformIm2Col, PaddingSpec, PoolSpec};
+use tract_core::ops::nn::DataFormat;
+
+pub fn register(registry: &mut Registry) {
+    registry.register_dumper(ser_deform_im2col);
+    registry.register_primitive(
+        "tract_core_deform_im2col",
+        &[
+            TypeName::Scalar.tensor().named("input"),
+            TypeName::Scalar.tensor().named("offsets"),
+            TypeName::Scalar.tensor().named("mask").default(false),
+            TypeName::Integer.array().named("kernel_shape"),
+            TypeName::Integer.array().named("dilation"),
+            TypeName::Integer.array().named("stride"),
+            TypeName::Integer.array().array().named("padding"),
+            TypeName::Integer.named("offset_group"),
+        ],
+        &[("output", TypeName::Scalar.tensor())],
+        de_deform_im2col,
+    );
+}
+
+fn ser_deform_im2col(
+    ast: &mut IntoAst,
+    node: &TypedNode,
+    op: &DeformIm2Col,
+) -> TractResult<Option<Arc<RValue>>> {
+    let input = ast.mapping[&node.inputs[0]].clone();
+    let offsets = ast.mapping[&node.inputs[1]].clone();
+    let pool_spec = &op.pool_spec;
+    let (before, after) = match &pool_spec.padding {
+        PaddingSpec::Explicit(before, after) => (before.clone(), after.clone()),
+        PaddingSpec::Valid => (tvec!(0; pool_spec.rank()), tvec!(0; pool_spec.rank())),
+        _ => return Ok(None),
+    };
+    let padding = array(
+        before
+            .iter()
+            .zip(after.iter())
+            .map(|(b, a)| tuple_2(numeric(b), numeric(a)))
+            .collect::<Vec<_>>(),
+    );
+    let mut named = tvec![
+        ("kernel_shape", ints(&pool_spec.kernel_shape)),
+        ("dilation", ints(&pool_spec.dilations())),
+        ("stride", ints(&pool_spec.strides())),
+        ("padding", padding),
+        ("offset_group", numeric(op.offset_group)),
+    ];
+    if let Some(mask) = node.inputs.get(2) {
+        named.push(("mask", (*ast.mapping[mask]).clone()));
+    }
+    Ok(Some(invocation("tract_core_deform_im2col", &[input, offsets], &named)))
+}
+
+fn de_deform_im2col(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<Value> {
+    let mut inputs: TVec<OutletId> = tvec!(
+        invocation.named_arg_as(builder, "input")?,
+        invocation.named_arg_as(builder, "offsets")?
+    );
+    if let Some(mask) = invocation.optional_named_arg_as(builder, "mask")? {
+        inputs.push(mask);
+    }
+    let kernel_shape: TVec<usize> = invocation.named_arg_as(builder, "kernel_shape")?;
+    let dilations: TVec<usize> = invocation.named_arg_as(builder, "dilation")?;
+    let strides: TVec<usize> = invocation.named_arg_as(builder, "stride")?;
+    let padding: TVec<TVec<usize>> = invocation.named_arg_as(builder, "padding")?;
+    let offset_group = invocation.named_arg_as(builder, "offset_group")?;
+    let padding = PaddingSpec::Explicit(
+        padding.iter().map(|p| p[0]).collect(),
+        padding.iter().map(|p| p[1]).collect(),
+    );
+    let channels = builder
+        .model
+        .outlet_fact(inputs[0])?
+        .shape
+        .get(1)
+        .and_then(|c| c.to_usize().ok())
+        .context("DeformIm2Col expects known input channels")?;
+    let pool_spec = PoolSpec::new(
+        DataFormat::NCHW,
+        kernel_shape,
+        padding,
+        Some(dilations),
+        Some(strides),
+        channels,
+        channels,
+    );
+    builder.wire(DeformIm2Col::new(pool_spec, offset_group), &inputs)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn model(with_mask: bool) -> TractResult<TypedModel> {
+        let pool_spec = PoolSpec::new(
+            DataFormat::NCHW,
+            tvec!(2, 2),
+            PaddingSpec::Explicit(tvec!(1, 0), tvec!(0, 1)),
+            Some(tvec!(1, 2)),
+            Some(tvec!(2, 1)),
+            2,
+            2,
+        );
+        let mut model = TypedModel::default();
+        let mut inputs = tvec!(
+            model.add_source("input", f32::fact([1, 2, 3, 4]))?,
+            model.add_source("offsets", f32::fact([1, 8, 2, 3]))?,
+        );
+        if with_mask {
+            inputs.push(model.add_source("mask", f32::fact([1, 4, 2, 3]))?);
+        }
+        let output = model.wire_node("im2col", DeformIm2Col::new(pool_spec, 1), &inputs)?;
+        model.set_output_outlets(&output)?;
+        Ok(model)
+    }
+
+    fn ramp(shape: &[usize], scale: f32) -> TractResult<TValue> {
+        let len = shape.iter().product::<usize>();
+        let data = (0..len).map(|i| (i as f32 * scale).sin() * 1.5).collect::<Vec<_>>();
+        Ok(tensor1(&data).into_shape(shape)?.into_tvalue())
+    }
+
+    #[test]
+    fn round_trip() -> TractResult<()> {
+        let nnef = crate::nnef().with_tract_core();
+        for with_mask in [false, true] {
+            let model = model(with_mask)?;
+            let buffer = nnef.write_to_tar(&model, vec![])?;
+            let reloaded = nnef.model_for_read(&mut &*buffer)?;
+            let reloaded_op = reloaded
+                .nodes()
+                .iter()
+                .find_map(|n| n.op_as::<DeformIm2Col>())
+                .context("DeformIm2Col not reloaded")?;
+            assert_eq!(reloaded_op.pool_spec.kernel_shape, tvec!(2, 2));
+            assert_eq!(&*reloaded_op.pool_spec.dilations(), &[1, 2]);
+            assert_eq!(&*reloaded_op.pool_spec.strides(), &[2, 1]);
+            let mut inputs = tvec!(ramp(&[1, 2, 3, 4], 1.)?, ramp(&[1, 8, 2, 3], 0.7)?);
+            if with_mask {
+                inputs.push(ramp(&[1, 4, 2, 3], 0.3)?);
+            }
+            let expected = model.into_runnable()?.run(inputs.clone())?;
+            let found = reloaded.into_runnable()?.run(inputs)?;
+            assert_eq!(found, expected);
+        }
+        Ok(())
+    }
+}
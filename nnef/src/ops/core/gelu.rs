@@ -0,0 +1,30 @@
+use crate::internal::*;
+use crate::ser::*;
+use tract_core::ops::element_wise::ElementWiseOp;
+use tract_core::ops::nn::{gelu, Gelu};
+
+pub fn register(registry: &mut Registry) {
+    registry.register_element_wise(
+        "tract_core_gelu",
+        TypeId::of::<Gelu>(),
+        Box::new(ser_gelu),
+        vec![
+            TypeName::Scalar.tensor().named("x"),
+            TypeName::Logical.named("approximate").default(false),
+        ],
+        de_gelu,
+    );
+}
+
+fn ser_gelu(ast: &mut IntoAst, node: &TypedNode) -> TractResult<Option<Arc<RValue>>> {
+    let op = node.op_as::<ElementWiseOp>().context("Wrong op")?;
+    let op = op.0.downcast_ref::<Gelu>().context("Wrong op")?;
+    let x = ast.mapping[&node.inputs[0]].clone();
+    Ok(Some(invocation("tract_core_gelu", &[x], &[("approximate", logical(op.approximate))])))
+}
+
+fn de_gelu(builder: &mut ModelBuilder, invocation: &ResolvedInvocation) -> TractResult<Value> {
+    let x = invocation.named_arg_as(builder, "x")?;
+    let approximate = invocation.named_arg_as(builder, "approximate")?;
+    builder.wire(gelu(approximate), &[x])
+}
@@ -0,0 +1,38 @@
+use crate::internal::*;
+use tract_core::ops::kv_cache::KvCache;
+
+pub fn register(registry: &mut Registry) {
+    registry.register_dumper(ser_kv_cache);
+    registry.register_primitive(
+        "tract_core_kv_cache",
+        &[
+            TypeName::Scalar.tensor().named("input"),
+            TypeName::Integer.named("axis"),
+            TypeName::String.named("past"),
+        ],
+        &[("output", TypeName::Scalar.tensor())],
+        de_kv_cache,
+    );
+}
+
+fn ser_kv_cache(
+    ast: &mut IntoAst,
+    node: &TypedNode,
+    op: &KvCache,
+) -> TractResult<Option<Arc<RValue>>> {
+    let input = ast.mapping[&node.inputs[0]].clone();
+    ast.ensure_symbol(&op.past)?;
+    Ok(Some(invocation(
+        "tract_core_kv_cache",
+        &[input],
+        &[("axis", numeric(op.axis)), ("past", string(op.past.to_string()))],
+    )))
+}
+
+fn de_kv_cache(builder: &mut ModelBuilder, invocation: &ResolvedInvocation) -> TractResult<Value> {
+    let input = invocation.named_arg_as(builder, "input")?;
+    let axis = invocation.named_arg_as::<i64>(builder, "axis")? as usize;
+    let past: String = invocation.named_arg_as(builder, "past")?;
+    let past = builder.model.symbol_table.sym(&past);
+    builder.wire(KvCache::new(axis, past), &[input])
+}
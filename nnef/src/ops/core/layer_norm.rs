@@ -0,0 +1,72 @@
+use crate::ast;
+use crate::internal::*;
+use crate::ser::*;
+use tract_core::ops::nn::{LayerNorm, RmsNorm};
+
+pub fn register(registry: &mut Registry) {
+    registry.register_dumper(ser_layer_norm);
+    registry.register_primitive(
+        "tract_core_layer_norm",
+        &parameters(),
+        &[("output", TypeName::Scalar.tensor())],
+        de_layer_norm,
+    );
+    registry.register_dumper(ser_rms_norm);
+    registry.register_primitive(
+        "tract_core_rms_norm",
+        &parameters(),
+        &[("output", TypeName::Scalar.tensor())],
+        de_rms_norm,
+    );
+}
+
+fn parameters() -> Vec<ast::Parameter> {
+    vec![
+        TypeName::Scalar.tensor().named("input"),
+        TypeName::Integer.array().named("axes"),
+        TypeName::Scalar.named("eps"),
+    ]
+}
+
+fn ser_layer_norm(
+    ast: &mut IntoAst,
+    node: &TypedNode,
+    op: &LayerNorm,
+) -> TractResult<Option<Arc<RValue>>> {
+    let input = ast.mapping[&node.inputs[0]].clone();
+    Ok(Some(invocation(
+        "tract_core_layer_norm",
+        &[input],
+        &[("axes", ints(&op.axes)), ("eps", numeric(op.eps))],
+    )))
+}
+
+fn de_layer_norm(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<Value> {
+    let input = invocation.named_arg_as(builder, "input")?;
+    let axes = invocation.named_arg_as(builder, "axes")?;
+    let eps = invocation.named_arg_as(builder, "eps")?;
+    builder.wire(LayerNorm { axes, eps }, &[input])
+}
+
+fn ser_rms_norm(
+    ast: &mut IntoAst,
+    node: &TypedNode,
+    op: &RmsNorm,
+) -> TractResult<Option<Arc<RValue>>> {
+    let input = ast.mapping[&node.inputs[0]].clone();
+    Ok(Some(invocation(
+        "tract_core_rms_norm",
+        &[input],
+        &[("axes", ints(&op.axes)), ("eps", numeric(op.eps))],
+    )))
+}
+
+fn de_rms_norm(builder: &mut ModelBuilder, invocation: &ResolvedInvocation) -> TractResult<Value> {
+    let input = invocation.named_arg_as(builder, "input")?;
+    let axes = invocation.named_arg_as(builder, "axes")?;
+    let eps = invocation.named_arg_as(builder, "eps")?;
+    builder.wire(RmsNorm { axes, eps }, &[input])
+}
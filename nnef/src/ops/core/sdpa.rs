@@ -0,0 +1,48 @@
+use crate::internal::*;
+use crate::ser::*;
+use tract_core::ops::nn::ScaledDotProductAttention;
+
+pub fn register(registry: &mut Registry) {
+    registry.register_dumper(ser_sdpa);
+    registry.register_primitive(
+        "tract_core_scaled_dot_product_attention",
+        &[
+            TypeName::Scalar.tensor().named("q"),
+            TypeName::Scalar.tensor().named("k"),
+            TypeName::Scalar.tensor().named("v"),
+            TypeName::Scalar.tensor().named("mask").default(0.0),
+            TypeName::Scalar.named("scale"),
+            TypeName::Logical.named("causal").default(false),
+        ],
+        &[("output", TypeName::Scalar.tensor())],
+        de_sdpa,
+    );
+}
+
+fn ser_sdpa(
+    ast: &mut IntoAst,
+    node: &TypedNode,
+    op: &ScaledDotProductAttention,
+) -> TractResult<Option<Arc<RValue>>> {
+    let inputs: TVec<Arc<RValue>> = node.inputs.iter().map(|i| ast.mapping[i].clone()).collect();
+    let mut named = vec![("scale", numeric(op.scale)), ("causal", logical(op.causal))];
+    if let Some(mask) = inputs.get(3) {
+        named.push(("mask", mask.as_ref().clone()));
+    }
+    Ok(Some(invocation("tract_core_scaled_dot_product_attention", &inputs[..3], &named)))
+}
+
+fn de_sdpa(builder: &mut ModelBuilder, invocation: &ResolvedInvocation) -> TractResult<Value> {
+    let q = invocation.named_arg_as(builder, "q")?;
+    let k = invocation.named_arg_as(builder, "k")?;
+    let v = invocation.named_arg_as(builder, "v")?;
+    let mask: OutletId = invocation.named_arg_as(builder, "mask")?;
+    let scale = invocation.named_arg_as(builder, "scale")?;
+    let causal = invocation.named_arg_as(builder, "causal")?;
+    let mut inputs = tvec!(q, k, v);
+    // a scalar mask is the default value: no mask
+    if builder.model.outlet_fact(mask)?.rank() > 0 {
+        inputs.push(mask);
+    }
+    builder.wire(ScaledDotProductAttention { scale, causal }, &inputs)
+}
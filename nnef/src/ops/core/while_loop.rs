@@ -0,0 +1,155 @@
+use crate::ast;
+use crate::ast::Identifier;
+use crate::deser::Value;
+use crate::internal::*;
+use crate::ser::*;
+use tract_core::ops::logic::WhileLoop;
+use tract_itertools::Itertools;
+
+pub fn register(registry: &mut Registry) {
+    registry.register_dumper(ser_while_loop);
+    registry.register_primitive(
+        "tract_core_while_loop",
+        &[
+            TypeName::String.named("body"),
+            ast::TypeSpec::Tuple(vec![
+                TypeName::String.spec(),   // body param name
+                TypeName::Scalar.tensor(), // input
+            ])
+            .array()
+            .named("inputs"),
+            TypeName::Integer.named("carried"),
+        ],
+        &[("outputs", TypeName::Scalar.tensor().array())],
+        de_while_loop,
+    );
+}
+
+fn ser_while_loop(
+    ast: &mut IntoAst,
+    node: &TypedNode,
+    op: &WhileLoop,
+) -> TractResult<Option<Arc<RValue>>> {
+    let (mut body, body_tensors) = crate::ser::to_fragment_def(ast, &op.body)?;
+    body.decl.id = Identifier(format!("while_loop_body_{}", ast.fragments.len()));
+    let mut inputs = vec![];
+    for (slot, input) in node.inputs.iter().enumerate() {
+        let name = string(&body.decl.parameters[slot].id.0);
+        inputs.push(tuple_2(name, ast.mapping[input].as_ref().clone()));
+    }
+    for tensor in body_tensors.iter().sorted_by_key(|t| &t.parameter_id) {
+        let t = ast.konst_variable(&tensor.label, &tensor.value)?;
+        inputs.push(tuple_2(string(&tensor.parameter_id), t.as_ref().clone()));
+    }
+    let invoke = invocation(
+        "tract_core_while_loop",
+        &[],
+        &[
+            ("body", string(&body.decl.id)),
+            ("inputs", array(inputs)),
+            ("carried", numeric(op.carried)),
+        ],
+    );
+    ast.fragments.insert(body.decl.id.clone(), body);
+    Ok(Some(invoke))
+}
+
+fn de_while_loop(
+    builder: &mut ModelBuilder,
+    invocation: &ResolvedInvocation,
+) -> TractResult<Value> {
+    let fragment_name: String = invocation.named_arg_as(builder, "body")?;
+    let fragment = builder
+        .proto_model
+        .doc
+        .fragments
+        .iter()
+        .find(|n| n.decl.id.0 == fragment_name)
+        .ok_or_else(|| format_err!("Cound not find fragment `{}'", fragment_name))?;
+    let inputs: TVec<(String, OutletId)> = invocation.named_arg_as(builder, "inputs")?;
+    let carried: usize = invocation.named_arg_as(builder, "carried")?;
+    let mut body =
+        ModelBuilder::new(builder.framework, builder.proto_model, &builder.model.symbol_table);
+    body.scopes.push(HashMap::new());
+    body.naming_scopes = builder.naming_scopes.clone();
+    body.registries = builder.registries.clone();
+    let mut outer_inputs: TVec<OutletId> = tvec!();
+    for par in &fragment.decl.parameters {
+        let Some((_, wire)) = inputs.iter().find(|i| i.0 == par.id.0) else {
+            bail!("Unbound body input parameter {}", par.id.0);
+        };
+        let fact = builder.model.outlet_fact(*wire)?;
+        let fact = fact.datum_type.fact(fact.shape.clone());
+        outer_inputs.push(*wire);
+        body.scopes.last_mut().unwrap().insert(
+            par.id.clone(),
+            Value::Wire(body.model.add_source(par.id.0.to_string(), fact)?),
+        );
+    }
+    body.wire_body(fragment.body.as_deref().unwrap()).context("wiring while loop body")?;
+    let body_outputs = fragment
+        .decl
+        .results
+        .iter()
+        .map(|r| {
+            body.scopes.last().unwrap().get(&r.id).with_context(|| {
+                format!("Could not find variable for while loop output named `{}'", r.id.0)
+            })
+        })
+        .collect::<TractResult<Vec<&Value>>>()?
+        .iter()
+        .map(|v| v.to::<OutletId>(builder))
+        .collect::<TractResult<Vec<OutletId>>>()?;
+    body.model.set_output_outlets(&body_outputs)?;
+    let iterations = builder.model.symbol_table.new_with_prefix("loop");
+    builder.wire(WhileLoop::new(body.model, carried, iterations)?, &outer_inputs)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tract_core::ops::math;
+
+    // sums 1.. while the sum stays below a limit, also outputting the partial sums
+    fn model() -> TractResult<TypedModel> {
+        let mut body = TypedModel::default();
+        let iteration = body.add_source("iteration", i64::scalar_fact())?;
+        let _cond = body.add_source("cond", bool::scalar_fact())?;
+        let sum = body.add_source("sum", i64::scalar_fact())?;
+        let limit = body.add_source("limit", i64::scalar_fact())?;
+        let one = body.add_const("one", tensor0(1i64))?;
+        let next = body.wire_node("next", math::add(), &[iteration, one])?[0];
+        let sum = body.wire_node("new_sum", math::add(), &[sum, next])?[0];
+        let cond = body.wire_node("cond_out", tract_core::ops::logic::less(), &[sum, limit])?[0];
+        let partial = body.wire_node("partial", AxisOp::Add(0), &[sum])?[0];
+        body.set_output_outlets(&[cond, sum, partial])?;
+
+        let mut model = TypedModel::default();
+        let op = WhileLoop::new(body, 1, model.symbol_table.sym("n"))?;
+        let inputs = tvec!(
+            model.add_source("max", i64::scalar_fact())?,
+            model.add_const("cond", tensor0(true))?,
+            model.add_source("init", i64::scalar_fact())?,
+            model.add_const("limit", tensor0(10i64))?,
+        );
+        let outputs = model.wire_node("loop", op, &inputs)?;
+        model.set_output_outlets(&outputs)?;
+        Ok(model)
+    }
+
+    #[test]
+    fn round_trip() -> TractResult<()> {
+        let nnef = crate::nnef().with_tract_core();
+        let model = model()?;
+        let buffer = nnef.write_to_tar(&model, vec![])?;
+        let reloaded = nnef.model_for_read(&mut &*buffer)?;
+        assert!(reloaded.nodes().iter().any(|n| n.op_is::<WhileLoop>()));
+        for max in [0i64, 2, 100] {
+            let inputs = tvec!(tensor0(max).into(), tensor0(0i64).into());
+            let expected = model.clone().into_runnable()?.run(inputs.clone())?;
+            let found = reloaded.clone().into_optimized()?.into_runnable()?.run(inputs)?;
+            assert_eq!(found, expected);
+        }
+        Ok(())
+    }
+}
@@ -8,6 +8,7 @@ use tract_core::ops::cast::cast;
 use tract_core::ops::cnn::deconv::adjustments;
 use tract_core::ops::cnn::PaddingSpec;
 use tract_core::ops::cnn::PoolSpec;
+use tract_core::ops::matmul::mir_quant::{link_per_axis_qparams, wire_zp_scale, zp_scale_tensors};
 use tract_core::ops::nn::{DataFormat, Softmax, SoftmaxExp};
 use tract_itertools::izip;
 use tract_itertools::Itertools;
@@ -374,10 +375,19 @@ pub fn conv_or_deconv(
         Box::new(Deconv::new(pool_spec, KernelFormat::OIHW, adjustments, group))
     } else {
         if let Some(odt) = &output_dt {
-            for dt in &[&input_fact.datum_type, &kernel_fact.datum_type, odt] {
-                let qp = dt.qparams().unwrap_or_default();
-                inputs.push(builder.add_const(tensor0(qp.zp_scale().0))?);
-                inputs.push(builder.add_const(tensor0(qp.zp_scale().1))?);
+            ensure!(
+                !input_fact.datum_type.is_per_axis_quantized() && !odt.is_per_axis_quantized(),
+                "Only the kernel of a quantized convolution can use per-axis quantization"
+            );
+            if let Some(params) =
+                kernel_fact.datum_type.qparams().and_then(|qp| qp.per_axis_params())
+            {
+                ensure!(params.axis == 0, "Per-axis quantized kernel must be quantized along O");
+            }
+            for dt in &[input_fact.datum_type, kernel_fact.datum_type, *odt] {
+                let (zp, scale) = zp_scale_tensors(*dt);
+                inputs.push(builder.add_const(zp)?);
+                inputs.push(builder.add_const(scale)?);
             }
         }
         Box::new(Conv::new(pool_spec, KernelFormat::OIHW, group, output_dt))
@@ -564,22 +574,24 @@ pub fn matmul(builder: &mut ModelBuilder, invocation: &ResolvedInvocation) -> Tr
             axes = axes.with_extra_input(2 + input)?;
         }
         let accum_dt = DatumType::QI32(QParams::ZpScale {
-            scale: a_dt.zp_scale().1 * b_dt.zp_scale().1,
+            scale: a_dt.try_zp_scale()?.1 * b_dt.try_zp_scale()?.1,
             zero_point: 0,
         });
-        let c_dt = invocation.dt_from_quant_file.first().cloned().flatten().unwrap_or(accum_dt);
+        let c_dt = invocation.dt_from_quant_file.first().cloned().flatten();
+        ensure!(
+            c_dt.is_some() || !(a_dt.is_per_axis_quantized() || b_dt.is_per_axis_quantized()),
+            "Matmul with per-axis quantized inputs requires an explicit output quantization"
+        );
+        let c_dt = c_dt.unwrap_or(accum_dt);
+        ensure!(!c_dt.is_per_axis_quantized(), "Per-axis quantized matmul output is not supported");
+        axes = link_per_axis_qparams(axes, a_dt, 0, [3, 4])?;
+        axes = link_per_axis_qparams(axes, b_dt, 1, [5, 6])?;
 
-        let a_qp = a_dt.qparams().unwrap_or_default().zp_scale();
-        let b_qp = b_dt.qparams().unwrap_or_default().zp_scale();
-        let c_qp = c_dt.qparams().unwrap_or_default().zp_scale();
         let bias =
             builder.model.add_const(format!("{name}.bias"), Tensor::zero_scalar_dt(accum_dt)?)?;
-        let a0 = builder.model.add_const(format!("{name}.a0"), rctensor0(a_qp.0))?;
-        let a_scale = builder.model.add_const(format!("{name}.a_scale"), rctensor0(a_qp.1))?;
-        let b0 = builder.model.add_const(format!("{name}.b0"), rctensor0(b_qp.0))?;
-        let b_scale = builder.model.add_const(format!("{name}.b_scale"), rctensor0(b_qp.1))?;
-        let c0 = builder.model.add_const(format!("{name}.c0"), rctensor0(c_qp.0))?;
-        let c_scale = builder.model.add_const(format!("{name}.c_scale"), rctensor0(c_qp.1))?;
+        let (a0, a_scale) = wire_zp_scale(&mut builder.model, &format!("{name}.a"), a_dt)?;
+        let (b0, b_scale) = wire_zp_scale(&mut builder.model, &format!("{name}.b"), b_dt)?;
+        let (c0, c_scale) = wire_zp_scale(&mut builder.model, &format!("{name}.c"), c_dt)?;
 
         builder.wire(
             ops::einsum::EinSum { axes, operating_dt: i32::datum_type(), q_params: Some(c_dt) },
@@ -359,6 +359,11 @@ impl<'a> IntoAst<'a> {
                     string(f)
                 })
                 .into());
+            } else if tensor.datum_type() == DatumType::Bool {
+                return Ok(Self::dump_rec_tensor(&tensor.to_array_view::<bool>()?, |b| {
+                    logical(*b)
+                })
+                .into());
             } else if tensor.datum_type() == DatumType::F32 {
                 return Ok(
                     Self::dump_rec_tensor(&tensor.to_array_view::<f32>()?, |f| numeric(f)).into()
@@ -1,7 +1,20 @@
 use byteorder::{ReadBytesExt, WriteBytesExt, LE};
 use tract_core::internal::*;
+use tract_core::ops::matmul::block_quant::BlockQuant;
 
 const TRACT_ITEM_TYPE_VENDOR: u16 = (b'T' as u16) << 8u16 | b'R' as u16;
+const TRACT_ITEM_TYPE_Q8_0: u16 = 0x2000;
+const TRACT_ITEM_TYPE_Q4_0: u16 = 0x2001;
+
+/// Marks a `[m, row_bytes(k)]` u8 tensor as block-quantized weights of a `[m, k]` matrix, so it
+/// is written to its `.dat` file with the block format in the header.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BlockQuantDat {
+    pub format: BlockQuant,
+    pub k: usize,
+}
+
+impl crate::resource::Resource for BlockQuantDat {}
 
 #[repr(C)]
 #[derive(Debug)]
@@ -62,6 +75,29 @@ pub fn read_tensor<R: std::io::Read>(mut reader: R) -> TractResult<Tensor> {
             bail!("Unknownn item type vendor {}", header.item_type_vendor);
         }
 
+        // block-quantized weights, loaded as the u8 matrix of their blocks
+        if header.item_type_vendor == TRACT_ITEM_TYPE_VENDOR
+            && (header.item_type == TRACT_ITEM_TYPE_Q8_0
+                || header.item_type == TRACT_ITEM_TYPE_Q4_0)
+        {
+            let format = if header.item_type == TRACT_ITEM_TYPE_Q8_0 {
+                BlockQuant::Q8_0
+            } else {
+                BlockQuant::Q4_0
+            };
+            ensure!(shape.len() == 2, "Block-quantized tensor must be a matrix, got {shape:?}");
+            let (m, k) = (shape[0], shape[1]);
+            ensure!(
+                k % format.block_len() == 0
+                    && header.data_size_bytes as usize == m * format.row_bytes(k),
+                "Shape and len mismatch for {format} tensor: shape:{shape:?}, bytes:{}",
+                header.data_size_bytes
+            );
+            let mut tensor = Tensor::zero::<u8>(&[m, format.row_bytes(k)])?;
+            reader.read_exact(tensor.as_slice_mut::<u8>()?)?;
+            return Ok(tensor);
+        }
+
         // last checked with spec 1.0.5: https://registry.khronos.org/NNEF/specs/1.0/nnef-1.0.5.html
         //
         // Quantized types are not instanciated as DatumType::Q* here since
@@ -204,6 +240,41 @@ pub fn write_tensor<W: std::io::Write>(w: &mut W, tensor: &Tensor) -> TractResul
     }
 }
 
+pub fn write_block_quant_tensor<W: std::io::Write>(
+    w: &mut W,
+    tensor: &Tensor,
+    format: BlockQuant,
+    k: usize,
+) -> TractResult<()> {
+    ensure!(
+        tensor.datum_type() == u8::datum_type()
+            && tensor.rank() == 2
+            && k % format.block_len() == 0
+            && tensor.shape()[1] == format.row_bytes(k),
+        "{tensor:?} does not hold {format} blocks for k={k}"
+    );
+    unsafe {
+        let mut header: Header = std::mem::zeroed();
+        header.magic = [0x4e, 0xef];
+        header.version_maj = 1;
+        header.version_min = 0;
+        header.rank = 2;
+        header.dims[0] = tensor.shape()[0] as u32;
+        header.dims[1] = k as u32;
+        header.data_size_bytes = tensor.len() as u32;
+        header.bits_per_item = 0xFFFFFFFF;
+        header.item_type_vendor = TRACT_ITEM_TYPE_VENDOR;
+        header.item_type = match format {
+            BlockQuant::Q8_0 => TRACT_ITEM_TYPE_Q8_0,
+            BlockQuant::Q4_0 => TRACT_ITEM_TYPE_Q4_0,
+        };
+        let header_buf: &[u8; 128] = std::mem::transmute(&header);
+        w.write_all(header_buf)?;
+        w.write_all(tensor.as_bytes())?;
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
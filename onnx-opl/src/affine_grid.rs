@@ -0,0 +1,205 @@
+use tract_nnef::internal::*;
+use tract_nnef::tract_ndarray::{ArrayD, Axis};
+use tract_nnef::tract_num_traits::Float;
+
+pub fn register(registry: &mut Registry) {
+    registry.register_primitive(
+        "tract_onnx_affine_grid",
+        &parameters(),
+        &[("output", TypeName::Scalar.tensor())],
+        load,
+    );
+    registry.register_dumper(dump);
+}
+
+/// https://github.com/onnx/onnx/blob/main/docs/Operators.md#AffineGrid
+///
+/// Theta is [N, r, r+1], size is [N, C, D1, ..., Dr] (and must be known at typing time).
+/// Output is a GridSample grid: [N, D1, ..., Dr, r].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct AffineGrid {
+    pub align_corners: bool,
+}
+
+impl AffineGrid {
+    /// Normalized coordinates of the pixel centers along an axis of length `len`.
+    fn base<T: Float>(&self, len: usize) -> Vec<T> {
+        let len_t = T::from(len).unwrap();
+        (0..len)
+            .map(|i| {
+                let i = T::from(i).unwrap();
+                if self.align_corners {
+                    if len == 1 {
+                        -T::one()
+                    } else {
+                        i * T::from(2.0).unwrap() / (len_t - T::one()) - T::one()
+                    }
+                } else {
+                    (i * T::from(2.0).unwrap() + T::one()) / len_t - T::one()
+                }
+            })
+            .collect()
+    }
+
+    fn eval_t<T: Datum + Float>(&self, theta: &Tensor, size: &[usize]) -> TractResult<Tensor> {
+        let theta = theta.to_array_view::<T>()?;
+        let rank = size.len() - 2;
+        let spatial = &size[2..];
+        let bases: TVec<Vec<T>> = spatial.iter().map(|&d| self.base(d)).collect();
+        let mut shape: TVec<usize> = size.iter().copied().collect();
+        shape.remove(1);
+        shape.push(rank);
+        let mut output = ArrayD::<T>::zeros(&*shape);
+        for (n, mut grid) in output.axis_iter_mut(Axis(0)).enumerate() {
+            let theta = theta.index_axis(Axis(0), n);
+            for (coords, value) in grid.indexed_iter_mut() {
+                // the grid coordinates are ordered from the innermost axis (x, y, ...)
+                let row = coords[rank];
+                let mut acc = theta[[row, rank]];
+                for col in 0..rank {
+                    let ax = rank - 1 - col;
+                    acc = acc + theta[[row, col]] * bases[ax][coords[ax]];
+                }
+                *value = acc;
+            }
+        }
+        Ok(output.into_tensor())
+    }
+}
+
+impl Op for AffineGrid {
+    fn name(&self) -> Cow<str> {
+        "AffineGrid".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("align_corners: {}", self.align_corners)])
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for AffineGrid {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        let (theta, size) = args_2!(inputs);
+        let size = size.cast_to::<i64>()?;
+        let size: TVec<usize> = size.as_slice::<i64>()?.iter().map(|&d| d as usize).collect();
+        let output = dispatch_floatlike!(Self::eval_t(theta.datum_type())(self, &theta, &size))?;
+        Ok(tvec!(output.into_tvalue()))
+    }
+}
+
+impl TypedOp for AffineGrid {
+    as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        let size = inputs[1]
+            .konst
+            .as_ref()
+            .context("AffineGrid requires its size input to be a constant")?
+            .cast_to::<TDim>()?;
+        let mut shape: TVec<TDim> = size.as_slice::<TDim>()?.into();
+        ensure!(shape.len() >= 3, "AffineGrid expects a size of length 3 or more");
+        let rank = shape.len() - 2;
+        ensure!(
+            inputs[0].rank() == 3
+                && inputs[0].shape[1] == rank.to_dim()
+                && inputs[0].shape[2] == (rank + 1).to_dim(),
+            "AffineGrid theta {:?} does not match size {:?}",
+            inputs[0],
+            shape
+        );
+        shape.remove(1);
+        shape.push(rank.to_dim());
+        Ok(tvec!(inputs[0].datum_type.fact(shape)))
+    }
+}
+
+fn parameters() -> Vec<Parameter> {
+    vec![
+        TypeName::Scalar.tensor().named("theta"),
+        TypeName::Integer.tensor().named("size"),
+        TypeName::Logical.named("align_corners").default(false),
+    ]
+}
+
+fn dump(ast: &mut IntoAst, node: &TypedNode, op: &AffineGrid) -> TractResult<Option<Arc<RValue>>> {
+    let theta = ast.mapping[&node.inputs[0]].clone();
+    let size = ast.mapping[&node.inputs[1]].clone();
+    Ok(Some(invocation(
+        "tract_onnx_affine_grid",
+        &[theta, size],
+        &[("align_corners", logical(op.align_corners))],
+    )))
+}
+
+fn load(builder: &mut ModelBuilder, invocation: &ResolvedInvocation) -> TractResult<Value> {
+    let theta = invocation.named_arg_as(builder, "theta")?;
+    let size = invocation.named_arg_as(builder, "size")?;
+    let align_corners = invocation.named_arg_as(builder, "align_corners")?;
+    builder.wire(AffineGrid { align_corners }, &[theta, size])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn run(align_corners: bool, theta: Tensor, size: &[i64]) -> TractResult<Tensor> {
+        let op = AffineGrid { align_corners };
+        let mut output = op.eval(tvec!(theta.into_tvalue(), tensor1(size).into_tvalue()))?;
+        Ok(output.remove(0).into_tensor())
+    }
+
+    // x is shifted by 0.5 and y is doubled
+    fn theta_2d() -> Tensor {
+        tensor3(&[[[1f32, 0., 0.5], [0., 2., 0.]]])
+    }
+
+    #[test]
+    fn affine_grid_2d() -> TractResult<()> {
+        let output = run(false, theta_2d(), &[1, 1, 2, 3])?;
+        let expected = tensor4(&[[
+            [[-2. / 3. + 0.5, -1.], [0.5, -1.], [2. / 3. + 0.5, -1f32]],
+            [[-2. / 3. + 0.5, 1.], [0.5, 1.], [2. / 3. + 0.5, 1.]],
+        ]]);
+        output.close_enough(&expected, Approximation::Close)
+    }
+
+    #[test]
+    fn affine_grid_2d_align_corners() -> TractResult<()> {
+        let output = run(true, theta_2d(), &[1, 1, 2, 3])?;
+        let expected = tensor4(&[[
+            [[-0.5, -2.], [0.5, -2.], [1.5, -2f32]],
+            [[-0.5, 2.], [0.5, 2.], [1.5, 2.]],
+        ]]);
+        output.close_enough(&expected, Approximation::Close)
+    }
+
+    fn check_3d(align_corners: bool, base: [f32; 2]) -> TractResult<()> {
+        // identity, plus a translation of z by 1
+        let theta = [[1f32, 0., 0., 0.], [0., 1., 0., 0.], [0., 0., 1., 1.]];
+        let theta = tensor3(&[theta, theta]);
+        let output = run(align_corners, theta, &[2, 1, 2, 2, 2])?;
+        assert_eq!(output.shape(), &[2, 2, 2, 2, 3]);
+        let output = output.to_array_view::<f32>()?;
+        for (n, d, h, w) in tract_ndarray::indices((2, 2, 2, 2)) {
+            let point: Vec<f32> = (0..3).map(|ax| output[[n, d, h, w, ax]]).collect();
+            assert_eq!(point, vec![base[w], base[h], base[d] + 1.]);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn affine_grid_3d() -> TractResult<()> {
+        check_3d(false, [-0.5, 0.5])
+    }
+
+    #[test]
+    fn affine_grid_3d_align_corners() -> TractResult<()> {
+        check_3d(true, [-1., 1.])
+    }
+}
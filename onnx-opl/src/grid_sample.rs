@@ -0,0 +1,386 @@
+use tract_nnef::internal::*;
+use tract_nnef::tract_ndarray::{ArrayD, IxDyn};
+use tract_nnef::tract_num_traits::Float;
+
+pub fn register(registry: &mut Registry) {
+    registry.register_primitive(
+        "tract_onnx_grid_sample",
+        &parameters(),
+        &[("output", TypeName::Scalar.tensor())],
+        load,
+    );
+    registry.register_dumper(dump);
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum InterpolationMode {
+    Nearest,
+    Linear,
+    Cubic,
+}
+
+impl InterpolationMode {
+    pub fn parse(s: &str) -> TractResult<InterpolationMode> {
+        Ok(match s {
+            "nearest" => InterpolationMode::Nearest,
+            "linear" | "bilinear" => InterpolationMode::Linear,
+            "cubic" | "bicubic" => InterpolationMode::Cubic,
+            other => bail!("unsupported GridSample mode: {}", other),
+        })
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InterpolationMode::Nearest => "nearest",
+            InterpolationMode::Linear => "linear",
+            InterpolationMode::Cubic => "cubic",
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PaddingMode {
+    Zeros,
+    Border,
+    Reflection,
+}
+
+impl PaddingMode {
+    pub fn parse(s: &str) -> TractResult<PaddingMode> {
+        Ok(match s {
+            "zeros" => PaddingMode::Zeros,
+            "border" => PaddingMode::Border,
+            "reflection" => PaddingMode::Reflection,
+            other => bail!("unsupported GridSample padding_mode: {}", other),
+        })
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PaddingMode::Zeros => "zeros",
+            PaddingMode::Border => "border",
+            PaddingMode::Reflection => "reflection",
+        }
+    }
+}
+
+/// https://github.com/onnx/onnx/blob/main/docs/Operators.md#GridSample
+///
+/// Input is [N, C, D1, ..., Dr], grid is [N, O1, ..., Or, r] with the coordinates of the
+/// innermost spatial axis first (x, y, ...). Output is [N, C, O1, ..., Or].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct GridSample {
+    pub mode: InterpolationMode,
+    pub padding_mode: PaddingMode,
+    pub align_corners: bool,
+}
+
+fn reflect<T: Float>(x: T, x_min: T, x_max: T) -> T {
+    let range = x_max - x_min;
+    let half = T::from(0.5).unwrap();
+    if range <= T::zero() {
+        return x_min;
+    }
+    if x < x_min {
+        let dx = x_min - x;
+        let n = (dx / range).trunc();
+        let r = dx - n * range;
+        if (n * half).fract() == T::zero() {
+            x_min + r
+        } else {
+            x_max - r
+        }
+    } else if x > x_max {
+        let dx = x - x_max;
+        let n = (dx / range).trunc();
+        let r = dx - n * range;
+        if (n * half).fract() == T::zero() {
+            x_max - r
+        } else {
+            x_min + r
+        }
+    } else {
+        x
+    }
+}
+
+fn round_half_even<T: Float>(x: T) -> T {
+    let r = x.round();
+    let half = T::from(0.5).unwrap();
+    if (r - x).abs() == half && (r * half).fract() != T::zero() {
+        r - x.signum()
+    } else {
+        r
+    }
+}
+
+// Integer position of a finite coordinate. Coordinates far outside of any tensor are clamped,
+// so they stay out of bounds without overflowing.
+fn index_of<T: Float>(x: T) -> i64 {
+    let bound = T::from(1i64 << 40).unwrap();
+    x.max(-bound).min(bound).to_i64().unwrap_or(0)
+}
+
+fn cubic_coeffs<T: Float>(t: T) -> [T; 4] {
+    let a = T::from(-0.75).unwrap();
+    let c = |x: f64| T::from(x).unwrap();
+    let (x0, x1, x2, x3) = (t + T::one(), t, T::one() - t, c(2.0) - t);
+    [
+        ((a * x0 - c(5.0) * a) * x0 + c(8.0) * a) * x0 - c(4.0) * a,
+        ((a + c(2.0)) * x1 - (a + c(3.0))) * x1 * x1 + T::one(),
+        ((a + c(2.0)) * x2 - (a + c(3.0))) * x2 * x2 + T::one(),
+        ((a * x3 - c(5.0) * a) * x3 + c(8.0) * a) * x3 - c(4.0) * a,
+    ]
+}
+
+impl GridSample {
+    /// Resolve an integer position along an axis of length `len` according to the padding mode.
+    /// None stands for a zero padded value.
+    fn pixel_index<T: Float>(&self, ix: i64, len: usize, border: (T, T)) -> Option<usize> {
+        match self.padding_mode {
+            PaddingMode::Zeros => (ix >= 0 && ix < len as i64).then_some(ix as usize),
+            PaddingMode::Border => Some(ix.clamp(0, len as i64 - 1) as usize),
+            PaddingMode::Reflection => {
+                let ix = index_of(reflect(T::from(ix).unwrap(), border.0, border.1));
+                Some(ix.clamp(0, len as i64 - 1) as usize)
+            }
+        }
+    }
+
+    /// Interpolation taps (index, weight) for one axis.
+    fn taps<T: Float>(&self, x: T, len: usize, border: (T, T)) -> TVec<(Option<usize>, T)> {
+        match self.mode {
+            InterpolationMode::Nearest => {
+                tvec!((self.pixel_index(index_of(x), len, border), T::one()))
+            }
+            InterpolationMode::Linear => {
+                let x0 = x.floor();
+                let ix = index_of(x0);
+                tvec!(
+                    (self.pixel_index(ix, len, border), x0 + T::one() - x),
+                    (self.pixel_index(ix + 1, len, border), x - x0),
+                )
+            }
+            InterpolationMode::Cubic => {
+                let x0 = x.floor();
+                let ix = index_of(x0);
+                cubic_coeffs(x - x0)
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, w)| (self.pixel_index(ix + i as i64 - 1, len, border), w))
+                    .collect()
+            }
+        }
+    }
+
+    fn eval_t<T: Datum + Float>(&self, input: &Tensor, grid: &Tensor) -> TractResult<Tensor> {
+        let input = input.to_array_view::<T>()?;
+        let grid = grid.cast_to::<T>()?;
+        let grid = grid.to_array_view::<T>()?;
+        let rank = input.ndim() - 2;
+        let (n, c) = (input.shape()[0], input.shape()[1]);
+        let dims: TVec<usize> = input.shape()[2..].into();
+        ensure!(
+            dims.iter().all(|&d| d > 0),
+            "GridSample needs non-empty spatial dimensions, got {:?}",
+            input.shape()
+        );
+        let strides: TVec<usize> =
+            (0..rank).map(|ax| dims[ax + 1..].iter().product::<usize>()).collect();
+        let borders: TVec<(T, T)> = dims
+            .iter()
+            .map(|&d| {
+                if self.align_corners {
+                    (T::zero(), T::from(d - 1).unwrap())
+                } else {
+                    let half = T::from(0.5).unwrap();
+                    (-half, T::from(d).unwrap() - half)
+                }
+            })
+            .collect();
+        let mut output_shape: TVec<usize> = tvec!(n, c);
+        output_shape.extend(grid.shape()[1..][..rank].iter().copied());
+        let mut output = ArrayD::<T>::zeros(&*output_shape);
+        let input = input.into_shape(IxDyn(&[n, c, dims.iter().product()]))?;
+        let grid_points = grid.shape()[1..][..rank].iter().product::<usize>();
+        let grid = grid.into_shape(IxDyn(&[n, grid_points, rank]))?;
+        let mut output_view = output.view_mut().into_shape(IxDyn(&[n, c, grid_points]))?;
+        let two = T::from(2.0).unwrap();
+        for b in 0..n {
+            for point in 0..grid_points {
+                let mut taps: TVec<(Option<usize>, T)> = tvec!((Some(0), T::one()));
+                for ax in 0..rank {
+                    let len = dims[ax];
+                    let normalized = grid[[b, point, rank - 1 - ax]];
+                    let mut x = if self.align_corners {
+                        (normalized + T::one()) / two * T::from(len - 1).unwrap()
+                    } else {
+                        ((normalized + T::one()) * T::from(len).unwrap() - T::one()) / two
+                    };
+                    if self.mode == InterpolationMode::Nearest {
+                        x = round_half_even(x);
+                    }
+                    let (x_min, x_max) = borders[ax];
+                    if x < x_min || x > x_max {
+                        match self.padding_mode {
+                            PaddingMode::Border => {
+                                x = x.max(T::zero()).min(T::from(len - 1).unwrap())
+                            }
+                            PaddingMode::Reflection => x = reflect(x, x_min, x_max),
+                            PaddingMode::Zeros => (),
+                        }
+                    }
+                    // NaN or infinite coordinates sample the zero padding
+                    let axis_taps = if x.is_finite() {
+                        self.taps(x, len, borders[ax])
+                    } else {
+                        tvec!((None, T::one()))
+                    };
+                    let stride = strides[ax];
+                    taps = taps
+                        .iter()
+                        .flat_map(|&(offset, weight)| {
+                            axis_taps.iter().map(move |&(ix, w)| {
+                                (offset.zip(ix).map(|(o, ix)| o + ix * stride), weight * w)
+                            })
+                        })
+                        .collect();
+                }
+                for ch in 0..c {
+                    output_view[[b, ch, point]] = taps
+                        .iter()
+                        .filter_map(|&(offset, w)| offset.map(|o| input[[b, ch, o]] * w))
+                        .fold(T::zero(), |acc, v| acc + v);
+                }
+            }
+        }
+        Ok(output.into_tensor())
+    }
+}
+
+impl Op for GridSample {
+    fn name(&self) -> Cow<str> {
+        "GridSample".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!(
+            "mode: {} padding: {} align_corners: {}",
+            self.mode.as_str(),
+            self.padding_mode.as_str(),
+            self.align_corners
+        )])
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for GridSample {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        let (input, grid) = args_2!(inputs);
+        let output = dispatch_floatlike!(Self::eval_t(input.datum_type())(self, &input, &grid))?;
+        Ok(tvec!(output.into_tvalue()))
+    }
+}
+
+impl TypedOp for GridSample {
+    as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        let (input, grid) = (inputs[0], inputs[1]);
+        let rank = input.rank();
+        ensure!(rank >= 3, "GridSample expects an input of rank 3 or more, got {:?}", input);
+        ensure!(
+            grid.rank() == rank && grid.shape[rank - 1] == (rank - 2).to_dim(),
+            "GridSample grid {:?} does not match input {:?}",
+            grid,
+            input
+        );
+        let mut shape: TVec<TDim> = input.shape[0..2].into();
+        shape.extend(grid.shape[1..rank - 1].iter().cloned());
+        Ok(tvec!(input.datum_type.fact(shape)))
+    }
+}
+
+fn parameters() -> Vec<Parameter> {
+    vec![
+        TypeName::Scalar.tensor().named("input"),
+        TypeName::Scalar.tensor().named("grid"),
+        TypeName::String.named("mode").default("linear"),
+        TypeName::String.named("padding_mode").default("zeros"),
+        TypeName::Logical.named("align_corners").default(false),
+    ]
+}
+
+fn dump(ast: &mut IntoAst, node: &TypedNode, op: &GridSample) -> TractResult<Option<Arc<RValue>>> {
+    let input = ast.mapping[&node.inputs[0]].clone();
+    let grid = ast.mapping[&node.inputs[1]].clone();
+    Ok(Some(invocation(
+        "tract_onnx_grid_sample",
+        &[input, grid],
+        &[
+            ("mode", string(op.mode.as_str())),
+            ("padding_mode", string(op.padding_mode.as_str())),
+            ("align_corners", logical(op.align_corners)),
+        ],
+    )))
+}
+
+fn load(builder: &mut ModelBuilder, invocation: &ResolvedInvocation) -> TractResult<Value> {
+    let input = invocation.named_arg_as(builder, "input")?;
+    let grid = invocation.named_arg_as(builder, "grid")?;
+    let mode = InterpolationMode::parse(&invocation.named_arg_as::<String>(builder, "mode")?)?;
+    let padding_mode =
+        PaddingMode::parse(&invocation.named_arg_as::<String>(builder, "padding_mode")?)?;
+    let align_corners = invocation.named_arg_as(builder, "align_corners")?;
+    builder.wire(GridSample { mode, padding_mode, align_corners }, &[input, grid])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn run(op: &GridSample, input: Tensor, grid: Tensor) -> TractResult<Tensor> {
+        Ok(op.eval(tvec!(input.into_tvalue(), grid.into_tvalue()))?.remove(0).into_tensor())
+    }
+
+    #[test]
+    fn non_finite_and_huge_coordinates() -> TractResult<()> {
+        let input = tensor4(&[[[[1f32, 2.], [3., 4.]]]]);
+        let coords = [f32::NAN, f32::INFINITY, f32::NEG_INFINITY, 1e30, -1e30, 0.];
+        let grid = tensor4(&[[coords.map(|x| [x, 0.])]]);
+        for mode in
+            [InterpolationMode::Nearest, InterpolationMode::Linear, InterpolationMode::Cubic]
+        {
+            for padding_mode in [PaddingMode::Zeros, PaddingMode::Border, PaddingMode::Reflection] {
+                for align_corners in [false, true] {
+                    let op = GridSample { mode, padding_mode, align_corners };
+                    let output = run(&op, input.clone(), grid.clone())?;
+                    let output = output.as_slice::<f32>()?;
+                    assert_eq!(output[0], 0., "{op:?}");
+                    if padding_mode == PaddingMode::Zeros {
+                        assert!(output[..5].iter().all(|x| *x == 0.), "{op:?}");
+                    }
+                    assert!(output.iter().all(|x| x.is_finite()), "{op:?}");
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn empty_spatial_dims() {
+        let op = GridSample {
+            mode: InterpolationMode::Linear,
+            padding_mode: PaddingMode::Border,
+            align_corners: true,
+        };
+        let input = Tensor::zero::<f32>(&[1, 1, 0, 2]).unwrap();
+        let grid = tensor4(&[[[[0f32, 0.]]]]);
+        assert!(run(&op, input, grid).is_err());
+    }
+}
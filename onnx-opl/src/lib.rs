@@ -2,6 +2,8 @@
 
 use tract_nnef::internal::*;
 
+pub mod affine_grid;
+pub mod grid_sample;
 pub mod is_inf;
 pub mod is_nan;
 pub mod lrn;
@@ -9,6 +11,7 @@ pub mod ml;
 pub mod multinomial;
 pub mod non_max_suppression;
 pub mod random;
+pub mod roi_align;
 
 pub trait WithOnnx {
     fn with_onnx(self) -> Self;
@@ -32,6 +35,9 @@ fn onnx_opl_registry() -> Registry {
     non_max_suppression::register(&mut registry);
     multinomial::register(&mut registry);
     random::register(&mut registry);
+    grid_sample::register(&mut registry);
+    affine_grid::register(&mut registry);
+    roi_align::register(&mut registry);
     registry.register_element_wise(
         "tract_onnx_isinf",
         TypeId::of::<is_inf::IsInf>(),
@@ -56,7 +56,7 @@ impl EvalOp for DirectLookup {
 
     fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
         let input = args_1!(inputs);
-        let output = dispatch_hash!(Self::eval_t(self.values.datum_type())(self, &input))?;
+        let output = dispatch_datum!(Self::eval_t(self.values.datum_type())(self, &input))?;
         Ok(tvec!(output.into_tvalue()))
     }
 }
@@ -97,10 +97,26 @@ impl TypedOp for DirectLookup {
 #[derive(Clone, Debug)]
 pub struct ReverseLookup {
     keys: Arc<Tensor>,
+    hashable_keys: Arc<Tensor>,
     index: HashMap<u64, SmallVec<[i32; 1]>>,
     fallback_value: i32,
 }
 
+/// Floats are not Hash: they are looked up by their bit patterns instead (with -0.0 folded on 0.0).
+fn hashable(t: &Tensor) -> TractResult<Cow<Tensor>> {
+    Ok(match t.datum_type() {
+        DatumType::F32 => {
+            let bits = t.as_slice::<f32>()?.iter().map(|x| (x + 0.0).to_bits()).collect_vec();
+            Cow::Owned(tensor1(&bits).into_shape(t.shape())?)
+        }
+        DatumType::F64 => {
+            let bits = t.as_slice::<f64>()?.iter().map(|x| (x + 0.0).to_bits()).collect_vec();
+            Cow::Owned(tensor1(&bits).into_shape(t.shape())?)
+        }
+        _ => Cow::Borrowed(t),
+    })
+}
+
 #[allow(clippy::manual_hash_one)]
 impl ReverseLookup {
     pub fn new(keys: Arc<Tensor>, fallback_value: i32) -> TractResult<ReverseLookup> {
@@ -115,12 +131,13 @@ impl ReverseLookup {
             }
             hashmap
         }
-        let index = unsafe { dispatch_hash!(new_t(keys.datum_type())(&keys)) };
-        Ok(ReverseLookup { index, keys, fallback_value })
+        let hashable_keys = hashable(&keys)?.into_owned().into_arc_tensor();
+        let index = unsafe { dispatch_hash!(new_t(hashable_keys.datum_type())(&hashable_keys)) };
+        Ok(ReverseLookup { index, keys, hashable_keys, fallback_value })
     }
 
     unsafe fn search_t<T: Datum + Hash>(&self, needle: &T) -> Option<i32> {
-        let keys = self.keys.as_slice_unchecked::<T>();
+        let keys = self.hashable_keys.as_slice_unchecked::<T>();
         let mut hasher = self.index.hasher().build_hasher();
         needle.hash(&mut hasher);
         let u = hasher.finish();
@@ -170,7 +187,8 @@ impl EvalOp for ReverseLookup {
 
     fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
         let input = args_1!(inputs);
-        let output = dispatch_hash!(Self::eval_t(self.keys.datum_type())(self, &input))?;
+        let input = hashable(&input)?;
+        let output = dispatch_hash!(Self::eval_t(self.hashable_keys.datum_type())(self, &input))?;
         Ok(tvec!(output.into_tvalue()))
     }
 }
@@ -258,8 +276,29 @@ fn load_reverse_lookup(
     invocation: &ResolvedInvocation,
 ) -> TractResult<Value> {
     let input = invocation.named_arg_as(builder, "input")?;
-    let keys: isize = invocation.named_arg_as(builder, "keys")?;
-    let fallback_value = invocation.named_arg_as(builder, "fallback")?;
-    let op = ReverseLookup::new(fallback_value, keys as i32)?;
+    let keys = invocation.named_arg_as(builder, "keys")?;
+    let fallback_value: i64 = invocation.named_arg_as(builder, "fallback")?;
+    let op = ReverseLookup::new(keys, fallback_value as i32)?;
     builder.wire(op, &[input])
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::WithOnnx;
+
+    #[test]
+    fn reverse_lookup_nnef_round_trip() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let x = model.add_source("x", i64::fact([4]))?;
+        let op = ReverseLookup::new(rctensor1(&[10i64, 20, 30]), -1)?;
+        let y = model.wire_node("lookup", op, &[x])?;
+        model.set_output_outlets(&y)?;
+        let nnef = tract_nnef::nnef().with_onnx();
+        let buffer = nnef.write_to_tar(&model, vec![])?;
+        let reloaded = nnef.model_for_read(&mut &*buffer)?;
+        let output = reloaded.into_runnable()?.run(tvec!(tensor1(&[30i64, 10, 15, 20]).into()))?;
+        assert_eq!(*output[0], tensor1(&[2i32, 0, -1, 1]));
+        Ok(())
+    }
+}
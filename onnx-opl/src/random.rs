@@ -6,7 +6,7 @@ use rand_distr::num_traits::Float;
 use rand_distr::StandardNormal;
 use tract_nnef::internal::*;
 use tract_nnef::ser::{array, tdims};
-use tract_nnef::tract_core::trivial_op_state_freeeze;
+use tract_nnef::tract_core::ops::OpStateFreeze;
 
 pub fn register(registry: &mut Registry) {
     registry.register_primitive(
@@ -159,7 +159,19 @@ impl OpState for RandomState {
     }
 }
 
-trivial_op_state_freeeze!(RandomState);
+impl OpStateFreeze for RandomState {
+    fn freeze(&self) -> Box<dyn FrozenOpState> {
+        Box::new(self.clone())
+    }
+}
+
+// the generator state is opaque, so it can not be saved: restoring a fresh one would silently
+// restart the random stream
+impl FrozenOpState for RandomState {
+    fn unfreeze(&self) -> Box<dyn OpState> {
+        Box::new(self.clone())
+    }
+}
 
 fn sample_uniform<T: Datum + SampleUniform + Copy>(
     t: &mut Tensor,
@@ -187,3 +199,21 @@ where
     t.as_slice_mut::<T>()?.iter_mut().zip(dist.sample_iter(r)).for_each(|(v, r)| *v = r);
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn random_state_can_not_be_saved() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let dist = Dist::Uniform { low: rctensor0(0f32), high: rctensor0(1f32) };
+        let op = Random { fact: f32::fact([4]), dist, seed: Some(42) };
+        let wire = model.wire_node("random", op, &[])?;
+        model.set_output_outlets(&wire)?;
+        let mut state = SimpleState::new(model.into_runnable()?)?;
+        state.run(tvec!())?;
+        assert!(state.freeze().save().is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,313 @@
+use tract_nnef::internal::*;
+use tract_nnef::tract_ndarray::{Array4, Ix2, Ix4};
+use tract_nnef::tract_num_traits::Float;
+
+pub fn register(registry: &mut Registry) {
+    registry.register_primitive(
+        "tract_onnx_roi_align",
+        &parameters(),
+        &[("output", TypeName::Scalar.tensor())],
+        load,
+    );
+    registry.register_dumper(dump);
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum RoiAlignMode {
+    Avg,
+    Max,
+}
+
+impl RoiAlignMode {
+    pub fn parse(s: &str) -> TractResult<RoiAlignMode> {
+        Ok(match s {
+            "avg" => RoiAlignMode::Avg,
+            "max" => RoiAlignMode::Max,
+            other => bail!("unsupported RoiAlign mode: {}", other),
+        })
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RoiAlignMode::Avg => "avg",
+            RoiAlignMode::Max => "max",
+        }
+    }
+}
+
+/// https://github.com/onnx/onnx/blob/main/docs/Operators.md#RoiAlign
+///
+/// Input is [N, C, H, W], rois is [R, 4] (x1, y1, x2, y2) and batch_indices is [R].
+/// Output is [R, C, output_height, output_width].
+#[derive(Clone, Debug, PartialEq)]
+pub struct RoiAlign {
+    pub mode: RoiAlignMode,
+    /// "half_pixel" coordinate transformation mode (shift by half a pixel), as opposed to the
+    /// legacy "output_half_pixel" behaviour.
+    pub half_pixel: bool,
+    pub output_height: usize,
+    pub output_width: usize,
+    pub sampling_ratio: usize,
+    pub spatial_scale: f32,
+}
+
+/// Four bilinear interpolation taps (offset in the feature map, weight).
+type Taps<T> = [(usize, T); 4];
+
+impl RoiAlign {
+    fn taps<T: Float>(y: T, x: T, height: usize, width: usize) -> Taps<T> {
+        let (h, w) = (T::from(height).unwrap(), T::from(width).unwrap());
+        // also rejects NaN coordinates
+        if !(y >= -T::one() && y <= h && x >= -T::one() && x <= w) {
+            return [(0, T::zero()); 4];
+        }
+        let (mut y, mut x) = (y.max(T::zero()), x.max(T::zero()));
+        let (mut y_low, mut x_low) = (y.to_usize().unwrap(), x.to_usize().unwrap());
+        let y_high = if y_low >= height - 1 {
+            y_low = height - 1;
+            y = T::from(y_low).unwrap();
+            y_low
+        } else {
+            y_low + 1
+        };
+        let x_high = if x_low >= width - 1 {
+            x_low = width - 1;
+            x = T::from(x_low).unwrap();
+            x_low
+        } else {
+            x_low + 1
+        };
+        let (ly, lx) = (y - T::from(y_low).unwrap(), x - T::from(x_low).unwrap());
+        let (hy, hx) = (T::one() - ly, T::one() - lx);
+        [
+            (y_low * width + x_low, hy * hx),
+            (y_low * width + x_high, hy * lx),
+            (y_high * width + x_low, ly * hx),
+            (y_high * width + x_high, ly * lx),
+        ]
+    }
+
+    fn eval_t<T: Datum + Float>(
+        &self,
+        input: &Tensor,
+        rois: &Tensor,
+        batch_indices: &Tensor,
+    ) -> TractResult<Tensor> {
+        let input = input.to_array_view::<T>()?.into_dimensionality::<Ix4>()?;
+        let rois = rois.cast_to::<T>()?;
+        let rois = rois.to_array_view::<T>()?.into_dimensionality::<Ix2>()?;
+        let batch_indices = batch_indices.cast_to::<i64>()?;
+        let batch_indices = batch_indices.as_slice::<i64>()?;
+        let (n, c, height, width) = input.dim();
+        ensure!(
+            height > 0 && width > 0,
+            "RoiAlign needs a non-empty feature map, got {:?}",
+            input.shape()
+        );
+        let (ph, pw) = (self.output_height, self.output_width);
+        let mut output = Array4::<T>::zeros((rois.nrows(), c, ph, pw));
+        let input = input.as_standard_layout();
+        let offset = if self.half_pixel { T::from(0.5).unwrap() } else { T::zero() };
+        let scale = T::from(self.spatial_scale).unwrap();
+        for (r, roi) in rois.outer_iter().enumerate() {
+            let batch = batch_indices[r];
+            ensure!(
+                batch >= 0 && (batch as usize) < n,
+                "RoiAlign batch index {} is out of range",
+                batch
+            );
+            let start_w = roi[0] * scale - offset;
+            let start_h = roi[1] * scale - offset;
+            let mut roi_width = roi[2] * scale - offset - start_w;
+            let mut roi_height = roi[3] * scale - offset - start_h;
+            if !self.half_pixel {
+                roi_width = roi_width.max(T::one());
+                roi_height = roi_height.max(T::one());
+            }
+            let bin_h = roi_height / T::from(ph).unwrap();
+            let bin_w = roi_width / T::from(pw).unwrap();
+            let grid_h = if self.sampling_ratio > 0 {
+                self.sampling_ratio
+            } else {
+                bin_h.ceil().to_usize().unwrap_or(0)
+            };
+            let grid_w = if self.sampling_ratio > 0 {
+                self.sampling_ratio
+            } else {
+                bin_w.ceil().to_usize().unwrap_or(0)
+            };
+            let count = T::from((grid_h * grid_w).max(1)).unwrap();
+            let mut taps: Vec<Taps<T>> = Vec::with_capacity(ph * pw * grid_h * grid_w);
+            for y in 0..ph {
+                for x in 0..pw {
+                    for iy in 0..grid_h {
+                        let yy = start_h
+                            + T::from(y).unwrap() * bin_h
+                            + (T::from(iy).unwrap() + T::from(0.5).unwrap()) * bin_h
+                                / T::from(grid_h).unwrap();
+                        for ix in 0..grid_w {
+                            let xx = start_w
+                                + T::from(x).unwrap() * bin_w
+                                + (T::from(ix).unwrap() + T::from(0.5).unwrap()) * bin_w
+                                    / T::from(grid_w).unwrap();
+                            taps.push(Self::taps(yy, xx, height, width));
+                        }
+                    }
+                }
+            }
+            let samples = grid_h * grid_w;
+            for ch in 0..c {
+                let plane = input.slice(tract_ndarray::s![batch as usize, ch, .., ..]);
+                let plane = plane.as_slice().context("Expected a contiguous feature map")?;
+                for y in 0..ph {
+                    for x in 0..pw {
+                        let bin = &taps[(y * pw + x) * samples..][..samples];
+                        let values = bin.iter().map(|taps| taps.map(|(o, w)| w * plane[o]));
+                        output[[r, ch, y, x]] = match self.mode {
+                            RoiAlignMode::Avg => {
+                                values
+                                    .map(|v| v.into_iter().fold(T::zero(), |acc, v| acc + v))
+                                    .fold(T::zero(), |acc, v| acc + v)
+                                    / count
+                            }
+                            RoiAlignMode::Max => values
+                                .map(|v| v.into_iter().fold(T::neg_infinity(), T::max))
+                                .reduce(T::max)
+                                .unwrap_or(T::zero()),
+                        };
+                    }
+                }
+            }
+        }
+        Ok(output.into_tensor())
+    }
+}
+
+impl Op for RoiAlign {
+    fn name(&self) -> Cow<str> {
+        "RoiAlign".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![
+            format!(
+                "mode: {} half_pixel: {} output: {}x{}",
+                self.mode.as_str(),
+                self.half_pixel,
+                self.output_height,
+                self.output_width
+            ),
+            format!(
+                "sampling_ratio: {} spatial_scale: {}",
+                self.sampling_ratio, self.spatial_scale
+            ),
+        ])
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for RoiAlign {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        let (input, rois, batch_indices) = args_3!(inputs);
+        let output = dispatch_floatlike!(Self::eval_t(input.datum_type())(
+            self,
+            &input,
+            &rois,
+            &batch_indices
+        ))?;
+        Ok(tvec!(output.into_tvalue()))
+    }
+}
+
+impl TypedOp for RoiAlign {
+    as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        ensure!(inputs[0].rank() == 4, "RoiAlign expects a 4D input, got {:?}", inputs[0]);
+        ensure!(
+            inputs[1].rank() == 2 && inputs[1].shape[1] == 4.to_dim(),
+            "RoiAlign expects rois of shape [R, 4], got {:?}",
+            inputs[1]
+        );
+        let shape = tvec!(
+            inputs[1].shape[0].clone(),
+            inputs[0].shape[1].clone(),
+            self.output_height.to_dim(),
+            self.output_width.to_dim()
+        );
+        Ok(tvec!(inputs[0].datum_type.fact(shape)))
+    }
+}
+
+fn parameters() -> Vec<Parameter> {
+    vec![
+        TypeName::Scalar.tensor().named("input"),
+        TypeName::Scalar.tensor().named("rois"),
+        TypeName::Integer.tensor().named("batch_indices"),
+        TypeName::String.named("mode").default("avg"),
+        TypeName::Logical.named("half_pixel").default(true),
+        TypeName::Integer.named("output_height").default(1),
+        TypeName::Integer.named("output_width").default(1),
+        TypeName::Integer.named("sampling_ratio").default(0),
+        TypeName::Scalar.named("spatial_scale").default(1.0),
+    ]
+}
+
+fn dump(ast: &mut IntoAst, node: &TypedNode, op: &RoiAlign) -> TractResult<Option<Arc<RValue>>> {
+    let inputs: TVec<Arc<RValue>> = node.inputs.iter().map(|i| ast.mapping[i].clone()).collect();
+    Ok(Some(invocation(
+        "tract_onnx_roi_align",
+        &inputs,
+        &[
+            ("mode", string(op.mode.as_str())),
+            ("half_pixel", logical(op.half_pixel)),
+            ("output_height", numeric(op.output_height)),
+            ("output_width", numeric(op.output_width)),
+            ("sampling_ratio", numeric(op.sampling_ratio)),
+            ("spatial_scale", numeric(op.spatial_scale)),
+        ],
+    )))
+}
+
+fn load(builder: &mut ModelBuilder, invocation: &ResolvedInvocation) -> TractResult<Value> {
+    let input = invocation.named_arg_as(builder, "input")?;
+    let rois = invocation.named_arg_as(builder, "rois")?;
+    let batch_indices = invocation.named_arg_as(builder, "batch_indices")?;
+    let op = RoiAlign {
+        mode: RoiAlignMode::parse(&invocation.named_arg_as::<String>(builder, "mode")?)?,
+        half_pixel: invocation.named_arg_as(builder, "half_pixel")?,
+        output_height: invocation.named_arg_as::<i64>(builder, "output_height")? as usize,
+        output_width: invocation.named_arg_as::<i64>(builder, "output_width")? as usize,
+        sampling_ratio: invocation.named_arg_as::<i64>(builder, "sampling_ratio")? as usize,
+        spatial_scale: invocation.named_arg_as(builder, "spatial_scale")?,
+    };
+    builder.wire(op, &[input, rois, batch_indices])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn empty_feature_map() {
+        let op = RoiAlign {
+            mode: RoiAlignMode::Avg,
+            half_pixel: true,
+            output_height: 1,
+            output_width: 1,
+            sampling_ratio: 1,
+            spatial_scale: 1.,
+        };
+        let inputs = tvec!(
+            Tensor::zero::<f32>(&[1, 1, 0, 2]).unwrap().into_tvalue(),
+            tensor2(&[[0f32, 0., 1., 1.]]).into_tvalue(),
+            tensor1(&[0i64]).into_tvalue(),
+        );
+        assert!(op.eval(inputs).is_err());
+    }
+}
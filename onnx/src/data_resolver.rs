@@ -1,6 +1,6 @@
 use std::fs::File;
 use std::io::{BufRead, BufReader};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use tract_hir::internal::*;
 
 use tract_hir::internal::TractResult;
@@ -73,3 +73,113 @@ impl ModelDataResolver for MmapDataResolver {
         Ok(())
     }
 }
+
+/// Resolves external data from buffers held in memory, keyed by the `location` recorded in the
+/// model (as `Onnx::model_for_read_with_external_data` parses with an empty model directory).
+#[derive(Clone, Debug, Default)]
+pub struct InMemoryDataResolver(pub HashMap<PathBuf, Arc<Vec<u8>>>);
+
+impl InMemoryDataResolver {
+    pub fn with(mut self, location: impl Into<PathBuf>, data: impl Into<Arc<Vec<u8>>>) -> Self {
+        self.0.insert(location.into(), data.into());
+        self
+    }
+}
+
+impl ModelDataResolver for InMemoryDataResolver {
+    fn read_bytes_from_path(
+        &self,
+        buf: &mut Vec<u8>,
+        p: &Path,
+        offset: usize,
+        length: Option<usize>,
+    ) -> TractResult<()> {
+        let data = self.0.get(p).with_context(|| format!("No external data provided for {p:?}"))?;
+        let end = match length {
+            Some(l) => offset.checked_add(l).context("External data range overflows")?,
+            None => data.len(),
+        };
+        ensure!(
+            offset <= end && end <= data.len(),
+            "External data {p:?} ({} bytes) has no range {offset}..{end}",
+            data.len()
+        );
+        buf.extend_from_slice(&data[offset..end]);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::pb::tensor_shape_proto::{dimension, Dimension};
+    use crate::pb::*;
+    use prost::Message;
+
+    fn value_info(name: &str) -> ValueInfoProto {
+        let dim = Dimension { value: Some(dimension::Value::DimValue(2)), ..Default::default() };
+        let tensor = type_proto::Tensor {
+            elem_type: tensor_proto::DataType::Float as i32,
+            shape: Some(TensorShapeProto { dim: vec![dim] }),
+        };
+        let r#type =
+            TypeProto { value: Some(type_proto::Value::TensorType(tensor)), ..Default::default() };
+        ValueInfoProto { name: name.into(), r#type: Some(r#type), ..Default::default() }
+    }
+
+    #[test]
+    fn external_data_in_memory() -> TractResult<()> {
+        let entry = |key: &str, value: &str| StringStringEntryProto {
+            key: key.into(),
+            value: value.into(),
+        };
+        let weights = TensorProto {
+            name: "w".into(),
+            dims: vec![2],
+            data_type: tensor_proto::DataType::Float as i32,
+            data_location: Some(tensor_proto::DataLocation::External as i32),
+            external_data: vec![entry("location", "weights.bin"), entry("offset", "4")],
+            ..Default::default()
+        };
+        let add = NodeProto {
+            op_type: "Add".into(),
+            input: vec!["x".into(), "w".into()],
+            output: vec!["y".into()],
+            ..Default::default()
+        };
+        let graph = GraphProto {
+            node: vec![add],
+            initializer: vec![weights],
+            input: vec![value_info("x")],
+            output: vec![value_info("y")],
+            ..Default::default()
+        };
+        let model = ModelProto {
+            graph: Some(graph),
+            opset_import: vec![OperatorSetIdProto { domain: "".into(), version: 13 }],
+            ..Default::default()
+        };
+        let bytes = model.encode_to_vec();
+        let data: Vec<u8> = [0f32, 1., 2.].iter().flat_map(|x| x.to_le_bytes()).collect();
+
+        let onnx = crate::onnx();
+        assert!(onnx.model_for_read(&mut &*bytes).is_err());
+        let resolver = InMemoryDataResolver::default().with("weights.bin", data);
+        let model = onnx.model_for_read_with_external_data(&mut &*bytes, resolver)?;
+        let output = model.into_runnable()?.run(tvec!(tensor1(&[3f32, 4.]).into()))?;
+        assert_eq!(*output[0], tensor1(&[4f32, 6.]));
+        Ok(())
+    }
+
+    #[test]
+    fn external_data_in_memory_out_of_range() {
+        let resolver = InMemoryDataResolver::default().with("weights.bin", vec![0u8; 8]);
+        let p = Path::new("weights.bin");
+        let mut buf = vec![];
+        assert!(resolver.read_bytes_from_path(&mut buf, p, 12, None).is_err());
+        assert!(resolver.read_bytes_from_path(&mut buf, p, 4, Some(8)).is_err());
+        assert!(resolver.read_bytes_from_path(&mut buf, p, 4, Some(usize::MAX)).is_err());
+        resolver.read_bytes_from_path(&mut buf, p, 4, None).unwrap();
+        assert_eq!(buf.len(), 4);
+    }
+}
@@ -6,8 +6,7 @@ use std::collections::HashMap;
 use tract_hir::internal::*;
 use tract_hir::prelude::tract_itertools::Itertools;
 
-use crate::pb::type_proto::Value;
-use crate::pb::{self, TensorProto, TypeProto};
+use crate::pb::{self, TensorProto};
 use crate::data_resolver::{self, ModelDataResolver};
 use crate::tensor::{load_tensor, translate_inference_fact};
 use prost::Message;
@@ -85,13 +84,13 @@ impl<'a> ParsingContext<'a> {
                 let id = model.add_const(input.name.to_owned(), init)?;
                 outlets_by_name.insert(input.name.to_owned(), id);
             } else {
-                let fact = input.r#type.as_ref().unwrap().value.as_ref().unwrap();
-                #[allow(irrefutable_let_patterns)]
-                let fact: InferenceFact = if let pb::type_proto::Value::TensorType(fact) = fact {
-                    translate_inference_fact(&ctx, fact, true)?
-                } else {
-                    bail!("Can not parse tensor type");
+                let Some(fact) = input.r#type.as_ref().and_then(|t| t.tensor_type()) else {
+                    bail!(
+                        "Can not parse type of input {} (only tensors and optional tensors are supported)",
+                        input.name
+                    );
                 };
+                let fact = translate_inference_fact(&ctx, fact, true)?;
                 trace!("Input: {} is a source ({:?})", input.name, fact);
                 let id = model.add_source(&*input.name, fact)?;
                 outlets_by_name.insert(input.name.to_owned(), id);
@@ -170,8 +169,7 @@ impl<'a> ParsingContext<'a> {
         for output in graph.output.iter() {
             let mut fact = InferenceFact::default();
             if self.framework.use_output_shapes {
-                if let Some(f) = output.r#type.as_ref().and_then(|t| t.value.as_ref()) {
-                    let pb::type_proto::Value::TensorType(f) = f;
+                if let Some(f) = output.r#type.as_ref().and_then(|t| t.tensor_type()) {
                     fact = translate_inference_fact(&ctx, f, false)?
                 };
             }
@@ -185,7 +183,7 @@ impl<'a> ParsingContext<'a> {
         }
         model.set_output_outlets(&outputs)?;
         for info in &graph.value_info {
-            if let Some(TypeProto { value: Some(Value::TensorType(t)), .. }) = &info.r#type {
+            if let Some(t) = info.r#type.as_ref().and_then(|t| t.tensor_type()) {
                 if let Some(outlet) = outlets_by_name.get(&info.name) {
                     let mut pbfact = translate_inference_fact(&ctx, t, false)?;
                     // be conservative, these are likely to be TDim
@@ -268,6 +266,21 @@ impl Onnx {
         ctx.parse_graph(graph)
     }
 
+    /// Load a model from `r`, taking its external data from memory instead of the filesystem.
+    pub fn model_for_read_with_external_data(
+        &self,
+        r: &mut dyn std::io::Read,
+        external_data: data_resolver::InMemoryDataResolver,
+    ) -> TractResult<InferenceModel> {
+        let proto = self.proto_model_for_read(r).context("Reading proto model")?;
+        let onnx = Onnx { provider: Arc::new(external_data), ..self.clone() };
+        let ParseResult { model, unresolved_inputs, .. } = onnx.parse(&proto, Some(""))?;
+        if unresolved_inputs.len() > 0 {
+            bail!("Could not resolve inputs at top-level: {:?}", unresolved_inputs)
+        }
+        Ok(model)
+    }
+
     pub fn with_ignore_output_shapes(self, ignore: bool) -> Onnx {
         Self { use_output_shapes: !ignore, ..self }
     }
@@ -0,0 +1,56 @@
+use crate::model::ParsingContext;
+use crate::pb::NodeProto;
+use tract_hir::internal::*;
+use tract_onnx_opl::affine_grid::AffineGrid;
+
+pub fn affine_grid(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let align_corners = node.get_attr_opt::<i64>("align_corners")?.unwrap_or(0) != 0;
+    Ok((expand(AffineGridExpansion(AffineGrid { align_corners })), vec![]))
+}
+
+#[derive(Clone, Debug)]
+struct AffineGridExpansion(AffineGrid);
+
+impl Expansion for AffineGridExpansion {
+    fn name(&self) -> Cow<str> {
+        "AffineGrid".into()
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 2)?;
+        check_output_arity(outputs, 1)?;
+        s.equals(&inputs[0].datum_type, &outputs[0].datum_type)?;
+        s.equals(&inputs[0].rank, 3)?;
+        s.equals(&inputs[1].rank, 1)?;
+        s.equals(&inputs[0].shape[0], &outputs[0].shape[0])?;
+        s.given(&inputs[1].value, move |s, size| {
+            let size = size.cast_to::<TDim>()?;
+            let size = size.as_slice::<TDim>()?;
+            let rank = size.len() - 2;
+            s.equals(&outputs[0].rank, size.len() as i64)?;
+            s.equals(&inputs[0].shape[1], rank.to_dim())?;
+            s.equals(&inputs[0].shape[2], (rank + 1).to_dim())?;
+            for ax in 0..rank {
+                s.equals(&outputs[0].shape[ax + 1], &size[ax + 2])?;
+            }
+            s.equals(&outputs[0].shape[rank + 1], rank.to_dim())
+        })
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        model.wire_node(prefix, self.0.clone(), inputs)
+    }
+}
@@ -0,0 +1,105 @@
+use crate::model::ParsingContext;
+use crate::pb::NodeProto;
+use tract_hir::internal::*;
+use tract_hir::ops::array::{Pad, PadMode};
+
+pub fn center_crop_pad(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let axes = node.get_attr_opt_vec("axes")?;
+    Ok((expand(CenterCropPad { axes }), vec![]))
+}
+
+/// Crop or zero-pad the input, keeping it centered, to reach the target shape along `axes`.
+/// The target shape must be constant, and is translated to Slice and Pad ops.
+#[derive(Debug, Clone, Hash)]
+struct CenterCropPad {
+    axes: Option<Vec<i64>>,
+}
+
+impl CenterCropPad {
+    fn axes(&self, rank: usize) -> Vec<usize> {
+        if let Some(axes) = &self.axes {
+            axes.iter().map(|&ax| if ax < 0 { ax + rank as i64 } else { ax } as usize).collect()
+        } else {
+            (0..rank).collect()
+        }
+    }
+}
+
+impl Expansion for CenterCropPad {
+    fn name(&self) -> Cow<str> {
+        "CenterCropPad".into()
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 2)?;
+        check_output_arity(outputs, 1)?;
+        s.equals(&inputs[0].datum_type, &outputs[0].datum_type)?;
+        s.equals(&inputs[0].rank, &outputs[0].rank)?;
+        s.equals(&inputs[1].rank, 1)?;
+        s.given_2(&inputs[0].shape, &inputs[1].value, move |s, shape, target| {
+            let target = target.cast_to::<TDim>()?;
+            let target = target.as_slice::<TDim>()?;
+            let axes = self.axes(shape.len());
+            ensure!(axes.len() == target.len(), "CenterCropPad shape does not match axes");
+            let mut shape = shape;
+            for (ax, dim) in axes.into_iter().zip(target) {
+                shape[ax] = dim.clone();
+            }
+            s.equals(&outputs[0].shape, shape)
+        })
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let fact = model.outlet_fact(inputs[0])?.clone();
+        let target = model
+            .outlet_fact(inputs[1])?
+            .konst
+            .clone()
+            .context("CenterCropPad requires its shape input to be a constant")?
+            .cast_to::<i64>()?
+            .into_owned();
+        let target = target.as_slice::<i64>()?;
+        let axes = self.axes(fact.rank());
+        ensure!(axes.len() == target.len(), "CenterCropPad shape does not match axes");
+        let mut wire = tvec!(inputs[0]);
+        let mut pads = vec![(0, 0); fact.rank()];
+        for (ax, &target) in axes.into_iter().zip(target) {
+            let dim =
+                fact.shape[ax].to_i64().context("CenterCropPad expects a known input shape")?;
+            if dim > target {
+                let start = (dim - target) / 2;
+                wire = model.wire_node(
+                    format!("{prefix}.crop-{ax}"),
+                    tract_core::ops::array::Slice::new(
+                        ax,
+                        start as usize,
+                        (start + target) as usize,
+                    ),
+                    &wire,
+                )?;
+            } else if dim < target {
+                let before = (target - dim) / 2;
+                pads[ax] = (before as usize, (target - dim - before) as usize);
+            }
+        }
+        if pads.iter().any(|&(before, after)| before > 0 || after > 0) {
+            let mode =
+                PadMode::Constant(Tensor::zero_scalar_dt(fact.datum_type)?.into_arc_tensor());
+            wire = model.wire_node(format!("{prefix}.pad"), Pad { pads, mode }, &wire)?;
+        }
+        Ok(wire)
+    }
+}
@@ -0,0 +1,233 @@
+use crate::model::ParsingContext;
+use crate::pb::NodeProto;
+use tract_hir::internal::*;
+
+pub fn col2im(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let dilations = node.get_attr_opt_tvec("dilations")?;
+    let pads = node.get_attr_opt_tvec("pads")?;
+    let strides = node.get_attr_opt_tvec("strides")?;
+    Ok((Box::new(Col2Im { dilations, pads, strides }), vec![]))
+}
+
+/// Inverse of im2col: rearranges the sliding blocks columns of the [N, C * prod(block_shape), L]
+/// input into a [N, C, *image_shape] image, summing overlapping values.
+///
+/// image_shape and block_shape are inputs, they must be constant at typing time.
+#[derive(Debug, Clone, Hash)]
+pub struct Col2Im {
+    pub dilations: Option<TVec<usize>>,
+    pub pads: Option<TVec<usize>>,
+    pub strides: Option<TVec<usize>>,
+}
+
+/// Geometry of a Col2Im, once the spatial rank is known.
+struct Geometry {
+    dilations: TVec<usize>,
+    pads: TVec<usize>,
+    strides: TVec<usize>,
+}
+
+impl Col2Im {
+    fn geometry(&self, spatial: usize) -> TractResult<Geometry> {
+        let geo = Geometry {
+            dilations: self.dilations.clone().unwrap_or_else(|| tvec!(1; spatial)),
+            pads: self.pads.clone().unwrap_or_else(|| tvec!(0; 2 * spatial)),
+            strides: self.strides.clone().unwrap_or_else(|| tvec!(1; spatial)),
+        };
+        ensure!(
+            geo.dilations.len() == spatial
+                && geo.pads.len() == 2 * spatial
+                && geo.strides.len() == spatial,
+            "Col2Im attributes do not match a {}D image",
+            spatial
+        );
+        Ok(geo)
+    }
+
+    fn output_shape<D: DimLike>(
+        input_shape: &[D],
+        image_shape: &[TDim],
+        block_shape: &[TDim],
+    ) -> TractResult<TVec<TDim>> {
+        ensure!(input_shape.len() == 3, "Col2Im expects a 3D input");
+        ensure!(image_shape.len() == block_shape.len(), "Col2Im image and block shapes mismatch");
+        let block = block_shape.iter().product::<TDim>().to_usize()?;
+        ensure!(block > 0, "Col2Im block shape {:?} is empty", block_shape);
+        let mut shape = tvec!(input_shape[0].to_dim(), input_shape[1].to_dim() / block);
+        shape.extend(image_shape.iter().cloned());
+        Ok(shape)
+    }
+
+    fn eval_t<T: Datum + Copy + tract_num_traits::Zero>(
+        &self,
+        input: &Tensor,
+        image: &[usize],
+        block: &[usize],
+    ) -> TractResult<Tensor> {
+        let spatial = image.len();
+        let geo = self.geometry(spatial)?;
+        let (n, cb, l) = (input.shape()[0], input.shape()[1], input.shape()[2]);
+        let mut blocks: TVec<usize> = tvec!();
+        for d in 0..spatial {
+            let padded = image[d] + geo.pads[d] + geo.pads[spatial + d];
+            ensure!(
+                block[d] > 0
+                    && geo.strides[d] > 0
+                    && padded > geo.dilations[d] * (block[d] - 1),
+                "Col2Im block {:?} does not fit in image {:?} (pads: {:?}, dilations: {:?}, strides: {:?})",
+                block,
+                image,
+                geo.pads,
+                geo.dilations,
+                geo.strides
+            );
+            blocks.push((padded - geo.dilations[d] * (block[d] - 1) - 1) / geo.strides[d] + 1);
+        }
+        let block_len: usize = block.iter().product();
+        let channels = cb / block_len;
+        ensure!(channels * block_len == cb, "Col2Im input channels do not match block shape");
+        ensure!(
+            blocks.iter().product::<usize>() == l,
+            "Col2Im input has {} blocks, expected {:?}",
+            l,
+            blocks
+        );
+        let image_len: usize = image.iter().product();
+        let mut output_shape = tvec!(n, channels);
+        output_shape.extend(image.iter().copied());
+        let mut output = Tensor::zero::<T>(&output_shape)?;
+        let out = output.as_slice_mut::<T>()?;
+        let input = input.as_slice::<T>()?;
+        // image offset for each (block position, block index) pair, None when in padding
+        let mut offsets: Vec<Option<usize>> = Vec::with_capacity(block_len * l);
+        for kb in 0..block_len {
+            for kl in 0..l {
+                let (mut rb, mut rl, mut offset) = (kb, kl, Some(0));
+                for d in (0..spatial).rev() {
+                    let (ib, il) = (rb % block[d], rl % blocks[d]);
+                    (rb, rl) = (rb / block[d], rl / blocks[d]);
+                    let pos = (il * geo.strides[d] + ib * geo.dilations[d]) as isize
+                        - geo.pads[d] as isize;
+                    let stride: usize = image[d + 1..].iter().product();
+                    offset = offset
+                        .filter(|_| pos >= 0 && (pos as usize) < image[d])
+                        .map(|o| o + pos as usize * stride);
+                }
+                offsets.push(offset);
+            }
+        }
+        for (i, columns) in input.chunks(block_len * l).enumerate() {
+            let plane = &mut out[i * image_len..][..image_len];
+            for (value, offset) in columns.iter().zip(&offsets) {
+                if let Some(o) = offset {
+                    plane[*o] = plane[*o] + *value;
+                }
+            }
+        }
+        Ok(output)
+    }
+}
+
+impl Op for Col2Im {
+    fn name(&self) -> Cow<str> {
+        "Col2Im".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!(
+            "dilations: {:?} pads: {:?} strides: {:?}",
+            self.dilations, self.pads, self.strides
+        )])
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for Col2Im {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        let (input, image, block) = args_3!(inputs);
+        let image: TVec<usize> =
+            image.cast_to::<i64>()?.as_slice::<i64>()?.iter().map(|&d| d as usize).collect();
+        let block: TVec<usize> =
+            block.cast_to::<i64>()?.as_slice::<i64>()?.iter().map(|&d| d as usize).collect();
+        let output =
+            dispatch_numbers!(Self::eval_t(input.datum_type())(self, &input, &image, &block))?;
+        Ok(tvec!(output.into_tvalue()))
+    }
+}
+
+impl InferenceRulesOp for Col2Im {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 3)?;
+        check_output_arity(outputs, 1)?;
+        s.equals(&inputs[0].datum_type, &outputs[0].datum_type)?;
+        s.equals(&inputs[0].rank, 3)?;
+        s.equals(&inputs[1].rank, 1)?;
+        s.equals(&inputs[2].rank, 1)?;
+        s.equals(&inputs[1].shape, &inputs[2].shape)?;
+        s.given(&inputs[1].shape[0], move |s, spatial| {
+            s.equals(&outputs[0].rank, spatial.to_i64()? + 2)
+        })?;
+        s.equals(&inputs[0].shape[0], &outputs[0].shape[0])?;
+        s.given_3(
+            &inputs[0].shape,
+            &inputs[1].value,
+            &inputs[2].value,
+            move |s, shape, image, block| {
+                let image = image.cast_to::<TDim>()?;
+                let block = block.cast_to::<TDim>()?;
+                let output_shape =
+                    Self::output_shape(&shape, image.as_slice()?, block.as_slice()?)?;
+                s.equals(&outputs[0].shape, ShapeFactoid::from(output_shape))
+            },
+        )
+    }
+
+    as_op!();
+    to_typed!();
+}
+
+impl TypedOp for Col2Im {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        let (Some(image), Some(block)) = (&inputs[1].konst, &inputs[2].konst) else {
+            bail!("Col2Im requires image_shape and block_shape to be constants")
+        };
+        let image = image.cast_to::<TDim>()?;
+        let block = block.cast_to::<TDim>()?;
+        let shape = Self::output_shape(&inputs[0].shape, image.as_slice()?, block.as_slice()?)?;
+        self.geometry(image.len())?;
+        Ok(tvec!(inputs[0].datum_type.fact(shape)))
+    }
+
+    as_op!();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn run(image: &[i64], block: &[i64]) -> TractResult<TVec<TValue>> {
+        let op = Col2Im { dilations: None, pads: None, strides: None };
+        let input = Tensor::zero::<f32>(&[1, 4, 1])?;
+        op.eval(tvec!(input.into_tvalue(), tensor1(image).into(), tensor1(block).into()))
+    }
+
+    #[test]
+    fn block_larger_than_image() {
+        assert!(run(&[1, 1], &[2, 2]).is_err());
+        assert!(run(&[2, 2], &[0, 2]).is_err());
+        assert!(run(&[2, 2], &[2, 2]).is_ok());
+    }
+}
@@ -0,0 +1,156 @@
+use crate::model::ParsingContext;
+use crate::pb::NodeProto;
+use tract_hir::internal::*;
+
+pub fn max_unpool(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let kernel_shape: TVec<usize> = node.get_attr_tvec("kernel_shape")?;
+    let spatial = kernel_shape.len();
+    let pads = node.get_attr_opt_tvec("pads")?.unwrap_or_else(|| tvec!(0; 2 * spatial));
+    let strides = node.get_attr_opt_tvec("strides")?.unwrap_or_else(|| tvec!(1; spatial));
+    ensure!(
+        pads.len() == 2 * spatial && strides.len() == spatial,
+        "Inconsistent MaxUnpool attributes"
+    );
+    Ok((Box::new(MaxUnpool { kernel_shape, pads, strides }), vec![]))
+}
+
+/// Partial inverse of MaxPool: scatters the input values at the flat positions given by the
+/// indices (as computed by MaxPool) in a zero-filled output. The output shape is either the
+/// (constant) third input, or derived from the pooling geometry.
+#[derive(Debug, Clone, Hash)]
+pub struct MaxUnpool {
+    pub kernel_shape: TVec<usize>,
+    pub pads: TVec<usize>,
+    pub strides: TVec<usize>,
+}
+
+impl MaxUnpool {
+    fn default_output_shape<D: DimLike>(&self, input_shape: &[D]) -> TVec<D> {
+        let spatial = self.kernel_shape.len();
+        let mut shape: TVec<D> = input_shape[..2].into();
+        for ax in 0..spatial {
+            shape.push(
+                (input_shape[2 + ax].clone() - 1) * self.strides[ax] + self.kernel_shape[ax]
+                    - self.pads[ax]
+                    - self.pads[spatial + ax],
+            );
+        }
+        shape
+    }
+
+    unsafe fn eval_t<T: Datum>(
+        input: &Tensor,
+        indices: &[i64],
+        output: &mut Tensor,
+    ) -> TractResult<()> {
+        let input = input.as_slice_unchecked::<T>();
+        let output = output.as_slice_mut_unchecked::<T>();
+        for (value, &ix) in input.iter().zip(indices) {
+            ensure!(
+                ix >= 0 && (ix as usize) < output.len(),
+                "MaxUnpool index {} is out of bounds",
+                ix
+            );
+            output[ix as usize] = value.clone();
+        }
+        Ok(())
+    }
+}
+
+impl Op for MaxUnpool {
+    fn name(&self) -> Cow<str> {
+        "MaxUnpool".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!(
+            "kernel: {:?} pads: {:?} strides: {:?}",
+            self.kernel_shape, self.pads, self.strides
+        )])
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for MaxUnpool {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        let shape: TVec<usize> = if let Some(shape) = inputs.get(2) {
+            shape.cast_to::<i64>()?.as_slice::<i64>()?.iter().map(|&d| d as usize).collect()
+        } else {
+            self.default_output_shape(inputs[0].shape())
+        };
+        let (input, indices) = (&inputs[0], inputs[1].cast_to::<i64>()?);
+        ensure!(
+            input.shape() == indices.shape(),
+            "MaxUnpool input {:?} and indices {:?} shapes mismatch",
+            input,
+            indices
+        );
+        let mut output = Tensor::zero_dt(input.datum_type(), &shape)?;
+        unsafe {
+            dispatch_datum_by_size!(Self::eval_t(input.datum_type())(
+                input,
+                indices.as_slice::<i64>()?,
+                &mut output
+            ))?;
+        }
+        Ok(tvec!(output.into_tvalue()))
+    }
+}
+
+impl InferenceRulesOp for MaxUnpool {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        ensure!(inputs.len() == 2 || inputs.len() == 3, "MaxUnpool expects 2 or 3 inputs");
+        check_output_arity(outputs, 1)?;
+        s.equals(&inputs[0].datum_type, &outputs[0].datum_type)?;
+        s.equals(&inputs[1].datum_type, i64::datum_type())?;
+        s.equals(&inputs[0].shape, &inputs[1].shape)?;
+        s.equals(&inputs[0].rank, self.kernel_shape.len() as i64 + 2)?;
+        s.equals(&inputs[0].rank, &outputs[0].rank)?;
+        if inputs.len() == 3 {
+            s.equals(&inputs[2].rank, 1)?;
+            s.given(&inputs[2].value, move |s, shape| {
+                let shape = shape.cast_to::<TDim>()?;
+                s.equals(&outputs[0].shape, ShapeFactoid::from(shape.as_slice::<TDim>()?))
+            })?;
+        } else {
+            s.given(&inputs[0].shape, move |s, shape| {
+                s.equals(&outputs[0].shape, ShapeFactoid::from(self.default_output_shape(&shape)))
+            })?;
+        }
+        Ok(())
+    }
+
+    as_op!();
+    to_typed!();
+}
+
+impl TypedOp for MaxUnpool {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        let shape: TVec<TDim> = if let Some(shape) = inputs.get(2) {
+            let shape = shape
+                .konst
+                .as_ref()
+                .context("MaxUnpool requires its output_shape input to be a constant")?
+                .cast_to::<TDim>()?;
+            shape.as_slice::<TDim>()?.into()
+        } else {
+            self.default_output_shape(&inputs[0].shape)
+        };
+        Ok(tvec!(inputs[0].datum_type.fact(shape)))
+    }
+
+    as_op!();
+}
@@ -1,13 +1,18 @@
+mod center_crop_pad;
+mod col2im;
 mod compress;
 mod nonzero;
+mod max_unpool;
 mod one_hot;
 mod pad;
+mod reverse_sequence;
 mod shape;
 mod slice;
 mod split;
 mod squeeze;
 mod topk;
 mod trilu;
+mod unique;
 mod unsqueeze;
 
 use tract_hir::internal::*;
@@ -18,6 +23,8 @@ use crate::pb::*;
 
 pub fn register_all_ops(reg: &mut OnnxOpRegister) {
     reg.insert("ArrayFeatureExtractor", array_feature_extractor);
+    reg.insert("CenterCropPad", center_crop_pad::center_crop_pad);
+    reg.insert("Col2Im", col2im::col2im);
     reg.insert("Compress", compress::compress);
     reg.insert("Concat", concat);
     reg.insert("ConstantLike", constant_like);
@@ -28,11 +35,13 @@ pub fn register_all_ops(reg: &mut OnnxOpRegister) {
     reg.insert("Gather", gather);
     reg.insert("GatherElements", gather_elements);
     reg.insert("GatherND", gather_nd);
+    reg.insert("MaxUnpool", max_unpool::max_unpool);
     reg.insert("NonZero", nonzero::non_zero);
     reg.insert("OneHot", one_hot::one_hot);
     reg.insert("Range", |_, _| Ok((expand(array::Range), vec![])));
     reg.insert("Pad", pad::pad);
     reg.insert("Reshape", |_, _| Ok((expand(array::Reshape::default()), vec![])));
+    reg.insert("ReverseSequence", reverse_sequence::reverse_sequence);
     reg.insert("Scatter", scatter_elements);
     reg.insert("ScatterElements", scatter_elements);
     reg.insert("ScatterND", |_, _| Ok((Box::new(array::ScatterNd), vec![])));
@@ -45,6 +54,7 @@ pub fn register_all_ops(reg: &mut OnnxOpRegister) {
     reg.insert("TopK", topk::topk);
     reg.insert("Transpose", transpose);
     reg.insert("Trilu", trilu::trilu);
+    reg.insert("Unique", unique::unique);
     reg.insert("Unsqueeze", unsqueeze::unsqueeze);
 }
 
@@ -0,0 +1,118 @@
+use crate::model::ParsingContext;
+use crate::pb::NodeProto;
+use tract_hir::internal::*;
+use tract_ndarray::Axis;
+
+pub fn reverse_sequence(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let batch_axis = node.get_attr_opt("batch_axis")?.unwrap_or(1);
+    let time_axis = node.get_attr_opt("time_axis")?.unwrap_or(0);
+    ensure!(
+        (batch_axis == 0 && time_axis == 1) || (batch_axis == 1 && time_axis == 0),
+        "ReverseSequence expects batch and time axes to be 0 and 1"
+    );
+    Ok((Box::new(ReverseSequence { batch_axis, time_axis }), vec![]))
+}
+
+/// Reverse the first `sequence_lens[b]` items along the time axis, for each batch entry `b`.
+#[derive(Debug, Clone, Hash)]
+pub struct ReverseSequence {
+    pub batch_axis: usize,
+    pub time_axis: usize,
+}
+
+impl ReverseSequence {
+    unsafe fn eval_t<T: Datum>(
+        &self,
+        input: &Tensor,
+        output: &mut Tensor,
+        lens: &[i64],
+    ) -> TractResult<()> {
+        let input = input.to_array_view_unchecked::<T>();
+        let mut output = output.to_array_view_mut_unchecked::<T>();
+        let len_max = input.shape()[self.time_axis];
+        // time axis, once the batch axis has been indexed out
+        let time_axis = Axis(self.time_axis - (self.time_axis > self.batch_axis) as usize);
+        for (b, &len) in lens.iter().enumerate() {
+            ensure!(
+                len >= 0 && len as usize <= len_max,
+                "ReverseSequence: invalid sequence length {} for batch {}",
+                len,
+                b
+            );
+            let input = input.index_axis(Axis(self.batch_axis), b);
+            let mut output = output.index_axis_mut(Axis(self.batch_axis), b);
+            for t in 0..len as usize {
+                output
+                    .index_axis_mut(time_axis, t)
+                    .assign(&input.index_axis(time_axis, len as usize - 1 - t));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Op for ReverseSequence {
+    fn name(&self) -> Cow<str> {
+        "ReverseSequence".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("batch_axis: {} time_axis: {}", self.batch_axis, self.time_axis)])
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for ReverseSequence {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        let (input, lens) = args_2!(inputs);
+        let lens = lens.cast_to::<i64>()?;
+        let mut output = input.clone().into_tensor();
+        unsafe {
+            dispatch_datum_by_size!(Self::eval_t(input.datum_type())(
+                self,
+                &input,
+                &mut output,
+                lens.as_slice::<i64>()?
+            ))?;
+        }
+        Ok(tvec!(output.into_tvalue()))
+    }
+}
+
+impl InferenceRulesOp for ReverseSequence {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 2)?;
+        check_output_arity(outputs, 1)?;
+        s.equals(&inputs[0].datum_type, &outputs[0].datum_type)?;
+        s.equals(&inputs[0].shape, &outputs[0].shape)?;
+        s.equals(&inputs[1].datum_type, i64::datum_type())?;
+        s.equals(&inputs[1].rank, 1)?;
+        s.equals(&inputs[1].shape[0], &inputs[0].shape[self.batch_axis])?;
+        Ok(())
+    }
+
+    as_op!();
+    to_typed!();
+}
+
+impl TypedOp for ReverseSequence {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        ensure!(inputs[0].rank() >= 2, "ReverseSequence expects an input of rank 2 or more");
+        Ok(tvec!(inputs[0].without_value()))
+    }
+
+    as_op!();
+}
@@ -0,0 +1,249 @@
+use std::cmp::Ordering;
+
+use crate::model::{optional_outputs, ParsingContext};
+use crate::pb::NodeProto;
+use tract_hir::internal::*;
+use tract_itertools::Itertools;
+use tract_ndarray::{Axis, IxDyn};
+
+pub fn unique(
+    ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let axis = node.get_attr_opt("axis")?;
+    let sorted = node.get_attr_opt("sorted")?.unwrap_or(1i64) == 1;
+    let mut outputs = optional_outputs(node);
+    let outputs = [(); 4].map(|_| outputs.next().unwrap());
+    let len = ctx.symbol_table.new_with_prefix("u");
+    Ok((expand(UniqueExpansion { axis, sorted, len, outputs }), vec![]))
+}
+
+/// Onnx Unique, with its four outputs (unique values, first occurrences indices, inverse indices
+/// and counts) all optional. The number of unique values is only known at runtime.
+#[derive(Debug, Clone, Hash)]
+struct UniqueExpansion {
+    axis: Option<i64>,
+    sorted: bool,
+    len: Symbol,
+    outputs: [Option<usize>; 4],
+}
+
+impl Expansion for UniqueExpansion {
+    fn name(&self) -> Cow<str> {
+        "Unique".into()
+    }
+
+    fn nboutputs(&self) -> TractResult<usize> {
+        Ok(self.outputs.iter().flatten().count())
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 1)?;
+        check_output_arity(outputs, self.nboutputs()?)?;
+        if let Some(y) = self.outputs[0] {
+            s.equals(&inputs[0].datum_type, &outputs[y].datum_type)?;
+            if let Some(axis) = self.axis {
+                s.equals(&inputs[0].rank, &outputs[y].rank)?;
+                s.given(&inputs[0].rank, move |s, rank| {
+                    let axis = if axis < 0 { axis + rank } else { axis } as usize;
+                    for ax in 0..rank as usize {
+                        if ax == axis {
+                            s.equals(&outputs[y].shape[ax], self.len.to_dim())?;
+                        } else {
+                            s.equals(&inputs[0].shape[ax], &outputs[y].shape[ax])?;
+                        }
+                    }
+                    Ok(())
+                })?;
+            } else {
+                s.equals(&outputs[y].rank, 1)?;
+                s.equals(&outputs[y].shape[0], self.len.to_dim())?;
+            }
+        }
+        for slot in [self.outputs[1], self.outputs[3]].into_iter().flatten() {
+            s.equals(&outputs[slot].datum_type, i64::datum_type())?;
+            s.equals(&outputs[slot].rank, 1)?;
+            s.equals(&outputs[slot].shape[0], self.len.to_dim())?;
+        }
+        if let Some(inverse) = self.outputs[2] {
+            s.equals(&outputs[inverse].datum_type, i64::datum_type())?;
+            s.equals(&outputs[inverse].rank, 1)?;
+            s.given(&inputs[0].shape, move |s, shape| {
+                let len = if let Some(axis) = self.axis {
+                    let axis = if axis < 0 { axis + shape.len() as i64 } else { axis } as usize;
+                    shape[axis].clone()
+                } else {
+                    shape.iter().product()
+                };
+                s.equals(&outputs[inverse].shape[0], len)
+            })?;
+        }
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let rank = model.outlet_fact(inputs[0])?.rank() as i64;
+        let axis = self.axis.map(|axis| if axis < 0 { axis + rank } else { axis } as usize);
+        let op = Unique { axis, sorted: self.sorted, len: self.len.clone() };
+        let wires = model.wire_node(prefix, op, inputs)?;
+        Ok(self.outputs.iter().zip(wires).filter(|(slot, _)| slot.is_some()).map(|p| p.1).collect())
+    }
+}
+
+/// Order used to sort the values: the natural order, falling back to `total_cmp` for NaN so the
+/// sort is always given a total order.
+trait UniqueOrd {
+    fn unique_cmp(&self, other: &Self) -> Ordering;
+}
+
+macro_rules! unique_ord {
+    ($($ord: ty),*; $($float: ty),*) => {
+        $(impl UniqueOrd for $ord {
+            fn unique_cmp(&self, other: &Self) -> Ordering {
+                self.cmp(other)
+            }
+        })*
+        $(impl UniqueOrd for $float {
+            fn unique_cmp(&self, other: &Self) -> Ordering {
+                self.partial_cmp(other).unwrap_or_else(|| self.total_cmp(other))
+            }
+        })*
+    };
+}
+
+unique_ord!(bool, u8, u16, u32, u64, i8, i16, i32, i64, String; f16, f32, f64);
+
+/// Unique values (or sub-tensors along `axis`) of the input. Outputs are the unique values,
+/// the index of their first occurrence, the index of each input value in the unique values,
+/// and the number of occurrences.
+#[derive(Debug, Clone, Hash)]
+pub struct Unique {
+    pub axis: Option<usize>,
+    pub sorted: bool,
+    pub len: Symbol,
+}
+
+impl Unique {
+    fn eval_t<T: Datum + UniqueOrd>(&self, input: &Tensor) -> TractResult<TVec<Tensor>> {
+        let view = input.to_array_view::<T>()?;
+        let (view, axis) = match self.axis {
+            Some(axis) => (view, axis),
+            None => (view.into_shape(IxDyn(&[input.len()]))?, 0),
+        };
+        let slices: Vec<_> = view.axis_iter(Axis(axis)).collect();
+        let mut order: Vec<usize> = (0..slices.len()).collect();
+        let cmp = |a: usize, b: usize| {
+            slices[a]
+                .iter()
+                .zip(slices[b].iter())
+                .map(|(a, b)| a.unique_cmp(b))
+                .find(|o| o.is_ne())
+                .unwrap_or(Ordering::Equal)
+        };
+        // stable sort, so the first occurrence of each value leads its group
+        order.sort_by(|&a, &b| cmp(a, b));
+        // (first occurrence, count) for each unique value, in sorted order
+        let mut groups: Vec<(usize, usize)> = vec![];
+        let mut inverse = vec![0usize; slices.len()];
+        for ix in order {
+            match groups.last_mut() {
+                Some((first, count)) if cmp(*first, ix).is_eq() => *count += 1,
+                _ => groups.push((ix, 1)),
+            }
+            inverse[ix] = groups.len() - 1;
+        }
+        if !self.sorted {
+            let mut by_occurrence: Vec<usize> = (0..groups.len()).collect();
+            by_occurrence.sort_by_key(|&g| groups[g].0);
+            let mut position = vec![0; groups.len()];
+            for (pos, &g) in by_occurrence.iter().enumerate() {
+                position[g] = pos;
+            }
+            inverse.iter_mut().for_each(|g| *g = position[*g]);
+            groups = by_occurrence.into_iter().map(|g| groups[g]).collect();
+        }
+        let firsts: Vec<usize> = groups.iter().map(|g| g.0).collect();
+        let mut values = view.select(Axis(axis), &firsts).into_tensor();
+        unsafe { values.set_datum_type(input.datum_type()) };
+        let indices = tensor1(&firsts.iter().map(|&ix| ix as i64).collect_vec());
+        let inverse = tensor1(&inverse.iter().map(|&g| g as i64).collect_vec());
+        let counts = tensor1(&groups.iter().map(|g| g.1 as i64).collect_vec());
+        Ok(tvec!(values, indices, inverse, counts))
+    }
+}
+
+impl Op for Unique {
+    fn name(&self) -> Cow<str> {
+        "Unique".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("axis: {:?} sorted: {}", self.axis, self.sorted)])
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for Unique {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        let input = args_1!(inputs);
+        let outputs = if input.datum_type() == String::datum_type() {
+            self.eval_t::<String>(&input)?
+        } else if input.datum_type() == bool::datum_type() {
+            self.eval_t::<bool>(&input)?
+        } else {
+            dispatch_numbers!(Self::eval_t(input.datum_type())(self, &input))?
+        };
+        Ok(outputs.into_iter().map(|t| t.into_tvalue()).collect())
+    }
+}
+
+impl TypedOp for Unique {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        let len = self.len.to_dim();
+        let (values, inverse) = if let Some(axis) = self.axis {
+            let mut shape = inputs[0].shape.to_tvec();
+            let inverse = std::mem::replace(&mut shape[axis], len.clone());
+            (inputs[0].datum_type.fact(shape), inverse)
+        } else {
+            (inputs[0].datum_type.fact(tvec!(len.clone())), inputs[0].shape.volume())
+        };
+        Ok(tvec!(values, i64::fact(tvec!(len.clone())), i64::fact(&[inverse]), i64::fact(&[len])))
+    }
+
+    as_op!();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn nan_are_grouped() -> TractResult<()> {
+        let op = Unique { axis: None, sorted: true, len: SymbolTable::default().sym("n") };
+        let input = tensor1(&[f32::NAN, 1., f32::NAN, 0., -0.]);
+        let outputs = op.eval(tvec!(input.into_tvalue()))?;
+        let values = outputs[0].as_slice::<f32>()?;
+        assert_eq!(&values[..2], &[0., 1.]);
+        assert!(values[2].is_nan());
+        assert_eq!(values.len(), 3);
+        assert_eq!(*outputs[1], tensor1(&[3i64, 1, 0]));
+        assert_eq!(*outputs[2], tensor1(&[2i64, 1, 2, 0, 0]));
+        assert_eq!(*outputs[3], tensor1(&[2i64, 1, 2]));
+        Ok(())
+    }
+}
@@ -0,0 +1,56 @@
+use crate::model::ParsingContext;
+use crate::pb::NodeProto;
+use tract_hir::internal::*;
+use tract_onnx_opl::grid_sample::{GridSample, InterpolationMode, PaddingMode};
+
+pub fn grid_sample(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let mode = InterpolationMode::parse(node.get_attr_opt("mode")?.unwrap_or("linear"))?;
+    let padding_mode = PaddingMode::parse(node.get_attr_opt("padding_mode")?.unwrap_or("zeros"))?;
+    let align_corners = node.get_attr_opt::<i64>("align_corners")?.unwrap_or(0) != 0;
+    Ok((expand(GridSampleExpansion(GridSample { mode, padding_mode, align_corners })), vec![]))
+}
+
+#[derive(Clone, Debug)]
+struct GridSampleExpansion(GridSample);
+
+impl Expansion for GridSampleExpansion {
+    fn name(&self) -> Cow<str> {
+        "GridSample".into()
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 2)?;
+        check_output_arity(outputs, 1)?;
+        s.equals(&inputs[0].datum_type, &outputs[0].datum_type)?;
+        s.equals(&inputs[0].rank, &inputs[1].rank)?;
+        s.equals(&inputs[0].rank, &outputs[0].rank)?;
+        s.equals(&inputs[0].shape[0], &inputs[1].shape[0])?;
+        s.equals(&inputs[0].shape[0], &outputs[0].shape[0])?;
+        s.equals(&inputs[0].shape[1], &outputs[0].shape[1])?;
+        s.given(&inputs[0].rank, move |s, rank| {
+            let rank = rank as usize;
+            s.equals(&inputs[1].shape[rank - 1], (rank - 2).to_dim())?;
+            for ax in 2..rank {
+                s.equals(&inputs[1].shape[ax - 1], &outputs[0].shape[ax])?;
+            }
+            Ok(())
+        })
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        model.wire_node(prefix, self.0.clone(), inputs)
+    }
+}
@@ -0,0 +1,177 @@
+use crate::model::{OnnxOpRegister, ParsingContext};
+use crate::pb::*;
+use tract_hir::internal::*;
+use tract_onnx_opl::ml::*;
+
+pub fn register_all_ops(reg: &mut OnnxOpRegister) {
+    reg.insert("LabelEncoder", label_encoder);
+}
+
+#[derive(Debug, Clone, Hash)]
+enum Mapping {
+    /// ai.onnx.ml v1: strings to their index in classes, or the other way around, depending on
+    /// the input type.
+    Classes { classes: Arc<Tensor>, default_int: Arc<Tensor>, default_string: Arc<Tensor> },
+    /// ai.onnx.ml v2 and later: keys to values.
+    KeysValues { keys: Arc<Tensor>, values: Arc<Tensor>, default: Arc<Tensor> },
+}
+
+#[derive(Debug, Clone, Hash)]
+struct LabelEncoder(Mapping);
+
+impl LabelEncoder {
+    /// (keys, values, default) for a given input type.
+    fn mapping(&self, input: DatumType) -> (Arc<Tensor>, Arc<Tensor>, Arc<Tensor>) {
+        match &self.0 {
+            Mapping::Classes { classes, default_int, default_string } => {
+                let indices = rctensor1(&(0..classes.len() as i64).collect::<Vec<_>>());
+                if input == String::datum_type() {
+                    (classes.clone(), indices, default_int.clone())
+                } else {
+                    (indices, classes.clone(), default_string.clone())
+                }
+            }
+            Mapping::KeysValues { keys, values, default } => {
+                (keys.clone(), values.clone(), default.clone())
+            }
+        }
+    }
+}
+
+impl Expansion for LabelEncoder {
+    fn name(&self) -> Cow<str> {
+        "LabelEncoder".into()
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 1)?;
+        check_output_arity(outputs, 1)?;
+        s.equals(&inputs[0].shape, &outputs[0].shape)?;
+        s.given(&inputs[0].datum_type, move |s, dt| {
+            s.equals(&outputs[0].datum_type, self.mapping(dt).1.datum_type())
+        })
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let input_dt = model.outlet_fact(inputs[0])?.datum_type;
+        let (keys, values, default) = self.mapping(input_dt);
+        let mut wire = tvec!(inputs[0]);
+        if input_dt != keys.datum_type() {
+            wire = model.wire_node(
+                format!("{prefix}.cast"),
+                tract_core::ops::cast::cast(keys.datum_type()),
+                &wire,
+            )?;
+        }
+        let wire =
+            model.wire_node(format!("{prefix}.reverse"), ReverseLookup::new(keys, -1)?, &wire)?;
+        model.wire_node(format!("{prefix}.direct"), DirectLookup::new(values, default)?, &wire)
+    }
+}
+
+fn label_encoder(
+    ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    if let Some(classes) = node.get_attr_opt_vec::<String>("classes_strings")? {
+        let default_int = node.get_attr_opt::<i64>("default_int64")?.unwrap_or(-1);
+        let default_string =
+            node.get_attr_opt::<String>("default_string")?.unwrap_or_else(|| "_Unused".into());
+        let mapping = Mapping::Classes {
+            classes: rctensor1(&classes),
+            default_int: rctensor0(default_int),
+            default_string: rctensor0(default_string),
+        };
+        return Ok((expand(LabelEncoder(mapping)), vec![]));
+    }
+    let keys = if let Some(keys) = node.get_attr_opt::<&TensorProto>("keys_tensor")? {
+        ctx.load_tensor(keys)?
+    } else if let Some(keys) = node.get_attr_opt_vec::<i64>("keys_int64s")? {
+        tensor1(&keys)
+    } else if let Some(keys) = node.get_attr_opt_vec::<String>("keys_strings")? {
+        tensor1(&keys)
+    } else if let Some(keys) = node.get_attr_opt_vec::<f32>("keys_floats")? {
+        tensor1(&keys)
+    } else {
+        bail!("LabelEncoder requires keys")
+    };
+    let values = if let Some(values) = node.get_attr_opt::<&TensorProto>("values_tensor")? {
+        ctx.load_tensor(values)?
+    } else if let Some(values) = node.get_attr_opt_vec::<i64>("values_int64s")? {
+        tensor1(&values)
+    } else if let Some(values) = node.get_attr_opt_vec::<String>("values_strings")? {
+        tensor1(&values)
+    } else if let Some(values) = node.get_attr_opt_vec::<f32>("values_floats")? {
+        tensor1(&values)
+    } else {
+        bail!("LabelEncoder requires values")
+    };
+    ensure!(keys.len() == values.len(), "LabelEncoder keys and values have different lengths");
+    let default = if let Some(default) = node.get_attr_opt::<&TensorProto>("default_tensor")? {
+        ctx.load_tensor(default)?.into_shape(&[])?
+    } else if values.datum_type() == i64::datum_type() {
+        tensor0(node.get_attr_opt::<i64>("default_int64")?.unwrap_or(-1))
+    } else if values.datum_type() == String::datum_type() {
+        tensor0(node.get_attr_opt::<String>("default_string")?.unwrap_or_else(|| "_Unused".into()))
+    } else {
+        tensor0(node.get_attr_opt::<f32>("default_float")?.unwrap_or(-0.0))
+    };
+    let default = default.cast_to_dt(values.datum_type())?.into_owned().into_arc_tensor();
+    let mapping = Mapping::KeysValues {
+        keys: keys.into_arc_tensor(),
+        values: values.into_arc_tensor(),
+        default,
+    };
+    Ok((expand(LabelEncoder(mapping)), vec![]))
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::test::*;
+    use super::*;
+
+    fn strings_tensor(strings: &[&str]) -> Tensor {
+        tensor1(&strings.iter().map(|s| s.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn strings_to_ints() -> TractResult<()> {
+        let attributes = vec![
+            strings("keys_strings", &["a", "b"]),
+            ints("values_int64s", &[1, 2]),
+            int("default_int64", -7),
+        ];
+        let output = run_node("LabelEncoder", attributes, 1, strings_tensor(&["b", "z", "a"]))?;
+        assert_eq!(*output[0], tensor1(&[2i64, -7, 1]));
+        Ok(())
+    }
+
+    #[test]
+    fn floats_to_strings() -> TractResult<()> {
+        let attributes =
+            vec![floats("keys_floats", &[0.5, -0.0]), strings("values_strings", &["half", "zero"])];
+        let output = run_node("LabelEncoder", attributes, 1, tensor1(&[0f32, 0.5, 1.]))?;
+        assert_eq!(*output[0], strings_tensor(&["zero", "half", "_Unused"]));
+        Ok(())
+    }
+
+    #[test]
+    fn classes_both_ways() -> TractResult<()> {
+        let attributes = || vec![strings("classes_strings", &["x", "y"])];
+        let output = run_node("LabelEncoder", attributes(), 1, strings_tensor(&["y", "w"]))?;
+        assert_eq!(*output[0], tensor1(&[1i64, -1]));
+        let output = run_node("LabelEncoder", attributes(), 1, tensor1(&[0i64, 3]))?;
+        assert_eq!(*output[0], strings_tensor(&["x", "_Unused"]));
+        Ok(())
+    }
+}
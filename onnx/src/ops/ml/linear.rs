@@ -0,0 +1,256 @@
+use crate::model::{OnnxOpRegister, ParsingContext};
+use crate::pb::NodeProto;
+use tract_hir::internal::*;
+use tract_hir::ops::array::TypedConcat;
+use tract_hir::tract_core::ops::einsum::EinSum;
+
+use super::{parse_post_transform, wire_labels, wire_post_transform, PostTransform};
+
+pub fn register_all_ops(reg: &mut OnnxOpRegister) {
+    reg.insert("LinearClassifier", linear_classifier);
+    reg.insert("LinearRegressor", linear_regressor);
+}
+
+fn parse_linear(node: &NodeProto, n_outputs: usize) -> TractResult<Linear> {
+    let coefficients = node.get_attr_vec::<f32>("coefficients")?;
+    node.expect_attr("coefficients", n_outputs > 0, "at least one output")?;
+    node.expect_attr("coefficients", coefficients.len() % n_outputs == 0, || {
+        format!("length to be a multiple of {n_outputs}")
+    })?;
+    let n_features = coefficients.len() / n_outputs;
+    let coefficients = tensor1(&coefficients).into_shape(&[n_outputs, n_features])?;
+    let intercepts = node.get_attr_opt_vec::<f32>("intercepts")?.unwrap_or(vec![0.0; n_outputs]);
+    node.expect_attr("intercepts", intercepts.len() == n_outputs, || {
+        format!("length {n_outputs}, got {}", intercepts.len())
+    })?;
+    let post_transform =
+        node.get_attr_opt("post_transform")?.map(parse_post_transform).transpose()?.unwrap_or(None);
+    Ok(Linear {
+        coefficients: coefficients.into_arc_tensor(),
+        intercepts: tensor1(&intercepts).into_shape(&[1, n_outputs])?.into_arc_tensor(),
+        post_transform,
+    })
+}
+
+fn linear_classifier(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let class_labels = if let Some(ints) = node.get_attr_opt_vec::<i64>("classlabels_ints")? {
+        rctensor1(&ints)
+    } else if let Some(strings) = node.get_attr_opt_vec::<String>("classlabels_strings")? {
+        rctensor1(&strings)
+    } else {
+        bail!("LinearClassifier requires one of 'classlabels_ints' or 'classlabels_strings'")
+    };
+    let n_scores = if let Some(intercepts) = node.get_attr_opt_slice::<f32>("intercepts")? {
+        intercepts.len()
+    } else {
+        class_labels.len()
+    };
+    node.expect(
+        n_scores == class_labels.len() || (n_scores == 1 && class_labels.len() == 2),
+        "as many intercepts as class labels, or one for binary classification",
+    )?;
+    let linear = parse_linear(node, n_scores)?;
+    Ok((expand(LinearClassifier { linear, class_labels }), vec![]))
+}
+
+fn linear_regressor(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let targets = node.get_attr_opt::<usize>("targets")?.unwrap_or(1);
+    Ok((expand(LinearRegressor(parse_linear(node, targets)?)), vec![]))
+}
+
+#[derive(Debug, Clone, Hash)]
+struct Linear {
+    /// [outputs, features]
+    coefficients: Arc<Tensor>,
+    /// [1, outputs]
+    intercepts: Arc<Tensor>,
+    post_transform: Option<PostTransform>,
+}
+
+impl Linear {
+    fn n_outputs(&self) -> usize {
+        self.coefficients.shape()[0]
+    }
+
+    /// [N, outputs] raw scores, before post transform.
+    fn wire_scores(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        input: OutletId,
+    ) -> TractResult<OutletId> {
+        let mut wire = input;
+        if model.outlet_fact(wire)?.datum_type != f32::datum_type() {
+            wire = model.wire_node(
+                format!("{prefix}.cast"),
+                tract_core::ops::cast::cast(f32::datum_type()),
+                &[wire],
+            )?[0];
+        }
+        let coefficients =
+            model.add_const(prefix.to_string() + ".coefficients", self.coefficients.clone())?;
+        let axes = AxesMapping::for_numpy_matmul(2, false, true, false)?;
+        wire = model.wire_node(
+            format!("{prefix}.matmul"),
+            EinSum::new(axes, f32::datum_type()),
+            &[wire, coefficients],
+        )?[0];
+        let intercepts =
+            model.add_const(prefix.to_string() + ".intercepts", self.intercepts.clone())?;
+        Ok(model.wire_node(
+            format!("{prefix}.add_intercepts"),
+            tract_core::ops::math::add(),
+            &[wire, intercepts],
+        )?[0])
+    }
+}
+
+#[derive(Debug, Clone, Hash)]
+struct LinearClassifier {
+    linear: Linear,
+    class_labels: Arc<Tensor>,
+}
+
+impl Expansion for LinearClassifier {
+    fn name(&self) -> Cow<str> {
+        "LinearClassifier".into()
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 1)?;
+        check_output_arity(outputs, 2)?;
+        s.equals(&inputs[0].rank, 2)?;
+        s.equals(&inputs[0].shape[1], self.linear.coefficients.shape()[1].to_dim())?;
+        s.equals(&outputs[0].datum_type, self.class_labels.datum_type())?;
+        s.equals(&outputs[1].datum_type, f32::datum_type())?;
+        s.equals(&outputs[0].rank, 1)?;
+        s.equals(&outputs[1].rank, 2)?;
+        s.equals(&outputs[0].shape[0], &inputs[0].shape[0])?;
+        s.equals(&outputs[1].shape[0], &inputs[0].shape[0])?;
+        s.equals(&outputs[1].shape[1], self.linear.n_outputs().max(2).to_dim())?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let mut scores = self.linear.wire_scores(prefix, model, inputs[0])?;
+        if self.linear.n_outputs() == 1 {
+            // binary classification: the single score is for the second class, so we
+            // build [-score, score] for the post transform and the argmax
+            let neg = model.wire_node(
+                format!("{prefix}.binary_neg"),
+                tract_core::ops::math::neg(),
+                &[scores],
+            )?[0];
+            scores = model.wire_node(
+                format!("{prefix}.binary_scores"),
+                TypedConcat::new(1),
+                &[neg, scores],
+            )?[0];
+        }
+        let scores = wire_post_transform(prefix, model, self.linear.post_transform, tvec!(scores))?;
+        let labels = wire_labels(prefix, model, scores[0], &self.class_labels)?;
+        Ok(tvec!(labels, scores[0]))
+    }
+
+    fn nboutputs(&self) -> TractResult<usize> {
+        Ok(2)
+    }
+}
+
+#[derive(Debug, Clone, Hash)]
+struct LinearRegressor(Linear);
+
+impl Expansion for LinearRegressor {
+    fn name(&self) -> Cow<str> {
+        "LinearRegressor".into()
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 1)?;
+        check_output_arity(outputs, 1)?;
+        s.equals(&inputs[0].rank, 2)?;
+        s.equals(&inputs[0].shape[1], self.0.coefficients.shape()[1].to_dim())?;
+        s.equals(&outputs[0].datum_type, f32::datum_type())?;
+        s.equals(&outputs[0].rank, 2)?;
+        s.equals(&outputs[0].shape[0], &inputs[0].shape[0])?;
+        s.equals(&outputs[0].shape[1], self.0.n_outputs().to_dim())?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let scores = self.0.wire_scores(prefix, model, inputs[0])?;
+        wire_post_transform(prefix, model, self.0.post_transform, tvec!(scores))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::test::*;
+    use super::*;
+
+    #[test]
+    fn classifier() -> TractResult<()> {
+        let attributes = vec![
+            floats("coefficients", &[1., 0., 0., 1., -1., -1.]),
+            floats("intercepts", &[0., 0., 0.5]),
+            ints("classlabels_ints", &[10, 20, 30]),
+        ];
+        let output = run_node("LinearClassifier", attributes, 2, tensor2(&[[2f32, 1.], [0., 0.]]))?;
+        assert_eq!(*output[0], tensor1(&[10i64, 30]));
+        assert_eq!(*output[1], tensor2(&[[2f32, 1., -2.5], [0., 0., 0.5]]));
+        Ok(())
+    }
+
+    #[test]
+    fn binary_classifier() -> TractResult<()> {
+        let attributes = vec![
+            floats("coefficients", &[1., -1.]),
+            floats("intercepts", &[0.]),
+            strings("classlabels_strings", &["neg", "pos"]),
+            string("post_transform", "LOGISTIC"),
+        ];
+        let output = run_node("LinearClassifier", attributes, 2, tensor2(&[[2i64, 1], [0, 3]]))?;
+        assert_eq!(*output[0], tensor1(&["pos".to_string(), "neg".into()]));
+        let sigmoid = |x: f32| 1. / (1. + (-x).exp());
+        let expected = tensor2(&[[sigmoid(-1.), sigmoid(1.)], [sigmoid(3.), sigmoid(-3.)]]);
+        output[1].close_enough(&expected, Approximation::Close)
+    }
+
+    #[test]
+    fn regressor() -> TractResult<()> {
+        let attributes = vec![
+            floats("coefficients", &[1., 2., 3., 4.]),
+            floats("intercepts", &[0.5, -0.5]),
+            int("targets", 2),
+        ];
+        let output = run_node("LinearRegressor", attributes, 1, tensor2(&[[1f32, 1.], [1., 0.]]))?;
+        assert_eq!(*output[0], tensor2(&[[3.5f32, 6.5], [1.5, 2.5]]));
+        Ok(())
+    }
+}
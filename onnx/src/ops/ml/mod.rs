@@ -1,9 +1,193 @@
 mod category_mapper;
+mod label_encoder;
+mod linear;
+mod normalizer;
+mod one_hot_encoder;
+mod string_normalizer;
+mod tfidf_vectorizer;
 mod tree_ensemble_classifier;
+mod tree_ensemble_regressor;
 
 use crate::model::OnnxOpRegister;
+use tract_hir::internal::*;
 
 pub fn register_all_ops(reg: &mut OnnxOpRegister) {
     category_mapper::register_all_ops(reg);
+    label_encoder::register_all_ops(reg);
+    linear::register_all_ops(reg);
+    normalizer::register_all_ops(reg);
+    one_hot_encoder::register_all_ops(reg);
+    string_normalizer::register_all_ops(reg);
+    tfidf_vectorizer::register_all_ops(reg);
     tree_ensemble_classifier::register_all_ops(reg);
+    tree_ensemble_regressor::register_all_ops(reg);
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PostTransform {
+    Softmax,
+    Logistic,
+    // SoftmaxZero,
+    // Probit, // probit, especially multinomial, is p.i.t.a. - so let's ignore it for now
+}
+
+pub fn parse_post_transform(s: &str) -> TractResult<Option<PostTransform>> {
+    match s {
+        "NONE" => Ok(None),
+        "SOFTMAX" => Ok(Some(PostTransform::Softmax)),
+        "LOGISTIC" => Ok(Some(PostTransform::Logistic)),
+        "PROBIT" | "SOFTMAX_ZERO" => bail!("PROBIT and SOFTMAX_ZERO unsupported"),
+        _ => bail!("Invalid post transform: {}", s),
+    }
+}
+
+/// Apply a post transform on [N, C] scores.
+fn wire_post_transform(
+    prefix: &str,
+    model: &mut TypedModel,
+    post_transform: Option<PostTransform>,
+    scores: TVec<OutletId>,
+) -> TractResult<TVec<OutletId>> {
+    match post_transform {
+        None => Ok(scores),
+        Some(PostTransform::Softmax) => tract_hir::ops::nn::LayerSoftmax::new(1, false).wire(
+            &format!("{prefix}.softmax"),
+            model,
+            &scores,
+        ),
+        Some(PostTransform::Logistic) => model.wire_node(
+            format!("{prefix}.logistic"),
+            tract_core::ops::nn::sigmoid(),
+            &scores,
+        ),
+    }
+}
+
+/// Label of the best [N, C] scores, looked up in the class labels.
+fn wire_labels(
+    prefix: &str,
+    model: &mut TypedModel,
+    scores: OutletId,
+    class_labels: &Arc<Tensor>,
+) -> TractResult<OutletId> {
+    use tract_core::ops::nn::{Reduce, Reducer};
+    let winners = model.wire_node(
+        format!("{prefix}.argmax"),
+        Reduce::new(tvec!(1), Reducer::ArgMax(false)),
+        &[scores],
+    )?;
+    let reduced = model.wire_node(
+        format!("{prefix}.rm_axis"),
+        tract_core::ops::change_axes::AxisOp::Rm(1),
+        &winners,
+    )?;
+    let casted = model.wire_node(
+        format!("{prefix}.casted"),
+        tract_core::ops::cast::cast(i32::datum_type()),
+        &reduced,
+    )?;
+    Ok(model.wire_node(
+        format!("{prefix}.labels"),
+        tract_onnx_opl::ml::DirectLookup::new(
+            class_labels.clone(),
+            // argmax is always in range, the fallback is never used
+            class_labels.slice(0, 0, 1)?.into_shape(&[])?.into_arc_tensor(),
+        )?,
+        &casted,
+    )?[0])
+}
+
+#[cfg(test)]
+pub(super) mod test {
+    use super::*;
+    use crate::pb::*;
+
+    pub fn int(name: &str, i: i64) -> AttributeProto {
+        let r#type = attribute_proto::AttributeType::Int as i32;
+        AttributeProto { name: name.into(), r#type, i, ..Default::default() }
+    }
+
+    pub fn ints(name: &str, ints: &[i64]) -> AttributeProto {
+        let r#type = attribute_proto::AttributeType::Ints as i32;
+        AttributeProto { name: name.into(), r#type, ints: ints.to_vec(), ..Default::default() }
+    }
+
+    pub fn floats(name: &str, floats: &[f32]) -> AttributeProto {
+        let r#type = attribute_proto::AttributeType::Floats as i32;
+        AttributeProto { name: name.into(), r#type, floats: floats.to_vec(), ..Default::default() }
+    }
+
+    pub fn string(name: &str, s: &str) -> AttributeProto {
+        let r#type = attribute_proto::AttributeType::String as i32;
+        AttributeProto { name: name.into(), r#type, s: s.into(), ..Default::default() }
+    }
+
+    pub fn strings(name: &str, strings: &[&str]) -> AttributeProto {
+        let r#type = attribute_proto::AttributeType::Strings as i32;
+        let strings = strings.iter().map(|s| s.as_bytes().to_vec()).collect();
+        AttributeProto { name: name.into(), r#type, strings, ..Default::default() }
+    }
+
+    /// Loads and runs a model made of a single ai.onnx.ml node, applied to `input`.
+    pub fn run_node(
+        op_type: &str,
+        attribute: Vec<AttributeProto>,
+        outputs: usize,
+        input: Tensor,
+    ) -> TractResult<TVec<TValue>> {
+        use tensor_proto::DataType;
+        let elem_type = match input.datum_type() {
+            DatumType::F32 => DataType::Float,
+            DatumType::F64 => DataType::Double,
+            DatumType::I32 => DataType::Int32,
+            DatumType::I64 => DataType::Int64,
+            DatumType::String => DataType::String,
+            dt => bail!("Unsupported input type {:?}", dt),
+        } as i32;
+        let tensor = type_proto::Tensor { elem_type, shape: None };
+        let r#type =
+            TypeProto { value: Some(type_proto::Value::TensorType(tensor)), ..Default::default() };
+        let value_info = |name: String, r#type: Option<TypeProto>| ValueInfoProto {
+            name,
+            r#type,
+            ..Default::default()
+        };
+        let output_names: Vec<String> = (0..outputs).map(|ix| format!("y{ix}")).collect();
+        let node = NodeProto {
+            op_type: op_type.into(),
+            domain: "ai.onnx.ml".into(),
+            input: vec!["x".into()],
+            output: output_names.clone(),
+            attribute,
+            ..Default::default()
+        };
+        let graph = GraphProto {
+            node: vec![node],
+            input: vec![value_info("x".into(), Some(r#type))],
+            output: output_names.into_iter().map(|name| value_info(name, None)).collect(),
+            ..Default::default()
+        };
+        let opset = |domain: &str, version| OperatorSetIdProto { domain: domain.into(), version };
+        let proto = ModelProto {
+            graph: Some(graph),
+            opset_import: vec![opset("", 13), opset("ai.onnx.ml", 3)],
+            ..Default::default()
+        };
+        let mut model = crate::onnx().model_for_proto_model(&proto)?;
+        model.set_input_fact(0, InferenceFact::dt_shape(input.datum_type(), input.shape()))?;
+        model.into_optimized()?.into_runnable()?.run(tvec!(input.into_tvalue()))
+    }
+
+    #[test]
+    fn string_labels() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let scores = model.add_source("scores", f32::fact([2, 3]))?;
+        let labels = rctensor1(&["a".to_string(), "b".into(), "c".into()]);
+        let labels = wire_labels("labels", &mut model, scores, &labels)?;
+        model.set_output_outlets(&[labels])?;
+        let scores = tensor2(&[[0f32, 2., 1.], [3., 2., 1.]]);
+        let output = model.into_runnable()?.run(tvec!(scores.into()))?;
+        assert_eq!(*output[0], tensor1(&["b".to_string(), "a".into()]));
+        Ok(())
+    }
 }
@@ -0,0 +1,206 @@
+use crate::model::{OnnxOpRegister, ParsingContext};
+use crate::pb::NodeProto;
+use tract_hir::internal::*;
+use tract_hir::tract_core::ops::binary::wire_with_rank_broadcast;
+use tract_hir::tract_core::ops::math;
+use tract_hir::tract_core::ops::nn::{Reduce, Reducer};
+
+pub fn register_all_ops(reg: &mut OnnxOpRegister) {
+    reg.insert("Normalizer", normalizer);
+    reg.insert("Scaler", scaler);
+}
+
+fn normalizer(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let norm = match node.get_attr_opt("norm")?.unwrap_or("MAX") {
+        "MAX" => Norm::Max,
+        "L1" => Norm::L1,
+        "L2" => Norm::L2,
+        other => bail!("Unsupported norm: {}", other),
+    };
+    Ok((expand(Normalizer { norm }), vec![]))
+}
+
+fn scaler(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let offset = node.get_attr_opt_vec::<f32>("offset")?.unwrap_or(vec![0.0]);
+    let scale = node.get_attr_opt_vec::<f32>("scale")?.unwrap_or(vec![1.0]);
+    Ok((expand(Scaler { offset: rctensor1(&offset), scale: rctensor1(&scale) }), vec![]))
+}
+
+fn wire_cast_to_f32(
+    prefix: &str,
+    model: &mut TypedModel,
+    input: OutletId,
+) -> TractResult<OutletId> {
+    if model.outlet_fact(input)?.datum_type == f32::datum_type() {
+        Ok(input)
+    } else {
+        Ok(model.wire_node(
+            format!("{prefix}.cast"),
+            tract_core::ops::cast::cast(f32::datum_type()),
+            &[input],
+        )?[0])
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Norm {
+    Max,
+    L1,
+    L2,
+}
+
+/// Normalizes each row (the last axis) by its max, L1 or L2 norm. Rows with a null norm are left
+/// untouched.
+#[derive(Debug, Clone, Hash)]
+struct Normalizer {
+    norm: Norm,
+}
+
+impl Expansion for Normalizer {
+    fn name(&self) -> Cow<str> {
+        "Normalizer".into()
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 1)?;
+        check_output_arity(outputs, 1)?;
+        s.equals(&outputs[0].datum_type, f32::datum_type())?;
+        s.equals(&inputs[0].shape, &outputs[0].shape)?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let x = wire_cast_to_f32(prefix, model, inputs[0])?;
+        let axis = model.outlet_fact(x)?.rank() - 1;
+        let norm = match self.norm {
+            Norm::Max => model.wire_node(
+                format!("{prefix}.max"),
+                Reduce::new(tvec!(axis), Reducer::Max),
+                &[x],
+            )?,
+            Norm::L1 => {
+                let abs = model.wire_node(format!("{prefix}.abs"), math::abs(), &[x])?;
+                model.wire_node(
+                    format!("{prefix}.sum"),
+                    Reduce::new(tvec!(axis), Reducer::Sum),
+                    &abs,
+                )?
+            }
+            Norm::L2 => {
+                let sqr = model.wire_node(format!("{prefix}.sqr"), math::square(), &[x])?;
+                let sum = model.wire_node(
+                    format!("{prefix}.sum"),
+                    Reduce::new(tvec!(axis), Reducer::Sum),
+                    &sqr,
+                )?;
+                model.wire_node(format!("{prefix}.sqrt"), math::sqrt(), &sum)?
+            }
+        };
+        let zero = model.add_const(
+            prefix.to_string() + ".zero",
+            tensor0(0f32).broadcast_into_rank(axis + 1)?,
+        )?;
+        let is_zero = model.wire_node(
+            format!("{prefix}.is_zero"),
+            tract_core::ops::logic::equals(),
+            &[norm[0], zero],
+        )?;
+        let is_zero = model.wire_node(
+            format!("{prefix}.is_zero_as_f32"),
+            tract_core::ops::cast::cast(f32::datum_type()),
+            &is_zero,
+        )?;
+        let divisor =
+            model.wire_node(format!("{prefix}.divisor"), math::add(), &[norm[0], is_zero[0]])?;
+        model.wire_node(format!("{prefix}.div"), math::div(), &[x, divisor[0]])
+    }
+}
+
+/// `(X - offset) * scale`, with offset and scale broadcast over the last axis.
+#[derive(Debug, Clone, Hash)]
+struct Scaler {
+    offset: Arc<Tensor>,
+    scale: Arc<Tensor>,
+}
+
+impl Expansion for Scaler {
+    fn name(&self) -> Cow<str> {
+        "Scaler".into()
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 1)?;
+        check_output_arity(outputs, 1)?;
+        s.equals(&outputs[0].datum_type, f32::datum_type())?;
+        s.equals(&inputs[0].shape, &outputs[0].shape)?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let x = wire_cast_to_f32(prefix, model, inputs[0])?;
+        let offset = model.add_const(prefix.to_string() + ".offset", self.offset.clone())?;
+        let scale = model.add_const(prefix.to_string() + ".scale", self.scale.clone())?;
+        let centered =
+            wire_with_rank_broadcast(format!("{prefix}.sub"), model, math::sub(), &[x, offset])?;
+        wire_with_rank_broadcast(format!("{prefix}.mul"), model, math::mul(), &[centered[0], scale])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::test::*;
+    use super::*;
+
+    fn normalize(norm: &str, input: Tensor) -> TractResult<Tensor> {
+        let output = run_node("Normalizer", vec![string("norm", norm)], 1, input)?;
+        Ok(output[0].clone().into_tensor())
+    }
+
+    #[test]
+    fn norms() -> TractResult<()> {
+        let input = tensor2(&[[1f32, 2.], [0., 0.], [-4., 2.]]);
+        let max = normalize("MAX", input.clone())?;
+        assert_eq!(max, tensor2(&[[0.5f32, 1.], [0., 0.], [-2., 1.]]));
+        let l1 = normalize("L1", input.clone())?;
+        let expected = tensor2(&[[1. / 3., 2. / 3.], [0., 0.], [-4. / 6., 2. / 6f32]]);
+        l1.close_enough(&expected, Approximation::Close)?;
+        let l2 = normalize("L2", tensor2(&[[3i64, 4], [0, 0]]))?;
+        l2.close_enough(&tensor2(&[[0.6f32, 0.8], [0., 0.]]), Approximation::Close)
+    }
+
+    #[test]
+    fn scaler() -> TractResult<()> {
+        let attributes = vec![floats("offset", &[1., 2.]), floats("scale", &[2., 0.5])];
+        let output = run_node("Scaler", attributes, 1, tensor2(&[[1f32, 4.], [3., 6.]]))?;
+        assert_eq!(*output[0], tensor2(&[[0f32, 1.], [4., 2.]]));
+        let output = run_node("Scaler", vec![floats("offset", &[1.])], 1, tensor1(&[3i64, 5]))?;
+        assert_eq!(*output[0], tensor1(&[2f32, 4.]));
+        Ok(())
+    }
+}
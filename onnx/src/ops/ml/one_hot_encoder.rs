@@ -0,0 +1,169 @@
+use crate::model::{OnnxOpRegister, ParsingContext};
+use crate::pb::NodeProto;
+use tract_hir::internal::*;
+use tract_onnx_opl::ml::*;
+
+pub fn register_all_ops(reg: &mut OnnxOpRegister) {
+    reg.insert("OneHotEncoder", one_hot_encoder);
+}
+
+fn one_hot_encoder(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let categories = if let Some(ints) = node.get_attr_opt_vec::<i64>("cats_int64s")? {
+        rctensor1(&ints)
+    } else if let Some(strings) = node.get_attr_opt_vec::<String>("cats_strings")? {
+        rctensor1(&strings)
+    } else {
+        bail!("OneHotEncoder requires one of 'cats_int64s' or 'cats_strings'")
+    };
+    let zeros = node.get_attr_opt::<i64>("zeros")?.unwrap_or(1) != 0;
+    Ok((expand(OneHotEncoder { categories, zeros }), vec![]))
+}
+
+/// Adds a trailing axis of length the number of categories, with a one at the position of the
+/// input category. Unknown categories get a row of zeros if `zeros` is set, and are an error
+/// otherwise.
+#[derive(Debug, Clone, Hash)]
+struct OneHotEncoder {
+    categories: Arc<Tensor>,
+    zeros: bool,
+}
+
+impl Expansion for OneHotEncoder {
+    fn name(&self) -> Cow<str> {
+        "OneHotEncoder".into()
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 1)?;
+        check_output_arity(outputs, 1)?;
+        s.equals(&outputs[0].datum_type, f32::datum_type())?;
+        s.equals(inputs[0].rank.bex() + 1, &outputs[0].rank)?;
+        s.given(&inputs[0].shape, move |s, shape| {
+            let mut shape = shape;
+            shape.push(self.categories.len().to_dim());
+            s.equals(&outputs[0].shape, shape)
+        })
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let fact = model.outlet_fact(inputs[0])?.clone();
+        let mut wire = tvec!(inputs[0]);
+        if fact.datum_type != self.categories.datum_type() {
+            wire = model.wire_node(
+                format!("{prefix}.cast"),
+                tract_core::ops::cast::cast(self.categories.datum_type()),
+                &wire,
+            )?;
+        }
+        wire = model.wire_node(
+            format!("{prefix}.index"),
+            ReverseLookup::new(self.categories.clone(), -1)?,
+            &wire,
+        )?;
+        if !self.zeros {
+            wire = model.wire_node(format!("{prefix}.known"), KnownCategories, &wire)?;
+        }
+        wire = model.wire_node(format!("{prefix}.add_axis"), AxisOp::Add(fact.rank()), &wire)?;
+        let range = tensor1(&(0..self.categories.len() as i32).collect::<Vec<_>>())
+            .broadcast_into_rank(fact.rank() + 1)?;
+        let range = model.add_const(prefix.to_string() + ".range", range)?;
+        wire = model.wire_node(
+            format!("{prefix}.eq"),
+            tract_core::ops::logic::equals(),
+            &[wire[0], range],
+        )?;
+        model.wire_node(
+            format!("{prefix}.to_f32"),
+            tract_core::ops::cast::cast(f32::datum_type()),
+            &wire,
+        )
+    }
+}
+
+/// Checks all the category indices found by the reverse lookup are valid.
+#[derive(Debug, Clone, Hash)]
+struct KnownCategories;
+
+impl Op for KnownCategories {
+    fn name(&self) -> Cow<str> {
+        "KnownCategories".into()
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for KnownCategories {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        let indices = args_1!(inputs);
+        ensure!(
+            indices.as_slice::<i32>()?.iter().all(|ix| *ix >= 0),
+            "OneHotEncoder input contains a category it does not know, and zeros is not set"
+        );
+        Ok(tvec!(indices))
+    }
+}
+
+impl TypedOp for KnownCategories {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        Ok(tvec!(inputs[0].datum_type.fact(inputs[0].shape.clone())))
+    }
+
+    as_op!();
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::test::*;
+    use super::*;
+
+    #[test]
+    fn int_categories() -> TractResult<()> {
+        let output = run_node(
+            "OneHotEncoder",
+            vec![ints("cats_int64s", &[1, 3, 5])],
+            1,
+            tensor2(&[[5i64, 1, 2]]),
+        )?;
+        let expected = tensor3(&[[[0f32, 0., 1.], [1., 0., 0.], [0., 0., 0.]]]);
+        assert_eq!(*output[0], expected);
+        Ok(())
+    }
+
+    #[test]
+    fn string_categories() -> TractResult<()> {
+        let output = run_node(
+            "OneHotEncoder",
+            vec![strings("cats_strings", &["a", "b"])],
+            1,
+            tensor1(&["b".to_string(), "c".into()]),
+        )?;
+        assert_eq!(*output[0], tensor2(&[[0f32, 1.], [0., 0.]]));
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_category_without_zeros() -> TractResult<()> {
+        let attributes = || vec![ints("cats_int64s", &[1, 3]), int("zeros", 0)];
+        let output = run_node("OneHotEncoder", attributes(), 1, tensor1(&[3i64, 1]))?;
+        assert_eq!(*output[0], tensor2(&[[0f32, 1.], [1., 0.]]));
+        assert!(run_node("OneHotEncoder", attributes(), 1, tensor1(&[3i64, 2])).is_err());
+        Ok(())
+    }
+}
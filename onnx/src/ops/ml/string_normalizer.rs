@@ -0,0 +1,136 @@
+use crate::model::{OnnxOpRegister, ParsingContext};
+use crate::pb::NodeProto;
+use tract_hir::internal::*;
+
+pub fn register_all_ops(reg: &mut OnnxOpRegister) {
+    reg.insert("StringNormalizer", string_normalizer);
+}
+
+fn string_normalizer(
+    ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let case_change = match node.get_attr_opt("case_change_action")?.unwrap_or("NONE") {
+        "NONE" => CaseChange::None,
+        "LOWER" => CaseChange::Lower,
+        "UPPER" => CaseChange::Upper,
+        other => bail!("Unsupported case_change_action: {}", other),
+    };
+    let case_sensitive = node.get_attr_opt("is_case_sensitive")?.unwrap_or(false);
+    let stopwords: Vec<String> = node.get_attr_opt_vec("stopwords")?.unwrap_or_default();
+    let stopwords = if case_sensitive {
+        stopwords
+    } else {
+        stopwords.iter().map(|w| w.to_lowercase()).collect()
+    };
+    // filtering stop words makes the output length data-dependent
+    let len = if stopwords.is_empty() { None } else { Some(ctx.symbol_table.new_with_prefix("s")) };
+    Ok((Box::new(StringNormalizer { case_change, case_sensitive, stopwords, len }), vec![]))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CaseChange {
+    None,
+    Lower,
+    Upper,
+}
+
+/// Removes stop words from a [C] or [1, C] string tensor, then changes the case of the remaining
+/// ones. The locale attribute is ignored: case changes follow Unicode rules.
+#[derive(Debug, Clone, Hash)]
+pub struct StringNormalizer {
+    pub case_change: CaseChange,
+    pub case_sensitive: bool,
+    pub stopwords: Vec<String>,
+    pub len: Option<Symbol>,
+}
+
+impl StringNormalizer {
+    fn output_shape(&self, input_shape: &[TDim]) -> TVec<TDim> {
+        let mut shape: TVec<TDim> = input_shape.into();
+        if let Some(len) = &self.len {
+            *shape.last_mut().unwrap() = len.to_dim();
+        }
+        shape
+    }
+}
+
+impl Op for StringNormalizer {
+    fn name(&self) -> Cow<str> {
+        "StringNormalizer".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!(
+            "case change: {:?} case sensitive: {:?} stopwords: {:?}",
+            self.case_change, self.case_sensitive, self.stopwords
+        )])
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for StringNormalizer {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        let input = args_1!(inputs);
+        let mut output: Vec<String> = input
+            .as_slice::<String>()?
+            .iter()
+            .filter(|w| {
+                if self.case_sensitive {
+                    !self.stopwords.contains(w)
+                } else {
+                    !self.stopwords.contains(&w.to_lowercase())
+                }
+            })
+            .map(|w| match self.case_change {
+                CaseChange::None => w.clone(),
+                CaseChange::Lower => w.to_lowercase(),
+                CaseChange::Upper => w.to_uppercase(),
+            })
+            .collect();
+        if output.is_empty() {
+            output.push(String::new());
+        }
+        let mut shape: TVec<usize> = input.shape().into();
+        *shape.last_mut().unwrap() = output.len();
+        Ok(tvec!(tensor1(&output).into_shape(&shape)?.into_tvalue()))
+    }
+}
+
+impl InferenceRulesOp for StringNormalizer {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 1)?;
+        check_output_arity(outputs, 1)?;
+        s.equals(&inputs[0].datum_type, String::datum_type())?;
+        s.equals(&outputs[0].datum_type, String::datum_type())?;
+        s.equals(&inputs[0].rank, &outputs[0].rank)?;
+        s.given(&inputs[0].shape, move |s, shape| {
+            ensure!(
+                shape.len() == 1 || (shape.len() == 2 && shape[0].is_one()),
+                "StringNormalizer expects a [C] or [1, C] input"
+            );
+            s.equals(&outputs[0].shape, ShapeFactoid::from(self.output_shape(&shape)))
+        })
+    }
+
+    as_op!();
+    to_typed!();
+}
+
+impl TypedOp for StringNormalizer {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        Ok(tvec!(String::fact(self.output_shape(&inputs[0].shape))))
+    }
+
+    as_op!();
+}
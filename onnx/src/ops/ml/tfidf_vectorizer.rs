@@ -0,0 +1,229 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::model::{OnnxOpRegister, ParsingContext};
+use crate::pb::NodeProto;
+use tract_hir::internal::*;
+use tract_ndarray::prelude::*;
+
+pub fn register_all_ops(reg: &mut OnnxOpRegister) {
+    reg.insert("TfIdfVectorizer", tfidf_vectorizer);
+}
+
+fn tfidf_vectorizer(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let min_gram_length: usize = node.get_attr("min_gram_length")?;
+    let max_gram_length: usize = node.get_attr("max_gram_length")?;
+    let max_skip_count: usize = node.get_attr("max_skip_count")?;
+    node.expect_attr("min_gram_length", min_gram_length > 0, "a strictly positive value")?;
+    node.expect_attr(
+        "max_gram_length",
+        max_gram_length >= min_gram_length,
+        "at least min_gram_length",
+    )?;
+    let mode = match node.get_attr("mode")? {
+        "TF" => TfIdfMode::Tf,
+        "IDF" => TfIdfMode::Idf,
+        "TFIDF" => TfIdfMode::TfIdf,
+        other => bail!("Unsupported TfIdfVectorizer mode: {}", other),
+    };
+    let pool = if let Some(ints) = node.get_attr_opt_vec::<i64>("pool_int64s")? {
+        tensor1(&ints)
+    } else if let Some(strings) = node.get_attr_opt_vec::<String>("pool_strings")? {
+        tensor1(&strings)
+    } else {
+        bail!("TfIdfVectorizer requires one of 'pool_int64s' or 'pool_strings'")
+    };
+    let ngram_counts = node.get_attr_vec::<usize>("ngram_counts")?;
+    let ngram_indexes = node.get_attr_vec::<usize>("ngram_indexes")?;
+    let output_len = ngram_indexes.iter().max().map(|&m| m + 1).unwrap_or(0);
+    let weights = node.get_attr_opt_vec::<f32>("weights")?;
+    if let Some(weights) = &weights {
+        node.expect_attr("weights", weights.len() == output_len, || {
+            format!("length {output_len}, got {}", weights.len())
+        })?;
+    }
+
+    // enumerate the n-grams in pool order, as ngram_indexes does, keeping the ones with a
+    // relevant length
+    let mut ngrams = vec![];
+    let mut id = 0;
+    for (ix, &start) in ngram_counts.iter().enumerate() {
+        let n = ix + 1;
+        let end = ngram_counts.get(ix + 1).copied().unwrap_or(pool.len());
+        node.expect_attr(
+            "ngram_counts",
+            start <= end && end <= pool.len(),
+            "increasing offsets in pool",
+        )?;
+        node.expect_attr("ngram_counts", (end - start) % n == 0, || format!("whole {n}-grams"))?;
+        for gram in (start..end).step_by(n) {
+            if (min_gram_length..=max_gram_length).contains(&n) {
+                let index = ngram_indexes.get(id).context("ngram_indexes is too short")?;
+                ngrams.push((pool.slice(0, gram, gram + n)?, *index));
+            }
+            id += 1;
+        }
+    }
+    Ok((
+        Box::new(TfIdfVectorizer {
+            min_gram_length,
+            max_gram_length,
+            max_skip_count,
+            mode,
+            ngrams,
+            output_len,
+            weights: weights.map(|w| rctensor1(&w)),
+        }),
+        vec![],
+    ))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TfIdfMode {
+    Tf,
+    Idf,
+    TfIdf,
+}
+
+/// Counts the n-grams of the pool in each row of the input (with up to `max_skip_count` items
+/// skipped between the n-gram items), and weights them according to the mode.
+#[derive(Debug, Clone, Hash)]
+pub struct TfIdfVectorizer {
+    pub min_gram_length: usize,
+    pub max_gram_length: usize,
+    pub max_skip_count: usize,
+    pub mode: TfIdfMode,
+    /// n-gram and its output index
+    pub ngrams: Vec<(Tensor, usize)>,
+    pub output_len: usize,
+    pub weights: Option<Arc<Tensor>>,
+}
+
+impl TfIdfVectorizer {
+    fn output_shape<D: DimLike>(&self, input_shape: &[D]) -> TVec<D> {
+        let mut shape: TVec<D> = input_shape.into();
+        *shape.last_mut().unwrap() = D::from(self.output_len);
+        shape
+    }
+
+    fn count_t<T: Datum + Hash + Eq>(&self, input: &Tensor) -> TractResult<Array2<f32>> {
+        let grams: HashMap<&[T], usize> = self
+            .ngrams
+            .iter()
+            .map(|(gram, ix)| Ok((gram.as_slice::<T>()?, *ix)))
+            .collect::<TractResult<_>>()?;
+        let input = input.to_array_view::<T>()?;
+        let row_len = input.shape().last().copied().unwrap_or(1);
+        let rows = input.len() / row_len.max(1);
+        let input = input.into_shape((rows, row_len))?;
+        let mut counts = Array2::<f32>::zeros((input.nrows(), self.output_len));
+        let mut gram = vec![];
+        for (row, mut counts) in input.outer_iter().zip(counts.outer_iter_mut()) {
+            let mut min_gram_length = self.min_gram_length;
+            for skip in 1..=self.max_skip_count + 1 {
+                for start in 0..row.len() {
+                    gram.clear();
+                    for item in (start..row.len()).step_by(skip).take(self.max_gram_length) {
+                        gram.push(row[item].clone());
+                        if gram.len() >= min_gram_length {
+                            if let Some(&ix) = grams.get(&*gram) {
+                                counts[ix] += 1.0;
+                            }
+                        }
+                    }
+                }
+                // unigrams do not depend on the skip distance, only count them once
+                if min_gram_length == 1 {
+                    min_gram_length = 2;
+                    if min_gram_length > self.max_gram_length {
+                        break;
+                    }
+                }
+            }
+        }
+        Ok(counts)
+    }
+}
+
+impl Op for TfIdfVectorizer {
+    fn name(&self) -> Cow<str> {
+        "TfIdfVectorizer".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!(
+            "mode: {:?} grams: {}..={} max skip: {} n-grams: {}",
+            self.mode,
+            self.min_gram_length,
+            self.max_gram_length,
+            self.max_skip_count,
+            self.ngrams.len()
+        )])
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for TfIdfVectorizer {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        let input = args_1!(inputs);
+        let mut counts = if input.datum_type() == String::datum_type() {
+            self.count_t::<String>(&input)?
+        } else {
+            let input = input.cast_to::<i64>()?;
+            self.count_t::<i64>(&input)?
+        };
+        let weights = self.weights.as_ref().map(|w| w.as_slice::<f32>()).transpose()?;
+        for mut row in counts.outer_iter_mut() {
+            for (ix, count) in row.iter_mut().enumerate() {
+                let weight = weights.map(|w| w[ix]).unwrap_or(1.0);
+                *count = match self.mode {
+                    TfIdfMode::Tf => *count,
+                    TfIdfMode::Idf => (*count > 0.0) as usize as f32 * weight,
+                    TfIdfMode::TfIdf => *count * weight,
+                };
+            }
+        }
+        let shape = self.output_shape(input.shape());
+        Ok(tvec!(counts.into_tensor().into_shape(&shape)?.into_tvalue()))
+    }
+}
+
+impl InferenceRulesOp for TfIdfVectorizer {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 1)?;
+        check_output_arity(outputs, 1)?;
+        s.equals(&outputs[0].datum_type, f32::datum_type())?;
+        s.equals(&inputs[0].rank, &outputs[0].rank)?;
+        s.given(&inputs[0].shape, move |s, shape| {
+            ensure!(
+                shape.len() == 1 || shape.len() == 2,
+                "TfIdfVectorizer expects a 1D or 2D input"
+            );
+            s.equals(&outputs[0].shape, ShapeFactoid::from(self.output_shape(&shape)))
+        })
+    }
+
+    as_op!();
+    to_typed!();
+}
+
+impl TypedOp for TfIdfVectorizer {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        Ok(tvec!(f32::fact(self.output_shape(&inputs[0].shape))))
+    }
+
+    as_op!();
+}
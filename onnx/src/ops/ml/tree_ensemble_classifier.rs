@@ -6,6 +6,8 @@ use tract_hir::internal::*;
 use tract_hir::ops::array::{Slice, TypedConcat};
 use tract_onnx_opl::ml::tree::*;
 
+use super::{parse_post_transform, wire_labels, wire_post_transform, PostTransform};
+
 pub fn register_all_ops(reg: &mut OnnxOpRegister) {
     reg.insert("TreeEnsembleClassifier", tree_classifier);
 }
@@ -43,24 +45,6 @@ fn tree_classifier(
     ))
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
-pub enum PostTransform {
-    Softmax,
-    Logistic,
-    // SoftmaxZero,
-    // Probit, // probit, especially multinomial, is p.i.t.a. - so let's ignore it for now
-}
-
-pub fn parse_post_transform(s: &str) -> TractResult<Option<PostTransform>> {
-    match s {
-        "NONE" => Ok(None),
-        "SOFTMAX" => Ok(Some(PostTransform::Softmax)),
-        "LOGISTIC" => Ok(Some(PostTransform::Logistic)),
-        "PROBIT" | "SOFTMAX_ZERO" => bail!("PROBIT and SOFTMAX_ZERO unsupported"),
-        _ => bail!("Invalid post transform: {}", s),
-    }
-}
-
 fn parse_node_mode(s: &str) -> TractResult<Option<Cmp>> {
     match s {
         "BRANCH_LEQ" => Ok(Some(Cmp::LessEqual)),
@@ -74,7 +58,7 @@ fn parse_node_mode(s: &str) -> TractResult<Option<Cmp>> {
     }
 }
 
-fn get_vec_attr<'a, T>(node: &'a NodeProto, attr: &str, n: usize) -> TractResult<Vec<T>>
+pub(super) fn get_vec_attr<'a, T>(node: &'a NodeProto, attr: &str, n: usize) -> TractResult<Vec<T>>
 where
     T: AttrTVecType<'a>,
 {
@@ -83,7 +67,11 @@ where
     Ok(vec)
 }
 
-fn get_vec_attr_opt<'a, T>(node: &'a NodeProto, attr: &str, n: usize) -> TractResult<Option<Vec<T>>>
+pub(super) fn get_vec_attr_opt<'a, T>(
+    node: &'a NodeProto,
+    attr: &str,
+    n: usize,
+) -> TractResult<Option<Vec<T>>>
 where
     T: AttrTVecType<'a>,
 {
@@ -114,7 +102,7 @@ fn parse_class_data(node: &NodeProto) -> TractResult<Arc<Tensor>> {
     }
 }
 
-fn parse_nodes_data(node: &NodeProto, is_classifier: bool) -> TractResult<TreeEnsemble> {
+pub(super) fn parse_nodes_data(node: &NodeProto, is_classifier: bool) -> TractResult<TreeEnsemble> {
     // parse n_classes from protobuf
     let n_classes = if is_classifier {
         let ints = node.get_attr_opt_slice::<i64>("classlabels_int64s")?;
@@ -157,7 +145,7 @@ fn parse_nodes_data(node: &NodeProto, is_classifier: bool) -> TractResult<TreeEn
     let aggregate_fn = parse_aggregate(if is_classifier {
         "SUM"
     } else {
-        node.get_attr_opt("aggregate")?.unwrap_or("SUM")
+        node.get_attr_opt("aggregate_function")?.unwrap_or("SUM")
     })?;
 
     // parse leaf data from protobuf
@@ -244,8 +232,6 @@ pub struct TreeEnsembleClassifier {
     pub binary_result_layout: bool,
 }
 
-
-
 impl Expansion for TreeEnsembleClassifier {
     fn name(&self) -> Cow<str> {
         "TreeEnsembleClassifier".into()
@@ -286,8 +272,6 @@ impl Expansion for TreeEnsembleClassifier {
         model: &mut TypedModel,
         inputs: &[OutletId],
     ) -> TractResult<TVec<OutletId>> {
-        use tract_core::ops::nn::*;
-
         let mut scores = model.wire_node(
             format!("{prefix}.classifier"),
             tract_onnx_opl::ml::tree_ensemble_classifier::TreeEnsembleClassifier {
@@ -304,23 +288,7 @@ impl Expansion for TreeEnsembleClassifier {
                 &[scores[0], base],
             )?;
         }
-        match self.post_transform {
-            None => (),
-            Some(PostTransform::Softmax) => {
-                scores = tract_hir::ops::nn::LayerSoftmax::new(1, false).wire(
-                    &format!("{prefix}.softmax"),
-                    model,
-                    &scores,
-                )?;
-            }
-            Some(PostTransform::Logistic) => {
-                scores = model.wire_node(
-                    &format!("{prefix}.logistic"),
-                    tract_core::ops::nn::sigmoid(),
-                    &scores,
-                )?;
-            }
-        }
+        scores = wire_post_transform(prefix, model, self.post_transform, scores)?;
         let processed_scores = scores.clone();
         if self.binary_result_layout {
             scores = model.wire_node(
@@ -340,29 +308,7 @@ impl Expansion for TreeEnsembleClassifier {
                 &[complement[0], scores[0]],
             )?;
         }
-        let winners = model.wire_node(
-            format!("{prefix}.argmax"),
-            Reduce::new(tvec!(1), Reducer::ArgMax(false)),
-            &processed_scores,
-        )?;
-        let reduced = model.wire_node(
-            format!("{prefix}.rm_axis"),
-            tract_core::ops::change_axes::AxisOp::Rm(1),
-            &winners,
-        )?;
-        let casted = model.wire_node(
-            format!("{prefix}.casted"),
-            tract_core::ops::cast::cast(i32::datum_type()),
-            &reduced,
-        )?;
-        let labels = model.wire_node(
-            format!("{prefix}.labels"),
-            tract_onnx_opl::ml::DirectLookup::new(
-                self.class_labels.clone(),
-                Tensor::zero_dt(self.class_labels.datum_type(), &[])?.into_arc_tensor(),
-            )?,
-            &casted,
-        )?[0];
+        let labels = wire_labels(prefix, model, processed_scores[0], &self.class_labels)?;
         Ok(tvec!(labels, scores[0]))
     }
 
@@ -0,0 +1,130 @@
+use crate::model::{OnnxOpRegister, ParsingContext};
+use crate::pb::NodeProto;
+use tract_hir::internal::*;
+use tract_onnx_opl::ml::tree::*;
+
+use super::tree_ensemble_classifier::{get_vec_attr_opt, parse_nodes_data};
+use super::{parse_post_transform, wire_post_transform, PostTransform};
+
+pub fn register_all_ops(reg: &mut OnnxOpRegister) {
+    reg.insert("TreeEnsembleRegressor", tree_regressor);
+}
+
+fn tree_regressor(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let ensemble = parse_nodes_data(node, false)?;
+    let base_values =
+        get_vec_attr_opt::<f32>(node, "base_values", ensemble.n_classes())?.map(|t| rctensor1(&t));
+    let post_transform =
+        node.get_attr_opt("post_transform")?.map(parse_post_transform).transpose()?.unwrap_or(None);
+    Ok((expand(TreeEnsembleRegressor { ensemble, base_values, post_transform }), vec![]))
+}
+
+#[derive(Debug, Clone, Hash)]
+pub struct TreeEnsembleRegressor {
+    pub ensemble: TreeEnsemble,
+    pub base_values: Option<Arc<Tensor>>,
+    pub post_transform: Option<PostTransform>,
+}
+
+impl Expansion for TreeEnsembleRegressor {
+    fn name(&self) -> Cow<str> {
+        "TreeEnsembleRegressor".into()
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 1)?;
+        check_output_arity(outputs, 1)?;
+        s.equals(&outputs[0].datum_type, DatumType::F32)?;
+        s.equals(&outputs[0].rank, 2)?;
+        s.equals(&outputs[0].shape[0], &inputs[0].shape[0])?;
+        s.equals(&outputs[0].shape[1], self.ensemble.n_classes().to_dim())?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        // the opl classifier op just computes the aggregated [N, n_targets] leaf values
+        let mut scores = model.wire_node(
+            format!("{prefix}.regressor"),
+            tract_onnx_opl::ml::tree_ensemble_classifier::TreeEnsembleClassifier {
+                ensemble: self.ensemble.clone(),
+            },
+            inputs,
+        )?;
+        if let Some(base_values) = self.base_values.as_deref() {
+            let base = base_values.clone().broadcast_into_rank(2)?.into_arc_tensor();
+            let base = model.add_const(prefix.to_string() + ".base", base)?;
+            scores = model.wire_node(
+                format!("{prefix}.base_values"),
+                tract_core::ops::math::add(),
+                &[scores[0], base],
+            )?;
+        }
+        wire_post_transform(prefix, model, self.post_transform, scores)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::test::*;
+    use tract_hir::internal::*;
+
+    // tree 0 is a stump on feature 0 (1 if x <= 0.5, else 3), tree 1 is a single leaf (5)
+    fn run(extra: Vec<crate::pb::AttributeProto>) -> TractResult<Tensor> {
+        let mut attributes = vec![
+            int("n_targets", 1),
+            ints("nodes_treeids", &[0, 0, 0, 1]),
+            ints("nodes_nodeids", &[0, 1, 2, 0]),
+            ints("nodes_featureids", &[0, 0, 0, 0]),
+            strings("nodes_modes", &["BRANCH_LEQ", "LEAF", "LEAF", "LEAF"]),
+            floats("nodes_values", &[0.5, 0., 0., 0.]),
+            ints("nodes_truenodeids", &[1, 0, 0, 0]),
+            ints("nodes_falsenodeids", &[2, 0, 0, 0]),
+            ints("target_treeids", &[0, 0, 1]),
+            ints("target_nodeids", &[1, 2, 0]),
+            ints("target_ids", &[0, 0, 0]),
+            floats("target_weights", &[1., 3., 5.]),
+        ];
+        attributes.extend(extra);
+        let input = tensor2(&[[0f32], [1.]]);
+        Ok(run_node("TreeEnsembleRegressor", attributes, 1, input)?.remove(0).into_tensor())
+    }
+
+    #[test]
+    fn sum() -> TractResult<()> {
+        assert_eq!(run(vec![])?, tensor2(&[[6f32], [8.]]));
+        Ok(())
+    }
+
+    #[test]
+    fn average() -> TractResult<()> {
+        let output = run(vec![string("aggregate_function", "AVERAGE")])?;
+        assert_eq!(output, tensor2(&[[3f32], [4.]]));
+        Ok(())
+    }
+
+    #[test]
+    fn base_values_and_post_transform() -> TractResult<()> {
+        let output = run(vec![
+            floats("base_values", &[-7.]),
+            string("aggregate_function", "MIN"),
+            string("post_transform", "LOGISTIC"),
+        ])?;
+        // min(1, 5) - 7 and min(3, 5) - 7
+        let expected =
+            tensor2(&[[-6f32], [-4.]]).into_array::<f32>()?.mapv(|x| 1. / (1. + (-x).exp()));
+        output.close_enough(&expected.into_tensor(), Approximation::Close)
+    }
+}
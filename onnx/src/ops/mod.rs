@@ -2,12 +2,14 @@ use crate::model::{OnnxOpRegister, ParsingContext};
 use crate::pb::*;
 use tract_hir::internal::*;
 
+mod affine_grid;
 mod array;
 mod cast;
 pub mod cumsum;
 mod d2s;
 mod einsum;
 mod fft;
+mod grid_sample;
 pub mod logic;
 mod math;
 mod ml;
@@ -18,7 +20,9 @@ mod quant;
 mod random;
 pub mod rec;
 mod resize;
+mod roi_align;
 mod s2d;
+mod sequence;
 
 pub fn register_all_ops(reg: &mut OnnxOpRegister) {
     reg.insert("Constant", konst);
@@ -29,6 +33,9 @@ pub fn register_all_ops(reg: &mut OnnxOpRegister) {
     reg.insert("Resize", resize::resize);
     reg.insert("NonMaxSuppression", non_max_suppression::non_max_suppression);
     reg.insert("Multinomial", multinomial::multinomial);
+    reg.insert("GridSample", grid_sample::grid_sample);
+    reg.insert("AffineGrid", affine_grid::affine_grid);
+    reg.insert("RoiAlign", roi_align::roi_align);
     array::register_all_ops(reg);
     cast::register_all_ops(reg);
     cumsum::register_all_ops(reg);
@@ -42,6 +49,7 @@ pub fn register_all_ops(reg: &mut OnnxOpRegister) {
     random::register_all_ops(reg);
     rec::register_all_ops(reg);
     s2d::register_all_ops(reg);
+    sequence::register_all_ops(reg);
 }
 
 fn konst(
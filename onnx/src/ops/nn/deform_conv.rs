@@ -0,0 +1,310 @@
+use crate::model::{optional_inputs, ParsingContext};
+use crate::pb::NodeProto;
+use tract_hir::internal::*;
+use tract_hir::ops::cnn::{PaddingSpec, PoolSpec};
+use tract_hir::ops::nn::DataFormat;
+use tract_hir::tract_core::ops::cnn::{wire_reshape_bias_for_bin, DeformIm2Col};
+use tract_hir::tract_core::ops::einsum::EinSum;
+
+pub fn deform_conv(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let kernel_shape = node.get_attr_opt_tvec("kernel_shape")?;
+    let group = node.get_attr_opt("group")?.unwrap_or(1);
+    let offset_group = node.get_attr_opt("offset_group")?.unwrap_or(1);
+    let dilations = node.get_attr_opt_tvec("dilations")?;
+    let strides = node.get_attr_opt_tvec("strides")?;
+    let padding = super::pad(node, false)?;
+    let mut options = optional_inputs(node).skip(3);
+    let bias_input = options.next().unwrap();
+    let mask_input = options.next().unwrap();
+    Ok((
+        expand(DeformConv {
+            kernel_shape,
+            group,
+            offset_group,
+            dilations,
+            strides,
+            padding,
+            bias_input,
+            mask_input,
+        }),
+        vec![],
+    ))
+}
+
+/// Deformable convolution, lowered to the core DeformIm2Col op followed by a grouped EinSum
+/// with the kernel.
+#[derive(Debug, Clone, Hash)]
+struct DeformConv {
+    kernel_shape: Option<TVec<usize>>,
+    group: usize,
+    offset_group: usize,
+    dilations: Option<TVec<usize>>,
+    strides: Option<TVec<usize>>,
+    padding: PaddingSpec,
+    bias_input: Option<usize>,
+    mask_input: Option<usize>,
+}
+
+impl DeformConv {
+    fn pool_spec(&self, input_channels: usize, kernel_shape: &[usize]) -> PoolSpec {
+        let padding = match &self.padding {
+            PaddingSpec::Valid => PaddingSpec::Explicit(
+                tvec!(0; kernel_shape.len() - 2),
+                tvec!(0; kernel_shape.len() - 2),
+            ),
+            padding => padding.clone(),
+        };
+        PoolSpec::new(
+            DataFormat::NCHW,
+            self.kernel_shape.clone().unwrap_or_else(|| kernel_shape[2..].into()),
+            padding,
+            self.dilations.clone(),
+            self.strides.clone(),
+            input_channels,
+            kernel_shape[0],
+        )
+    }
+}
+
+impl Expansion for DeformConv {
+    fn name(&self) -> Cow<str> {
+        "DeformConv".into()
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        let expected_inputs =
+            3 + self.bias_input.is_some() as usize + self.mask_input.is_some() as usize;
+        check_input_arity(inputs, expected_inputs)?;
+        check_output_arity(outputs, 1)?;
+        s.equals(&inputs[0].datum_type, &outputs[0].datum_type)?;
+        s.equals(&inputs[1].datum_type, &outputs[0].datum_type)?;
+        s.equals(&inputs[2].datum_type, &outputs[0].datum_type)?;
+        s.equals(&inputs[0].rank, 4)?;
+        s.equals(&inputs[1].rank, 4)?;
+        s.equals(&inputs[2].rank, 4)?;
+        s.equals(&outputs[0].rank, 4)?;
+        s.equals(&outputs[0].shape[0], &inputs[0].shape[0])?;
+        s.equals(&outputs[0].shape[1], &inputs[1].shape[0])?;
+        s.equals(&inputs[0].shape[1], self.group as i64 * inputs[1].shape[1].bex())?;
+        if let Some(bias) = self.bias_input {
+            s.equals(&inputs[bias].rank, 1)?;
+            s.equals(&inputs[bias].shape[0], &inputs[1].shape[0])?;
+        }
+        if let Some(mask) = self.mask_input {
+            s.equals(&inputs[mask].datum_type, &outputs[0].datum_type)?;
+            s.equals(&inputs[mask].rank, 4)?;
+        }
+        s.given_2(&inputs[0].shape, &inputs[1].shape, move |s, input_shape, kernel_shape| {
+            let Some(kernel_shape) =
+                kernel_shape.iter().map(|d| d.to_usize().ok()).collect::<Option<TVec<_>>>()
+            else {
+                return Ok(());
+            };
+            let Ok(input_channels) = input_shape[1].to_usize() else { return Ok(()) };
+            let pool_spec = self.pool_spec(input_channels, &kernel_shape);
+            for (ix, d) in pool_spec.computed_padding(&input_shape[2..]).into_iter().enumerate() {
+                s.equals(&outputs[0].shape[2 + ix], d.convoluted)?;
+            }
+            Ok(())
+        })
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let input_fact = model.outlet_fact(inputs[0])?.clone();
+        let kernel_shape = model
+            .outlet_fact(inputs[1])?
+            .shape
+            .as_concrete()
+            .context("DeformConv expects a kernel of known shape")?
+            .to_vec();
+        let input_channels =
+            input_fact.shape[1].to_usize().context("DeformConv expects known input channels")?;
+        let pool_spec = self.pool_spec(input_channels, &kernel_shape);
+        let output_spatial: TVec<TDim> = pool_spec
+            .computed_padding(&input_fact.shape[2..])
+            .into_iter()
+            .map(|d| d.convoluted)
+            .collect();
+        let (group, output_channels) = (self.group, kernel_shape[0]);
+        let k: usize = kernel_shape[2..].iter().product();
+        let group_k = kernel_shape[1] * k;
+
+        let mut im2col_inputs = tvec!(inputs[0], inputs[2]);
+        if let Some(mask) = self.mask_input {
+            im2col_inputs.push(inputs[mask]);
+        }
+        // [N, C * K, P] -> [N, G, C/G * K, P]
+        let mut cols = model.wire_node(
+            format!("{prefix}.im2col"),
+            DeformIm2Col::new(pool_spec, self.offset_group),
+            &im2col_inputs,
+        )?;
+        cols = model.wire_node(
+            format!("{prefix}.split_groups"),
+            AxisOp::Reshape(
+                1,
+                tvec!((input_channels * k).to_dim()),
+                tvec!(group.to_dim(), group_k.to_dim()),
+            ),
+            &cols,
+        )?;
+        // [O, C/G, KH, KW] -> [G, O/G, C/G * K]
+        let kernel = model.wire_node(
+            format!("{prefix}.kernel_as_matrices"),
+            AxisOp::Reshape(
+                0,
+                kernel_shape.iter().map(|d| d.to_dim()).collect(),
+                tvec!(group.to_dim(), (output_channels / group).to_dim(), group_k.to_dim()),
+            ),
+            &[inputs[1]],
+        )?;
+        let mut wire = model.wire_node(
+            format!("{prefix}.matmul"),
+            EinSum::new("gok,ngkp->ngop".parse()?, input_fact.datum_type),
+            &[kernel[0], cols[0]],
+        )?;
+        wire = model.wire_node(
+            format!("{prefix}.merge_groups"),
+            AxisOp::Reshape(
+                1,
+                tvec!(group.to_dim(), (output_channels / group).to_dim()),
+                tvec!(output_channels.to_dim()),
+            ),
+            &wire,
+        )?;
+        wire = model.wire_node(
+            format!("{prefix}.spatial"),
+            AxisOp::Reshape(2, tvec!(output_spatial.iter().product()), output_spatial),
+            &wire,
+        )?;
+        if let Some(bias) = self.bias_input {
+            let bias =
+                wire_reshape_bias_for_bin(model, prefix, inputs[bias], 4, 1, output_channels)?;
+            wire = model.wire_node(
+                format!("{prefix}.add_bias"),
+                tract_core::ops::math::add(),
+                &[wire[0], bias[0]],
+            )?;
+        }
+        Ok(wire)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::pb::*;
+    use tract_hir::internal::*;
+
+    // runs a single node model, inputs named after their position, "" marking omitted inputs
+    fn run(
+        op_type: &str,
+        group: i64,
+        ints: &[(&str, &[i64])],
+        inputs: &[Option<Tensor>],
+    ) -> TractResult<Tensor> {
+        let names: Vec<String> = inputs
+            .iter()
+            .enumerate()
+            .map(|(ix, input)| if input.is_some() { format!("i{ix}") } else { String::new() })
+            .collect();
+        let group = AttributeProto {
+            name: "group".into(),
+            r#type: attribute_proto::AttributeType::Int as i32,
+            i: group,
+            ..Default::default()
+        };
+        let attribute = ints
+            .iter()
+            .map(|(name, values)| AttributeProto {
+                name: name.to_string(),
+                r#type: attribute_proto::AttributeType::Ints as i32,
+                ints: values.to_vec(),
+                ..Default::default()
+            })
+            .chain(std::iter::once(group))
+            .collect();
+        let node = NodeProto {
+            op_type: op_type.into(),
+            input: names.clone(),
+            output: vec!["y".into()],
+            attribute,
+            ..Default::default()
+        };
+        let tensor =
+            type_proto::Tensor { elem_type: tensor_proto::DataType::Float as i32, shape: None };
+        let r#type =
+            TypeProto { value: Some(type_proto::Value::TensorType(tensor)), ..Default::default() };
+        let value_info = |name: &str| ValueInfoProto {
+            name: name.into(),
+            r#type: Some(r#type.clone()),
+            ..Default::default()
+        };
+        let graph = GraphProto {
+            node: vec![node],
+            input: names.iter().filter(|n| !n.is_empty()).map(|n| value_info(n)).collect(),
+            output: vec![value_info("y")],
+            ..Default::default()
+        };
+        let proto = ModelProto {
+            graph: Some(graph),
+            opset_import: vec![OperatorSetIdProto { domain: "".into(), version: 19 }],
+            ..Default::default()
+        };
+        let mut model = crate::onnx().model_for_proto_model(&proto)?;
+        let inputs: TVec<Tensor> = inputs.iter().flatten().cloned().collect();
+        for (ix, input) in inputs.iter().enumerate() {
+            model.set_input_fact(ix, InferenceFact::dt_shape(f32::datum_type(), input.shape()))?;
+        }
+        let outputs = model
+            .into_optimized()?
+            .into_runnable()?
+            .run(inputs.into_iter().map(|t| t.into_tvalue()).collect())?;
+        Ok(outputs[0].clone().into_tensor())
+    }
+
+    fn ramp(shape: &[usize]) -> TractResult<Tensor> {
+        let len = shape.iter().product::<usize>();
+        let data = (0..len).map(|i| (i as f32 * 0.37).sin()).collect::<Vec<_>>();
+        tensor1(&data).into_shape(shape)
+    }
+
+    #[test]
+    fn zero_offsets_is_conv() -> TractResult<()> {
+        let ints: &[(&str, &[i64])] =
+            &[("pads", &[1, 0, 0, 1]), ("strides", &[2, 1]), ("dilations", &[1, 2])];
+        let (input, kernel, bias) = (ramp(&[2, 4, 5, 6])?, ramp(&[6, 2, 2, 3])?, ramp(&[6])?);
+        let conv =
+            run("Conv", 2, ints, &[Some(input.clone()), Some(kernel.clone()), Some(bias.clone())])?;
+        let (oh, ow) = (conv.shape()[2], conv.shape()[3]);
+        let offsets = Tensor::zero::<f32>(&[2, 2 * 6, oh, ow])?;
+        let inputs = [Some(input), Some(kernel), Some(offsets), Some(bias)];
+        let deform = run("DeformConv", 2, ints, &inputs)?;
+        deform.close_enough(&conv, Approximation::Close)
+    }
+
+    #[test]
+    fn offsets_and_mask() -> TractResult<()> {
+        // a 1x1 kernel reading one pixel to the right, with a mask halving the second row
+        let input = tensor4(&[[[[1f32, 2.], [3., 4.]]]]);
+        let kernel = tensor4(&[[[[2f32]]]]);
+        let offsets = tensor4(&[[[[0f32, 0.], [0., 0.]], [[1., 1.], [1., 1.]]]]);
+        let mask = tensor4(&[[[[1f32, 1.], [0.5, 0.5]]]]);
+        let inputs = [Some(input), Some(kernel), Some(offsets), None, Some(mask)];
+        let output = run("DeformConv", 1, &[], &inputs)?;
+        assert_eq!(output, tensor4(&[[[[4f32, 0.], [4., 0.]]]]));
+        Ok(())
+    }
+}
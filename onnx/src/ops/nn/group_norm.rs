@@ -0,0 +1,128 @@
+use crate::model::ParsingContext;
+use crate::pb::NodeProto;
+use tract_core::ops::cast::cast;
+use tract_core::ops::math::{add, mul};
+use tract_hir::internal::*;
+use tract_hir::ops::logic::wire_with_rank_broadcast;
+
+pub fn group_normalization(
+    ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let epsilon = node.get_attr_opt("epsilon")?.unwrap_or(1e-5);
+    let num_groups = node.get_attr("num_groups")?;
+    // stash_type appeared with opset 21, earlier versions compute in the input type
+    let datum_type = if ctx.onnx_operator_set_version >= 21 {
+        Some(node.get_attr_opt("stash_type")?.unwrap_or(DatumType::F32))
+    } else {
+        None
+    };
+    Ok((expand(GroupNorm { epsilon, num_groups, datum_type }), vec![]))
+}
+
+#[derive(Debug, Clone)]
+pub struct GroupNorm {
+    epsilon: f32,
+    num_groups: usize,
+    datum_type: Option<DatumType>,
+}
+
+impl Expansion for GroupNorm {
+    fn name(&self) -> Cow<str> {
+        "GroupNorm".into()
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 3)?;
+        check_output_arity(outputs, 1)?;
+        s.equals(&inputs[0].datum_type, &outputs[0].datum_type)?;
+        s.equals(&inputs[0].datum_type, &inputs[1].datum_type)?;
+        s.equals(&inputs[0].datum_type, &inputs[2].datum_type)?;
+        s.equals(&inputs[1].shape, &inputs[2].shape)?;
+        s.equals(&inputs[1].rank, 1)?;
+        s.equals(&inputs[0].shape, &outputs[0].shape)?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let fact = model.outlet_fact(inputs[0])?.clone();
+        let rank = fact.rank();
+        let channels = fact.shape[1].clone();
+        let groups = self.num_groups;
+        if let Ok(c) = channels.to_usize() {
+            ensure!(c % groups == 0, "GroupNorm: {c} channels can not be split in {groups} groups");
+        }
+        // scale and bias are per group up to opset 18, per channel from opset 21
+        let per_group = model.outlet_fact(inputs[1])?.shape[0] != channels;
+        let cast_x = model.wire_node(
+            format!("{prefix}.cast_x"),
+            cast(self.datum_type.unwrap_or(fact.datum_type)),
+            &[inputs[0]],
+        )?;
+        let grouped = model.wire_node(
+            format!("{prefix}.grouped"),
+            AxisOp::Reshape(
+                1,
+                tvec!(channels.clone()),
+                tvec!(groups.to_dim(), channels.clone() / groups),
+            ),
+            &cast_x,
+        )?;
+        let normalized = model.wire_node(
+            format!("{prefix}.normalized"),
+            tract_core::ops::nn::LayerNorm::new((2..rank + 1).collect(), self.epsilon),
+            &grouped,
+        )?;
+        let mut wire = model.wire_node(
+            format!("{prefix}.cast_normalized"),
+            cast(fact.datum_type),
+            &normalized,
+        )?;
+        if per_group {
+            wire = wire_scale_and_bias(prefix, model, wire[0], inputs[1], inputs[2], rank + 1)?;
+        }
+        wire = model.wire_node(
+            format!("{prefix}.ungrouped"),
+            AxisOp::Reshape(1, tvec!(groups.to_dim(), channels.clone() / groups), tvec!(channels)),
+            &wire,
+        )?;
+        if !per_group {
+            wire = wire_scale_and_bias(prefix, model, wire[0], inputs[1], inputs[2], rank)?;
+        }
+        Ok(wire)
+    }
+}
+
+/// Apply scale and bias along axis 1 of `x`, which has `rank` axes.
+fn wire_scale_and_bias(
+    prefix: &str,
+    model: &mut TypedModel,
+    x: OutletId,
+    scale: OutletId,
+    bias: OutletId,
+    rank: usize,
+) -> TractResult<TVec<OutletId>> {
+    let mut affine = tvec!(scale, bias);
+    for (ix, name) in ["scale", "bias"].iter().enumerate() {
+        for axis in 1..rank - 1 {
+            affine[ix] = model.wire_node(
+                format!("{prefix}.{name}.add_axis_{axis}"),
+                AxisOp::Add(1),
+                &[affine[ix]],
+            )?[0];
+        }
+    }
+    let scaled =
+        wire_with_rank_broadcast(format!("{prefix}.scaled"), model, mul(), &[x, affine[0]])?;
+    wire_with_rank_broadcast(format!("{prefix}.y"), model, add(), &[scaled[0], affine[1]])
+}
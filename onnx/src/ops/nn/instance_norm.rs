@@ -45,45 +45,10 @@ impl Expansion for InstanceNorm {
     ) -> TractResult<TVec<OutletId>> {
         let input_fact = model.outlet_fact(inputs[0])?.clone();
         let rank = input_fact.rank();
-        let axes: Vec<_> = (0..rank as i64).filter(|&axis| axis != 1).collect();
-        let mean = tract_hir::ops::nn::Reduce::new(
-            Some(axes.clone()),
-            true,
-            tract_hir::ops::nn::Reducer::Mean,
-        )
-        .wire(&format!("{name}.mean"), model, &inputs[0..1])?[0];
-        let diff = model.wire_node(
-            format!("{name}.diff"),
-            tract_hir::ops::math::sub(),
-            &[inputs[0], mean],
-        )?;
-        let sqr_diff =
-            model.wire_node(format!("{name}.sqr"), tract_hir::ops::math::square(), &diff)?;
-        let vari =
-            tract_hir::ops::nn::Reduce::new(Some(axes), true, tract_hir::ops::nn::Reducer::Mean)
-                .wire(&format!("{name}.variance"), model, &sqr_diff)?[0];
-        let epsilon = model.add_const(
-            format!("{name}.epsilon.cst"),
-            tensor0(self.epsilon)
-                .cast_to_dt(input_fact.datum_type)?
-                .into_owned()
-                .broadcast_into_rank(rank)?
-                .into_arc_tensor(),
-        )?;
-        let vari_sane = model.wire_node(
-            format!("{name}.epsilon"),
-            tract_hir::ops::math::add(),
-            &[vari, epsilon],
-        )?;
-        let div = model.wire_node(
-            format!("{name}.rsqrt"),
-            tract_hir::ops::math::rsqrt(),
-            &vari_sane,
-        )?;
-        let divised = model.wire_node(
-            format!("{name}.div"),
-            tract_hir::ops::math::mul(),
-            &[diff[0], div[0]],
+        let normalized = model.wire_node(
+            format!("{name}.normalized"),
+            tract_core::ops::nn::LayerNorm::new((2..rank).collect(), self.epsilon),
+            &inputs[0..1],
         )?;
         let mut scale =
             model.wire_node(format!("{name}.add-scale-axis-n"), AxisOp::Add(0), &inputs[1..2])?;
@@ -97,7 +62,7 @@ impl Expansion for InstanceNorm {
         let scaled = model.wire_node(
             format!("{name}.scaled"),
             tract_hir::ops::math::mul(),
-            &[divised[0], scale[0]],
+            &[normalized[0], scale[0]],
         )?;
         let mut bias =
             model.wire_node(format!("{name}.add-bias-axis-n"), AxisOp::Add(0), &inputs[2..3])?;
@@ -119,6 +119,14 @@ impl Expansion for LayerNorm {
             None
         };
         let axes: TVec<usize> = (axis..fact.rank()).collect();
+        if self.mean_output.is_none() && self.invstddev_output.is_none() {
+            let normalized = model.wire_node(
+                format!("{prefix}.normalized"),
+                tract_core::ops::nn::LayerNorm::new(axes, self.epsilon),
+                &cast_x,
+            )?;
+            return self.wire_scale_and_bias(prefix, model, &fact, normalized, cast_scale, cast_bias);
+        }
         let reduced_sum_x = model.wire_node(
             format!("{prefix}.reduced_sum"),
             Reduce { axes: axes.clone(), reducer: Reducer::Sum },
@@ -160,6 +168,29 @@ impl Expansion for LayerNorm {
         let inv_std_dev = model.wire_node(format!("{prefix}.inv_std_dev"), rsqrt(), &var_eps)?;
         let normalized =
             model.wire_node(format!("{prefix}.normalized"), mul(), &[d[0], inv_std_dev[0]])?;
+        let y =
+            self.wire_scale_and_bias(prefix, model, &fact, normalized, cast_scale, cast_bias)?;
+        let mut outputs = tvec!(y[0]);
+        if self.mean_output.is_some() {
+            outputs.push(reduced_mean_x[0]);
+        }
+        if self.invstddev_output.is_some() {
+            outputs.push(inv_std_dev[0]);
+        }
+        Ok(outputs)
+    }
+}
+
+impl LayerNorm {
+    fn wire_scale_and_bias(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        fact: &TypedFact,
+        normalized: TVec<OutletId>,
+        cast_scale: TVec<OutletId>,
+        cast_bias: Option<TVec<OutletId>>,
+    ) -> TractResult<TVec<OutletId>> {
         // NormalizedScaled = Mul(Normalized, Scale) Y = Add(NormalizedScaled, B)
         let cast_normalized = model.wire_node(
             format!("{prefix}.cast_normalized"),
@@ -172,23 +203,15 @@ impl Expansion for LayerNorm {
             mul(),
             &[cast_normalized[0], cast_scale[0]],
         )?;
-        let y = if let Some(bias) = cast_bias {
+        if let Some(bias) = cast_bias {
             wire_with_rank_broadcast(
                 format!("{prefix}.y"),
                 model,
                 add(),
                 &[normalized_scaled[0], bias[0]],
-            )?
+            )
         } else {
-            normalized_scaled
-        };
-        let mut outputs = tvec!(y[0]);
-        if self.mean_output.is_some() {
-            outputs.push(reduced_mean_x[0]);
-        }
-        if self.invstddev_output.is_some() {
-            outputs.push(inv_std_dev[0]);
+            Ok(normalized_scaled)
         }
-        Ok(outputs)
     }
 }
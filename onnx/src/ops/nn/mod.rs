@@ -8,10 +8,13 @@ use crate::pb_helpers::OptionExt;
 
 mod batch_norm;
 mod conv_transpose;
+mod deform_conv;
 mod dropout;
+mod group_norm;
 mod instance_norm;
 mod layer_norm;
 mod lrn;
+mod mvn;
 mod reduce;
 
 pub fn arg_max_min(
@@ -38,11 +41,14 @@ pub fn register_all_ops(reg: &mut OnnxOpRegister) {
     reg.insert("Conv", conv);
     reg.insert("ConvInteger", conv_integer);
     reg.insert("ConvTranspose", conv_transpose::conv_transpose);
+    reg.insert("DeformConv", deform_conv::deform_conv);
     reg.insert("Dropout", dropout::dropout);
     reg.insert("Elu", elu);
+    reg.insert("Gelu", gelu);
     reg.insert("GlobalAveragePool", |_, _| Ok((expand(ops::nn::GlobalAvgPool), vec![])));
     reg.insert("GlobalLpPool", global_lp_pool);
     reg.insert("GlobalMaxPool", |_, _| Ok((expand(ops::nn::GlobalMaxPool), vec![])));
+    reg.insert("GroupNormalization", group_norm::group_normalization);
     reg.insert("Hardmax", layer_hard_max);
     reg.insert("HardSigmoid", hard_sigmoid);
     reg.insert("InstanceNormalization", instance_norm::instance_normalization);
@@ -51,6 +57,7 @@ pub fn register_all_ops(reg: &mut OnnxOpRegister) {
     reg.insert("LogSoftmax", layer_log_soft_max);
     reg.insert("LRN", lrn::lrn);
     reg.insert("MaxPool", max_pool);
+    reg.insert("MeanVarianceNormalization", mvn::mean_variance_normalization);
     reg.insert("ParametricSoftplus", parametric_softplus);
     reg.insert("QLinearConv", conv_qlinear);
     reg.insert("PRelu", |_, _| Ok((expand(Prelu), vec![])));
@@ -288,6 +295,18 @@ pub fn layer_soft_max(
     }
 }
 
+pub fn gelu(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let approximate = match node.get_attr_opt("approximate")?.unwrap_or("none") {
+        "none" => false,
+        "tanh" => true,
+        other => bail!("Unsupported Gelu approximation {}", other),
+    };
+    Ok((ops::nn::gelu(approximate).into_hir(), vec![]))
+}
+
 pub fn leaky_relu(
     _ctx: &ParsingContext,
     node: &NodeProto,
@@ -407,3 +426,85 @@ pub fn thresholded_relu(
     let alpha = node.get_attr_opt("alpha")?.unwrap_or(1.);
     Ok((expand(ops::activations::ThresholdRelu(alpha)), vec![]))
 }
+
+#[cfg(test)]
+mod test {
+    use crate::pb::tensor_shape_proto::{dimension, Dimension};
+    use crate::pb::*;
+    use tract_hir::internal::*;
+
+    fn gelu_model(approximate: Option<&str>) -> ModelProto {
+        let dim = Dimension { value: Some(dimension::Value::DimValue(40)), ..Default::default() };
+        let tensor = type_proto::Tensor {
+            elem_type: tensor_proto::DataType::Float as i32,
+            shape: Some(TensorShapeProto { dim: vec![dim] }),
+        };
+        let r#type =
+            TypeProto { value: Some(type_proto::Value::TensorType(tensor)), ..Default::default() };
+        let value_info = |name: &str| ValueInfoProto {
+            name: name.into(),
+            r#type: Some(r#type.clone()),
+            ..Default::default()
+        };
+        let attribute = approximate.map(|approximate| AttributeProto {
+            name: "approximate".into(),
+            r#type: attribute_proto::AttributeType::String as i32,
+            s: approximate.as_bytes().to_vec(),
+            ..Default::default()
+        });
+        let gelu = NodeProto {
+            op_type: "Gelu".into(),
+            input: vec!["x".into()],
+            output: vec!["y".into()],
+            attribute: attribute.into_iter().collect(),
+            ..Default::default()
+        };
+        let graph = GraphProto {
+            node: vec![gelu],
+            input: vec![value_info("x")],
+            output: vec![value_info("y")],
+            ..Default::default()
+        };
+        ModelProto {
+            graph: Some(graph),
+            opset_import: vec![OperatorSetIdProto { domain: "".into(), version: 20 }],
+            ..Default::default()
+        }
+    }
+
+    fn check_gelu(approximate: Option<&str>, reference: impl Fn(f64) -> f64) -> TractResult<()> {
+        let model = crate::onnx().model_for_proto_model(&gelu_model(approximate))?;
+        let input: Vec<f32> = (-20..20).map(|x| x as f32 / 4.).collect();
+        let expected: Vec<f32> = input.iter().map(|&x| reference(x as f64) as f32).collect();
+        let output = model.into_optimized()?.into_runnable()?.run(tvec!(tensor1(&input).into()))?;
+        output[0].close_enough(&tensor1(&expected), Approximation::Approximate)
+    }
+
+    #[test]
+    fn gelu_erf() -> TractResult<()> {
+        // erf from Abramowitz and Stegun 7.1.26, precise enough for the reference
+        let erf = |x: f64| {
+            let t = 1. / (1. + 0.3275911 * x.abs());
+            let poly = t
+                * (0.254829592
+                    + t * (-0.284496736
+                        + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+            (1. - poly * (-x * x).exp()).copysign(x)
+        };
+        check_gelu(None, |x| 0.5 * x * (1. + erf(x / 2f64.sqrt())))?;
+        check_gelu(Some("none"), |x| 0.5 * x * (1. + erf(x / 2f64.sqrt())))
+    }
+
+    #[test]
+    fn gelu_tanh() -> TractResult<()> {
+        check_gelu(Some("tanh"), |x| {
+            0.5 * x
+                * (1. + ((2. / std::f64::consts::PI).sqrt() * (x + 0.044715 * x.powi(3))).tanh())
+        })
+    }
+
+    #[test]
+    fn gelu_unknown_approximation() {
+        assert!(crate::onnx().model_for_proto_model(&gelu_model(Some("sigmoid"))).is_err());
+    }
+}
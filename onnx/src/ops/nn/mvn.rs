@@ -0,0 +1,55 @@
+use crate::model::ParsingContext;
+use crate::pb::NodeProto;
+use tract_hir::internal::*;
+
+pub fn mean_variance_normalization(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let axes = node.get_attr_opt_vec("axes")?.unwrap_or_else(|| vec![0, 2, 3]);
+    Ok((expand(MeanVarianceNorm { axes }), vec![]))
+}
+
+#[derive(Debug, Clone)]
+pub struct MeanVarianceNorm {
+    axes: Vec<i64>,
+}
+
+impl Expansion for MeanVarianceNorm {
+    fn name(&self) -> Cow<str> {
+        "MeanVarianceNorm".into()
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 1)?;
+        check_output_arity(outputs, 1)?;
+        s.equals(&inputs[0].datum_type, &outputs[0].datum_type)?;
+        s.equals(&inputs[0].shape, &outputs[0].shape)?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let rank = model.outlet_fact(inputs[0])?.rank();
+        let mut axes: TVec<usize> = self
+            .axes
+            .iter()
+            .map(|&ax| if ax < 0 { ax + rank as i64 } else { ax } as usize)
+            .collect();
+        axes.sort();
+        axes.dedup();
+        // the reference divides by (stddev + 1e-9) where LayerNorm divides by sqrt(variance +
+        // 1e-9): both guard against division by zero, but results differ when the variance is
+        // close to or below 1e-9
+        model.wire_node(prefix, tract_core::ops::nn::LayerNorm::new(axes, 1e-9), inputs)
+    }
+}
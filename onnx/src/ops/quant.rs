@@ -1,6 +1,7 @@
 use crate::model::{OnnxOpRegister, ParsingContext};
 use crate::pb::NodeProto;
 use tract_hir::internal::*;
+use tract_hir::ops::cast::cast;
 use tract_hir::ops::quant::*;
 use tract_ndarray::ArrayViewD;
 
@@ -14,7 +15,8 @@ fn quantize_linear(
     _ctx: &ParsingContext,
     node: &NodeProto,
 ) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
-    let op = QuantizeLinear::new(Some(2).filter(|_| node.input.len() == 3));
+    let axis = node.get_attr_opt::<i64>("axis")?.unwrap_or(1);
+    let op = QuantizeLinear::new(Some(2).filter(|_| node.input.len() == 3), axis);
     Ok((expand(op), vec![]))
 }
 
@@ -22,7 +24,8 @@ fn dequantize_linear(
     _ctx: &ParsingContext,
     node: &NodeProto,
 ) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
-    let op = DequantizeLinear::new(Some(2).filter(|_| node.input.len() == 3));
+    let axis = node.get_attr_opt::<i64>("axis")?.unwrap_or(1);
+    let op = DequantizeLinear::new(Some(2).filter(|_| node.input.len() == 3), axis);
     Ok((expand(op), vec![]))
 }
 
@@ -34,9 +37,34 @@ fn dynamic_quantize_linear(
     Ok((expand(op), vec![]))
 }
 
+/// Per-axis quantization parameters, when the scale is a 1-D tensor of several values.
+fn per_axis_qparams(
+    target: &TypedModel,
+    input: OutletId,
+    axis: i64,
+    scale: &Tensor,
+    zero_point: &Tensor,
+) -> TractResult<Option<QParams>> {
+    if scale.len() <= 1 {
+        return Ok(None);
+    }
+    let rank = target.outlet_fact(input)?.rank();
+    let axis = if axis < 0 { axis + rank as i64 } else { axis } as usize;
+    let scales = scale.as_slice::<f32>()?;
+    let zero_points = zero_point.cast_to::<i32>()?;
+    let zero_points = if zero_points.len() == 1 {
+        vec![zero_points.as_slice::<i32>()?[0]; scales.len()]
+    } else {
+        zero_points.as_slice::<i32>()?.to_vec()
+    };
+    ensure!(zero_points.len() == scales.len(), "Mismatching zero point and scale lengths");
+    Ok(Some(QParams::per_axis(axis, &zero_points, scales)?))
+}
+
 #[derive(Debug, Clone, new, Default, Hash)]
 pub struct QuantizeLinear {
     optional_zero_point_input: Option<usize>,
+    axis: i64,
 }
 
 
@@ -73,13 +101,8 @@ impl Expansion for QuantizeLinear {
         inputs: &[OutletId],
     ) -> TractResult<TVec<OutletId>> {
         use tract_hir::ops::quant::*;
-        let scale = target
-            .outlet_fact(inputs[1])?
-            .konst
-            .as_ref()
-            .context("y_scale must be a const")?
-            .as_slice::<f32>()?[0]
-            .recip();
+        let scale =
+            target.outlet_fact(inputs[1])?.konst.clone().context("y_scale must be a const")?;
         let zero_point = if self.optional_zero_point_input.is_some() {
             target
                 .outlet_fact(inputs[2])?
@@ -90,6 +113,12 @@ impl Expansion for QuantizeLinear {
         } else {
             rctensor0(0u8)
         };
+        if let Some(qp) = per_axis_qparams(target, inputs[0], self.axis, &scale, &zero_point)? {
+            let dt = zero_point.datum_type().quantize(qp);
+            let wire = target.wire_node(format!("{prefix}.quantize"), cast(dt), &[inputs[0]])?;
+            return target.wire_node(prefix, cast(dt.unquantized()), &wire);
+        }
+        let scale = scale.as_slice::<f32>()?[0].recip();
         let op: Box<dyn TypedOp> = if zero_point.datum_type() == u8::datum_type() {
             Box::new(quantize_linear_u8(scale, zero_point.as_slice::<u8>()?[0]))
         } else {
@@ -102,6 +131,7 @@ impl Expansion for QuantizeLinear {
 #[derive(Debug, Clone, new, Default, Hash)]
 pub struct DequantizeLinear {
     optional_zero_point_input: Option<usize>,
+    axis: i64,
 }
 
 
@@ -136,12 +166,8 @@ impl Expansion for DequantizeLinear {
         target: &mut TypedModel,
         inputs: &[OutletId],
     ) -> TractResult<TVec<OutletId>> {
-        let scale = target
-            .outlet_fact(inputs[1])?
-            .konst
-            .as_ref()
-            .context("y_scale must be a const")?
-            .as_slice::<f32>()?[0];
+        let scale =
+            target.outlet_fact(inputs[1])?.konst.clone().context("y_scale must be a const")?;
         let zero_point = if self.optional_zero_point_input.is_some() {
             target
                 .outlet_fact(inputs[2])?
@@ -152,6 +178,12 @@ impl Expansion for DequantizeLinear {
         } else {
             rctensor0(0u8)
         };
+        if let Some(qp) = per_axis_qparams(target, inputs[0], self.axis, &scale, &zero_point)? {
+            let dt = target.outlet_fact(inputs[0])?.datum_type.quantize(qp);
+            let wire = target.wire_node(format!("{prefix}.reinterpret"), cast(dt), &[inputs[0]])?;
+            return target.wire_node(prefix, cast(f32::datum_type()), &wire);
+        }
+        let scale = scale.as_slice::<f32>()?[0];
         let op: Box<dyn TypedOp> = if zero_point.datum_type() == u8::datum_type() {
             Box::new(DequantizeLinearF32::new(scale, zero_point.as_slice::<u8>()?[0] as i32))
         } else if zero_point.datum_type() == i8::datum_type() {
@@ -0,0 +1,205 @@
+use crate::model::{optional_inputs, ParseResult, ParsingContext};
+use crate::pb::*;
+use tract_core::ops::logic::WhileLoop;
+use tract_hir::internal::*;
+use tract_hir::ops;
+use tract_hir::ops::scan::InferenceScan;
+
+pub fn _loop(
+    ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let graph: &GraphProto = node.get_attr("body")?;
+    let ParseResult { mut model, unresolved_inputs, .. } = ctx.parse_graph(graph)?;
+    let mut options = optional_inputs(node);
+    let trip_count_input = options.next().unwrap();
+    let cond_input = options.next().unwrap();
+    ensure!(graph.input.len() >= 2, "Loop body must take at least two inputs");
+    let carried = graph.input.len() - 2;
+    let outputs = model.output_outlets()?.len();
+    ensure!(
+        outputs > carried,
+        "Loop body must output the condition and {carried} carried values, it has {outputs} outputs"
+    );
+    let scan_outputs = outputs - 1 - carried;
+    for ix in 0..scan_outputs {
+        let outlet = model.output_outlets()?[1 + carried + ix];
+        InferenceModelPatch::intercept(
+            &model,
+            outlet,
+            format!("{}.output-{}-adjust-dim", node.name, ix),
+            expand(ops::array::AddDims::new(vec![0])),
+            InferenceFact::default(),
+        )?
+        .apply(&mut model)?;
+    }
+    let iterations = ctx.symbol_table.new_with_prefix("loop");
+    Ok((
+        Box::new(Loop { body: model, trip_count_input, cond_input, carried, iterations }),
+        unresolved_inputs,
+    ))
+}
+
+/// ONNX Loop: runs its body while the trip count is not reached and the condition holds.
+///
+/// Body inputs are the iteration number, the condition, the carried values and the closures.
+/// Body outputs are the condition, the carried values and the per-iteration scan outputs.
+#[derive(Debug, Clone)]
+pub struct Loop {
+    pub body: InferenceModel,
+    pub trip_count_input: Option<usize>,
+    pub cond_input: Option<usize>,
+    pub carried: usize,
+    pub iterations: Symbol,
+}
+
+impl Loop {
+    fn first_carried_input(&self) -> usize {
+        self.trip_count_input.is_some() as usize + self.cond_input.is_some() as usize
+    }
+
+    fn scan_outputs(&self) -> usize {
+        self.body.outputs.len() - 1 - self.carried
+    }
+}
+
+impl Op for Loop {
+    fn name(&self) -> Cow<str> {
+        "Loop".into()
+    }
+
+    not_a_typed_op!();
+}
+
+impl EvalOp for Loop {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        let max = if let Some(ix) = self.trip_count_input {
+            inputs[ix].cast_to_scalar::<i64>()?
+        } else {
+            i64::MAX
+        };
+        let cond = if let Some(ix) = self.cond_input {
+            inputs[ix].cast_to_scalar::<bool>()?
+        } else {
+            true
+        };
+        let mut loop_inputs = tvec!(tensor0(max).into_tvalue(), tensor0(cond).into_tvalue());
+        loop_inputs.extend(inputs[self.first_carried_input()..].iter().cloned());
+        let body = self.body.clone().into_typed()?;
+        let op = WhileLoop::new(body, self.carried, self.iterations.clone())?;
+        op.body_state()?.eval(&mut SessionState::default(), &op, loop_inputs)
+    }
+}
+
+impl InferenceOp for Loop {
+    fn infer_facts(
+        &mut self,
+        inputs: TVec<&InferenceFact>,
+        outputs: TVec<&InferenceFact>,
+        observed: TVec<&InferenceFact>,
+    ) -> TractResult<(TVec<InferenceFact>, TVec<InferenceFact>, TVec<InferenceFact>)> {
+        let mut inputs: TVec<InferenceFact> = inputs.into_iter().cloned().collect();
+        let mut outputs: TVec<InferenceFact> = outputs.into_iter().cloned().collect();
+        let first = self.first_carried_input();
+        let body_inputs = self.body.input_outlets()?.len();
+        ensure!(
+            inputs.len() == first + body_inputs - 2,
+            "Loop expects {} inputs, got {}",
+            first + body_inputs - 2,
+            inputs.len()
+        );
+        let scalar = |dt: DatumType| InferenceFact::dt_shape(dt, TVec::<usize>::new());
+        loop {
+            let mut changed = false;
+            if let Some(ix) = self.trip_count_input {
+                changed |= inputs[ix].unify_with(&scalar(i64::datum_type()))?;
+            }
+            if let Some(ix) = self.cond_input {
+                changed |= inputs[ix].unify_with(&scalar(bool::datum_type()))?;
+            }
+            changed |= self.body.input_fact_mut(0)?.unify_with(&scalar(i64::datum_type()))?;
+            changed |= self.body.input_fact_mut(1)?.unify_with(&scalar(bool::datum_type()))?;
+            changed |= self.body.output_fact_mut(0)?.unify_with(&scalar(bool::datum_type()))?;
+            for ix in 0..self.carried {
+                changed |=
+                    self.body.input_fact_mut(2 + ix)?.unify_with_mut(&mut inputs[first + ix])?;
+                // the shape of carried values may change from one iteration to the next
+                let mut dt = self.body.input_fact(2 + ix)?.datum_type;
+                changed |= dt.unify_with_mut(&mut self.body.output_fact_mut(1 + ix)?.datum_type)?;
+                changed |= dt.unify_with_mut(&mut outputs[ix].datum_type)?;
+                changed |= dt.unify_with_mut(&mut inputs[first + ix].datum_type)?;
+                changed |= self.body.input_fact_mut(2 + ix)?.datum_type.unify_with(&dt)?;
+            }
+            for ix in 2 + self.carried..body_inputs {
+                changed |=
+                    self.body.input_fact_mut(ix)?.unify_with_mut(&mut inputs[first + ix - 2])?;
+            }
+            for ix in 0..self.scan_outputs() {
+                changed |= InferenceScan::unify_scanning_tensor_fact(
+                    &mut outputs[self.carried + ix],
+                    self.body.output_fact_mut(1 + self.carried + ix)?,
+                    0,
+                )?;
+            }
+            changed |= self.body.analyse(false)?;
+            if !changed {
+                return Ok((inputs, outputs, observed.into_iter().cloned().collect()));
+            }
+        }
+    }
+
+    fn nboutputs(&self) -> TractResult<usize> {
+        Ok(self.carried + self.scan_outputs())
+    }
+
+    fn to_typed(
+        &self,
+        _source: &InferenceModel,
+        node: &InferenceNode,
+        target: &mut TypedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+    ) -> TractResult<TVec<OutletId>> {
+        let body = self.body.clone().into_typed()?;
+        let max = if let Some(ix) = self.trip_count_input {
+            mapping[&node.inputs[ix]]
+        } else {
+            target.add_const(format!("{}.max_trip_count", node.name), tensor0(i64::MAX))?
+        };
+        let cond = if let Some(ix) = self.cond_input {
+            mapping[&node.inputs[ix]]
+        } else {
+            target.add_const(format!("{}.cond", node.name), tensor0(true))?
+        };
+        let mut inputs = tvec!(max, cond);
+        inputs.extend(node.inputs[self.first_carried_input()..].iter().map(|o| mapping[o]));
+        for (ix, input) in inputs.iter_mut().enumerate() {
+            let fact = target.outlet_fact(*input)?.clone();
+            let expected = body.input_fact(ix)?;
+            if fact.rank() == 1 && expected.rank() == 0 {
+                *input = target.wire_node(
+                    format!("{}.input-{}.as_scalar", node.name, ix),
+                    AxisOp::Rm(0),
+                    &[*input],
+                )?[0];
+            }
+            if fact.datum_type != expected.datum_type {
+                *input = target.wire_node(
+                    format!("{}.input-{}.cast", node.name, ix),
+                    tract_core::ops::cast::cast(expected.datum_type),
+                    &[*input],
+                )?[0];
+            }
+        }
+        let op = WhileLoop::new(body, self.carried, self.iterations.clone())?;
+        if let Some(outputs) = op.wire_as_scan(target, &node.name, &inputs)? {
+            return Ok(outputs);
+        }
+        target.wire_node(&*node.name, op, &inputs)
+    }
+
+    as_op!();
+}
@@ -0,0 +1,70 @@
+use crate::model::ParsingContext;
+use crate::pb::NodeProto;
+use tract_hir::internal::*;
+use tract_onnx_opl::roi_align::{RoiAlign, RoiAlignMode};
+
+pub fn roi_align(
+    ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let mode = RoiAlignMode::parse(node.get_attr_opt("mode")?.unwrap_or("avg"))?;
+    // opset 10 only had the legacy behaviour
+    let default_transform =
+        if ctx.onnx_operator_set_version >= 16 { "half_pixel" } else { "output_half_pixel" };
+    let half_pixel =
+        match node.get_attr_opt("coordinate_transformation_mode")?.unwrap_or(default_transform) {
+            "half_pixel" => true,
+            "output_half_pixel" => false,
+            s => bail!("unsupported RoiAlign coordinate_transformation_mode: {}", s),
+        };
+    let op = RoiAlign {
+        mode,
+        half_pixel,
+        output_height: node.get_attr_opt::<usize>("output_height")?.unwrap_or(1),
+        output_width: node.get_attr_opt::<usize>("output_width")?.unwrap_or(1),
+        sampling_ratio: node.get_attr_opt::<usize>("sampling_ratio")?.unwrap_or(0),
+        spatial_scale: node.get_attr_opt("spatial_scale")?.unwrap_or(1.0),
+    };
+    Ok((expand(RoiAlignExpansion(op)), vec![]))
+}
+
+#[derive(Clone, Debug)]
+struct RoiAlignExpansion(RoiAlign);
+
+impl Expansion for RoiAlignExpansion {
+    fn name(&self) -> Cow<str> {
+        "RoiAlign".into()
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 3)?;
+        check_output_arity(outputs, 1)?;
+        let op = &self.0;
+        s.equals(&inputs[0].datum_type, &outputs[0].datum_type)?;
+        s.equals(&inputs[0].rank, 4)?;
+        s.equals(&inputs[1].rank, 2)?;
+        s.equals(&inputs[1].shape[1], 4.to_dim())?;
+        s.equals(&inputs[2].rank, 1)?;
+        s.equals(&inputs[1].shape[0], &inputs[2].shape[0])?;
+        s.equals(&outputs[0].rank, 4)?;
+        s.equals(&outputs[0].shape[0], &inputs[1].shape[0])?;
+        s.equals(&outputs[0].shape[1], &inputs[0].shape[1])?;
+        s.equals(&outputs[0].shape[2], op.output_height.to_dim())?;
+        s.equals(&outputs[0].shape[3], op.output_width.to_dim())?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        model.wire_node(prefix, self.0.clone(), inputs)
+    }
+}
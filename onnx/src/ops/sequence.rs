@@ -0,0 +1,797 @@
+use crate::model::{OnnxOpRegister, ParsingContext};
+use crate::pb::NodeProto;
+use tract_hir::internal::*;
+use tract_hir::ops::identity::Identity;
+
+pub fn register_all_ops(reg: &mut OnnxOpRegister) {
+    reg.insert("SequenceConstruct", |_, _| Ok((Box::new(SequenceConstruct), vec![])));
+    reg.insert("SequenceEmpty", |_, _| Ok((Box::new(SequenceEmpty), vec![])));
+    reg.insert("SequenceInsert", |_, _| Ok((Box::new(SequenceInsert), vec![])));
+    reg.insert("SequenceErase", |_, _| Ok((Box::new(SequenceErase), vec![])));
+    reg.insert("SequenceAt", |_, _| Ok((Box::new(SequenceAt), vec![])));
+    reg.insert("SequenceLength", |_, _| Ok((Box::new(SequenceLength), vec![])));
+    reg.insert("SplitToSequence", split_to_sequence);
+    reg.insert("ConcatFromSequence", concat_from_sequence);
+    reg.insert("Optional", |_, _| Ok((Box::new(Optional), vec![])));
+    reg.insert("OptionalHasElement", |_, _| Ok((Box::new(OptionalHasElement), vec![])));
+    reg.insert("OptionalGetElement", |_, _| Ok((Box::new(OptionalGetElement), vec![])));
+}
+
+fn split_to_sequence(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let axis = node.get_attr_opt("axis")?.unwrap_or(0);
+    let keepdims = node.get_attr_opt("keepdims")?.unwrap_or(true);
+    Ok((Box::new(SplitToSequence { axis, keepdims }), vec![]))
+}
+
+fn concat_from_sequence(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let axis = node.get_attr("axis")?;
+    let new_axis = node.get_attr_opt("new_axis")?.unwrap_or(false);
+    Ok((Box::new(ConcatFromSequence { axis, new_axis }), vec![]))
+}
+
+/// Typed wires holding the elements of the sequence value at `outlet`.
+///
+/// Sequences only exist between the operators building and consuming them: in the inference
+/// graph, a sequence value is a i64 scalar holding its length. Its elements are resolved by
+/// walking back to the operators that built it when the model is translated to typed form, so
+/// the typed model only deals with plain tensors.
+pub fn sequence_elements(
+    source: &InferenceModel,
+    outlet: OutletId,
+    target: &mut TypedModel,
+    mapping: &HashMap<OutletId, OutletId>,
+) -> TractResult<TVec<OutletId>> {
+    let node = source.node(outlet.node);
+    let inputs: TVec<OutletId> = node.inputs.iter().map(|i| mapping[i]).collect();
+    if node.op_is::<SequenceConstruct>() {
+        Ok(inputs)
+    } else if node.op_is::<SequenceEmpty>() {
+        Ok(tvec!())
+    } else if node.op_is::<SequenceInsert>() {
+        let mut elements = sequence_elements(source, node.inputs[0], target, mapping)?;
+        let position = if let Some(pos) = inputs.get(2) {
+            position(target, *pos, elements.len() + 1)?
+        } else {
+            elements.len()
+        };
+        elements.insert(position, inputs[1]);
+        Ok(elements)
+    } else if node.op_is::<SequenceErase>() {
+        let mut elements = sequence_elements(source, node.inputs[0], target, mapping)?;
+        ensure!(elements.len() > 0, "Erasing from an empty sequence in {}", node);
+        let position = if let Some(pos) = inputs.get(1) {
+            position(target, *pos, elements.len())?
+        } else {
+            elements.len() - 1
+        };
+        elements.remove(position);
+        Ok(elements)
+    } else if let Some(split) = node.op_as::<SplitToSequence>() {
+        split.wire_elements(&node.name, target, &inputs)
+    } else if is_alias(node) {
+        sequence_elements(source, node.inputs[0], target, mapping)
+    } else {
+        bail!("Could not statically resolve the sequence computed by {}", node)
+    }
+}
+
+/// Inference graph outlets holding the elements of the sequence value at `outlet`, as far as they
+/// can be found. Operators extracting tensors from a sequence observe them to get their types.
+fn sequence_element_outlets(model: &InferenceModel, outlet: OutletId) -> TVec<OutletId> {
+    let node = model.node(outlet.node);
+    if node.op_is::<SequenceConstruct>() {
+        node.inputs.clone().into()
+    } else if node.op_is::<SequenceInsert>() {
+        let mut elements = sequence_element_outlets(model, node.inputs[0]);
+        elements.push(node.inputs[1]);
+        elements
+    } else if node.op_is::<SequenceErase>() || is_alias(node) {
+        sequence_element_outlets(model, node.inputs[0])
+    } else if node.op_is::<SplitToSequence>() {
+        tvec!(node.inputs[0])
+    } else {
+        tvec!()
+    }
+}
+
+// tensors extracted from a sequence have the datum type of its elements
+fn unify_with_elements(
+    extracted: &mut InferenceFact,
+    elements: TVec<&InferenceFact>,
+) -> TractResult<TVec<InferenceFact>> {
+    let mut elements: TVec<InferenceFact> = elements.into_iter().cloned().collect();
+    for element in &mut elements {
+        extracted.datum_type.unify_with_mut(&mut element.datum_type)?;
+    }
+    for element in &mut elements {
+        element.datum_type.unify_with(&extracted.datum_type)?;
+    }
+    Ok(elements)
+}
+
+fn is_alias(node: &InferenceNode) -> bool {
+    node.inputs.len() == 1
+        && (node.op_is::<Identity>()
+            || node.op_is::<Optional>()
+            || node.op_is::<OptionalGetElement>())
+}
+
+fn is_empty_optional(source: &InferenceModel, outlet: OutletId) -> bool {
+    let node = source.node(outlet.node);
+    if node.op_is::<Optional>() && node.inputs.is_empty() {
+        true
+    } else if is_alias(node) {
+        is_empty_optional(source, node.inputs[0])
+    } else {
+        false
+    }
+}
+
+// sequence positions can be negative, counting from the end
+fn position(target: &TypedModel, outlet: OutletId, len: usize) -> TractResult<usize> {
+    let pos = target
+        .outlet_fact(outlet)?
+        .konst
+        .as_ref()
+        .context("Sequence positions must be known when the model is loaded")?
+        .cast_to_scalar::<i64>()?;
+    let resolved = if pos < 0 { pos + len as i64 } else { pos };
+    ensure!(
+        0 <= resolved && resolved < len as i64,
+        "Position {} is out of bounds for sequence (upper bound: {})",
+        pos,
+        len
+    );
+    Ok(resolved as usize)
+}
+
+// producers are translated to the constant length of the sequence they build
+fn wire_sequence_length(
+    source: &InferenceModel,
+    node: &InferenceNode,
+    target: &mut TypedModel,
+    mapping: &HashMap<OutletId, OutletId>,
+) -> TractResult<TVec<OutletId>> {
+    let len = sequence_elements(source, OutletId::new(node.id, 0), target, mapping)?.len();
+    Ok(tvec!(target.add_const(&*node.name, tensor0(len as i64))?))
+}
+
+fn sequence_rules<'r, 'p: 'r>(s: &mut Solver<'r>, outputs: &'p [TensorProxy]) -> InferenceResult {
+    check_output_arity(outputs, 1)?;
+    s.equals(&outputs[0].datum_type, i64::datum_type())?;
+    s.equals(&outputs[0].rank, 0)?;
+    Ok(())
+}
+
+// Sequence operators have no runtime implementation: they are resolved when the model is
+// translated to typed form, by walking the graph back to the operators that built the sequence.
+// Reporting them as stateful keeps eager evaluation from calling them during analysis, and keeps
+// the typed translation from folding them to constants once their length is known, so their
+// `to_typed` always gets a chance to resolve the elements.
+macro_rules! sequence_op {
+    ($op: ident) => {
+        impl Op for $op {
+            fn name(&self) -> Cow<str> {
+                stringify!($op).into()
+            }
+
+            not_a_typed_op!();
+        }
+
+        impl EvalOp for $op {
+            fn is_stateless(&self) -> bool {
+                false
+            }
+        }
+    };
+}
+
+#[derive(Debug, Clone, Hash)]
+pub struct SequenceConstruct;
+sequence_op!(SequenceConstruct);
+
+impl InferenceRulesOp for SequenceConstruct {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        sequence_rules(s, outputs)?;
+        s.equals(&outputs[0].value, rctensor0(inputs.len() as i64))?;
+        if inputs.len() > 0 {
+            s.equals_all((0..inputs.len()).map(|i| (&inputs[i].datum_type).bex()).collect())?;
+        }
+        Ok(())
+    }
+
+    fn to_typed(
+        &self,
+        source: &InferenceModel,
+        node: &InferenceNode,
+        target: &mut TypedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+    ) -> TractResult<TVec<OutletId>> {
+        wire_sequence_length(source, node, target, mapping)
+    }
+
+    as_op!();
+}
+
+#[derive(Debug, Clone, Hash)]
+pub struct SequenceEmpty;
+sequence_op!(SequenceEmpty);
+
+impl InferenceRulesOp for SequenceEmpty {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 0)?;
+        sequence_rules(s, outputs)?;
+        s.equals(&outputs[0].value, rctensor0(0i64))?;
+        Ok(())
+    }
+
+    fn to_typed(
+        &self,
+        source: &InferenceModel,
+        node: &InferenceNode,
+        target: &mut TypedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+    ) -> TractResult<TVec<OutletId>> {
+        wire_sequence_length(source, node, target, mapping)
+    }
+
+    as_op!();
+}
+
+#[derive(Debug, Clone, Hash)]
+pub struct SequenceInsert;
+sequence_op!(SequenceInsert);
+
+impl InferenceRulesOp for SequenceInsert {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        ensure!(inputs.len() == 2 || inputs.len() == 3, "SequenceInsert expects 2 or 3 inputs");
+        sequence_rules(s, outputs)?;
+        s.given(&inputs[0].value, move |s, len| {
+            let len = len.cast_to_scalar::<i64>()?;
+            s.equals(&outputs[0].value, rctensor0(len + 1))
+        })?;
+        Ok(())
+    }
+
+    fn to_typed(
+        &self,
+        source: &InferenceModel,
+        node: &InferenceNode,
+        target: &mut TypedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+    ) -> TractResult<TVec<OutletId>> {
+        wire_sequence_length(source, node, target, mapping)
+    }
+
+    as_op!();
+}
+
+#[derive(Debug, Clone, Hash)]
+pub struct SequenceErase;
+sequence_op!(SequenceErase);
+
+impl InferenceRulesOp for SequenceErase {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        ensure!(inputs.len() == 1 || inputs.len() == 2, "SequenceErase expects 1 or 2 inputs");
+        sequence_rules(s, outputs)?;
+        s.given(&inputs[0].value, move |s, len| {
+            let len = len.cast_to_scalar::<i64>()?;
+            s.equals(&outputs[0].value, rctensor0((len - 1).max(0)))
+        })?;
+        Ok(())
+    }
+
+    fn to_typed(
+        &self,
+        source: &InferenceModel,
+        node: &InferenceNode,
+        target: &mut TypedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+    ) -> TractResult<TVec<OutletId>> {
+        wire_sequence_length(source, node, target, mapping)
+    }
+
+    as_op!();
+}
+
+#[derive(Debug, Clone, Hash)]
+pub struct SplitToSequence {
+    pub axis: i64,
+    pub keepdims: bool,
+}
+sequence_op!(SplitToSequence);
+
+impl SplitToSequence {
+    fn wire_elements(
+        &self,
+        name: &str,
+        target: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let fact = target.outlet_fact(inputs[0])?.clone();
+        let axis = if self.axis < 0 { self.axis + fact.rank() as i64 } else { self.axis } as usize;
+        let dim = fact.shape[axis].to_usize().with_context(|| {
+            format!("SplitToSequence needs a known dimension to split, got {fact:?}")
+        })?;
+        let sizes: Vec<usize> = if let Some(split) = inputs.get(1) {
+            let split =
+                target.outlet_fact(*split)?.konst.clone().context(
+                    "SplitToSequence needs the split to be known when the model is loaded",
+                )?;
+            let split = split.cast_to::<i64>()?;
+            if split.rank() == 0 {
+                let chunk = *split.to_scalar::<i64>()? as usize;
+                ensure!(chunk > 0, "SplitToSequence split must be positive");
+                (0..dim).step_by(chunk).map(|start| chunk.min(dim - start)).collect()
+            } else {
+                split.as_slice::<i64>()?.iter().map(|s| *s as usize).collect()
+            }
+        } else {
+            vec![1; dim]
+        };
+        ensure!(
+            sizes.iter().sum::<usize>() == dim,
+            "SplitToSequence split {:?} does not match dimension {}",
+            sizes,
+            dim
+        );
+        let squeeze = inputs.len() == 1 && !self.keepdims;
+        let mut start = 0;
+        let mut elements = tvec!();
+        for (ix, size) in sizes.into_iter().enumerate() {
+            let slice = tract_core::ops::array::Slice::new(axis, start, start + size);
+            let mut wire = wire_once(target, format!("{name}.slice-{ix}"), slice, &[inputs[0]])?;
+            if squeeze {
+                wire =
+                    wire_once(target, format!("{name}.rm-axis-{ix}"), AxisOp::Rm(axis), &[wire])?;
+            }
+            elements.push(wire);
+            start += size;
+        }
+        Ok(elements)
+    }
+}
+
+// sequence elements may be resolved once per consumer, make sure they are only wired once
+fn wire_once(
+    target: &mut TypedModel,
+    name: String,
+    op: impl Into<Box<dyn TypedOp>>,
+    inputs: &[OutletId],
+) -> TractResult<OutletId> {
+    if let Ok(id) = target.node_id_by_name(&name) {
+        return Ok(OutletId::new(id, 0));
+    }
+    Ok(target.wire_node(name, op, inputs)?[0])
+}
+
+impl InferenceRulesOp for SplitToSequence {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        ensure!(inputs.len() == 1 || inputs.len() == 2, "SplitToSequence expects 1 or 2 inputs");
+        sequence_rules(s, outputs)?;
+        let axis = self.axis;
+        s.given(&inputs[0].rank, move |s, rank| {
+            let axis = if axis < 0 { axis + rank } else { axis } as usize;
+            if inputs.len() == 1 {
+                s.given(&inputs[0].shape[axis], move |s, dim| {
+                    if let Ok(dim) = dim.to_i64() {
+                        s.equals(&outputs[0].value, rctensor0(dim))?;
+                    }
+                    Ok(())
+                })
+            } else {
+                s.given_2(&inputs[0].shape[axis], &inputs[1].value, move |s, dim, split| {
+                    let len = if split.rank() == 1 {
+                        split.len() as i64
+                    } else if let Ok(dim) = dim.to_i64() {
+                        let chunk = split.cast_to_scalar::<i64>()?.max(1);
+                        (dim + chunk - 1) / chunk
+                    } else {
+                        return Ok(());
+                    };
+                    s.equals(&outputs[0].value, rctensor0(len))
+                })
+            }
+        })?;
+        Ok(())
+    }
+
+    fn to_typed(
+        &self,
+        source: &InferenceModel,
+        node: &InferenceNode,
+        target: &mut TypedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+    ) -> TractResult<TVec<OutletId>> {
+        wire_sequence_length(source, node, target, mapping)
+    }
+
+    as_op!();
+}
+
+#[derive(Debug, Clone, Hash)]
+pub struct SequenceAt;
+sequence_op!(SequenceAt);
+
+impl InferenceOp for SequenceAt {
+    fn infer_facts(
+        &mut self,
+        inputs: TVec<&InferenceFact>,
+        outputs: TVec<&InferenceFact>,
+        observed: TVec<&InferenceFact>,
+    ) -> TractResult<(TVec<InferenceFact>, TVec<InferenceFact>, TVec<InferenceFact>)> {
+        ensure!(inputs.len() == 2, "SequenceAt expects 2 inputs");
+        ensure!(outputs.len() == 1, "SequenceAt expects 1 output");
+        let inputs: TVec<InferenceFact> = inputs.into_iter().cloned().collect();
+        let mut outputs: TVec<InferenceFact> = outputs.into_iter().cloned().collect();
+        let observed = unify_with_elements(&mut outputs[0], observed)?;
+        Ok((inputs, outputs, observed))
+    }
+
+    fn observe_outlets(
+        &self,
+        model: &InferenceModel,
+        node: &InferenceNode,
+    ) -> TractResult<Vec<OutletId>> {
+        Ok(sequence_element_outlets(model, node.inputs[0]).into_vec())
+    }
+
+    fn to_typed(
+        &self,
+        source: &InferenceModel,
+        node: &InferenceNode,
+        target: &mut TypedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+    ) -> TractResult<TVec<OutletId>> {
+        let elements = sequence_elements(source, node.inputs[0], target, mapping)?;
+        let position = position(target, mapping[&node.inputs[1]], elements.len())?;
+        Ok(tvec!(elements[position]))
+    }
+
+    as_op!();
+}
+
+#[derive(Debug, Clone, Hash)]
+pub struct SequenceLength;
+
+impl Op for SequenceLength {
+    fn name(&self) -> Cow<str> {
+        "SequenceLength".into()
+    }
+
+    not_a_typed_op!();
+}
+
+// the inference value of a sequence is its length, so the length is just passed through
+impl EvalOp for SequenceLength {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        Ok(inputs)
+    }
+}
+
+impl InferenceRulesOp for SequenceLength {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 1)?;
+        sequence_rules(s, outputs)?;
+        s.equals(&inputs[0].value, &outputs[0].value)?;
+        Ok(())
+    }
+
+    fn to_typed(
+        &self,
+        source: &InferenceModel,
+        node: &InferenceNode,
+        target: &mut TypedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+    ) -> TractResult<TVec<OutletId>> {
+        let len = sequence_elements(source, node.inputs[0], target, mapping)?.len();
+        Ok(tvec!(target.add_const(&*node.name, tensor0(len as i64))?))
+    }
+
+    as_op!();
+}
+
+#[derive(Debug, Clone, Hash)]
+pub struct ConcatFromSequence {
+    pub axis: i64,
+    pub new_axis: bool,
+}
+sequence_op!(ConcatFromSequence);
+
+impl InferenceOp for ConcatFromSequence {
+    fn infer_facts(
+        &mut self,
+        inputs: TVec<&InferenceFact>,
+        outputs: TVec<&InferenceFact>,
+        observed: TVec<&InferenceFact>,
+    ) -> TractResult<(TVec<InferenceFact>, TVec<InferenceFact>, TVec<InferenceFact>)> {
+        ensure!(inputs.len() == 1, "ConcatFromSequence expects 1 input");
+        ensure!(outputs.len() == 1, "ConcatFromSequence expects 1 output");
+        let inputs: TVec<InferenceFact> = inputs.into_iter().cloned().collect();
+        let mut outputs: TVec<InferenceFact> = outputs.into_iter().cloned().collect();
+        let observed = unify_with_elements(&mut outputs[0], observed)?;
+        Ok((inputs, outputs, observed))
+    }
+
+    fn observe_outlets(
+        &self,
+        model: &InferenceModel,
+        node: &InferenceNode,
+    ) -> TractResult<Vec<OutletId>> {
+        Ok(sequence_element_outlets(model, node.inputs[0]).into_vec())
+    }
+
+    fn to_typed(
+        &self,
+        source: &InferenceModel,
+        node: &InferenceNode,
+        target: &mut TypedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+    ) -> TractResult<TVec<OutletId>> {
+        let mut elements = sequence_elements(source, node.inputs[0], target, mapping)?;
+        ensure!(elements.len() > 0, "ConcatFromSequence on an empty sequence in {}", node);
+        let rank = target.outlet_fact(elements[0])?.rank() + self.new_axis as usize;
+        let axis = if self.axis < 0 { self.axis + rank as i64 } else { self.axis } as usize;
+        if self.new_axis {
+            for (ix, element) in elements.iter_mut().enumerate() {
+                *element = target.wire_node(
+                    format!("{}.add-axis-{}", node.name, ix),
+                    AxisOp::Add(axis),
+                    &[*element],
+                )?[0];
+            }
+        }
+        target.wire_node(&*node.name, tract_core::ops::array::TypedConcat::new(axis), &elements)
+    }
+
+    as_op!();
+}
+
+/// Optional values are their element when they have one: only empty optionals need to be
+/// tracked, and they are resolved statically too.
+#[derive(Debug, Clone, Hash)]
+pub struct Optional;
+sequence_op!(Optional);
+
+impl InferenceRulesOp for Optional {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        ensure!(inputs.len() <= 1, "Optional expects at most one input");
+        check_output_arity(outputs, 1)?;
+        if inputs.len() == 1 {
+            s.equals(&inputs[0].datum_type, &outputs[0].datum_type)?;
+            s.equals(&inputs[0].shape, &outputs[0].shape)?;
+        }
+        Ok(())
+    }
+
+    fn to_typed(
+        &self,
+        _source: &InferenceModel,
+        node: &InferenceNode,
+        target: &mut TypedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+    ) -> TractResult<TVec<OutletId>> {
+        if let Some(input) = node.inputs.first() {
+            Ok(tvec!(mapping[input]))
+        } else {
+            Ok(tvec!(target.add_const(&*node.name, tensor0(false))?))
+        }
+    }
+
+    as_op!();
+}
+
+#[derive(Debug, Clone, Hash)]
+pub struct OptionalHasElement;
+sequence_op!(OptionalHasElement);
+
+impl InferenceRulesOp for OptionalHasElement {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        ensure!(inputs.len() <= 1, "OptionalHasElement expects at most one input");
+        check_output_arity(outputs, 1)?;
+        s.equals(&outputs[0].datum_type, bool::datum_type())?;
+        s.equals(&outputs[0].rank, 0)?;
+        if inputs.is_empty() {
+            s.equals(&outputs[0].value, rctensor0(false))?;
+        }
+        Ok(())
+    }
+
+    fn to_typed(
+        &self,
+        source: &InferenceModel,
+        node: &InferenceNode,
+        target: &mut TypedModel,
+        _mapping: &HashMap<OutletId, OutletId>,
+    ) -> TractResult<TVec<OutletId>> {
+        let has_element = node.inputs.first().is_some_and(|i| !is_empty_optional(source, *i));
+        Ok(tvec!(target.add_const(&*node.name, tensor0(has_element))?))
+    }
+
+    as_op!();
+}
+
+#[derive(Debug, Clone, Hash)]
+pub struct OptionalGetElement;
+
+impl Op for OptionalGetElement {
+    fn name(&self) -> Cow<str> {
+        "OptionalGetElement".into()
+    }
+
+    not_a_typed_op!();
+}
+
+// an optional with an element is that element, so getting it is an identity
+impl EvalOp for OptionalGetElement {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        Ok(inputs)
+    }
+}
+
+impl InferenceRulesOp for OptionalGetElement {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 1)?;
+        check_output_arity(outputs, 1)?;
+        s.equals(&inputs[0].datum_type, &outputs[0].datum_type)?;
+        s.equals(&inputs[0].shape, &outputs[0].shape)?;
+        s.equals(&inputs[0].value, &outputs[0].value)?;
+        Ok(())
+    }
+
+    fn to_typed(
+        &self,
+        source: &InferenceModel,
+        node: &InferenceNode,
+        _target: &mut TypedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+    ) -> TractResult<TVec<OutletId>> {
+        ensure!(
+            !is_empty_optional(source, node.inputs[0]),
+            "Getting the element of an empty optional in {}",
+            node
+        );
+        Ok(tvec!(mapping[&node.inputs[0]]))
+    }
+
+    as_op!();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::pb::tensor_shape_proto::{dimension, Dimension};
+    use crate::pb::*;
+
+    fn node(op_type: &str, input: &[&str], output: &str) -> NodeProto {
+        NodeProto {
+            op_type: op_type.into(),
+            input: input.iter().map(|s| s.to_string()).collect(),
+            output: vec![output.into()],
+            ..Default::default()
+        }
+    }
+
+    fn model(nodes: Vec<NodeProto>, outputs: &[&str]) -> ModelProto {
+        let dim = Dimension { value: Some(dimension::Value::DimValue(2)), ..Default::default() };
+        let tensor = type_proto::Tensor {
+            elem_type: tensor_proto::DataType::Float as i32,
+            shape: Some(TensorShapeProto { dim: vec![dim] }),
+        };
+        let r#type =
+            TypeProto { value: Some(type_proto::Value::TensorType(tensor)), ..Default::default() };
+        let x = ValueInfoProto { name: "x".into(), r#type: Some(r#type), ..Default::default() };
+        let pos = TensorProto {
+            name: "pos".into(),
+            data_type: tensor_proto::DataType::Int64 as i32,
+            int64_data: vec![-1],
+            ..Default::default()
+        };
+        let graph = GraphProto {
+            node: nodes,
+            initializer: vec![pos],
+            input: vec![x],
+            output: outputs
+                .iter()
+                .map(|name| ValueInfoProto { name: name.to_string(), ..Default::default() })
+                .collect(),
+            ..Default::default()
+        };
+        ModelProto {
+            graph: Some(graph),
+            opset_import: vec![OperatorSetIdProto { domain: "".into(), version: 13 }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn extracted_tensors_have_element_type() -> TractResult<()> {
+        let mut concat = node("ConcatFromSequence", &["seq"], "concat");
+        concat.attribute.push(AttributeProto {
+            name: "axis".into(),
+            r#type: attribute_proto::AttributeType::Int as i32,
+            i: 0,
+            ..Default::default()
+        });
+        let proto = model(
+            vec![
+                node("SequenceConstruct", &["x"], "seq1"),
+                node("Neg", &["x"], "y"),
+                node("SequenceInsert", &["seq1", "y"], "seq"),
+                node("SequenceAt", &["seq", "pos"], "at"),
+                concat,
+            ],
+            &["at", "concat"],
+        );
+        let mut model = crate::onnx().model_for_proto_model(&proto)?;
+        model.analyse(false)?;
+        for output in model.output_outlets()?.to_vec() {
+            let fact = model.outlet_fact(output)?;
+            assert_eq!(fact.datum_type.concretize(), Some(f32::datum_type()));
+        }
+        let outputs =
+            model.into_typed()?.into_runnable()?.run(tvec!(tensor1(&[1f32, 2.]).into()))?;
+        assert_eq!(*outputs[0], tensor1(&[-1f32, -2.]));
+        assert_eq!(*outputs[1], tensor1(&[1f32, 2., -1., -2.]));
+        Ok(())
+    }
+}
@@ -287,6 +287,17 @@ impl<'a> AttrTVecType<'a> for usize {
     }
 }
 
+impl TypeProto {
+    /// Tensor type of a value, looking through optional wrappers. Sequences have none.
+    pub fn tensor_type(&self) -> Option<&type_proto::Tensor> {
+        match self.value.as_ref()? {
+            type_proto::Value::TensorType(t) => Some(t),
+            type_proto::Value::OptionalType(o) => o.elem_type.as_ref()?.tensor_type(),
+            type_proto::Value::SequenceType(_) => None,
+        }
+    }
+}
+
 impl NodeProto {
     pub fn bail<T>(&self, msg: &str) -> TractResult<T> {
         bail!("Node {} ({}): {}", self.name, self.op_type, msg)
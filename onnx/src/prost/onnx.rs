@@ -712,7 +712,7 @@ pub struct TypeProto {
     /// for pre-defined type denotations.
     #[prost(string, tag="6")]
     pub denotation: ::prost::alloc::string::String,
-    #[prost(oneof="type_proto::Value", tags="1")]
+    #[prost(oneof="type_proto::Value", tags="1, 4, 9")]
     pub value: ::core::option::Option<type_proto::Value>,
 }
 /// Nested message and enum types in `TypeProto`.
@@ -726,11 +726,34 @@ pub mod type_proto {
         #[prost(message, optional, tag="2")]
         pub shape: ::core::option::Option<super::TensorShapeProto>,
     }
+    /// repeated T
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct Sequence {
+        /// The type and optional shape of each element of the sequence.
+        /// This field MUST be present for this version of the IR.
+        #[prost(message, optional, boxed, tag="1")]
+        pub elem_type: ::core::option::Option<::prost::alloc::boxed::Box<super::TypeProto>>,
+    }
+    /// wrapper for Tensor, Sequence, or Map
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct Optional {
+        /// The type and optional shape of the element wrapped.
+        /// This field MUST be present for this version of the IR.
+        /// Possible values correspond to OptionalProto.DataType enum
+        #[prost(message, optional, boxed, tag="1")]
+        pub elem_type: ::core::option::Option<::prost::alloc::boxed::Box<super::TypeProto>>,
+    }
     #[derive(Clone, PartialEq, ::prost::Oneof)]
     pub enum Value {
         /// The type of a tensor.
         #[prost(message, tag="1")]
         TensorType(Tensor),
+        /// The type of a sequence.
+        #[prost(message, tag="4")]
+        SequenceType(Sequence),
+        /// The type of an optional.
+        #[prost(message, tag="9")]
+        OptionalType(Optional),
     }
 }
 /// Operator Sets
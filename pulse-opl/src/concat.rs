@@ -1,6 +1,6 @@
 use std::ops::Range;
 use tract_nnef::internal::*;
-use tract_nnef::tract_core::trivial_op_state_freeeze;
+use tract_nnef::tract_core::ops::OpStateFreeze;
 
 /// Concat with pulse along concat axis
 #[derive(Debug, Clone, Hash)]
@@ -46,7 +46,26 @@ impl TypedOp for PulsedSameAxisConcat {
 pub struct PulsedSameAxisConcatState {
     current_pos: usize,
 }
-trivial_op_state_freeeze!(PulsedSameAxisConcatState);
+
+impl OpStateFreeze for PulsedSameAxisConcatState {
+    fn freeze(&self) -> Box<dyn FrozenOpState> {
+        Box::new(self.clone())
+    }
+}
+
+impl FrozenOpState for PulsedSameAxisConcatState {
+    fn unfreeze(&self) -> Box<dyn OpState> {
+        Box::new(self.clone())
+    }
+
+    fn save(&self) -> TractResult<SavedOpState> {
+        Ok(SavedOpState { counters: vec![self.current_pos as i64], ..SavedOpState::default() })
+    }
+
+    fn load(&self, saved: &SavedOpState) -> TractResult<Box<dyn FrozenOpState>> {
+        Ok(Box::new(PulsedSameAxisConcatState { current_pos: saved.counter(0)? as usize }))
+    }
+}
 
 impl OpState for PulsedSameAxisConcatState {
     fn eval(
@@ -134,4 +134,19 @@ impl FrozenOpState for FrozenDeconvDelayState {
             buffer: self.buffer.as_ref().map(|t| t.clone().into_tensor()),
         })
     }
+
+    fn save(&self) -> TractResult<SavedOpState> {
+        Ok(SavedOpState {
+            counters: vec![self.valid_inputed as i64],
+            tensors: vec![self.buffer.as_ref().map(|t| t.clone().into_tensor())],
+            ..SavedOpState::default()
+        })
+    }
+
+    fn load(&self, saved: &SavedOpState) -> TractResult<Box<dyn FrozenOpState>> {
+        Ok(Box::new(FrozenDeconvDelayState {
+            valid_inputed: saved.counter(0)? as isize,
+            buffer: saved.tensor(0)?.map(|t| t.into_arc_tensor()),
+        }))
+    }
 }
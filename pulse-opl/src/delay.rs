@@ -207,4 +207,13 @@ impl FrozenOpState for FrozenDelayState {
     fn unfreeze(&self) -> Box<dyn OpState> {
         Box::new(DelayState { buffer: self.buffer.as_ref().map(|t| t.clone().into_tensor()) })
     }
+
+    fn save(&self) -> TractResult<SavedOpState> {
+        let buffer = self.buffer.as_ref().map(|t| t.clone().into_tensor());
+        Ok(SavedOpState { tensors: vec![buffer], ..SavedOpState::default() })
+    }
+
+    fn load(&self, saved: &SavedOpState) -> TractResult<Box<dyn FrozenOpState>> {
+        Ok(Box::new(FrozenDelayState { buffer: saved.tensor(0)?.map(|t| t.into_arc_tensor()) }))
+    }
 }
@@ -1,6 +1,6 @@
 use tract_nnef::internal::*;
 use tract_nnef::ser::tdim;
-use tract_nnef::tract_core::trivial_op_state_freeeze;
+use tract_nnef::tract_core::ops::OpStateFreeze;
 
 pub fn register(registry: &mut Registry) {
     registry.register_primitive(
@@ -145,4 +145,22 @@ impl TypedOp for PulseMask {
     as_op!();
 }
 
-trivial_op_state_freeeze!(PulseMaskOpState);
+impl OpStateFreeze for PulseMaskOpState {
+    fn freeze(&self) -> Box<dyn FrozenOpState> {
+        Box::new(self.clone())
+    }
+}
+
+impl FrozenOpState for PulseMaskOpState {
+    fn unfreeze(&self) -> Box<dyn OpState> {
+        Box::new(self.clone())
+    }
+
+    fn save(&self) -> TractResult<SavedOpState> {
+        Ok(SavedOpState { counters: vec![self.current_pos as i64], ..SavedOpState::default() })
+    }
+
+    fn load(&self, saved: &SavedOpState) -> TractResult<Box<dyn FrozenOpState>> {
+        Ok(Box::new(PulseMaskOpState { current_pos: saved.counter(0)? as usize }))
+    }
+}
@@ -276,4 +276,19 @@ impl FrozenOpState for FrozenPulsePadOpState {
             last_valid_frame: self.last_valid_frame.as_ref().map(|t| t.clone().into_tensor()),
         })
     }
+
+    fn save(&self) -> TractResult<SavedOpState> {
+        Ok(SavedOpState {
+            counters: vec![self.current_pos as i64],
+            tensors: vec![self.last_valid_frame.as_ref().map(|t| t.clone().into_tensor())],
+            ..SavedOpState::default()
+        })
+    }
+
+    fn load(&self, saved: &SavedOpState) -> TractResult<Box<dyn FrozenOpState>> {
+        Ok(Box::new(FrozenPulsePadOpState {
+            current_pos: saved.counter(0)? as usize,
+            last_valid_frame: saved.tensor(0)?.map(|t| t.into_arc_tensor()),
+        }))
+    }
 }
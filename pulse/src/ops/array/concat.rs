@@ -2,7 +2,7 @@ use crate::internal::*;
 use tract_core::ops::array::TypedConcat;
 use tract_pulse_opl::concat::overwrite_part_of_pulse;
 use tract_pulse_opl::ops::Delay;
-use tract_pulse_opl::tract_core::trivial_op_state_freeeze;
+use tract_pulse_opl::tract_core::ops::OpStateFreeze;
 
 register_all!(TypedConcat: pulsify);
 
@@ -144,7 +144,29 @@ pub struct PulsedSameAxisConcatState {
     current_pos: usize,
     symbols_in_dim: Vec<Symbol>,
 }
-trivial_op_state_freeeze!(PulsedSameAxisConcatState);
+
+impl OpStateFreeze for PulsedSameAxisConcatState {
+    fn freeze(&self) -> Box<dyn FrozenOpState> {
+        Box::new(self.clone())
+    }
+}
+
+impl FrozenOpState for PulsedSameAxisConcatState {
+    fn unfreeze(&self) -> Box<dyn OpState> {
+        Box::new(self.clone())
+    }
+
+    fn save(&self) -> TractResult<SavedOpState> {
+        Ok(SavedOpState { counters: vec![self.current_pos as i64], ..SavedOpState::default() })
+    }
+
+    fn load(&self, saved: &SavedOpState) -> TractResult<Box<dyn FrozenOpState>> {
+        Ok(Box::new(PulsedSameAxisConcatState {
+            current_pos: saved.counter(0)? as usize,
+            ..self.clone()
+        }))
+    }
+}
 
 impl OpState for PulsedSameAxisConcatState {
     fn eval(
@@ -127,4 +127,37 @@ mod test {
             assert_eq!(&output[0].as_slice::<u8>().unwrap()[skip..], &expect[skip..]);
         }
     }
+
+    #[test]
+    fn save_and_load() -> TractResult<()> {
+        let mut model = PulsedModel::default();
+        let stream_dim = model.symbol_table.sym("S").to_dim();
+        let fact = PulsedFact {
+            datum_type: u8::datum_type(),
+            shape: (&[4]).into(),
+            stream: Some(StreamInfo { axis: 0, dim: stream_dim, delay: 0 }),
+        };
+        let source = model.add_source("source", fact.clone())?;
+        let delay =
+            model.wire_node("delay", Delay::new_typed(&(&fact).into(), 0, 3, 2), &[source])?;
+        model.set_output_outlets(&delay)?;
+
+        let plan = Arc::new(SimplePlan::new(model)?);
+        let mut state = tract_core::plan::SimpleState::new(plan.clone())?;
+        let pulses: Vec<Tensor> =
+            (0..4u8).map(|i| tensor1(&[4 * i, 4 * i + 1, 4 * i + 2, 4 * i + 3])).collect();
+        state.run(tvec!(pulses[0].clone().into()))?;
+        state.run(tvec!(pulses[1].clone().into()))?;
+
+        let bytes = state.freeze().save()?.to_bytes()?;
+        let saved = SavedSimpleState::from_bytes(&bytes)?;
+        let mut loaded =
+            tract_core::plan::SimpleState::new(plan)?.freeze().load(&saved)?.unfreeze();
+        for pulse in &pulses[2..] {
+            let expected = state.run(tvec!(pulse.clone().into()))?;
+            let found = loaded.run(tvec!(pulse.clone().into()))?;
+            assert_eq!(expected, found);
+        }
+        Ok(())
+    }
 }
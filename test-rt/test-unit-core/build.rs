@@ -12,4 +12,10 @@ fn main() {
         "unoptimized()",
         "Approximation::Approximate",
     );
+    suite.test_runtime(
+        "parallel",
+        "suite_unit::suite().unwrap()",
+        "parallel()",
+        "Approximation::Approximate",
+    );
 }
@@ -32,3 +32,12 @@ mod unoptimized {
     include!(concat!(env!("OUT_DIR"), "/tests/unoptimized.rs"));
 }
 
+mod parallel {
+    use super::*;
+
+    pub fn parallel() -> &'static ParallelRuntime {
+        &ParallelRuntime { threads: 3 }
+    }
+    include!(concat!(env!("OUT_DIR"), "/tests/parallel.rs"));
+}
+
@@ -20,6 +20,7 @@ mod tflite_generated;
 pub use tflite_generated::tflite;
 
 pub use model::Tflite;
+pub use ops::{Resize, UnidirectionalSequenceLstm};
 
 pub mod prelude {
     pub use tract_core::prelude::*;
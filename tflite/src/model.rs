@@ -5,7 +5,9 @@ use flatbuffers::FlatBufferBuilder;
 use tract_core::internal::*;
 
 use crate::registry::Registry;
-use crate::tensors::{flat_tensor_to_tract_fact, flat_tensor_uses_per_axis_q};
+use crate::tensors::{
+    flat_tensor_is_variable, flat_tensor_to_tract_fact, flat_tensor_uses_per_axis_q,
+};
 use crate::tflite;
 use crate::tflite::{Buffer, BufferArgs};
 
@@ -95,9 +97,18 @@ impl Framework<TfliteProtoModel, TypedModel> for Tflite {
         }
         for op in main.operators().context("No operators in Tflite model")? {
             for input in op.inputs().context("No input in Tflite  operator")? {
+                if input < 0 {
+                    continue;
+                }
                 if let Entry::Vacant(slot) = mapping.entry(input) {
                     let (fact, name) = flat_tensor_to_tract_fact(&root, main, input)?;
-                    let value = fact.konst.with_context(|| format!("Error in TF file for operator {:?}. No prior computation nor constant for input {}", op, input))?;
+                    // variable tensors (recurrent states) start zeroed
+                    let value = if flat_tensor_is_variable(main, input) {
+                        let shape = fact.shape.as_concrete().context("Symbolic variable tensor")?;
+                        Tensor::zero_dt(fact.datum_type, shape)?.into_arc_tensor()
+                    } else {
+                        fact.konst.with_context(|| format!("Error in TF file for operator {:?}. No prior computation nor constant for input {}", op, input))?
+                    };
                     let konst = target.add_const(name, value)?;
                     slot.insert(konst);
                 }
@@ -1,5 +1,5 @@
 use tract_core::internal::*;
-use tract_core::ops::array::{MultiBroadcastTo, Slice, TypedConcat};
+use tract_core::ops::array::{Gather, MultiBroadcastTo, Slice, TypedConcat};
 use tract_core::ops::binary::wire_cast;
 use tract_core::ops::Downsample;
 use tract_core::prelude::tract_itertools::Itertools;
@@ -8,9 +8,11 @@ use tract_ndarray::ArrayView2;
 use crate::registry::{DeserOp, Registry};
 use crate::ser::{BuiltinOp, SubgraphBuilder};
 use crate::tflite::{
-    BuiltinOperator, BuiltinOptions, ExpandDimsOptions, ExpandDimsOptionsArgs, ReshapeOptions,
-    ReshapeOptionsArgs, SliceOptions, SliceOptionsArgs, SqueezeOptions, SqueezeOptionsArgs,
-    StridedSliceOptions, StridedSliceOptionsArgs, TransposeOptions, TransposeOptionsArgs,
+    ActivationFunctionType, BuiltinOperator, BuiltinOptions, ConcatenationOptions,
+    ConcatenationOptionsArgs, ExpandDimsOptions, ExpandDimsOptionsArgs, GatherOptions,
+    GatherOptionsArgs, ReshapeOptions, ReshapeOptionsArgs, SliceOptions, SliceOptionsArgs,
+    SqueezeOptions, SqueezeOptionsArgs, StridedSliceOptions, StridedSliceOptionsArgs,
+    TransposeOptions, TransposeOptionsArgs,
 };
 
 use super::wire_fused_activation;
@@ -18,20 +20,27 @@ use super::wire_fused_activation;
 pub fn register_all(reg: &mut Registry) {
     reg.reg_to_tflite(ser_axisop);
     reg.reg_to_tflite(ser_broadcast_to);
+    reg.reg_to_tflite(ser_concat);
     reg.reg_to_tflite(ser_downsample);
+    reg.reg_to_tflite(ser_gather);
     reg.reg_to_tflite(ser_slice);
 
     reg.reg_to_tract(BuiltinOperator::BROADCAST_TO, de_broadcast_to);
     reg.reg_to_tract(BuiltinOperator::CONCATENATION, de_concat);
     reg.reg_to_tract(BuiltinOperator::EXPAND_DIMS, de_expand_dims);
+    reg.reg_to_tract(BuiltinOperator::GATHER, de_gather);
+    reg.reg_to_tract(BuiltinOperator::PACK, de_pack);
     reg.reg_to_tract(BuiltinOperator::PAD, de_pad);
     reg.reg_to_tract(BuiltinOperator::PADV2, de_padv2);
     reg.reg_to_tract(BuiltinOperator::RESHAPE, de_reshape);
     reg.reg_to_tract(BuiltinOperator::SHAPE, de_shape);
     reg.reg_to_tract(BuiltinOperator::SLICE, de_slice);
+    reg.reg_to_tract(BuiltinOperator::SPLIT, de_split);
+    reg.reg_to_tract(BuiltinOperator::SPLIT_V, de_split_v);
     reg.reg_to_tract(BuiltinOperator::SQUEEZE, de_squeeze);
     reg.reg_to_tract(BuiltinOperator::STRIDED_SLICE, de_strided_slice);
     reg.reg_to_tract(BuiltinOperator::TRANSPOSE, de_transpose);
+    reg.reg_to_tract(BuiltinOperator::UNPACK, de_unpack);
 }
 
 fn de_broadcast_to(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
@@ -65,6 +74,34 @@ fn de_expand_dims(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
     Ok(wire)
 }
 
+fn de_gather(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
+    let options = builtin!(op, builtin_options_as_gather_options);
+    ensure!(options.batch_dims() == 0, "GATHER with batch_dims is not supported");
+    let rank = op.facts()?[0].rank();
+    let axis =
+        if options.axis() < 0 { rank as i32 + options.axis() } else { options.axis() } as usize;
+    op.ctx.target.wire_node(op.prefix, Gather::new(axis), op.inputs)
+}
+
+fn de_pack(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
+    let options = builtin!(op, builtin_options_as_pack_options);
+    let rank = op.facts()?[0].rank() + 1;
+    let axis =
+        if options.axis() < 0 { rank as i32 + options.axis() } else { options.axis() } as usize;
+    let prefix = op.prefix;
+    let mut wires = tvec!();
+    for (ix, input) in op.inputs.iter().enumerate() {
+        wires.push(
+            op.ctx.target.wire_node(
+                format!("{prefix}.add_axis.{ix}"),
+                AxisOp::Add(axis),
+                &[*input],
+            )?[0],
+        );
+    }
+    op.ctx.target.wire_node(prefix, TypedConcat::new(axis), &wires)
+}
+
 fn de_pad(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
     let (input, pads) = args_2!(op.facts()?);
     let pads = pads.konst.as_ref().context("Dynamic PAD is not supported")?;
@@ -131,6 +168,59 @@ fn de_slice(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
     Ok(wire)
 }
 
+fn wire_split(
+    op: &mut DeserOp,
+    input: OutletId,
+    axis: i32,
+    sizes: &[TDim],
+) -> TractResult<TVec<OutletId>> {
+    let rank = op.ctx.target.outlet_fact(input)?.rank();
+    let axis = if axis < 0 { rank as i32 + axis } else { axis } as usize;
+    let mut start = TDim::Val(0);
+    let mut outputs = tvec!();
+    for (ix, size) in sizes.iter().enumerate() {
+        let end = start.clone() + size;
+        outputs.push(
+            op.ctx.target.wire_node(
+                format!("{}.{ix}", op.prefix),
+                Slice::new(axis, start, end.clone()),
+                &[input],
+            )?[0],
+        );
+        start = end;
+    }
+    Ok(outputs)
+}
+
+fn de_split(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
+    let options = builtin!(op, builtin_options_as_split_options);
+    let (axis, input) = args_2!(op.facts()?);
+    let axis = axis.konst.context("Dynamic SPLIT is not supported")?.cast_to_scalar::<i32>()?;
+    let splits = options.num_splits() as usize;
+    let rank = input.rank() as i32;
+    let dim = &input.shape[if axis < 0 { rank + axis } else { axis } as usize];
+    let sizes = tvec!(dim.clone() / splits; splits);
+    wire_split(op, op.inputs[1], axis, &sizes)
+}
+
+fn de_split_v(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
+    let (input, sizes, axis) = args_3!(op.facts()?);
+    let axis = axis.konst.context("Dynamic SPLIT_V is not supported")?.cast_to_scalar::<i32>()?;
+    let sizes = sizes.konst.context("Dynamic SPLIT_V is not supported")?;
+    let sizes = sizes.cast_to::<i64>()?;
+    let rank = input.rank() as i32;
+    let dim = &input.shape[if axis < 0 { rank + axis } else { axis } as usize];
+    // at most one size can be -1, and it takes the remainder
+    let known: TDim =
+        sizes.as_slice::<i64>()?.iter().filter(|s| **s >= 0).map(|s| s.to_dim()).sum();
+    let sizes: TVec<TDim> = sizes
+        .as_slice::<i64>()?
+        .iter()
+        .map(|s| if *s < 0 { dim.clone() - &known } else { s.to_dim() })
+        .collect();
+    wire_split(op, op.inputs[0], axis, &sizes)
+}
+
 fn de_squeeze(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
     let options = builtin!(op, builtin_options_as_squeeze_options);
     let mut wire = tvec!(op.inputs[0]);
@@ -173,6 +263,26 @@ fn de_transpose(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
     Ok(wire)
 }
 
+fn de_unpack(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
+    let options = builtin!(op, builtin_options_as_unpack_options);
+    let input = args_1!(op.facts()?);
+    let rank = input.rank();
+    let axis =
+        if options.axis() < 0 { rank as i32 + options.axis() } else { options.axis() } as usize;
+    let prefix = op.prefix;
+    let mut outputs = tvec!();
+    for ix in 0..options.num() as usize {
+        let wire = op.ctx.target.wire_node(
+            format!("{prefix}.slice.{ix}"),
+            Slice::new(axis, ix, ix + 1),
+            &op.inputs[0..1],
+        )?;
+        outputs
+            .push(op.ctx.target.wire_node(format!("{prefix}.{ix}"), AxisOp::Rm(axis), &wire)?[0]);
+    }
+    Ok(outputs)
+}
+
 fn ser_axisop(
     builder: &mut SubgraphBuilder,
     model: &TypedModel,
@@ -261,6 +371,29 @@ fn ser_broadcast_to(
     builder.write_op(&inputs, &[output], 130, 3, BuiltinOperator::BROADCAST_TO)
 }
 
+fn ser_concat(
+    builder: &mut SubgraphBuilder,
+    model: &TypedModel,
+    node: &TypedNode,
+    op: &TypedConcat,
+) -> TractResult<()> {
+    let inputs = builder.map_outlets(model, &node.inputs)?;
+    let output = builder.outlets_to_tensors[&node.id.into()];
+    let options = ConcatenationOptions::create(
+        builder.fb(),
+        &ConcatenationOptionsArgs {
+            axis: op.axis as i32,
+            fused_activation_function: ActivationFunctionType::NONE,
+        },
+    );
+    builder.write_op_with_options(
+        &inputs,
+        &[output],
+        BuiltinOp::new(2, 1, BuiltinOperator::CONCATENATION, BuiltinOptions::ConcatenationOptions),
+        options.as_union_value(),
+    )
+}
+
 fn ser_downsample(
     builder: &mut SubgraphBuilder,
     model: &TypedModel,
@@ -307,6 +440,26 @@ fn ser_downsample(
     )
 }
 
+fn ser_gather(
+    builder: &mut SubgraphBuilder,
+    model: &TypedModel,
+    node: &TypedNode,
+    op: &Gather,
+) -> TractResult<()> {
+    let inputs = builder.map_outlets(model, &node.inputs)?;
+    let output = builder.outlets_to_tensors[&node.id.into()];
+    let options = GatherOptions::create(
+        builder.fb(),
+        &GatherOptionsArgs { axis: op.axis as i32, batch_dims: 0 },
+    );
+    builder.write_op_with_options(
+        &inputs,
+        &[output],
+        BuiltinOp::new(36, 1, BuiltinOperator::GATHER, BuiltinOptions::GatherOptions),
+        options.as_union_value(),
+    )
+}
+
 fn ser_slice(
     builder: &mut SubgraphBuilder,
     model: &TypedModel,
@@ -4,25 +4,32 @@ use crate::ser::{BuiltinOp, SubgraphBuilder};
 use crate::tflite::{
     ActivationFunctionType, BuiltinOperator, BuiltinOptions, Conv2DOptions, Conv2DOptionsArgs,
     DepthwiseConv2DOptions, DepthwiseConv2DOptionsArgs, PadOptions, PadOptionsArgs, Padding,
+    Pool2DOptions, Pool2DOptionsArgs, TransposeConvOptions, TransposeConvOptionsArgs,
 };
 use tract_core::internal::*;
 use tract_core::ops as core;
 use tract_core::ops::array::{Pad, PadMode};
 use tract_core::ops::cast::cast;
+use tract_core::ops::cnn::deconv::adjustments;
 use tract_core::ops::cnn::KernelFormat;
-use tract_core::ops::cnn::{Conv, PaddingSpec};
+use tract_core::ops::cnn::{Conv, Deconv, MaxPool, PaddingSpec, PoolSpec, SumPool};
 use tract_core::ops::nn::DataFormat;
-use tract_core::prelude::tract_itertools::Itertools;
+use tract_core::prelude::tract_itertools::{izip, Itertools};
 
 pub fn register_all(reg: &mut Registry) {
-    reg.reg_to_tract(BuiltinOperator::AVERAGE_POOL_2D, average_pool_2d);
+    reg.reg_to_tract(BuiltinOperator::AVERAGE_POOL_2D, |op| de_pool_2d(op, false));
+    reg.reg_to_tflite(ser_sum_pool);
     reg.reg_to_tract(BuiltinOperator::CONV_2D, de_conv2d);
     reg.reg_to_tflite(ser_conv);
     reg.reg_to_tract(BuiltinOperator::DEPTHWISE_CONV_2D, de_dw_conv2d);
+    reg.reg_to_tract(BuiltinOperator::MAX_POOL_2D, |op| de_pool_2d(op, true));
+    reg.reg_to_tflite(ser_max_pool);
     reg.reg_to_tflite(ser_pad);
+    reg.reg_to_tract(BuiltinOperator::TRANSPOSE_CONV, de_transpose_conv);
+    reg.reg_to_tflite(ser_deconv);
 }
 
-fn average_pool_2d(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
+fn de_pool_2d(op: &mut DeserOp, max: bool) -> TractResult<TVec<OutletId>> {
     let options = builtin!(op, builtin_options_as_pool_2_doptions);
     let strides = tvec!(options.stride_h() as usize, options.stride_w() as usize);
     let kernel_shape = tvec!(options.filter_height() as usize, options.filter_width() as usize);
@@ -36,7 +43,7 @@ fn average_pool_2d(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
         .c()
         .to_usize()
         .context("Except defined integer depth")?;
-    let pool_spec = core::cnn::PoolSpec {
+    let pool_spec = PoolSpec {
         data_format: DataFormat::NHWC,
         kernel_shape,
         padding,
@@ -45,11 +52,71 @@ fn average_pool_2d(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
         input_channels: ci,
         output_channels: ci,
     };
-    let pool = core::cnn::SumPool { pool_spec, normalize: true, count_include_pad: false };
-    let wires = op.ctx.target.wire_node(op.prefix, pool, &op.inputs[0..1])?;
+    let wires = if max {
+        let pool = MaxPool { pool_spec, with_index_outputs: None };
+        op.ctx.target.wire_node(op.prefix, pool, &op.inputs[0..1])?
+    } else {
+        let pool = SumPool { pool_spec, normalize: true, count_include_pad: false };
+        op.ctx.target.wire_node(op.prefix, pool, &op.inputs[0..1])?
+    };
     wire_fused_activation(op, &wires, &options.fused_activation_function())
 }
 
+fn ser_pool(
+    builder: &mut SubgraphBuilder,
+    model: &TypedModel,
+    node: &TypedNode,
+    pool_spec: &PoolSpec,
+    op: BuiltinOp,
+) -> TractResult<()> {
+    ensure!(pool_spec.data_format == DataFormat::NHWC);
+    ensure!(model.node_input_facts(node.id)?[0].rank() == 4);
+    ensure!(pool_spec.dilations().iter().all(|d| *d == 1), "tflite pools can not be dilated");
+    let padding = match pool_spec.padding {
+        PaddingSpec::Valid => Padding::VALID,
+        PaddingSpec::SameUpper => Padding::SAME,
+        _ => bail!("Unsupported padding for tflite pool: {:?}", pool_spec.padding),
+    };
+    let input = builder.map_outlet(model, node.inputs[0])?;
+    let output = builder.outlets_to_tensors[&node.id.into()];
+    let options = Pool2DOptions::create(
+        builder.fb(),
+        &Pool2DOptionsArgs {
+            padding,
+            stride_h: pool_spec.stride(0) as _,
+            stride_w: pool_spec.stride(1) as _,
+            filter_height: pool_spec.kernel_shape[0] as _,
+            filter_width: pool_spec.kernel_shape[1] as _,
+            fused_activation_function: ActivationFunctionType::NONE,
+        },
+    );
+    builder.write_op_with_options(&[input], &[output], op, options.as_union_value())
+}
+
+fn ser_max_pool(
+    builder: &mut SubgraphBuilder,
+    model: &TypedModel,
+    node: &TypedNode,
+    pool: &MaxPool,
+) -> TractResult<()> {
+    ensure!(pool.with_index_outputs.is_none(), "tflite MAX_POOL_2D has no index output");
+    let op = BuiltinOp::new(17, 1, BuiltinOperator::MAX_POOL_2D, BuiltinOptions::Pool2DOptions);
+    ser_pool(builder, model, node, &pool.pool_spec, op)
+}
+
+fn ser_sum_pool(
+    builder: &mut SubgraphBuilder,
+    model: &TypedModel,
+    node: &TypedNode,
+    pool: &SumPool,
+) -> TractResult<()> {
+    ensure!(pool.normalize, "tflite only supports average pooling");
+    // tflite never counts padding in the average
+    ensure!(!pool.count_include_pad || pool.pool_spec.padding == PaddingSpec::Valid);
+    let op = BuiltinOp::new(1, 1, BuiltinOperator::AVERAGE_POOL_2D, BuiltinOptions::Pool2DOptions);
+    ser_pool(builder, model, node, &pool.pool_spec, op)
+}
+
 fn ser_conv(
     builder: &mut SubgraphBuilder,
     model: &TypedModel,
@@ -64,48 +131,11 @@ fn ser_conv(
         conv.pool_spec.padding == PaddingSpec::Valid
             || conv.pool_spec.padding == PaddingSpec::SameUpper
     );
-    let node_name = &node.name;
     let mut inputs = tvec!(builder.map_outlet(model, node.inputs[0])?);
     if conv.q_params.is_some() {
-        let facts = model.node_input_facts(node.id)?;
-        let iscale = facts[0].datum_type.zp_scale().1;
-        // 0 1 2 3  4  5  6  7  8
-        // x w b x0 xs k0 ks y0 ys
-        let k0_tract = facts[5].konst.as_ref().unwrap().cast_to_scalar::<i32>()? as i64;
-        let kscale = facts[6].konst.as_ref().unwrap().as_slice::<f32>()?;
-        let per_channel = !kscale.iter().all_equal();
-        if per_channel {
-            let kernel = model.outlet_fact(node.inputs[1])?.konst.as_ref().context(
-                "tract TODO: dynamic convolution and per-channel scales",
-            )?;
-            let bias = model.outlet_fact(node.inputs[2])?.konst.as_ref().context(
-                "tract TODO: dynamic convolution and per-channel scales",
-            )?;
-            inputs.push(builder.write_fact_with_per_axis_q(
-                &format!("{node_name}.weights"),
-                kernel,
-                &vec![k0_tract; conv.output_channels()],
-                kscale,
-                0,
-            )?);
-            let bscale = kscale.iter().map(|k| k * iscale).collect_vec();
-            let bias = bias.clone().into_tensor().cast_to::<i32>()?.into_owned().into_arc_tensor();
-            inputs.push(builder.write_fact_with_per_axis_q(
-                &format!("{node_name}.bias"),
-                &bias,
-                &vec![0i64; bias.len()],
-                &bscale,
-                0,
-            )?);
-        } else {
-            inputs.push(builder.map_outlet(model, node.inputs[1])?);
-            let bias = facts[2].konst.as_ref().context("FIXME: Dumper require constant bias")?;
-            let bias_qdt = bias
-                .datum_type()
-                .quantize(QParams::ZpScale { zero_point: 0, scale: iscale * kscale[0] });
-            let bias = bias.cast_to_dt(bias_qdt)?.into_owned();
-            inputs.push(builder.write_fact(&format!("{node_name}.bias"), bias)?);
-        }
+        // depthwise kernels are [1, H, W, C], their channels are on the last axis
+        let axis = if conv.group == 1 { 0 } else { 3 };
+        inputs.extend(super::ser_linearops_quantization_suport(builder, model, node, axis)?);
     } else {
         inputs.push(builder.map_outlet(model, node.inputs[1])?);
         ensure!(model.outlet_fact(node.inputs[2])?.rank() == 1);
@@ -198,7 +228,7 @@ fn de_conv2d(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
 }
 
 fn de_dw_conv2d(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
-    let (input, kernel, _bias) = args_3!(op.facts()?);
+    let (input, kernel, bias) = args_3!(op.facts()?);
     let kernel_full_shape: TVec<usize> = kernel.shape.as_concrete().unwrap().into();
     let kernel_shape: TVec<usize> = KernelFormat::OHWI.spatial_shape(&kernel_full_shape).into();
     let options = builtin!(op, builtin_options_as_depthwise_conv_2_doptions);
@@ -222,6 +252,10 @@ fn de_dw_conv2d(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
     };
     let mut inputs = tvec!(op.inputs[0], op.inputs[1], op.inputs[2]);
     let q_params = super::linearops_quantization_suport(op, &input, &mut inputs)?;
+    let bias_dt = bias.datum_type.unquantized();
+    inputs[2] =
+        op.ctx.target.wire_node(format!("{}.cast_bias", op.prefix), cast(bias_dt), &[inputs[2]])?
+            [0];
     let conv = core::cnn::Conv {
         pool_spec,
         kernel_fmt: KernelFormat::OHWI,
@@ -232,6 +266,145 @@ fn de_dw_conv2d(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
     wire_fused_activation(op, &wires, &options.fused_activation_function())
 }
 
+fn de_transpose_conv(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
+    let options = builtin!(op, builtin_options_as_transpose_conv_options);
+    let facts = op.facts()?;
+    let (output_shape, kernel, input) = (&facts[0], &facts[1], &facts[2]);
+    ensure!(input.datum_type.is_float(), "Quantized TRANSPOSE_CONV is not supported");
+    let output_shape = output_shape
+        .konst
+        .as_ref()
+        .context("Dynamic output shape for TRANSPOSE_CONV is not supported")?
+        .cast_to::<i64>()?
+        .as_slice::<i64>()?
+        .iter()
+        .map(|d| *d as usize)
+        .collect_vec();
+    let input_shape = input.shape.as_concrete().context("Expect concrete input shape")?;
+    let kernel_full_shape = kernel.shape.as_concrete().context("Expect concrete kernel shape")?;
+    let kernel_shape: TVec<usize> = KernelFormat::OHWI.spatial_shape(kernel_full_shape).into();
+    let strides = tvec!(options.stride_h() as usize, options.stride_w() as usize);
+    let (input_geo, output_geo) = (&input_shape[1..3], &output_shape[1..3]);
+    let padding = match options.padding() {
+        Padding::VALID => PaddingSpec::Valid,
+        Padding::SAME => {
+            let (before, after) =
+                transpose_conv_same_padding(input_geo, output_geo, &kernel_shape, &strides)
+                    .into_iter()
+                    .unzip();
+            PaddingSpec::Explicit(before, after)
+        }
+        padding => bail!("Unsupported padding {padding:?}"),
+    };
+    let pool_spec = PoolSpec {
+        data_format: DataFormat::NHWC,
+        kernel_shape,
+        padding,
+        strides: Some(strides),
+        dilations: None,
+        input_channels: *KernelFormat::OHWI.i(kernel_full_shape),
+        output_channels: *KernelFormat::OHWI.o(kernel_full_shape),
+    };
+    let adjustments = adjustments(&pool_spec, input_geo, output_geo)?;
+    let bias = if let Some(bias) = op.inputs.get(3) {
+        *bias
+    } else {
+        let zero = Tensor::zero_scalar_dt(input.datum_type)?;
+        op.ctx.target.add_const(format!("{}.bias", op.prefix), zero)?
+    };
+    let deconv = Deconv::new(pool_spec, KernelFormat::OHWI, adjustments, 1);
+    let wires = op.ctx.target.wire_node(op.prefix, deconv, &[op.inputs[2], op.inputs[1], bias])?;
+    wire_fused_activation(op, &wires, &options.fused_activation_function())
+}
+
+// SAME padding of a transposed convolution is the one of the convolution it transposes (going
+// from the transposed convolution output back to its input)
+fn transpose_conv_same_padding(
+    input_geo: &[usize],
+    output_geo: &[usize],
+    kernel_shape: &[usize],
+    strides: &[usize],
+) -> Vec<(usize, usize)> {
+    izip!(input_geo, output_geo, kernel_shape, strides)
+        .map(|(x, y, k, s)| ((x - 1) * s + k).saturating_sub(*y))
+        .map(|total| (total / 2, total - total / 2))
+        .collect()
+}
+
+fn ser_deconv(
+    builder: &mut SubgraphBuilder,
+    model: &TypedModel,
+    node: &TypedNode,
+    deconv: &Deconv,
+) -> TractResult<()> {
+    let pool_spec = &deconv.pool_spec;
+    ensure!(pool_spec.data_format == DataFormat::NHWC);
+    ensure!(deconv.kernel_format == KernelFormat::OHWI);
+    ensure!(deconv.group == 1, "tflite TRANSPOSE_CONV does not support groups");
+    ensure!(
+        pool_spec.dilations().iter().all(|d| *d == 1),
+        "tflite TRANSPOSE_CONV can not be dilated"
+    );
+    let input_fact = model.outlet_fact(node.inputs[0])?;
+    ensure!(input_fact.rank() == 4);
+    ensure!(input_fact.datum_type.is_float());
+    let input_shape = input_fact.shape.as_concrete().context("Expect concrete input shape")?;
+    let output_shape = node.outputs[0]
+        .fact
+        .shape
+        .as_concrete()
+        .context("Can not serialize symbolic dims to tflite")?
+        .to_vec();
+    let actual = pool_spec.padding.compute_for_deconv(
+        &input_shape[1..3],
+        &pool_spec.kernel_shape,
+        &pool_spec.dilations(),
+        &pool_spec.strides(),
+        &deconv.adjustments,
+    )?;
+    let same = transpose_conv_same_padding(
+        &input_shape[1..3],
+        &output_shape[1..3],
+        &pool_spec.kernel_shape,
+        &pool_spec.strides(),
+    );
+    let padding = if actual.iter().all(|d| d.pad_before == 0 && d.pad_after == 0) {
+        Padding::VALID
+    } else if actual.iter().zip(same.iter()).all(|(d, s)| (d.pad_before, d.pad_after) == *s) {
+        Padding::SAME
+    } else {
+        bail!("Padding of deconvolution can not be expressed in tflite")
+    };
+    let output_shape = output_shape.iter().map(|d| *d as i32).collect_vec();
+    let inputs = [
+        builder.write_fact(format!("{}.output_shape", node.name), tensor1(&output_shape))?,
+        builder.map_outlet(model, node.inputs[1])?,
+        builder.map_outlet(model, node.inputs[0])?,
+        builder.map_outlet(model, node.inputs[2])?,
+    ];
+    let output = builder.outlets_to_tensors[&node.id.into()];
+    let options = TransposeConvOptions::create(
+        builder.fb(),
+        &TransposeConvOptionsArgs {
+            padding,
+            stride_h: pool_spec.stride(0) as _,
+            stride_w: pool_spec.stride(1) as _,
+            fused_activation_function: ActivationFunctionType::NONE,
+        },
+    );
+    builder.write_op_with_options(
+        &inputs,
+        &[output],
+        BuiltinOp::new(
+            67,
+            3,
+            BuiltinOperator::TRANSPOSE_CONV,
+            BuiltinOptions::TransposeConvOptions,
+        ),
+        options.as_union_value(),
+    )
+}
+
 fn ser_pad(
     builder: &mut SubgraphBuilder,
     _model: &TypedModel,
@@ -0,0 +1,472 @@
+use tract_core::internal::*;
+use tract_core::ops::binary::wire_with_rank_broadcast;
+use tract_core::ops::einsum::EinSum;
+use tract_core::ops::math::{add, max, min, mul, sub, tanh};
+use tract_core::ops::nn::sigmoid;
+use tract_core::ops::OpStateFreeze;
+use tract_core::ops::scan::{InputMapping, OutputMapping, Scan, ScanInfo};
+
+use crate::registry::{DeserOp, Registry};
+use crate::ser::{BuiltinOp, SubgraphBuilder};
+use crate::tflite::{
+    ActivationFunctionType, BuiltinOperator, BuiltinOptions, UnidirectionalSequenceLSTMOptions,
+    UnidirectionalSequenceLSTMOptionsArgs,
+};
+
+pub fn register_all(reg: &mut Registry) {
+    reg.reg_to_tflite(ser_lstm);
+    reg.reg_to_tract(BuiltinOperator::UNIDIRECTIONAL_SEQUENCE_LSTM, de_lstm);
+}
+
+// tflite input slots, before the two state variables
+const INPUT: usize = 0;
+const INPUT_TO_INPUT: usize = 1;
+const INPUT_TO_FORGET: usize = 2;
+const INPUT_TO_CELL: usize = 3;
+const INPUT_TO_OUTPUT: usize = 4;
+const RECURRENT_TO_INPUT: usize = 5;
+const RECURRENT_TO_FORGET: usize = 6;
+const RECURRENT_TO_CELL: usize = 7;
+const RECURRENT_TO_OUTPUT: usize = 8;
+const CELL_TO_INPUT: usize = 9;
+const CELL_TO_FORGET: usize = 10;
+const CELL_TO_OUTPUT: usize = 11;
+const INPUT_GATE_BIAS: usize = 12;
+const FORGET_GATE_BIAS: usize = 13;
+const CELL_BIAS: usize = 14;
+const OUTPUT_GATE_BIAS: usize = 15;
+const PROJECTION: usize = 16;
+const PROJECTION_BIAS: usize = 17;
+const OUTPUT_STATE: usize = 18;
+const CELL_STATE: usize = 19;
+const PARAMS: usize = 18;
+// then come the four layer normalization coefficients
+const ALL_INPUTS: usize = 24;
+
+const MANDATORY: [usize; 10] = [
+    INPUT,
+    INPUT_TO_FORGET,
+    INPUT_TO_CELL,
+    INPUT_TO_OUTPUT,
+    RECURRENT_TO_FORGET,
+    RECURRENT_TO_CELL,
+    RECURRENT_TO_OUTPUT,
+    FORGET_GATE_BIAS,
+    CELL_BIAS,
+    OUTPUT_GATE_BIAS,
+];
+
+/// Input weights, recurrent weights, peephole and bias slots of a gate.
+struct Gate {
+    name: &'static str,
+    input: usize,
+    recurrent: usize,
+    peephole: Option<usize>,
+    bias: usize,
+}
+
+const INPUT_GATE: Gate = Gate {
+    name: "i",
+    input: INPUT_TO_INPUT,
+    recurrent: RECURRENT_TO_INPUT,
+    peephole: Some(CELL_TO_INPUT),
+    bias: INPUT_GATE_BIAS,
+};
+const FORGET_GATE: Gate = Gate {
+    name: "f",
+    input: INPUT_TO_FORGET,
+    recurrent: RECURRENT_TO_FORGET,
+    peephole: Some(CELL_TO_FORGET),
+    bias: FORGET_GATE_BIAS,
+};
+const CELL_GATE: Gate = Gate {
+    name: "g",
+    input: INPUT_TO_CELL,
+    recurrent: RECURRENT_TO_CELL,
+    peephole: None,
+    bias: CELL_BIAS,
+};
+const OUTPUT_GATE: Gate = Gate {
+    name: "o",
+    input: INPUT_TO_OUTPUT,
+    recurrent: RECURRENT_TO_OUTPUT,
+    peephole: Some(CELL_TO_OUTPUT),
+    bias: OUTPUT_GATE_BIAS,
+};
+
+/// TFLite UNIDIRECTIONAL_SEQUENCE_LSTM, with zero initial states.
+///
+/// `inputs` maps the tflite input slots (input, weights, peepholes, biases and projection) to
+/// the node inputs, absent optional slots being None. The op is kept as is through
+/// decluttering so it can be dumped back, and lowered to a Scan for codegen. Evaluation uses the
+/// same lowering, built once per state and input shapes.
+#[derive(Clone, Debug)]
+pub struct UnidirectionalSequenceLstm {
+    pub activation: ActivationFunctionType,
+    pub cell_clip: f32,
+    pub proj_clip: f32,
+    pub time_major: bool,
+    pub inputs: [Option<usize>; PARAMS],
+}
+
+impl UnidirectionalSequenceLstm {
+    fn input(&self, slot: usize) -> TractResult<usize> {
+        self.inputs[slot].with_context(|| format!("Missing mandatory LSTM input {slot}"))
+    }
+
+    /// Batch, output and cell sizes.
+    fn sizes(&self, model: &TypedModel, inputs: &[OutletId]) -> TractResult<(TDim, TDim, TDim)> {
+        let x = model.outlet_fact(inputs[self.input(INPUT)?])?;
+        let batch = x.shape[self.time_major as usize].clone();
+        // recurrent to output weights: [n_cell, n_output]
+        let r = model.outlet_fact(inputs[self.input(RECURRENT_TO_OUTPUT)?])?;
+        Ok((batch, r.shape[1].clone(), r.shape[0].clone()))
+    }
+
+    fn wire_activation(
+        &self,
+        body: &mut TypedModel,
+        name: &str,
+        wire: OutletId,
+    ) -> TractResult<OutletId> {
+        let zero = body.add_const(format!("{name}.zero"), tensor0(0f32))?;
+        let six = body.add_const(format!("{name}.six"), tensor0(6f32))?;
+        Ok(match self.activation {
+            ActivationFunctionType::NONE => wire,
+            ActivationFunctionType::TANH => body.wire_node(name, tanh(), &[wire])?[0],
+            ActivationFunctionType::RELU => {
+                wire_with_rank_broadcast(name, body, max(), &[wire, zero])?[0]
+            }
+            ActivationFunctionType::RELU6 => {
+                let relu =
+                    wire_with_rank_broadcast(format!("{name}.relu"), body, max(), &[wire, zero])?;
+                wire_with_rank_broadcast(name, body, min(), &[relu[0], six])?[0]
+            }
+            af => bail!("Unsupported LSTM activation {af:?}"),
+        })
+    }
+
+    fn wire_clip(
+        body: &mut TypedModel,
+        name: &str,
+        wire: OutletId,
+        clip: f32,
+    ) -> TractResult<OutletId> {
+        if clip <= 0.0 {
+            return Ok(wire);
+        }
+        let low = body.add_const(format!("{name}.low"), tensor0(-clip))?;
+        let high = body.add_const(format!("{name}.high"), tensor0(clip))?;
+        let wire = wire_with_rank_broadcast(format!("{name}.max"), body, max(), &[wire, low])?;
+        Ok(wire_with_rank_broadcast(format!("{name}.min"), body, min(), &[wire[0], high])?[0])
+    }
+
+    /// Wire the recurrence as a Scan on the time axis.
+    fn wire_scan(
+        &self,
+        prefix: &str,
+        target: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let x_input = inputs[self.input(INPUT)?];
+        let x_fact = target.outlet_fact(x_input)?.clone();
+        let dt = x_fact.datum_type;
+        let axis = !self.time_major as usize;
+        let (batch, n_output, n_cell) = self.sizes(target, inputs)?;
+
+        let mut body = TypedModel::default();
+        let mut outer_inputs = tvec!(x_input);
+        let mut input_mapping = vec![InputMapping::Scan(ScanInfo { axis, chunk: 1 })];
+        let mut x_source_fact = x_fact.without_value();
+        x_source_fact.shape.set(axis, 1.to_dim());
+        let x = body.add_source("x", x_source_fact)?;
+        let x = body.wire_node("x.rm", AxisOp::Rm(axis), &[x])?[0];
+
+        let mut params = [None; PARAMS];
+        for (slot, ix) in self.inputs.iter().enumerate().skip(INPUT_TO_INPUT) {
+            if let Some(ix) = *ix {
+                outer_inputs.push(inputs[ix]);
+                input_mapping.push(InputMapping::Full);
+                let fact = target.outlet_fact(inputs[ix])?.clone();
+                params[slot] = Some(body.add_source(format!("param_{slot}"), fact)?);
+            }
+        }
+
+        let mut states = tvec!();
+        for (name, size) in [("h", &n_output), ("c", &n_cell)] {
+            let mut shape = tvec!(batch.to_usize()?, size.to_usize()?);
+            shape.insert(axis, 1);
+            let zero = Tensor::zero_dt(dt, &shape)?;
+            outer_inputs.push(target.add_const(format!("{prefix}.{name}0"), zero)?);
+            input_mapping.push(InputMapping::State);
+            let source = body.add_source(format!("{name}_source"), dt.fact(&shape))?;
+            states.push(body.wire_node(format!("{name}.rm"), AxisOp::Rm(axis), &[source])?[0]);
+        }
+        let (h, c) = (states[0], states[1]);
+
+        // gate pre-activation
+        let gate = |body: &mut TypedModel, gate: &Gate, cell: OutletId| -> TractResult<OutletId> {
+            let name = gate.name;
+            let param = |slot: usize| {
+                params[slot].with_context(|| format!("Missing LSTM input {slot} for gate {name}"))
+            };
+            let ein = EinSum::new("bi,ci->bc".parse()?, dt);
+            let xw = body.wire_node(format!("{name}.xw"), ein.clone(), &[x, param(gate.input)?])?;
+            let hr = body.wire_node(format!("{name}.hr"), ein, &[h, param(gate.recurrent)?])?;
+            let mut wire = body.wire_node(format!("{name}.sum"), add(), &[xw[0], hr[0]])?[0];
+            if let Some(p) = gate.peephole.and_then(|slot| params[slot]) {
+                let peep =
+                    wire_with_rank_broadcast(format!("{name}.peep"), body, mul(), &[p, cell])?;
+                wire = body.wire_node(format!("{name}.add_peep"), add(), &[wire, peep[0]])?[0];
+            }
+            let b = param(gate.bias)?;
+            Ok(wire_with_rank_broadcast(format!("{name}.bias"), body, add(), &[wire, b])?[0])
+        };
+
+        let f = gate(&mut body, &FORGET_GATE, c)?;
+        let f = body.wire_node("f", sigmoid(), &[f])?[0];
+        let i = if params[INPUT_TO_INPUT].is_some() {
+            let i = gate(&mut body, &INPUT_GATE, c)?;
+            body.wire_node("i", sigmoid(), &[i])?[0]
+        } else {
+            // coupled input and forget gates
+            let one = body.add_const("one", tensor0(1f32))?;
+            wire_with_rank_broadcast("i", &mut body, sub(), &[one, f])?[0]
+        };
+        let g = gate(&mut body, &CELL_GATE, c)?;
+        let g = self.wire_activation(&mut body, "g", g)?;
+        let fc = body.wire_node("fc", mul(), &[f, c])?[0];
+        let ig = body.wire_node("ig", mul(), &[i, g])?[0];
+        let new_c = body.wire_node("new_c", add(), &[fc, ig])?[0];
+        let new_c = Self::wire_clip(&mut body, "new_c.clip", new_c, self.cell_clip)?;
+        let o = gate(&mut body, &OUTPUT_GATE, new_c)?;
+        let o = body.wire_node("o", sigmoid(), &[o])?[0];
+        let act_c = self.wire_activation(&mut body, "act_c", new_c)?;
+        let mut new_h = body.wire_node("m", mul(), &[o, act_c])?[0];
+        if let Some(proj) = params[PROJECTION] {
+            let ein = EinSum::new("bc,oc->bo".parse()?, dt);
+            new_h = body.wire_node("proj", ein, &[new_h, proj])?[0];
+            if let Some(b) = params[PROJECTION_BIAS] {
+                new_h = wire_with_rank_broadcast("proj.bias", &mut body, add(), &[new_h, b])?[0];
+            }
+            new_h = Self::wire_clip(&mut body, "proj.clip", new_h, self.proj_clip)?;
+        }
+        let new_h = body.wire_node("h_out", AxisOp::Add(axis), &[new_h])?[0];
+        let new_c = body.wire_node("c_out", AxisOp::Add(axis), &[new_c])?[0];
+        body.set_output_outlets(&[new_h, new_c])?;
+
+        let output_mapping = vec![
+            OutputMapping {
+                state: true,
+                full_dim_hint: None,
+                last_value_slot: None,
+                scan: Some((0, ScanInfo { axis, chunk: 1 })),
+            },
+            OutputMapping { state: true, full_dim_hint: None, last_value_slot: None, scan: None },
+        ];
+        let scan = Scan::new(body, input_mapping, output_mapping, 0)?;
+        target.wire_node(prefix, scan, &outer_inputs)
+    }
+}
+
+impl Op for UnidirectionalSequenceLstm {
+    fn name(&self) -> Cow<str> {
+        "UnidirectionalSequenceLstm".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!(
+            "activation: {:?}, cell_clip: {}, proj_clip: {}, time_major: {}",
+            self.activation, self.cell_clip, self.proj_clip, self.time_major
+        )])
+    }
+
+    fn validation(&self) -> Validation {
+        Validation::Rounding
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for UnidirectionalSequenceLstm {
+    fn is_stateless(&self) -> bool {
+        false
+    }
+
+    fn state(
+        &self,
+        _session: &mut SessionState,
+        _node_id: usize,
+    ) -> TractResult<Option<Box<dyn OpState>>> {
+        Ok(Some(Box::<LstmState>::default()))
+    }
+}
+
+/// Lowered LSTM plan, and the input facts it has been built for.
+#[derive(Clone, Debug, Default)]
+struct LstmState {
+    lowered: Option<(TVec<TypedFact>, Arc<TypedSimplePlan<TypedModel>>)>,
+}
+
+impl LstmState {
+    fn lowered(
+        &mut self,
+        op: &UnidirectionalSequenceLstm,
+        inputs: &[TValue],
+    ) -> TractResult<&TypedSimplePlan<TypedModel>> {
+        let facts: TVec<TypedFact> =
+            inputs.iter().map(|input| TypedFact::shape_and_dt_of(input)).collect();
+        if self.lowered.as_ref().map_or(true, |(built_for, _)| built_for != &facts) {
+            let mut model = TypedModel::default();
+            let sources = facts
+                .iter()
+                .enumerate()
+                .map(|(ix, fact)| model.add_source(format!("s{ix}"), fact.clone()))
+                .collect::<TractResult<TVec<OutletId>>>()?;
+            let output = op.wire_scan("lstm", &mut model, &sources)?;
+            model.set_output_outlets(&output[0..1])?;
+            self.lowered = Some((facts, Arc::new(model.into_runnable()?)));
+        }
+        Ok(&self.lowered.as_ref().unwrap().1)
+    }
+}
+
+impl OpState for LstmState {
+    fn eval(
+        &mut self,
+        _session: &mut SessionState,
+        op: &dyn Op,
+        inputs: TVec<TValue>,
+    ) -> TractResult<TVec<TValue>> {
+        let op = op.downcast_ref::<UnidirectionalSequenceLstm>().context("Wrong op")?;
+        self.lowered(op, &inputs)?.run(inputs).context("In lowered LSTM eval")
+    }
+}
+
+impl OpStateFreeze for LstmState {
+    fn freeze(&self) -> Box<dyn FrozenOpState> {
+        Box::new(self.clone())
+    }
+}
+
+impl FrozenOpState for LstmState {
+    fn unfreeze(&self) -> Box<dyn OpState> {
+        Box::new(self.clone())
+    }
+
+    // initial states are zero, so only the lowered plan is kept, and it is rebuilt on demand
+    fn save(&self) -> TractResult<SavedOpState> {
+        Ok(SavedOpState::default())
+    }
+
+    fn load(&self, _saved: &SavedOpState) -> TractResult<Box<dyn FrozenOpState>> {
+        Ok(Box::<LstmState>::default())
+    }
+}
+
+impl TypedOp for UnidirectionalSequenceLstm {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        let x = inputs[self.input(INPUT)?];
+        ensure!(x.rank() == 3 && x.datum_type == f32::datum_type());
+        let r = inputs[self.input(RECURRENT_TO_OUTPUT)?];
+        let mut shape = x.shape.to_tvec();
+        shape[2] = r.shape[1].clone();
+        Ok(tvec!(x.datum_type.fact(shape)))
+    }
+
+    fn codegen(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        let mut patch = TypedModelPatch::default();
+        let inputs = patch.taps(model, &node.inputs)?;
+        let output = self.wire_scan(&node.name, &mut patch, &inputs)?;
+        patch.shunt_outside(model, node.id.into(), output[0])?;
+        Ok(Some(patch))
+    }
+
+    as_op!();
+}
+
+fn de_lstm(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
+    let options = builtin!(op, builtin_options_as_unidirectional_sequence_lstmoptions);
+    ensure!(op.output_facts[0].datum_type == f32::datum_type(), "Only float LSTM are supported");
+    ensure!(!options.diagonal_recurrent_tensors(), "Diagonal recurrent LSTM are not supported");
+    // op.inputs skips absent optional inputs, the flat op keeps them as -1
+    let flat_inputs = op.flat.inputs().context("Missing LSTM inputs")?;
+    let mut inputs = [None; PARAMS];
+    let mut wires = tvec!();
+    let mut present = op.inputs.iter();
+    for (slot, id) in flat_inputs.iter().enumerate() {
+        if id < 0 {
+            continue;
+        }
+        let wire = *present.next().context("Inconsistent LSTM inputs")?;
+        if slot < PARAMS {
+            inputs[slot] = Some(wires.len());
+            wires.push(wire);
+        } else if slot == OUTPUT_STATE || slot == CELL_STATE {
+            let state = op.ctx.target.outlet_fact(wire)?.konst.clone();
+            ensure!(
+                state.and_then(|s| s.as_uniform()).is_some_and(|s| s.is_zero().unwrap_or(false)),
+                "LSTM initial states must be zero"
+            );
+        } else {
+            bail!("Layer normalized LSTM are not supported")
+        }
+    }
+    for slot in MANDATORY {
+        ensure!(inputs[slot].is_some(), "Missing mandatory LSTM input {slot}");
+    }
+    let lstm = UnidirectionalSequenceLstm {
+        activation: options.fused_activation_function(),
+        cell_clip: options.cell_clip(),
+        proj_clip: options.proj_clip(),
+        time_major: options.time_major(),
+        inputs,
+    };
+    op.ctx.target.wire_node(op.prefix, lstm, &wires)
+}
+
+fn ser_lstm(
+    builder: &mut SubgraphBuilder,
+    model: &TypedModel,
+    node: &TypedNode,
+    op: &UnidirectionalSequenceLstm,
+) -> TractResult<()> {
+    let mut inputs = vec![-1; ALL_INPUTS];
+    for (slot, ix) in op.inputs.iter().enumerate() {
+        if let Some(ix) = ix {
+            inputs[slot] = builder.map_outlet(model, node.inputs[*ix])?;
+        }
+    }
+    let (batch, n_output, n_cell) = op.sizes(model, &node.inputs)?;
+    inputs[OUTPUT_STATE] = builder
+        .write_variable(format!("{}.output_state", node.name), f32::fact([&batch, &n_output]))?;
+    inputs[CELL_STATE] = builder
+        .write_variable(format!("{}.cell_state", node.name), f32::fact([&batch, &n_cell]))?;
+    let output = builder.map_outlet(model, node.id.into())?;
+    let options = UnidirectionalSequenceLSTMOptions::create(
+        builder.fb(),
+        &UnidirectionalSequenceLSTMOptionsArgs {
+            fused_activation_function: op.activation,
+            cell_clip: op.cell_clip,
+            proj_clip: op.proj_clip,
+            time_major: op.time_major,
+            asymmetric_quantize_inputs: false,
+            diagonal_recurrent_tensors: false,
+        },
+    );
+    builder.write_op_with_options(
+        &inputs,
+        &[output],
+        BuiltinOp::new(
+            44,
+            1,
+            BuiltinOperator::UNIDIRECTIONAL_SEQUENCE_LSTM,
+            BuiltinOptions::UnidirectionalSequenceLSTMOptions,
+        ),
+        options.as_union_value(),
+    )
+}
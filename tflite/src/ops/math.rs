@@ -31,16 +31,28 @@ pub fn register_all(reg: &mut Registry) {
 }
 
 fn wire_cast_and_rank_broadcast(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
+    let facts = op.facts()?;
+    // quantized operands keep their own quantization parameters, the output ones are
+    // carried by the operator
+    if facts.iter().all(|f| f.datum_type.is_quantized()) {
+        return wire_rank_broadcast(op.prefix, op.ctx.target, op.inputs);
+    }
     let wire = wire_cast(
         format!("{}.cast", op.prefix),
         op.ctx.target,
         op.inputs,
-        DatumType::super_type_for(op.facts()?.iter().map(|f| f.datum_type))
-            .context("No super type")?,
+        DatumType::super_type_for(facts.iter().map(|f| f.datum_type)).context("No super type")?,
     )?;
     wire_rank_broadcast(op.prefix, op.ctx.target, &wire)
 }
 
+fn wire_arith(op: &mut DeserOp, mini: TypedBinOp) -> TractResult<TVec<OutletId>> {
+    let wires = wire_cast_and_rank_broadcast(op)?;
+    let output_dt = op.output_facts[0].datum_type;
+    let output_dt = Some(output_dt).filter(|dt| dt.is_quantized());
+    op.ctx.target.wire_node(op.prefix, TypedBinOp(mini.0, output_dt), &wires)
+}
+
 fn deser_bin(op: &mut DeserOp, mini: TypedBinOp) -> TractResult<TVec<OutletId>> {
     let wires = wire_cast_and_rank_broadcast(op)?;
     op.ctx.target.wire_node(op.prefix, mini, &wires)
@@ -48,29 +60,25 @@ fn deser_bin(op: &mut DeserOp, mini: TypedBinOp) -> TractResult<TVec<OutletId>>
 
 fn deser_add(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
     let options = builtin!(op, builtin_options_as_add_options);
-    let wires = wire_cast_and_rank_broadcast(op)?;
-    let wires = op.ctx.target.wire_node(op.prefix, tract_core::ops::math::add(), &wires)?;
+    let wires = wire_arith(op, tract_core::ops::math::add())?;
     wire_fused_activation(op, &wires, &options.fused_activation_function())
 }
 
 fn deser_sub(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
     let options = builtin!(op, builtin_options_as_sub_options);
-    let wires = wire_cast_and_rank_broadcast(op)?;
-    let wires = op.ctx.target.wire_node(op.prefix, tract_core::ops::math::sub(), &wires)?;
+    let wires = wire_arith(op, tract_core::ops::math::sub())?;
     wire_fused_activation(op, &wires, &options.fused_activation_function())
 }
 
 fn deser_mul(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
     let options = builtin!(op, builtin_options_as_mul_options);
-    let wires = wire_cast_and_rank_broadcast(op)?;
-    let wires = op.ctx.target.wire_node(op.prefix, tract_core::ops::math::mul(), &wires)?;
+    let wires = wire_arith(op, tract_core::ops::math::mul())?;
     wire_fused_activation(op, &wires, &options.fused_activation_function())
 }
 
 fn deser_div(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
     let options = builtin!(op, builtin_options_as_div_options);
-    let wires = wire_cast_and_rank_broadcast(op)?;
-    let wires = op.ctx.target.wire_node(op.prefix, tract_core::ops::math::div(), &wires)?;
+    let wires = wire_arith(op, tract_core::ops::math::div())?;
     wire_fused_activation(op, &wires, &options.fused_activation_function())
 }
 
@@ -24,8 +24,17 @@ macro_rules! builtin {
 mod array;
 mod cnn;
 mod element_wise;
+mod lstm;
 mod math;
 mod nn;
+mod quant;
+mod resize;
+mod split;
+
+pub use lstm::UnidirectionalSequenceLstm;
+pub(crate) use nn::einsum_as_fully_connected;
+pub use resize::Resize;
+pub(crate) use split::{Pack, Split};
 
 pub fn register_all(reg: &mut Registry) {
     array::register_all(reg);
@@ -33,6 +42,10 @@ pub fn register_all(reg: &mut Registry) {
     element_wise::register_all(reg);
     math::register_all(reg);
     nn::register_all(reg);
+    lstm::register_all(reg);
+    quant::register_all(reg);
+    resize::register_all(reg);
+    split::register_all(reg);
     reg.reg_to_tflite(ser_iff);
     reg.reg_to_tract(BuiltinOperator::SELECT, de_iff);
     reg.reg_to_tract(BuiltinOperator::SELECT_V2, de_iff);
@@ -82,18 +95,95 @@ fn linearops_quantization_suport(
         } else {
             tensor1(&k_zp)
         };
-        inputs.push(op.ctx.target.add_const(format!("{p}.i0"), rctensor0(iqp.zp_scale().0))?);
-        inputs.push(op.ctx.target.add_const(format!("{p}.iscale"), rctensor0(iqp.zp_scale().1))?);
+        let (i0, iscale) = iqp.try_zp_scale()?;
+        let (c0, cscale) = oqp.try_zp_scale()?;
+        inputs.push(op.ctx.target.add_const(format!("{p}.i0"), rctensor0(i0))?);
+        inputs.push(op.ctx.target.add_const(format!("{p}.iscale"), rctensor0(iscale))?);
         inputs.push(op.ctx.target.add_const(format!("{p}.k0"), k_zp.into_arc_tensor())?);
         inputs.push(op.ctx.target.add_const(format!("{p}.kscale"), k_scale)?);
-        inputs.push(op.ctx.target.add_const(format!("{p}.c0"), rctensor0(oqp.zp_scale().0))?);
-        inputs.push(op.ctx.target.add_const(format!("{p}.cscale"), rctensor0(oqp.zp_scale().1))?);
+        inputs.push(op.ctx.target.add_const(format!("{p}.c0"), rctensor0(c0))?);
+        inputs.push(op.ctx.target.add_const(format!("{p}.cscale"), rctensor0(cscale))?);
         Ok(Some(oqp))
     } else {
         Ok(None)
     }
 }
 
+// weights and bias of a quantized conv or fully connected node, with per-channel scales
+// along `axis` of the weights when they are not uniform
+fn ser_linearops_quantization_suport(
+    builder: &mut SubgraphBuilder,
+    model: &TypedModel,
+    node: &TypedNode,
+    axis: usize,
+) -> TractResult<[i32; 2]> {
+    let node_name = &node.name;
+    let facts = model.node_input_facts(node.id)?;
+    let iscale = facts[0].datum_type.try_zp_scale()?.1;
+    // 0 1 2 3  4  5  6  7  8
+    // x w b x0 xs k0 ks y0 ys
+    let k0 = facts[5].konst.as_ref().context("Expect constant kernel zero point")?;
+    let k0 = k0.cast_to::<i64>()?;
+    let k0 = k0.as_slice::<i64>()?;
+    let kscale = facts[6].konst.as_ref().context("Expect constant kernel scale")?;
+    let kscale = kscale.cast_to::<f32>()?;
+    let kscale = kscale.as_slice::<f32>()?;
+    if !kscale.iter().all_equal() || !k0.iter().all_equal() {
+        let kernel = facts[1]
+            .konst
+            .as_ref()
+            .context("tract TODO: dynamic convolution and per-channel scales")?;
+        let bias = facts[2]
+            .konst
+            .as_ref()
+            .context("tract TODO: dynamic convolution and per-channel scales")?;
+        let channels = kernel.shape()[axis];
+        let k0 = if k0.len() == 1 { vec![k0[0]; channels] } else { k0.to_vec() };
+        let kscale = if kscale.len() == 1 { vec![kscale[0]; channels] } else { kscale.to_vec() };
+        let weights = builder.write_fact_with_per_axis_q(
+            format!("{node_name}.weights"),
+            kernel,
+            &k0,
+            &kscale,
+            axis,
+        )?;
+        let bscale = kscale.iter().map(|k| k * iscale).collect_vec();
+        let bias = bias.cast_to::<i32>()?.into_owned();
+        let bias = if bias.rank() == 0 {
+            tensor1(&vec![bias.cast_to_scalar::<i32>()?; channels])
+        } else {
+            bias
+        };
+        let bias = builder.write_fact_with_per_axis_q(
+            format!("{node_name}.bias"),
+            bias,
+            &vec![0i64; channels],
+            &bscale,
+            0,
+        )?;
+        Ok([weights, bias])
+    } else {
+        let weights = if let Some(kernel) = &facts[1].konst {
+            builder.write_fact_with_per_axis_q(
+                format!("{node_name}.weights"),
+                kernel,
+                &k0[0..1],
+                &kscale[0..1],
+                0,
+            )?
+        } else {
+            builder.map_outlet(model, node.inputs[1])?
+        };
+        let bias = facts[2].konst.as_ref().context("FIXME: Dumper require constant bias")?;
+        let bias_qdt = bias
+            .datum_type()
+            .quantize(QParams::ZpScale { zero_point: 0, scale: iscale * kscale[0] });
+        let bias = bias.cast_to_dt(bias_qdt)?.into_owned();
+        let bias = builder.write_fact(format!("{node_name}.bias"), bias)?;
+        Ok([weights, bias])
+    }
+}
+
 fn ser_iff(
     builder: &mut SubgraphBuilder,
     model: &TypedModel,
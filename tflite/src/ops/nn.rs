@@ -12,6 +12,7 @@ use tract_core::prelude::tract_itertools::Itertools;
 use crate::registry::{DeserOp, Registry};
 use crate::ser::BuiltinOp;
 use crate::ser::SubgraphBuilder;
+use crate::tflite::ActivationFunctionType;
 use crate::tflite::ArgMaxOptions;
 use crate::tflite::ArgMaxOptionsArgs;
 use crate::tflite::BatchMatMulOptions;
@@ -19,6 +20,8 @@ use crate::tflite::BatchMatMulOptionsArgs;
 use crate::tflite::BuiltinOptions;
 use crate::tflite::ExpandDimsOptions;
 use crate::tflite::ExpandDimsOptionsArgs;
+use crate::tflite::FullyConnectedOptions;
+use crate::tflite::FullyConnectedOptionsArgs;
 use crate::tflite::ReducerOptions;
 use crate::tflite::ReducerOptionsArgs;
 use crate::tflite::SoftmaxOptions;
@@ -30,6 +33,7 @@ pub fn register_all(reg: &mut Registry) {
     reg.reg_to_tflite(ser_matmul);
     reg.reg_to_tract(BuiltinOperator::BATCH_MATMUL, de_batch_matmul);
 
+    reg.reg_to_tflite(ser_fully_connected);
     reg.reg_to_tract(BuiltinOperator::FULLY_CONNECTED, de_fully_connected);
     reg.reg_to_tract(BuiltinOperator::MEAN, de_reduce_mean);
     reg.reg_to_tflite(ser_softmax);
@@ -68,23 +72,68 @@ fn de_batch_matmul(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
 }
 
 fn de_fully_connected(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
-    let (input, weights, bias) = args_3!(op.facts()?);
+    let facts = op.facts()?;
+    let (input, weights) = (&facts[0], &facts[1]);
     let options = builtin!(op, builtin_options_as_fully_connected_options);
     ensure!(options.weights_format() == FullyConnectedOptionsWeightsFormat::DEFAULT);
     ensure!(!options.asymmetric_quantize_inputs());
-    ensure!(input.rank() == 2);
     ensure!(weights.rank() == 2);
-    ensure!(bias.rank() == 1);
+    let p = op.prefix;
     let mut inputs: TVec<OutletId> = op.inputs.into();
-    let qp = super::linearops_quantization_suport(op, &input, &mut inputs)?;
-    let operating_dt =
-        if input.datum_type.is_float() { input.datum_type } else { i32::datum_type() };
-    let einsum = EinSum { axes: "BI,OI,O,,,,,,->BO".parse()?, q_params: qp, operating_dt };
+    // without keep_num_dims, the input leading axes are flattened to a single batch axis
+    let rank = if options.keep_num_dims() { input.rank() } else { 2 };
+    if input.rank() > rank {
+        let leading: TVec<TDim> = input.shape[..input.rank() - 1].into();
+        let batch = leading.iter().product();
+        let reshape = AxisOp::Reshape(0, leading, tvec!(batch));
+        inputs[0] = op.ctx.target.wire_node(format!("{p}.flatten"), reshape, &inputs[0..1])?[0];
+    }
+    let batch_axes: String = ('a'..).take(rank - 1).collect();
+    if input.datum_type.is_float() {
+        let axes = format!("{batch_axes}I,OI->{batch_axes}O").parse()?;
+        let einsum = EinSum { axes, q_params: None, operating_dt: input.datum_type };
+        let mut wires = op.ctx.target.wire_node(p, einsum, &inputs[0..2])?;
+        if let Some(bias) = facts.get(2) {
+            ensure!(bias.rank() == 1);
+            wires = wire_with_rank_broadcast(
+                format!("{p}.bias"),
+                op.ctx.target,
+                core::math::add(),
+                &[wires[0], inputs[2]],
+            )?;
+        }
+        return super::wire_fused_activation(op, &wires, &options.fused_activation_function());
+    }
+    if let Some(bias) = facts.get(2) {
+        ensure!(bias.rank() == 1);
+        let bias_dt = bias.datum_type.unquantized();
+        inputs[2] = op.ctx.target.wire_node(
+            format!("{p}.cast_bias"),
+            Cast { to: bias_dt },
+            &[inputs[2]],
+        )?[0];
+    } else {
+        let bias = Tensor::zero::<i32>(&[weights.shape[0].to_usize()?])?;
+        inputs.push(op.ctx.target.add_const(format!("{p}.bias"), bias)?);
+    }
+    let qp = super::linearops_quantization_suport(op, input, &mut inputs)?;
+    // per-channel kernel quantization is carried by the O axis
+    let k_qp_axes = inputs[5..7]
+        .iter()
+        .map(|o| Ok(if op.ctx.target.outlet_fact(*o)?.rank() == 1 { "O" } else { "" }))
+        .collect::<TractResult<Vec<_>>>()?;
+    let axes = format!("{batch_axes}I,OI,O,,,{},{},,->{batch_axes}O", k_qp_axes[0], k_qp_axes[1])
+        .parse()?;
+    let einsum = EinSum { axes, q_params: qp, operating_dt: i32::datum_type() };
     let wires = op.ctx.target.wire_node(op.prefix, einsum, &inputs)?;
     super::wire_fused_activation(op, &wires, &options.fused_activation_function())
 }
 
 fn de_reduce(op: &mut DeserOp, reducer: Reducer) -> TractResult<TVec<OutletId>> {
+    wire_reduce(op, op.inputs[0], reducer)
+}
+
+fn wire_reduce(op: &mut DeserOp, input: OutletId, reducer: Reducer) -> TractResult<TVec<OutletId>> {
     let (_, axes) = args_2!(op.facts()?);
     let options = builtin!(op, builtin_options_as_reducer_options);
     let axes: TVec<usize> = axes
@@ -100,7 +149,7 @@ fn de_reduce(op: &mut DeserOp, reducer: Reducer) -> TractResult<TVec<OutletId>>
     let mut wire = op.ctx.target.wire_node(
         format!("{p}.reduce"),
         core::nn::Reduce::new(axes.clone(), reducer),
-        &[op.inputs[0]],
+        &[input],
     )?;
     if !options.keep_dims() {
         for axis in axes.iter().rev() {
@@ -123,8 +172,19 @@ fn de_reduce_mean(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
         .sorted()
         .collect();
     let norm: TDim = axes.iter().map(|d| &input.shape[*d]).product();
-    let wire = de_reduce(op, Reducer::Sum)?;
     let p = &op.prefix;
+    // quantized sums saturate, so quantized means are computed on dequantized values
+    let wire = if input.datum_type.is_quantized() {
+        op.ctx.target.wire_node(
+            format!("{p}.dequant"),
+            Cast { to: f32::datum_type() },
+            op.inputs,
+        )?[0]
+    } else {
+        op.inputs[0]
+    };
+    let wire = wire_reduce(op, wire, Reducer::Sum)?;
+    let p = op.prefix;
     let norm = op.ctx.target.add_const(format!("{p}.card"), tensor0(norm))?;
     let norm = op.ctx.target.wire_node(
         format!("{p}.as_float"),
@@ -132,7 +192,18 @@ fn de_reduce_mean(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
         &[norm],
     )?;
     let norm = op.ctx.target.wire_node(format!("{p}.recip"), core::math::recip(), &norm)?;
-    wire_with_rank_broadcast(op.prefix, op.ctx.target, core::quant::scale(), &[norm[0], wire[0]])
+    let dt = op.output_facts[0].datum_type;
+    if dt.is_quantized() {
+        let wire = wire_with_rank_broadcast(
+            format!("{p}.mean"),
+            op.ctx.target,
+            core::math::mul(),
+            &[wire[0], norm[0]],
+        )?;
+        op.ctx.target.wire_node(p, Cast { to: dt }, &wire)
+    } else {
+        wire_with_rank_broadcast(p, op.ctx.target, core::quant::scale(), &[norm[0], wire[0]])
+    }
 }
 
 fn de_softmax(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
@@ -206,6 +277,94 @@ fn ser_matmul(
     Ok(())
 }
 
+// quantized einsums contracting the last axis of the input with the second axis of a [O, I]
+// weight are fully connected. The input leading axes are either all kept (keep_num_dims), or
+// flattened to a single batch axis, trivial ones being dropped.
+pub(crate) fn einsum_as_fully_connected(
+    op: &EinSum,
+    input: &TypedFact,
+) -> TractResult<Option<bool>> {
+    let axes = &op.axes;
+    let rank = axes.rank(InOut::In(0));
+    let output_rank = axes.rank(InOut::Out(0));
+    if op.q_params.is_none() || rank < 2 || axes.rank(InOut::In(1)) != 2 || output_rank < 2 {
+        return Ok(None);
+    }
+    let k = axes.axis((InOut::In(0), rank - 1))?;
+    let o = axes.axis((InOut::In(1), 0))?;
+    if k.inputs[1][..] != [1]
+        || !k.outputs[0].is_empty()
+        || !o.inputs[0].is_empty()
+        || o.outputs[0][..] != [output_rank - 1]
+    {
+        return Ok(None);
+    }
+    let leading =
+        (0..rank - 1).map(|ix| axes.axis((InOut::In(0), ix))).collect::<TractResult<Vec<_>>>()?;
+    if leading.iter().any(|axis| !axis.inputs[1].is_empty()) {
+        return Ok(None);
+    }
+    if output_rank == rank
+        && leading.iter().enumerate().all(|(ix, axis)| axis.outputs[0][..] == [ix])
+    {
+        return Ok(Some(rank > 2));
+    }
+    let batch = leading.iter().filter(|axis| !axis.outputs[0].is_empty()).collect_vec();
+    if output_rank == 2
+        && batch.len() == 1
+        && batch[0].outputs[0][..] == [0]
+        && leading
+            .iter()
+            .enumerate()
+            .all(|(ix, axis)| !axis.outputs[0].is_empty() || input.shape[ix].is_one())
+    {
+        return Ok(Some(false));
+    }
+    Ok(None)
+}
+
+fn ser_fully_connected(
+    builder: &mut SubgraphBuilder,
+    model: &TypedModel,
+    node: &TypedNode,
+    op: &EinSum,
+) -> TractResult<()> {
+    let input_fact = model.outlet_fact(node.inputs[0])?;
+    let keep_num_dims = einsum_as_fully_connected(op, input_fact)?.with_context(|| {
+        format!("Only quantized fully connected einsums can be translated ({})", op.axes)
+    })?;
+    let version = if keep_num_dims {
+        5
+    } else if input_fact.datum_type.unquantized() == i8::datum_type() {
+        4
+    } else {
+        1
+    };
+    let mut inputs = vec![builder.map_outlet(model, node.inputs[0])?];
+    inputs.extend(super::ser_linearops_quantization_suport(builder, model, node, 0)?);
+    let output = builder.map_outlet(model, node.id.into())?;
+    let options = FullyConnectedOptions::create(
+        builder.fb(),
+        &FullyConnectedOptionsArgs {
+            fused_activation_function: ActivationFunctionType::NONE,
+            weights_format: FullyConnectedOptionsWeightsFormat::DEFAULT,
+            keep_num_dims,
+            asymmetric_quantize_inputs: false,
+        },
+    );
+    builder.write_op_with_options(
+        &inputs,
+        &[output],
+        BuiltinOp::new(
+            9,
+            version,
+            BuiltinOperator::FULLY_CONNECTED,
+            BuiltinOptions::FullyConnectedOptions,
+        ),
+        options.as_union_value(),
+    )
+}
+
 fn ser_reduce(
     builder: &mut SubgraphBuilder,
     model: &TypedModel,
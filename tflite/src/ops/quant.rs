@@ -0,0 +1,74 @@
+use crate::registry::{DeserOp, Registry};
+use crate::ser::{BuiltinOp, SubgraphBuilder};
+use crate::tflite::{
+    BuiltinOperator, BuiltinOptions, DequantizeOptions, DequantizeOptionsArgs, QuantizeOptions,
+    QuantizeOptionsArgs,
+};
+use tract_core::internal::*;
+use tract_core::ops::cast::{cast, Cast};
+
+pub fn register_all(reg: &mut Registry) {
+    reg.reg_to_tflite(ser_cast);
+    reg.reg_to_tract(BuiltinOperator::DEQUANTIZE, de_quant);
+    reg.reg_to_tract(BuiltinOperator::QUANTIZE, de_quant);
+}
+
+// quantization parameters are carried by the datum types, so QUANTIZE (including
+// requantization) and DEQUANTIZE are plain casts on tract side
+fn de_quant(op: &mut DeserOp) -> TractResult<TVec<OutletId>> {
+    let dt = op.output_facts[0].datum_type;
+    op.ctx.target.wire_node(op.prefix, cast(dt), op.inputs)
+}
+
+fn ser_cast(
+    builder: &mut SubgraphBuilder,
+    model: &TypedModel,
+    node: &TypedNode,
+    op: &Cast,
+) -> TractResult<()> {
+    let input_dt = model.outlet_fact(node.inputs[0])?.datum_type;
+    let input = builder.map_outlet(model, node.inputs[0])?;
+    let output = builder.map_outlet(model, node.id.into())?;
+    if op.to.is_quantized() && (input_dt.is_float() || input_dt.is_quantized()) {
+        let version = if op.to.unquantized() == i8::datum_type() { 2 } else { 1 };
+        let options = QuantizeOptions::create(builder.fb(), &QuantizeOptionsArgs {});
+        builder.write_op_with_options(
+            &[input],
+            &[output],
+            BuiltinOp::new(
+                114,
+                version,
+                BuiltinOperator::QUANTIZE,
+                BuiltinOptions::QuantizeOptions,
+            ),
+            options.as_union_value(),
+        )
+    } else if op.to == f32::datum_type()
+        && (input_dt.is_quantized() || input_dt == f16::datum_type())
+    {
+        let version = if input_dt == f16::datum_type() {
+            3
+        } else if input_dt.unquantized() == i8::datum_type() {
+            2
+        } else {
+            1
+        };
+        let options = DequantizeOptions::create(builder.fb(), &DequantizeOptionsArgs {});
+        builder.write_op_with_options(
+            &[input],
+            &[output],
+            BuiltinOp::new(
+                6,
+                version,
+                BuiltinOperator::DEQUANTIZE,
+                BuiltinOptions::DequantizeOptions,
+            ),
+            options.as_union_value(),
+        )
+    } else {
+        bail!(
+            "Only quantization and dequantization casts are supported ({input_dt:?} to {:?})",
+            op.to
+        )
+    }
+}
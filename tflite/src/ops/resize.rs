@@ -0,0 +1,213 @@
+use tract_core::internal::*;
+use tract_core::ndarray::{Array4, ArrayView4, Axis, Ix4};
+
+use crate::registry::{DeserOp, Registry};
+use crate::ser::{BuiltinOp, SubgraphBuilder};
+use crate::tflite::{
+    BuiltinOperator, BuiltinOptions, ResizeBilinearOptions, ResizeBilinearOptionsArgs,
+    ResizeNearestNeighborOptions, ResizeNearestNeighborOptionsArgs,
+};
+
+pub fn register_all(reg: &mut Registry) {
+    reg.reg_to_tflite(ser_resize);
+    reg.reg_to_tract(BuiltinOperator::RESIZE_BILINEAR, |op| de_resize(op, false));
+    reg.reg_to_tract(BuiltinOperator::RESIZE_NEAREST_NEIGHBOR, |op| de_resize(op, true));
+}
+
+/// Spatial resizing of a NHWC image, with tflite coordinate conventions.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct Resize {
+    pub nearest: bool,
+    pub align_corners: bool,
+    pub half_pixel_centers: bool,
+    pub size: [usize; 2],
+}
+
+impl Resize {
+    fn scale(&self, input: usize, output: usize) -> f32 {
+        if self.align_corners && output > 1 {
+            (input - 1) as f32 / (output - 1) as f32
+        } else {
+            input as f32 / output as f32
+        }
+    }
+
+    fn nearest_indices(&self, input: usize, output: usize) -> Vec<usize> {
+        let scale = self.scale(input, output);
+        let offset = if self.half_pixel_centers { 0.5 } else { 0.0 };
+        (0..output)
+            .map(|x| {
+                let x = (x as f32 + offset) * scale;
+                let x = if self.align_corners { x.round() } else { x.floor() };
+                (x.max(0.0) as usize).min(input - 1)
+            })
+            .collect()
+    }
+
+    // lower and upper input positions, and weight of the upper one
+    fn linear_taps(&self, input: usize, output: usize) -> (Vec<usize>, Vec<usize>, Vec<f32>) {
+        let scale = self.scale(input, output);
+        let mut taps = (vec![], vec![], vec![]);
+        for x in 0..output {
+            let x = if self.half_pixel_centers {
+                (x as f32 + 0.5) * scale - 0.5
+            } else {
+                x as f32 * scale
+            };
+            let lower = x.floor().max(0.0) as usize;
+            taps.0.push(lower);
+            taps.1.push((x.ceil().max(0.0) as usize).min(input - 1));
+            taps.2.push(x - lower as f32);
+        }
+        taps
+    }
+
+    unsafe fn eval_nearest<T: Datum>(&self, input: &Tensor) -> TractResult<Tensor> {
+        let mut data =
+            input.to_array_view_unchecked::<T>().into_dimensionality::<Ix4>()?.to_owned();
+        for (ix, size) in self.size.iter().enumerate() {
+            let indices = self.nearest_indices(data.shape()[ix + 1], *size);
+            data = data.select(Axis(ix + 1), &indices);
+        }
+        Ok(data.into_tensor())
+    }
+
+    fn eval_bilinear(&self, input: ArrayView4<f32>) -> Array4<f32> {
+        let mut data = input.to_owned();
+        for (ix, size) in self.size.iter().enumerate() {
+            let axis = Axis(ix + 1);
+            let (lower, upper, weights) = self.linear_taps(data.shape()[ix + 1], *size);
+            let mut output = data.select(axis, &lower);
+            let upper = data.select(axis, &upper);
+            for (pos, w) in weights.iter().enumerate() {
+                output
+                    .index_axis_mut(axis, pos)
+                    .zip_mut_with(&upper.index_axis(axis, pos), |l, u| *l = *l * (1.0 - w) + u * w);
+            }
+            data = output;
+        }
+        data
+    }
+}
+
+impl Op for Resize {
+    fn name(&self) -> Cow<str> {
+        "Resize".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!(
+            "{} to {:?}, align_corners: {:?}, half_pixel_centers: {:?}",
+            if self.nearest { "Nearest" } else { "Bilinear" },
+            self.size,
+            self.align_corners,
+            self.half_pixel_centers
+        )])
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for Resize {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        let input = args_1!(inputs);
+        let dt = input.datum_type();
+        let mut output = if self.nearest {
+            unsafe { dispatch_datum_by_size!(Self::eval_nearest(dt)(self, &input))? }
+        } else {
+            let input = input.cast_to::<f32>()?;
+            let input = input.to_array_view::<f32>()?.into_dimensionality::<Ix4>()?;
+            self.eval_bilinear(input).into_tensor().cast_to_dt(dt)?.into_owned()
+        };
+        unsafe { output.set_datum_type(dt) };
+        Ok(tvec!(output.into_tvalue()))
+    }
+}
+
+impl TypedOp for Resize {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        ensure!(inputs[0].rank() == 4, "Resize expects a NHWC input");
+        let mut shape = inputs[0].shape.to_tvec();
+        shape[1] = self.size[0].to_dim();
+        shape[2] = self.size[1].to_dim();
+        Ok(tvec!(inputs[0].datum_type.fact(shape)))
+    }
+
+    as_op!();
+}
+
+fn de_resize(op: &mut DeserOp, nearest: bool) -> TractResult<TVec<OutletId>> {
+    let (_input, size) = args_2!(op.facts()?);
+    let size = size.konst.context("Dynamic resizing is not supported")?;
+    let size = size.cast_to::<i32>()?;
+    let size = size.as_slice::<i32>()?;
+    ensure!(size.len() == 2);
+    let (align_corners, half_pixel_centers) = if nearest {
+        let options = builtin!(op, builtin_options_as_resize_nearest_neighbor_options);
+        (options.align_corners(), options.half_pixel_centers())
+    } else {
+        let options = builtin!(op, builtin_options_as_resize_bilinear_options);
+        (options.align_corners(), options.half_pixel_centers())
+    };
+    let resize = Resize {
+        nearest,
+        align_corners,
+        half_pixel_centers,
+        size: [size[0] as usize, size[1] as usize],
+    };
+    op.ctx.target.wire_node(op.prefix, resize, &op.inputs[0..1])
+}
+
+fn ser_resize(
+    builder: &mut SubgraphBuilder,
+    model: &TypedModel,
+    node: &TypedNode,
+    op: &Resize,
+) -> TractResult<()> {
+    let size = tensor1(&[op.size[0] as i32, op.size[1] as i32]);
+    let inputs = [
+        builder.map_outlet(model, node.inputs[0])?,
+        builder.write_fact(format!("{}.size", node.name), size)?,
+    ];
+    let output = builder.outlets_to_tensors[&node.id.into()];
+    let (align_corners, half_pixel_centers) = (op.align_corners, op.half_pixel_centers);
+    if op.nearest {
+        let version = if align_corners || half_pixel_centers { 3 } else { 1 };
+        let options = ResizeNearestNeighborOptions::create(
+            builder.fb(),
+            &ResizeNearestNeighborOptionsArgs { align_corners, half_pixel_centers },
+        );
+        builder.write_op_with_options(
+            &inputs,
+            &[output],
+            BuiltinOp::new(
+                97,
+                version,
+                BuiltinOperator::RESIZE_NEAREST_NEIGHBOR,
+                BuiltinOptions::ResizeNearestNeighborOptions,
+            ),
+            options.as_union_value(),
+        )
+    } else {
+        let version = if half_pixel_centers { 3 } else { 1 };
+        let options = ResizeBilinearOptions::create(
+            builder.fb(),
+            &ResizeBilinearOptionsArgs { align_corners, half_pixel_centers },
+        );
+        builder.write_op_with_options(
+            &inputs,
+            &[output],
+            BuiltinOp::new(
+                23,
+                version,
+                BuiltinOperator::RESIZE_BILINEAR,
+                BuiltinOptions::ResizeBilinearOptions,
+            ),
+            options.as_union_value(),
+        )
+    }
+}
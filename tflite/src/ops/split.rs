@@ -0,0 +1,197 @@
+use tract_core::internal::*;
+
+use crate::registry::Registry;
+use crate::ser::{BuiltinOp, SubgraphBuilder};
+use crate::tflite::{
+    BuiltinOperator, BuiltinOptions, PackOptions, PackOptionsArgs, SplitOptions, SplitOptionsArgs,
+    SplitVOptions, SplitVOptionsArgs, UnpackOptions, UnpackOptionsArgs,
+};
+
+pub fn register_all(reg: &mut Registry) {
+    reg.reg_to_tflite(ser_pack);
+    reg.reg_to_tflite(ser_split);
+}
+
+/// TFLite SPLIT, SPLIT_V and UNPACK: cuts the input in consecutive chunks along an axis, that
+/// is removed from the outputs when squeezing (UNPACK).
+///
+/// Loading translates these builtins to Slice and AxisOp. This op is only introduced by the
+/// pre-dump rewriter, so they are dumped back as one operator.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct Split {
+    pub axis: usize,
+    pub sizes: TVec<usize>,
+    pub squeeze: bool,
+}
+
+impl Op for Split {
+    fn name(&self) -> Cow<str> {
+        "Split".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("axis: {}, sizes: {:?}, squeeze: {}", self.axis, self.sizes, self.squeeze)])
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for Split {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        let input = args_1!(inputs);
+        let mut start = 0;
+        let mut outputs = tvec!();
+        for size in &self.sizes {
+            let mut chunk = input.slice(self.axis, start, start + size)?;
+            if self.squeeze {
+                chunk.remove_axis(self.axis)?;
+            }
+            outputs.push(chunk.into_tvalue());
+            start += size;
+        }
+        Ok(outputs)
+    }
+}
+
+impl TypedOp for Split {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        let input = inputs[0];
+        ensure!(self.axis < input.rank());
+        ensure!(input.shape[self.axis] == self.sizes.iter().sum::<usize>().to_dim());
+        ensure!(!self.squeeze || self.sizes.iter().all(|s| *s == 1));
+        Ok(self
+            .sizes
+            .iter()
+            .map(|size| {
+                let mut shape = input.shape.to_tvec();
+                if self.squeeze {
+                    shape.remove(self.axis);
+                } else {
+                    shape[self.axis] = size.to_dim();
+                }
+                input.datum_type.fact(shape)
+            })
+            .collect())
+    }
+
+    as_op!();
+}
+
+/// TFLite PACK: stacks same shaped inputs along a new axis.
+///
+/// Loading translates it to AxisOp and TypedConcat, this op is only introduced by the pre-dump
+/// rewriter.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct Pack {
+    pub axis: usize,
+}
+
+impl Op for Pack {
+    fn name(&self) -> Cow<str> {
+        "Pack".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("axis: {}", self.axis)])
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for Pack {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        let inputs = inputs
+            .into_iter()
+            .map(|input| {
+                let mut input = input.into_tensor();
+                input.insert_axis(self.axis)?;
+                Ok(input)
+            })
+            .collect::<TractResult<TVec<Tensor>>>()?;
+        Ok(tvec!(Tensor::stack_tensors(self.axis, &inputs)?.into_tvalue()))
+    }
+}
+
+impl TypedOp for Pack {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        ensure!(!inputs.is_empty());
+        ensure!(inputs.iter().all(|i| i.datum_type == inputs[0].datum_type));
+        ensure!(inputs.iter().all(|i| i.shape == inputs[0].shape));
+        ensure!(self.axis <= inputs[0].rank());
+        let mut shape = inputs[0].shape.to_tvec();
+        shape.insert(self.axis, inputs.len().to_dim());
+        Ok(tvec!(inputs[0].datum_type.fact(shape)))
+    }
+
+    as_op!();
+}
+
+fn ser_split(
+    builder: &mut SubgraphBuilder,
+    model: &TypedModel,
+    node: &TypedNode,
+    op: &Split,
+) -> TractResult<()> {
+    let input = builder.map_outlet(model, node.inputs[0])?;
+    let outputs =
+        builder.map_outlets(model, (0..node.outputs.len()).map(|s| OutletId::new(node.id, s)))?;
+    let axis = op.axis as i32;
+    let num = op.sizes.len() as i32;
+    if op.squeeze {
+        let options = UnpackOptions::create(builder.fb(), &UnpackOptionsArgs { num, axis });
+        builder.write_op_with_options(
+            &[input],
+            &outputs,
+            BuiltinOp::new(88, 1, BuiltinOperator::UNPACK, BuiltinOptions::UnpackOptions),
+            options.as_union_value(),
+        )
+    } else if op.sizes.iter().all(|s| *s == op.sizes[0]) {
+        let axis = builder.write_fact(format!("{}.axis", node.name), tensor0(axis))?;
+        let options = SplitOptions::create(builder.fb(), &SplitOptionsArgs { num_splits: num });
+        builder.write_op_with_options(
+            &[axis, input],
+            &outputs,
+            BuiltinOp::new(49, 1, BuiltinOperator::SPLIT, BuiltinOptions::SplitOptions),
+            options.as_union_value(),
+        )
+    } else {
+        let sizes = tensor1(&op.sizes.iter().map(|s| *s as i32).collect::<Vec<_>>());
+        let sizes = builder.write_fact(format!("{}.sizes", node.name), sizes)?;
+        let axis = builder.write_fact(format!("{}.axis", node.name), tensor0(axis))?;
+        let options = SplitVOptions::create(builder.fb(), &SplitVOptionsArgs { num_splits: num });
+        builder.write_op_with_options(
+            &[input, sizes, axis],
+            &outputs,
+            BuiltinOp::new(102, 1, BuiltinOperator::SPLIT_V, BuiltinOptions::SplitVOptions),
+            options.as_union_value(),
+        )
+    }
+}
+
+fn ser_pack(
+    builder: &mut SubgraphBuilder,
+    model: &TypedModel,
+    node: &TypedNode,
+    op: &Pack,
+) -> TractResult<()> {
+    let inputs = builder.map_outlets(model, &node.inputs)?;
+    let output = builder.map_outlet(model, node.id.into())?;
+    let options = PackOptions::create(
+        builder.fb(),
+        &PackOptionsArgs { values_count: inputs.len() as i32, axis: op.axis as i32 },
+    );
+    builder.write_op_with_options(
+        &inputs,
+        &[output],
+        BuiltinOp::new(83, 1, BuiltinOperator::PACK, BuiltinOptions::PackOptions),
+        options.as_union_value(),
+    )
+}
@@ -1,16 +1,18 @@
 use tract_core::internal::*;
-use tract_core::ops::array::{Pad, PadMode};
+use tract_core::ops::array::{Pad, PadMode, Slice, TypedConcat};
 use tract_core::ops::binary::wire_with_rank_broadcast;
-use tract_core::ops::cnn::{rewrite_conv_with_n_axis, KernelFormat};
-use tract_core::ops::cnn::{Conv, PaddingSpec};
-use tract_core::ops::einsum::BasicMatMul;
+use tract_core::ops::cnn::{rewrite_conv_with_n_axis, rewrite_deconv_with_n_axis, KernelFormat};
+use tract_core::ops::cnn::{Conv, Deconv, MaxPool, PaddingSpec, PoolSpec, SumPool};
+use tract_core::ops::einsum::{rewrite_einsum_as_matmul, BasicMatMul, EinSum};
 use tract_core::ops::element_wise::ElementWiseOp;
 use tract_core::ops::math::Recip;
 use tract_core::ops::nn::{expand_mean_of_squares, DataFormat, Softmax};
 use tract_core::tract_data::itertools::Itertools;
 
+use crate::ops::{einsum_as_fully_connected, Pack, Split};
+
 pub fn rewrite_for_tflite(model: &mut TypedModel) -> TractResult<()> {
-    tract_core::ops::einsum::rewrite_einsums_as_matmul(model)?;
+    Rewriter::default().with_rule_for("einsum_as_matmul", einsum_as_matmul).rewrite(&(), model)?;
     Rewriter::default()
         .with_rule_for("trivial_axes_around_matmul", trivial_axes_around_matmul)
         .with_rule_for("kernel_in_ohwi", kernel_in_ohwi)
@@ -20,12 +22,37 @@ pub fn rewrite_for_tflite(model: &mut TypedModel) -> TractResult<()> {
         .with_rule_for("rewrite_conv_with_n_axis", rewrite_conv_with_n_axis)
         .with_rule_for("nchw-to-nhwc", nchw_to_nhwc)
         .with_rule_for("padding", padding)
+        .with_rule_for("max_pool_nchw_to_nhwc", max_pool_nchw_to_nhwc)
+        .with_rule_for("sum_pool_nchw_to_nhwc", sum_pool_nchw_to_nhwc)
+        .with_rule_for("max_pool_padding", max_pool_padding)
+        .with_rule_for("sum_pool_padding", sum_pool_padding)
+        .with_rule_for("deconv_kernel_in_ohwi", deconv_kernel_in_ohwi)
+        .with_rule_for("deconv_bias_as_vector", deconv_bias_as_vector)
+        .with_rule_for("rewrite_deconv_with_n_axis", rewrite_deconv_with_n_axis)
+        .with_rule_for("deconv_nchw_to_nhwc", deconv_nchw_to_nhwc)
         .with_rule_for("manual_recip", manual_recip)
         .with_rule_for("softmax_on_last_axis", softmax_on_last_axis)
         .with_rule_for("expand-means-of-square", expand_mean_of_squares)
+        .with_rule_for("slices_as_split", slices_as_split)
+        .with_rule_for("concat_as_pack", concat_as_pack)
         .rewrite(&(), model)
 }
 
+// quantized fully connected einsums are dumped as is, as they carry bias and per-channel
+// quantization that matmul can not express
+fn einsum_as_matmul(
+    ctx: &(),
+    model: &TypedModel,
+    node: &TypedNode,
+    name: &str,
+    op: &EinSum,
+) -> TractResult<Option<TypedModelPatch>> {
+    if einsum_as_fully_connected(op, model.outlet_fact(node.inputs[0])?)?.is_some() {
+        return Ok(None);
+    }
+    rewrite_einsum_as_matmul(ctx, model, node, name, op)
+}
+
 fn trivial_axes_around_matmul(
     _ctx: &(),
     model: &TypedModel,
@@ -270,6 +297,168 @@ fn padding(
     Ok(None)
 }
 
+fn max_pool_nchw_to_nhwc(
+    _ctx: &(),
+    model: &TypedModel,
+    node: &TypedNode,
+    name: &str,
+    pool: &MaxPool,
+) -> TractResult<Option<TypedModelPatch>> {
+    pool_nchw_to_nhwc(model, node, name, &pool.pool_spec, |pool_spec| {
+        Box::new(MaxPool { pool_spec, ..pool.clone() })
+    })
+}
+
+fn sum_pool_nchw_to_nhwc(
+    _ctx: &(),
+    model: &TypedModel,
+    node: &TypedNode,
+    name: &str,
+    pool: &SumPool,
+) -> TractResult<Option<TypedModelPatch>> {
+    pool_nchw_to_nhwc(model, node, name, &pool.pool_spec, |pool_spec| {
+        Box::new(SumPool { pool_spec, ..pool.clone() })
+    })
+}
+
+fn pool_nchw_to_nhwc(
+    model: &TypedModel,
+    node: &TypedNode,
+    name: &str,
+    pool_spec: &PoolSpec,
+    rebuild: impl Fn(PoolSpec) -> Box<dyn TypedOp>,
+) -> TractResult<Option<TypedModelPatch>> {
+    if pool_spec.data_format.c_is_last() {
+        return Ok(None);
+    }
+    let mut new = pool_spec.clone();
+    new.data_format = match pool_spec.data_format {
+        DataFormat::NHWC | DataFormat::HWC => unreachable!(),
+        DataFormat::CHW => DataFormat::HWC,
+        DataFormat::NCHW => DataFormat::NHWC,
+    };
+    let mut patch = TypedModelPatch::default();
+    let fact = model.outlet_fact(node.inputs[0])?;
+    let before = pool_spec.data_format.shape(&fact.shape)?.c_axis();
+    let after = fact.rank() - 1;
+    let mut wire = patch.taps(model, &node.inputs)?;
+    wire[0] = patch.wire_node(format!("{name}.nhwc"), AxisOp::Move(before, after), &[wire[0]])?[0];
+    wire = patch.wire_node(name, rebuild(new), &wire)?;
+    wire = patch.wire_node(format!("{name}.nchw"), AxisOp::Move(after, before), &wire)?;
+    patch.shunt_outside(model, node.id.into(), wire[0])?;
+    Ok(Some(patch))
+}
+
+fn max_pool_padding(
+    _ctx: &(),
+    model: &TypedModel,
+    node: &TypedNode,
+    _name: &str,
+    pool: &MaxPool,
+) -> TractResult<Option<TypedModelPatch>> {
+    pool_padding(model, node, &pool.pool_spec, |pool_spec| {
+        Box::new(MaxPool { pool_spec, ..pool.clone() })
+    })
+}
+
+fn sum_pool_padding(
+    _ctx: &(),
+    model: &TypedModel,
+    node: &TypedNode,
+    _name: &str,
+    pool: &SumPool,
+) -> TractResult<Option<TypedModelPatch>> {
+    pool_padding(model, node, &pool.pool_spec, |pool_spec| {
+        Box::new(SumPool { pool_spec, ..pool.clone() })
+    })
+}
+
+// tflite pools only know about VALID and SAME paddings, so explicit paddings are swapped for
+// the equivalent one, if any
+fn pool_padding(
+    model: &TypedModel,
+    node: &TypedNode,
+    pool_spec: &PoolSpec,
+    rebuild: impl Fn(PoolSpec) -> Box<dyn TypedOp>,
+) -> TractResult<Option<TypedModelPatch>> {
+    if pool_spec.padding == PaddingSpec::Valid || pool_spec.padding == PaddingSpec::SameUpper {
+        return Ok(None);
+    }
+    let fact = model.outlet_fact(node.inputs[0])?;
+    let shape = pool_spec.data_format.shape(&fact.shape)?;
+    let actual = pool_spec.computed_padding(shape.hw_dims());
+    for pad in [PaddingSpec::Valid, PaddingSpec::SameUpper] {
+        let found = pad.compute(
+            shape.hw_dims(),
+            &pool_spec.kernel_shape,
+            &pool_spec.dilations(),
+            &pool_spec.strides(),
+        );
+        if actual == found {
+            let new = rebuild(PoolSpec { padding: pad, ..pool_spec.clone() });
+            return Ok(Some(TypedModelPatch::replace_single_op(model, node, &node.inputs, new)?));
+        }
+    }
+    Ok(None)
+}
+
+fn deconv_kernel_in_ohwi(
+    _ctx: &(),
+    model: &TypedModel,
+    node: &TypedNode,
+    name: &str,
+    deconv: &Deconv,
+) -> TractResult<Option<TypedModelPatch>> {
+    if deconv.kernel_format == KernelFormat::OHWI || deconv.group != 1 {
+        return Ok(None);
+    }
+    let rank = model.outlet_fact(node.inputs[1])?.rank();
+    let reorg = match deconv.kernel_format {
+        KernelFormat::OIHW => AxisOp::Move(1, rank - 1),
+        KernelFormat::HWIO => AxisOp::Move(rank - 1, 0),
+        KernelFormat::OHWI => unreachable!(),
+    };
+    let mut patch = TypedModelPatch::default();
+    let mut wire = patch.taps(model, &node.inputs)?;
+    wire[1] = patch.wire_node(format!("{name}.kernel_reorg"), reorg, &[wire[1]])?[0];
+    let new = Deconv { kernel_format: KernelFormat::OHWI, ..deconv.clone() };
+    wire = patch.wire_node(name, new, &wire)?;
+    patch.shunt_outside(model, node.id.into(), wire[0])?;
+    Ok(Some(patch))
+}
+
+fn deconv_bias_as_vector(
+    _ctx: &(),
+    model: &TypedModel,
+    node: &TypedNode,
+    name: &str,
+    deconv: &Deconv,
+) -> TractResult<Option<TypedModelPatch>> {
+    let bias_fact = model.outlet_fact(node.inputs[2])?;
+    let co = deconv.pool_spec.output_channels;
+    if *bias_fact.shape == [co.to_dim()] {
+        return Ok(None);
+    }
+    let mut patch = TypedModelPatch::default();
+    let mut wire = patch.taps(model, &node.inputs)?;
+    wire[2] = tract_core::ops::cnn::wire_reshape_bias_as_vector(&mut patch, name, wire[2], co)?[0];
+    wire = patch.wire_node(name, deconv.clone(), &wire)?;
+    patch.shunt_outside(model, node.id.into(), wire[0])?;
+    Ok(Some(patch))
+}
+
+fn deconv_nchw_to_nhwc(
+    _ctx: &(),
+    model: &TypedModel,
+    node: &TypedNode,
+    name: &str,
+    deconv: &Deconv,
+) -> TractResult<Option<TypedModelPatch>> {
+    pool_nchw_to_nhwc(model, node, name, &deconv.pool_spec, |pool_spec| {
+        Box::new(Deconv { pool_spec, ..deconv.clone() })
+    })
+}
+
 fn manual_recip(
     _ctx: &(),
     model: &TypedModel,
@@ -329,3 +518,86 @@ fn softmax_on_last_axis(
         Ok(None)
     }
 }
+
+// sibling slices tiling an axis are gathered in a SPLIT, SPLIT_V or UNPACK
+fn slices_as_split(
+    _ctx: &(),
+    model: &TypedModel,
+    node: &TypedNode,
+    name: &str,
+    slice: &Slice,
+) -> TractResult<Option<TypedModelPatch>> {
+    let input = node.inputs[0];
+    let Ok(dim) = model.outlet_fact(input)?.shape[slice.axis].to_usize() else { return Ok(None) };
+    let mut chunks = vec![];
+    for succ in model.outlet_successors(input) {
+        let sibling = model.node(succ.node);
+        let Some(s) = sibling.op_as::<Slice>().filter(|s| s.axis == slice.axis) else { continue };
+        if let (Ok(start), Ok(end)) = (s.start.to_usize(), s.end.to_usize()) {
+            chunks.push((start, end, sibling.id));
+        }
+    }
+    chunks.sort();
+    // rewrite once, from the first chunk
+    if chunks.len() < 2
+        || chunks[0].2 != node.id
+        || chunks[0].0 != 0
+        || chunks[chunks.len() - 1].1 != dim
+        || chunks.iter().any(|(start, end, _)| start >= end)
+        || chunks.windows(2).any(|w| w[0].1 != w[1].0)
+    {
+        return Ok(None);
+    }
+    let sizes: TVec<usize> = chunks.iter().map(|(start, end, _)| end - start).collect();
+    // unit slices whose only successor removes the axis are unpacked
+    let squeezed: Option<TVec<OutletId>> = chunks
+        .iter()
+        .map(|(_, _, id)| {
+            let succs = &model.node(*id).outputs[0].successors;
+            let rm = model.node(succs.first()?.node);
+            (succs.len() == 1
+                && !model.outputs.contains(&OutletId::new(*id, 0))
+                && rm.op_as::<AxisOp>() == Some(&AxisOp::Rm(slice.axis)))
+            .then(|| rm.id.into())
+        })
+        .collect();
+    let squeeze = sizes.iter().all(|s| *s == 1) && squeezed.is_some();
+    let replaced: TVec<OutletId> = if squeeze {
+        squeezed.unwrap()
+    } else {
+        chunks.iter().map(|(_, _, id)| (*id).into()).collect()
+    };
+    let mut patch = TypedModelPatch::default();
+    let tap = patch.tap_model(model, input)?;
+    let split = Split { axis: slice.axis, sizes, squeeze };
+    let outputs = patch.wire_node(format!("{name}.split"), split, &[tap])?;
+    for (outlet, by) in replaced.into_iter().zip(outputs) {
+        patch.shunt_outside(model, outlet, by)?;
+    }
+    Ok(Some(patch))
+}
+
+// concatenation of inputs with an added axis is a PACK
+fn concat_as_pack(
+    _ctx: &(),
+    model: &TypedModel,
+    node: &TypedNode,
+    name: &str,
+    concat: &TypedConcat,
+) -> TractResult<Option<TypedModelPatch>> {
+    let mut inputs = tvec!();
+    for input in &node.inputs {
+        let prec = model.node(input.node);
+        if prec.op_as::<AxisOp>() != Some(&AxisOp::Add(concat.axis))
+            || prec.outputs[0].successors.len() != 1
+        {
+            return Ok(None);
+        }
+        inputs.push(prec.inputs[0]);
+    }
+    let mut patch = TypedModelPatch::default();
+    let taps = patch.taps(model, &inputs)?;
+    let pack = patch.wire_node(format!("{name}.pack"), Pack { axis: concat.axis }, &taps)?;
+    patch.shunt_outside(model, node.id.into(), pack[0])?;
+    Ok(Some(patch))
+}
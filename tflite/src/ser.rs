@@ -134,13 +134,13 @@ impl<'f, 'b, 'mb> SubgraphBuilder<'f, 'b, 'mb> {
         {
             let qp =
                 fact.datum_type.qparams().unwrap_or(QParams::ZpScale { zero_point: 0, scale: 1. });
-            self.write_fact_with_per_axis_q(
-                name,
-                fact,
-                &[qp.zp_scale().0 as i64],
-                &[qp.zp_scale().1],
-                0,
-            )
+            if let Some(params) = qp.per_axis_params() {
+                let zp = params.zero_points.iter().map(|&zp| zp as i64).collect_vec();
+                let (scales, axis) = (&params.scales, params.axis);
+                return self.write_fact_with_per_axis_q(name, fact, &zp, scales, axis);
+            }
+            let (zp, scale) = qp.zp_scale();
+            self.write_fact_with_per_axis_q(name, fact, &[zp as i64], &[scale], 0)
         } else {
             self.write_fact_with_quantization(name, fact, None)
         }
@@ -154,12 +154,16 @@ impl<'f, 'b, 'mb> SubgraphBuilder<'f, 'b, 'mb> {
     ) -> TractResult<i32> {
         let fact = fact.into();
         if let Some(qp) = fact.datum_type.qparams() {
+            if qp.is_per_axis() {
+                return self.write_fact(name, fact);
+            }
             let dim = fact.shape[axis].to_usize()?;
+            let (zp, scale) = qp.zp_scale();
             self.write_fact_with_per_axis_q(
                 name,
                 fact,
-                &vec![qp.zp_scale().0 as i64; dim],
-                &vec![qp.zp_scale().1; dim],
+                &vec![zp as i64; dim],
+                &vec![scale; dim],
                 axis,
             )
         } else {
@@ -235,6 +239,41 @@ impl<'f, 'b, 'mb> SubgraphBuilder<'f, 'b, 'mb> {
         Ok(self.tensors.len() as i32 - 1)
     }
 
+    /// Variable tensors hold state (LSTM cell and output) and are zeroed by the runtime.
+    pub fn write_variable(
+        &mut self,
+        name: impl AsRef<str>,
+        fact: impl Into<TypedFact>,
+    ) -> TractResult<i32> {
+        let fact = fact.into();
+        let shape = fact
+            .shape
+            .as_concrete()
+            .context("Can not serialize symbolic dims to tflite")?
+            .iter()
+            .map(|d| *d as i32)
+            .collect_vec();
+        let shape = self.fb().create_vector(&shape);
+        let name = self.fb().create_string(name.as_ref());
+        let tensor = Tensor::create(
+            self.fb(),
+            &TensorArgs {
+                name: Some(name),
+                buffer: 0,
+                is_variable: true,
+                quantization: None,
+                shape: Some(shape),
+                type_: fact.datum_type.try_into()?,
+                sparsity: None,
+                shape_signature: None,
+                has_rank: true,
+                variant_tensors: None,
+            },
+        );
+        self.tensors.push(tensor);
+        Ok(self.tensors.len() as i32 - 1)
+    }
+
     fn write_subgraph(&mut self, model: &TypedModel) -> TractResult<()> {
         for &node_id in &model.eval_order()? {
             let node = &model.nodes[node_id];
@@ -90,6 +90,10 @@ pub fn flat_tensor_uses_per_axis_q<'m>(graph: &'m SubGraph<'m>, id: i32) -> bool
     false
 }
 
+pub fn flat_tensor_is_variable<'m>(graph: &'m SubGraph<'m>, id: i32) -> bool {
+    graph.tensors().unwrap().get(id as _).is_variable()
+}
+
 pub fn per_axis_q_params<'m>(
     graph: &'m SubGraph<'m>,
     id: i32,